@@ -0,0 +1,27 @@
+//! Build script for the Sudan Digital Archive MCP Server.
+//!
+//! Embeds the current git commit as `SDA_GIT_COMMIT` so the `server_info`
+//! tool can report exactly which build is running. Builds from a source
+//! tarball without git simply leave the variable unset.
+
+use std::path::Path;
+use std::process::Command;
+
+fn main() {
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok());
+    if let Some(commit) = commit {
+        println!("cargo:rustc-env=SDA_GIT_COMMIT={}", commit.trim());
+    }
+
+    // Re-run when HEAD moves; watching a missing path would re-run every build.
+    for path in [".git/HEAD", ".git/refs/heads"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+}
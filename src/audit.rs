@@ -0,0 +1,96 @@
+//! Append-only audit trail of moderation actions.
+//!
+//! `--audit-file` names a JSON lines file recording who changed what: each
+//! audited tool call appends one entry, a single JSON object on its own line,
+//! and existing lines are never rewritten. Each entry is written with one
+//! `write` call on a file opened in append mode, so concurrent entries never
+//! interleave and a crash loses at most the entry being written.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+use crate::error::{Result, SdaError};
+
+/// The audit file, opened for each entry.
+pub struct AuditLog {
+    /// Path of the JSON lines file.
+    path: PathBuf,
+    /// Serializes appends between concurrent tool calls.
+    lock: Mutex<()>,
+}
+
+impl AuditLog {
+    /// Creates a log appending to `path`, which is created on first use.
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Path of the audit file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends `entry` as one line of JSON, writing on the blocking pool.
+    pub async fn append<T: Serialize>(self: &Arc<Self>, entry: &T) -> Result<()> {
+        let mut line =
+            serde_json::to_vec(entry).map_err(|e| SdaError::decode(self.error_context(), e))?;
+        line.push(b'\n');
+        let log = Arc::clone(self);
+        tokio::task::spawn_blocking(move || log.append_blocking(&line))
+            .await
+            .map_err(|e| SdaError::io(self.error_context(), std::io::Error::other(e)))?
+    }
+
+    fn append_blocking(&self, line: &[u8]) -> Result<()> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| SdaError::io(self.error_context(), e))?;
+        file.write_all(line)
+            .and_then(|()| file.sync_data())
+            .map_err(|e| SdaError::io(self.error_context(), e))
+    }
+
+    fn error_context(&self) -> String {
+        format!("Failed to write audit file {}", self.path.display())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::TempDir;
+
+    #[tokio::test]
+    async fn test_append_keeps_earlier_entries() {
+        let dir = TempDir::create("sda-audit-test").unwrap();
+        let path = dir.path().join("audit.jsonl");
+        Arc::new(AuditLog::new(path.clone()))
+            .append(&serde_json::json!({"ids": [1, 2]}))
+            .await
+            .unwrap();
+        let log = Arc::new(AuditLog::new(path.clone()));
+        log.append(&serde_json::json!({"ids": [3]})).await.unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let entries: Vec<serde_json::Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                serde_json::json!({"ids": [1, 2]}),
+                serde_json::json!({"ids": [3]})
+            ]
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! Accessions bookmarked to revisit, kept in a local JSON file.
+//!
+//! The SDA API has no notion of per-user state, so `--bookmarks-file`
+//! names a file holding a JSON array of bookmarks, one per accession ID,
+//! that outlives the MCP session. Each change rewrites the whole file via a
+//! temporary file and a rename, so a crash never leaves it half written. A
+//! missing file is an empty list. The file is read and written on tokio's
+//! blocking pool so tool calls never stall the runtime.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+
+use crate::error::{Result, SdaError};
+use crate::model::Bookmark;
+
+/// The bookmarks file, read and rewritten on every call.
+pub struct BookmarkStore {
+    /// Path of the JSON file.
+    path: PathBuf,
+    /// Serializes read-modify-write cycles between concurrent tool calls.
+    lock: Mutex<()>,
+}
+
+impl BookmarkStore {
+    /// Creates a store backed by `path`, which need not exist yet.
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Returns every bookmark, ordered by accession ID.
+    pub async fn list(self: &Arc<Self>) -> Result<Vec<Bookmark>> {
+        self.locked(|store| store.load()).await
+    }
+
+    /// Bookmarks `accession_id`, or replaces the note of an existing
+    /// bookmark; a blank note clears it. Returns the bookmark and whether
+    /// it is new.
+    pub async fn add(
+        self: &Arc<Self>,
+        accession_id: i64,
+        note: String,
+    ) -> Result<(Bookmark, bool)> {
+        self.locked(move |store| store.add_blocking(accession_id, note))
+            .await
+    }
+
+    /// Removes the bookmark of `accession_id`, returning whether there was one.
+    pub async fn remove(self: &Arc<Self>, accession_id: i64) -> Result<bool> {
+        self.locked(move |store| store.remove_blocking(accession_id))
+            .await
+    }
+
+    /// Runs `f` on the blocking pool while holding the lock. A panic in an
+    /// earlier call leaves nothing half done in memory, so a poisoned lock
+    /// is simply taken over.
+    async fn locked<T: Send + 'static>(
+        self: &Arc<Self>,
+        f: impl FnOnce(&Self) -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let store = Arc::clone(self);
+        tokio::task::spawn_blocking(move || {
+            let _guard = store.lock.lock().unwrap_or_else(|e| e.into_inner());
+            f(&store)
+        })
+        .await
+        .map_err(|e| {
+            SdaError::io(
+                format!("Failed to access bookmarks file {}", self.path.display()),
+                std::io::Error::other(e),
+            )
+        })?
+    }
+
+    fn add_blocking(&self, accession_id: i64, note: String) -> Result<(Bookmark, bool)> {
+        let mut bookmarks = self.load()?;
+        let note = Some(note).filter(|n| !n.trim().is_empty());
+        let (bookmark, created) = match bookmarks
+            .iter_mut()
+            .find(|b| b.accession_id == accession_id)
+        {
+            Some(existing) => {
+                existing.note = note;
+                (existing.clone(), false)
+            }
+            None => {
+                let bookmark = Bookmark {
+                    accession_id,
+                    note,
+                    created_at: Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                };
+                bookmarks.push(bookmark.clone());
+                bookmarks.sort_by_key(|b| b.accession_id);
+                (bookmark, true)
+            }
+        };
+        self.save(&bookmarks)?;
+        Ok((bookmark, created))
+    }
+
+    fn remove_blocking(&self, accession_id: i64) -> Result<bool> {
+        let mut bookmarks = self.load()?;
+        let before = bookmarks.len();
+        bookmarks.retain(|b| b.accession_id != accession_id);
+        if bookmarks.len() == before {
+            return Ok(false);
+        }
+        self.save(&bookmarks)?;
+        Ok(true)
+    }
+
+    fn load(&self) -> Result<Vec<Bookmark>> {
+        let text = match std::fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(SdaError::io(
+                    format!("Failed to read bookmarks file {}", self.path.display()),
+                    e,
+                ));
+            }
+        };
+        serde_json::from_str(&text).map_err(|e| {
+            SdaError::decode(
+                format!("Failed to parse bookmarks file {}", self.path.display()),
+                e,
+            )
+        })
+    }
+
+    fn save(&self, bookmarks: &[Bookmark]) -> Result<()> {
+        let context = || format!("Failed to write bookmarks file {}", self.path.display());
+        let mut temp = self.path.clone().into_os_string();
+        temp.push(".tmp");
+        let json =
+            serde_json::to_string_pretty(bookmarks).map_err(|e| SdaError::decode(context(), e))?;
+        std::fs::write(&temp, json).map_err(|e| SdaError::io(context(), e))?;
+        std::fs::rename(&temp, &self.path).map_err(|e| SdaError::io(context(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::TempDir;
+
+    #[tokio::test]
+    async fn test_bookmarks_persist_across_stores() {
+        let dir = TempDir::create("sda-bookmarks-test").unwrap();
+        let path = dir.path().join("bookmarks.json");
+        let store = Arc::new(BookmarkStore::new(path.clone()));
+        assert!(store.list().await.unwrap().is_empty());
+
+        let (bookmark, created) = store.add(12, "flood photos".to_string()).await.unwrap();
+        assert!(created);
+        assert_eq!(bookmark.note.as_deref(), Some("flood photos"));
+        store.add(3, String::new()).await.unwrap();
+        let (bookmark, created) = store.add(12, "  ".to_string()).await.unwrap();
+        assert!(!created);
+        assert_eq!(bookmark.note, None);
+
+        let reopened = Arc::new(BookmarkStore::new(path));
+        let ids: Vec<i64> = reopened
+            .list()
+            .await
+            .unwrap()
+            .iter()
+            .map(|b| b.accession_id)
+            .collect();
+        assert_eq!(ids, [3, 12]);
+        assert!(reopened.remove(3).await.unwrap());
+        assert!(!reopened.remove(3).await.unwrap());
+        assert_eq!(store.list().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_bookmarks_file_is_decode_error() {
+        let dir = TempDir::create("sda-bookmarks-test").unwrap();
+        let path = dir.path().join("bookmarks.json");
+        std::fs::write(&path, "not json").unwrap();
+        let store = Arc::new(BookmarkStore::new(path));
+        assert!(matches!(store.list().await, Err(SdaError::Decode { .. })));
+        assert!(store.add(1, String::new()).await.is_err());
+    }
+}
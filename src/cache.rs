@@ -0,0 +1,455 @@
+//! In-memory TTL cache for read-mostly API responses.
+//!
+//! Subject and collection metadata changes rarely, so `SdaClient` keeps raw
+//! response bodies for a short time, keyed by the full request URL including
+//! its query string. The cache is eventually consistent: edits made outside
+//! this server (another client, the web UI) become visible only once the
+//! cached entry expires. Mutations made through this server clear the whole
+//! cache immediately, since collections embed subjects and subject listings
+//! can be filtered by collection.
+//!
+//! Accessions change as crawls progress, so they are never served from the
+//! cache unchecked. Instead their bodies are kept with the `ETag` the API
+//! sent, as validated entries, and each fetch revalidates with
+//! `If-None-Match`; a `304 Not Modified` reuses the stored body.
+//!
+//! With `--state-file`, the cache is saved on shutdown and loaded back at
+//! startup, with the wall-clock time each entry was fetched and when it
+//! expires, so a restart keeps the remaining lifetime of each entry rather
+//! than renewing it. Entries that expired in the meantime are skipped. The
+//! file is readable by its owner only and records a hash of the base URL and
+//! API key it was saved with; a file saved for another API or key is not
+//! loaded, so one deployment never serves another's (possibly private)
+//! responses.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::{Result, SdaError};
+
+/// A cached response body and the time it was stored.
+struct CacheEntry {
+    /// When the body was fetched from the API.
+    stored_at: Instant,
+    /// Raw response body.
+    body: Vec<u8>,
+}
+
+/// A response body kept for revalidation, with the `ETag` it was sent with.
+struct ValidatedEntry {
+    /// When the body was fetched from the API.
+    stored_at: Instant,
+    /// The `ETag` header of the response.
+    etag: String,
+    /// Raw response body.
+    body: Vec<u8>,
+}
+
+/// One entry as written to the state file.
+#[derive(Serialize, Deserialize)]
+struct SavedEntry {
+    /// Request URL the entry is keyed by.
+    key: String,
+    /// When the body was fetched from the API.
+    stored_at: DateTime<Utc>,
+    /// When the entry expires under the TTL it was cached with.
+    expires_at: DateTime<Utc>,
+    /// `ETag` of a validated entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    etag: Option<String>,
+    /// Response body, which the API sends as JSON text.
+    body: String,
+}
+
+/// Contents of the state file.
+#[derive(Serialize, Deserialize)]
+struct SavedCache {
+    /// `owner_fingerprint` of the client that saved the file.
+    #[serde(default)]
+    owner: String,
+    /// Entries served while fresh.
+    entries: Vec<SavedEntry>,
+    /// Entries served after revalidation.
+    validated: Vec<SavedEntry>,
+}
+
+/// Identifies the API and key a cache was filled with, as a SHA-256 hex
+/// digest so the key itself is never written to the state file.
+pub fn owner_fingerprint(base_url: &str, api_key: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(base_url.as_bytes());
+    hasher.update([0]);
+    hasher.update(api_key.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Thread-safe map of request URL to response body with a fixed time to live.
+///
+/// A zero TTL disables caching entirely.
+pub struct ResponseCache {
+    /// How long an entry stays fresh.
+    ttl: Duration,
+    /// Cached entries keyed by request URL.
+    entries: Mutex<HashMap<String, CacheEntry>>,
+    /// Bodies served only after revalidation, keyed by request URL.
+    validated: Mutex<HashMap<String, ValidatedEntry>>,
+}
+
+impl ResponseCache {
+    /// Creates an empty cache whose entries expire after `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+            validated: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns whether the cache stores anything at all.
+    pub fn is_enabled(&self) -> bool {
+        !self.ttl.is_zero()
+    }
+
+    /// Returns the body cached for `key` if it is still fresh.
+    ///
+    /// Expired entries are evicted on lookup.
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match entries.get(key) {
+            Some(entry) if entry.stored_at.elapsed() < self.ttl => Some(entry.body.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Stores `body` under `key`, replacing any previous entry.
+    pub fn insert(&self, key: String, body: Vec<u8>) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.insert(
+            key,
+            CacheEntry {
+                stored_at: Instant::now(),
+                body,
+            },
+        );
+    }
+
+    /// Returns the `ETag` and body stored for `key` for revalidation, unless
+    /// they are older than the TTL.
+    pub fn get_validated(&self, key: &str) -> Option<(String, Vec<u8>)> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let mut validated = self.validated.lock().unwrap_or_else(|e| e.into_inner());
+        match validated.get(key) {
+            Some(entry) if entry.stored_at.elapsed() < self.ttl => {
+                Some((entry.etag.clone(), entry.body.clone()))
+            }
+            Some(_) => {
+                validated.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Stores `body` and its `etag` under `key` for revalidation.
+    pub fn insert_validated(&self, key: String, etag: String, body: Vec<u8>) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut validated = self.validated.lock().unwrap_or_else(|e| e.into_inner());
+        validated.insert(
+            key,
+            ValidatedEntry {
+                stored_at: Instant::now(),
+                etag,
+                body,
+            },
+        );
+    }
+
+    /// Drops whatever is stored under `key`.
+    pub fn remove(&self, key: &str) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(key);
+        self.validated
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(key);
+    }
+
+    /// Writes every fresh entry to `path` for `owner` (see
+    /// `owner_fingerprint`), replacing the file in one step, and returns how
+    /// many were written. The file is created readable by its owner only.
+    pub fn save(&self, path: &Path, owner: &str) -> Result<usize> {
+        let now = Utc::now();
+        let saved = |key: &String, stored_at: Instant, etag: Option<&String>, body: &[u8]| {
+            let age = stored_at.elapsed();
+            let remaining = self.ttl.checked_sub(age).filter(|r| !r.is_zero())?;
+            Some(SavedEntry {
+                key: key.clone(),
+                stored_at: now - chrono::Duration::from_std(age).ok()?,
+                expires_at: now + chrono::Duration::from_std(remaining).ok()?,
+                etag: etag.cloned(),
+                body: String::from_utf8(body.to_vec()).ok()?,
+            })
+        };
+        let cache = SavedCache {
+            owner: owner.to_string(),
+            entries: self
+                .entries
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .iter()
+                .filter_map(|(key, e)| saved(key, e.stored_at, None, &e.body))
+                .collect(),
+            validated: self
+                .validated
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .iter()
+                .filter_map(|(key, e)| saved(key, e.stored_at, Some(&e.etag), &e.body))
+                .collect(),
+        };
+
+        let context = || format!("Failed to write state file {}", path.display());
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        let json = serde_json::to_vec(&cache).map_err(|e| SdaError::decode(context(), e))?;
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options
+            .open(&temp)
+            .map_err(|e| SdaError::io(context(), e))?;
+        std::io::Write::write_all(&mut file, &json).map_err(|e| SdaError::io(context(), e))?;
+        drop(file);
+        std::fs::rename(&temp, path).map_err(|e| SdaError::io(context(), e))?;
+        Ok(cache.entries.len() + cache.validated.len())
+    }
+
+    /// Loads the entries saved in `path` that have not expired, returning
+    /// how many. An entry expires at the earlier of the time recorded with
+    /// it and its fetch time plus the current TTL. A missing file loads
+    /// nothing, and a file saved for another `owner` is refused.
+    pub fn load(&self, path: &Path, owner: &str) -> Result<usize> {
+        if !self.is_enabled() {
+            return Ok(0);
+        }
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => {
+                return Err(SdaError::io(
+                    format!("Failed to read state file {}", path.display()),
+                    e,
+                ));
+            }
+        };
+        let cache: SavedCache = serde_json::from_str(&text).map_err(|e| {
+            SdaError::decode(format!("Failed to parse state file {}", path.display()), e)
+        })?;
+        if cache.owner != owner {
+            return Err(SdaError::Validation(format!(
+                "State file {} was saved for a different SDA API URL or API key",
+                path.display()
+            )));
+        }
+
+        let now = Utc::now();
+        // The entry's stored time as an `Instant`, placed so that it expires
+        // when it should under this cache's TTL.
+        let restored_at = |entry: &SavedEntry| {
+            let ttl = chrono::Duration::from_std(self.ttl).ok()?;
+            let expires_at = entry.expires_at.min(entry.stored_at + ttl);
+            let remaining = (expires_at - now).to_std().ok().filter(|r| !r.is_zero())?;
+            Instant::now().checked_sub(self.ttl.saturating_sub(remaining))
+        };
+        let mut loaded = 0;
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        for entry in cache.entries {
+            if let Some(stored_at) = restored_at(&entry) {
+                let body = entry.body.into_bytes();
+                entries.insert(entry.key, CacheEntry { stored_at, body });
+                loaded += 1;
+            }
+        }
+        let mut validated = self.validated.lock().unwrap_or_else(|e| e.into_inner());
+        for entry in cache.validated {
+            if let (Some(stored_at), Some(etag)) = (restored_at(&entry), entry.etag) {
+                let body = entry.body.into_bytes();
+                validated.insert(
+                    entry.key,
+                    ValidatedEntry {
+                        stored_at,
+                        etag,
+                        body,
+                    },
+                );
+                loaded += 1;
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Drops every cached entry.
+    pub fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+        self.validated
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_returns_fresh_entry() {
+        let cache = ResponseCache::new(Duration::from_secs(60));
+        cache.insert(
+            "https://api/subjects?lang=english".to_string(),
+            b"[]".to_vec(),
+        );
+
+        assert_eq!(
+            cache.get("https://api/subjects?lang=english"),
+            Some(b"[]".to_vec())
+        );
+        assert_eq!(cache.get("https://api/subjects?lang=arabic"), None);
+    }
+
+    #[test]
+    fn test_get_evicts_expired_entry() {
+        let cache = ResponseCache::new(Duration::from_millis(1));
+        cache.insert("key".to_string(), b"{}".to_vec());
+        std::thread::sleep(Duration::from_millis(5));
+
+        assert_eq!(cache.get("key"), None);
+        assert!(cache.entries.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_zero_ttl_disables_cache() {
+        let cache = ResponseCache::new(Duration::ZERO);
+        cache.insert("key".to_string(), b"{}".to_vec());
+
+        assert!(!cache.is_enabled());
+        assert_eq!(cache.get("key"), None);
+    }
+
+    #[test]
+    fn test_validated_entries_are_separate_and_removable() {
+        let cache = ResponseCache::new(Duration::from_secs(60));
+        cache.insert_validated("key".to_string(), "\"v1\"".to_string(), b"{}".to_vec());
+
+        assert_eq!(cache.get("key"), None);
+        assert_eq!(
+            cache.get_validated("key"),
+            Some(("\"v1\"".to_string(), b"{}".to_vec()))
+        );
+        cache.remove("key");
+        assert_eq!(cache.get_validated("key"), None);
+    }
+
+    #[test]
+    fn test_clear_drops_entries() {
+        let cache = ResponseCache::new(Duration::from_secs(60));
+        cache.insert("key".to_string(), b"{}".to_vec());
+        cache.clear();
+
+        assert_eq!(cache.get("key"), None);
+    }
+
+    #[test]
+    fn test_state_file_restores_fresh_entries_only() {
+        let dir = crate::export::TempDir::create("sda-cache-state-test").unwrap();
+        let path = dir.path().join("state.json");
+        let cache = ResponseCache::new(Duration::from_secs(60));
+        let owner = owner_fingerprint("https://api.test", "key");
+        assert_eq!(cache.load(&path, &owner).unwrap(), 0);
+        cache.insert("subjects".to_string(), b"[]".to_vec());
+        cache.insert_validated(
+            "accession".to_string(),
+            "\"v1\"".to_string(),
+            b"{}".to_vec(),
+        );
+        assert_eq!(cache.save(&path, &owner).unwrap(), 2);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // A file saved for another API or key is refused.
+        let other = ResponseCache::new(Duration::from_secs(60));
+        for stranger in [
+            owner_fingerprint("https://api.test", "other-key"),
+            owner_fingerprint("https://other.test", "key"),
+        ] {
+            assert!(matches!(
+                other.load(&path, &stranger),
+                Err(SdaError::Validation(_))
+            ));
+        }
+        assert_eq!(other.get("subjects"), None);
+
+        let restarted = ResponseCache::new(Duration::from_secs(60));
+        assert_eq!(restarted.load(&path, &owner).unwrap(), 2);
+        assert_eq!(restarted.get("subjects"), Some(b"[]".to_vec()));
+        assert_eq!(
+            restarted.get_validated("accession"),
+            Some(("\"v1\"".to_string(), b"{}".to_vec()))
+        );
+
+        // Entries past their recorded expiry, or past a shorter TTL, are skipped.
+        let now = Utc::now();
+        let entry = |key: &str, age: i64, lifetime: i64| SavedEntry {
+            key: key.to_string(),
+            stored_at: now - chrono::Duration::seconds(age),
+            expires_at: now - chrono::Duration::seconds(age) + chrono::Duration::seconds(lifetime),
+            etag: None,
+            body: "[]".to_string(),
+        };
+        let saved = SavedCache {
+            owner: owner.clone(),
+            entries: vec![
+                entry("fresh", 10, 60),
+                entry("expired", 120, 60),
+                entry("beyond-ttl", 40, 300),
+            ],
+            validated: Vec::new(),
+        };
+        std::fs::write(&path, serde_json::to_vec(&saved).unwrap()).unwrap();
+        let shorter = ResponseCache::new(Duration::from_secs(30));
+        assert_eq!(shorter.load(&path, &owner).unwrap(), 1);
+        assert!(shorter.get("fresh").is_some());
+        assert!(shorter.get("expired").is_none());
+        assert!(shorter.get("beyond-ttl").is_none());
+    }
+}
@@ -0,0 +1,500 @@
+//! Recording SDA API exchanges to files for support cases.
+//!
+//! With `--debug-capture-dir`, each request `SdaClient` sends is written to
+//! its own text file in that directory together with the response: the
+//! method, URL, headers and body of the request, then the status, headers
+//! and body of the response, or the error that prevented one. The API key
+//! and other sensitive headers are replaced with `<redacted>`, as is the
+//! query of URLs outside the API, which for pre-signed archive URLs holds the
+//! signature. Bodies are cut after `MAX_CAPTURED_BODY_BYTES`, only the newest
+//! files are kept, and on Unix only the owner can read them.
+//!
+//! File names start with the time the request was sent, so they sort in
+//! order. The response body is copied as the caller reads it, so downloads
+//! stay streamed; the file is written once the body has been read to the
+//! end or dropped. Files are written and rotated on a background thread, so
+//! recording never blocks the async runtime.
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use chrono::Utc;
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use reqwest::header::{AUTHORIZATION, COOKIE, HeaderMap, SET_COOKIE};
+use reqwest::{Body, Request, Response, ResponseBuilderExt, Url};
+
+use crate::error::{Result, SdaError};
+use crate::middleware::API_KEY_HEADER;
+
+/// Default number of capture files kept.
+pub const DEFAULT_MAX_CAPTURE_FILES: usize = 200;
+
+/// Largest part of a request or response body written to a capture file.
+const MAX_CAPTURED_BODY_BYTES: usize = 256 * 1024;
+
+/// Prefix of capture file names, so rotation never touches other files.
+const FILE_PREFIX: &str = "sda-";
+
+/// Work for the background writer.
+#[derive(Debug)]
+enum Job {
+    /// Write a capture file and rotate out the oldest.
+    Write { name: String, text: String },
+    /// Reply once every earlier job is done.
+    #[cfg(test)]
+    Flush(mpsc::Sender<()>),
+}
+
+/// The capture directory.
+#[derive(Debug)]
+pub struct DebugCapture {
+    /// Directory the files are written to.
+    dir: PathBuf,
+    /// Distinguishes requests sent in the same millisecond.
+    sequence: AtomicU64,
+    /// Queue of the background writer, which stops when this is dropped.
+    jobs: mpsc::Sender<Job>,
+}
+
+impl DebugCapture {
+    /// Creates a capture writing to `dir`, which is created if missing,
+    /// and keeping at most `max_files` files (at least one). Files left by
+    /// earlier runs count towards the limit.
+    pub fn new(dir: PathBuf, max_files: usize) -> Result<Self> {
+        let context = || format!("Failed to create debug capture directory {}", dir.display());
+        std::fs::create_dir_all(&dir).map_err(|e| SdaError::io(context(), e))?;
+        let mut existing: Vec<String> = std::fs::read_dir(&dir)
+            .map_err(|e| SdaError::io(context(), e))?
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|name| name.starts_with(FILE_PREFIX) && name.ends_with(".txt"))
+            .collect();
+        existing.sort();
+        let mut writer = Writer {
+            dir: dir.clone(),
+            max_files: max_files.max(1),
+            files: existing.into(),
+        };
+        writer.rotate();
+        let (jobs, queue) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("debug-capture".to_string())
+            .spawn(move || writer.run(queue))
+            .map_err(|e| SdaError::io("Failed to start the debug capture writer", e))?;
+        Ok(Self {
+            dir,
+            sequence: AtomicU64::new(0),
+            jobs,
+        })
+    }
+
+    /// Directory the files are written to.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Starts recording the exchange beginning with `request`. Unless
+    /// `is_api`, the query of its URL is hidden.
+    pub fn start(self: &Arc<Self>, request: &Request, is_api: bool) -> Exchange {
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed);
+        let name = format!(
+            "{}{}-{:06}-{}.txt",
+            FILE_PREFIX,
+            Utc::now().format("%Y%m%dT%H%M%S%.3fZ"),
+            sequence,
+            request.method()
+        );
+        let shown_url = shown_url(request.url(), is_api);
+        let mut text = format!("> {} {}\n", request.method(), shown_url);
+        write_headers(&mut text, '>', request.headers());
+        text.push('\n');
+        match request.body() {
+            None => {}
+            Some(body) => match body.as_bytes() {
+                Some(bytes) => write_body(&mut text, bytes, bytes.len()),
+                None => text.push_str("<streamed body, not captured>\n"),
+            },
+        }
+        let hidden_url =
+            (shown_url != request.url().as_str()).then(|| (request.url().to_string(), shown_url));
+        Exchange {
+            capture: Arc::clone(self),
+            name,
+            text,
+            hidden_url,
+            started: Instant::now(),
+        }
+    }
+
+    /// Queues one capture file for the background writer.
+    fn write(&self, name: String, text: String) {
+        // The writer only stops when the capture is dropped.
+        let _ = self.jobs.send(Job::Write { name, text });
+    }
+
+    /// Waits until every file queued so far is written.
+    #[cfg(test)]
+    fn flush(&self) {
+        let (done, wait) = mpsc::channel();
+        if self.jobs.send(Job::Flush(done)).is_ok() {
+            let _ = wait.recv();
+        }
+    }
+}
+
+/// Writes capture files on a dedicated thread, keeping the names of the
+/// files on disk in memory so rotation never lists the directory.
+struct Writer {
+    /// Directory the files are written to.
+    dir: PathBuf,
+    /// Number of files kept; older ones are deleted.
+    max_files: usize,
+    /// Capture files on disk, oldest first.
+    files: VecDeque<String>,
+}
+
+impl Writer {
+    /// Handles jobs until the capture is dropped.
+    fn run(mut self, queue: mpsc::Receiver<Job>) {
+        for job in queue {
+            match job {
+                Job::Write { name, text } => self.write(name, &text),
+                #[cfg(test)]
+                Job::Flush(done) => {
+                    let _ = done.send(());
+                }
+            }
+        }
+    }
+
+    /// Writes one capture file, readable only by its owner on Unix, and
+    /// deletes the oldest beyond `max_files`.
+    fn write(&mut self, name: String, text: &str) {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let written = options
+            .open(self.dir.join(&name))
+            .and_then(|mut file| std::io::Write::write_all(&mut file, text.as_bytes()));
+        if let Err(e) = written {
+            tracing::warn!("Failed to write debug capture {}: {}", name, e);
+            return;
+        }
+        self.files.push_back(name);
+        self.rotate();
+    }
+
+    /// Deletes the oldest files beyond `max_files`.
+    fn rotate(&mut self) {
+        while self.files.len() > self.max_files {
+            if let Some(old) = self.files.pop_front() {
+                let _ = std::fs::remove_file(self.dir.join(old));
+            }
+        }
+    }
+}
+
+/// One request being recorded, written out when its response is complete.
+#[derive(Debug)]
+pub struct Exchange {
+    /// Where the file goes.
+    capture: Arc<DebugCapture>,
+    /// File name.
+    name: String,
+    /// Text recorded so far.
+    text: String,
+    /// The request URL and how it is shown, when its query is hidden.
+    hidden_url: Option<(String, String)>,
+    /// When the request was sent.
+    started: Instant,
+}
+
+impl Exchange {
+    /// Records the error that prevented a response and writes the file.
+    pub fn fail(mut self, error: &dyn std::fmt::Display) {
+        let mut error = error.to_string();
+        if let Some((url, shown)) = &self.hidden_url {
+            error = error.replace(url.as_str(), shown);
+        }
+        let _ = writeln!(
+            self.text,
+            "\n! {} ({} ms)",
+            error,
+            self.started.elapsed().as_millis()
+        );
+        self.capture.write(self.name, self.text);
+    }
+
+    /// Records the status and headers of `response` and returns it with its
+    /// body copied into the file as it is read.
+    pub fn wrap(mut self, response: Response) -> Response {
+        let _ = writeln!(
+            self.text,
+            "\n< {} ({} ms)",
+            response.status(),
+            self.started.elapsed().as_millis()
+        );
+        write_headers(&mut self.text, '<', response.headers());
+        self.text.push('\n');
+
+        let url = response.url().clone();
+        let (parts, body) = http::Response::<Body>::from(response).into_parts();
+        let head = http::Response::builder()
+            .status(parts.status)
+            .version(parts.version)
+            .url(url)
+            .body(());
+        let mut head = match head {
+            Ok(head) => head,
+            Err(e) => {
+                // Return the response uncaptured rather than lose it.
+                self.fail(&format!("response could not be captured: {}", e));
+                return Response::from(http::Response::from_parts(parts, body));
+            }
+        };
+        *head.headers_mut() = parts.headers;
+        head.extensions_mut().extend(parts.extensions);
+        let body = CapturedBody {
+            inner: body,
+            exchange: Some(self),
+            captured: Vec::new(),
+            total: 0,
+        };
+        Response::from(head.map(|()| Body::wrap(body)))
+    }
+
+    /// Appends the body and an optional note, and writes the file.
+    fn finish(mut self, body: &[u8], total: usize, note: Option<&str>) {
+        write_body(&mut self.text, body, total);
+        if let Some(note) = note {
+            let _ = writeln!(self.text, "! {}", note);
+        }
+        self.capture.write(self.name, self.text);
+    }
+}
+
+/// A response body copied into its capture file as it is read.
+struct CapturedBody {
+    /// The original body.
+    inner: Body,
+    /// The exchange, until the file is written.
+    exchange: Option<Exchange>,
+    /// Start of the body, up to `MAX_CAPTURED_BODY_BYTES`.
+    captured: Vec<u8>,
+    /// Bytes read so far.
+    total: usize,
+}
+
+impl CapturedBody {
+    fn finish(&mut self, note: Option<&str>) {
+        if let Some(exchange) = self.exchange.take() {
+            exchange.finish(&self.captured, self.total, note);
+        }
+    }
+}
+
+impl HttpBody for CapturedBody {
+    type Data = <Body as HttpBody>::Data;
+    type Error = reqwest::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<std::result::Result<Frame<Self::Data>, Self::Error>>> {
+        let this = &mut *self;
+        let poll = Pin::new(&mut this.inner).poll_frame(cx);
+        match &poll {
+            Poll::Ready(Some(Ok(frame))) => {
+                if let Some(data) = frame.data_ref() {
+                    let room = MAX_CAPTURED_BODY_BYTES.saturating_sub(this.captured.len());
+                    this.captured
+                        .extend_from_slice(&data[..room.min(data.len())]);
+                    this.total += data.len();
+                }
+            }
+            Poll::Ready(Some(Err(e))) => this.finish(Some(&format!("body failed: {}", e))),
+            Poll::Ready(None) => this.finish(None),
+            Poll::Pending => {}
+        }
+        poll
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl Drop for CapturedBody {
+    fn drop(&mut self) {
+        self.finish(Some("body not read to the end"));
+    }
+}
+
+/// Returns `url` as written to a capture file: as is for the API, and
+/// otherwise with its query replaced by `<redacted>`.
+fn shown_url(url: &Url, is_api: bool) -> String {
+    if is_api || url.query().is_none() {
+        return url.to_string();
+    }
+    let mut shown = url.clone();
+    shown.set_query(None);
+    shown.set_fragment(None);
+    format!("{}?<redacted>", shown)
+}
+
+/// Appends `headers`, one per line after `marker`, hiding secrets.
+fn write_headers(text: &mut String, marker: char, headers: &HeaderMap) {
+    for (name, value) in headers {
+        let hidden = value.is_sensitive()
+            || name == API_KEY_HEADER
+            || name == AUTHORIZATION
+            || name == COOKIE
+            || name == SET_COOKIE;
+        let value = if hidden {
+            "<redacted>"
+        } else {
+            value.to_str().unwrap_or("<binary>")
+        };
+        let _ = writeln!(text, "{} {}: {}", marker, name, value);
+    }
+}
+
+/// Appends the captured start of a body of `total` bytes.
+fn write_body(text: &mut String, captured: &[u8], total: usize) {
+    if total == 0 {
+        return;
+    }
+    text.push_str(&String::from_utf8_lossy(captured));
+    if total > captured.len() {
+        let _ = write!(
+            text,
+            "\n<{} more bytes not captured>",
+            total - captured.len()
+        );
+    }
+    text.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::TempDir;
+    use reqwest::header::HeaderValue;
+    use reqwest::{Method, Url};
+
+    fn captured_files(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[tokio::test]
+    async fn test_capture_records_exchange_without_api_key() {
+        let dir = TempDir::create("sda-capture-test").unwrap();
+        let capture = Arc::new(DebugCapture::new(dir.path().join("captures"), 2).unwrap());
+        let mut request = Request::new(
+            Method::POST,
+            Url::parse("https://api.test/api/v1/subjects").unwrap(),
+        );
+        let mut key = HeaderValue::from_static("secret-key");
+        key.set_sensitive(true);
+        request.headers_mut().insert(API_KEY_HEADER, key);
+        *request.body_mut() = Some(Body::from(r#"{"metadata_subject":"Darfur"}"#));
+
+        let response = Response::from(
+            http::Response::builder()
+                .status(201)
+                .header("content-type", "application/json")
+                .header("set-cookie", "session=abc")
+                .body(r#"{"id":7}"#)
+                .unwrap(),
+        );
+        let response = capture.start(&request, true).wrap(response);
+        assert_eq!(response.status(), 201);
+        assert_eq!(response.content_length(), Some(8));
+        assert_eq!(response.text().await.unwrap(), r#"{"id":7}"#);
+
+        capture.flush();
+        let files = captured_files(capture.dir());
+        assert_eq!(files.len(), 1);
+        let text = std::fs::read_to_string(capture.dir().join(&files[0])).unwrap();
+        assert!(
+            text.starts_with("> POST https://api.test/api/v1/subjects\n"),
+            "{}",
+            text
+        );
+        assert!(text.contains("> x-api-key: <redacted>"), "{}", text);
+        assert!(!text.contains("secret-key"));
+        assert!(text.contains(r#"{"metadata_subject":"Darfur"}"#));
+        assert!(text.contains("< 201 Created"));
+        assert!(text.contains("< content-type: application/json"));
+        assert!(text.contains("< set-cookie: <redacted>"), "{}", text);
+        assert!(!text.contains("session=abc"));
+        assert!(text.trim_end().ends_with(r#"{"id":7}"#), "{}", text);
+
+        // Only the newest files are kept.
+        for _ in 0..3 {
+            let response = Response::from(http::Response::new("ok"));
+            drop(capture.start(&request, true).wrap(response));
+        }
+        capture.flush();
+        let newest = captured_files(capture.dir());
+        assert_eq!(newest.len(), 2);
+        assert!(!newest.contains(&files[0]));
+        let text = std::fs::read_to_string(capture.dir().join(&newest[1])).unwrap();
+        assert!(text.contains("! body not read to the end"), "{}", text);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let metadata = std::fs::metadata(capture.dir().join(&newest[1])).unwrap();
+            assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+        }
+
+        // Files left by an earlier run count towards the limit.
+        let restarted = DebugCapture::new(capture.dir().to_path_buf(), 1).unwrap();
+        assert_eq!(captured_files(restarted.dir()), [newest[1].clone()]);
+    }
+
+    #[test]
+    fn test_capture_hides_query_of_urls_outside_the_api() {
+        let dir = TempDir::create("sda-capture-test").unwrap();
+        let capture = Arc::new(DebugCapture::new(dir.path().to_path_buf(), 2).unwrap());
+        let url = "https://files.test/5.wacz?X-Amz-Signature=abc&X-Amz-Credential=key";
+        let request = Request::new(Method::GET, Url::parse(url).unwrap());
+        capture
+            .start(&request, false)
+            .fail(&format!("error sending request for url ({})", url));
+        let api = Request::new(
+            Method::GET,
+            Url::parse("https://api.test/api/v1/accessions?page=0").unwrap(),
+        );
+        capture.start(&api, true).fail(&"timed out");
+
+        capture.flush();
+        let files = captured_files(capture.dir());
+        let download = std::fs::read_to_string(capture.dir().join(&files[0])).unwrap();
+        assert!(
+            download.starts_with("> GET https://files.test/5.wacz?<redacted>\n"),
+            "{}",
+            download
+        );
+        assert!(!download.contains("X-Amz"), "{}", download);
+        let listing = std::fs::read_to_string(capture.dir().join(&files[1])).unwrap();
+        assert!(listing.starts_with("> GET https://api.test/api/v1/accessions?page=0\n"));
+    }
+}
@@ -2,62 +2,837 @@
 //!
 //! This module provides a client for making HTTP requests to the SDA API.
 
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::cache::ResponseCache;
+use crate::capture::DebugCapture;
+use crate::crawl_policy::CrawlPolicy;
+use crate::dates;
+use crate::diagnostics;
+use crate::diff;
+use crate::error::{Result, SdaError};
+use crate::export;
+use crate::language;
+use crate::middleware::{self, Middleware};
 use crate::model::*;
-use anyhow::{Context, Result};
-use reqwest::Client;
+use crate::preview;
+use crate::remote_zip::{self, ZipEntry};
+use crate::schema_check;
+use crate::validation;
+use crate::wacz;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use governor::{Quota, RateLimiter};
+use http::Extensions;
+use reqwest::header::{
+    CONTENT_RANGE, CONTENT_TYPE, DATE, ETAG, HeaderMap, HeaderName, HeaderValue, IF_MATCH,
+    IF_NONE_MATCH, RANGE,
+};
+use reqwest::{Certificate, Client, Proxy, StatusCode};
+use reqwest_middleware::{ClientWithMiddleware, RequestBuilder};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Semaphore;
+
+/// Maximum number of requests issued concurrently by batch operations.
+const BATCH_CONCURRENCY: usize = 8;
+
+/// Default number of times a rate-limited request is retried: once, so a
+/// throttled call fails after a single wait instead of piling up delays.
+const DEFAULT_MAX_RETRIES: u32 = 1;
+
+/// Default upper bound on how long to wait before retrying a rate-limited request.
+const DEFAULT_MAX_RETRY_WAIT: Duration = Duration::from_secs(60);
+
+/// Default maximum number of outbound requests in flight at once.
+pub const DEFAULT_MAX_CONCURRENCY: usize = 8;
+
+/// Default number of idle connections kept open to the API, matching the
+/// default concurrency so a full burst can reuse warm connections.
+pub const DEFAULT_POOL_MAX_IDLE_PER_HOST: usize = DEFAULT_MAX_CONCURRENCY;
+
+/// Default time an idle pooled connection is kept before it is closed.
+pub const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Default timeout of ordinary API requests: reads, updates and deletes.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default timeout of a request that starts a crawl, which the API may only
+/// answer once the crawl has been set up.
+pub const DEFAULT_CRAWL_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Default timeout of a whole WACZ download, reading the body included.
+pub const DEFAULT_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Default time to live of cached subject and collection responses.
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Default cap on the size of a JSON response body (64 MiB).
+pub const DEFAULT_MAX_RESPONSE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Header carrying a crawl request's idempotency key.
+const IDEMPOTENCY_KEY: &str = "idempotency-key";
+
+/// Default `User-Agent` sent with every request.
+const DEFAULT_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// Index of the first page in the API's zero-based pagination.
+pub const FIRST_PAGE: i64 = 0;
+
+/// Largest page list `inspect_wacz` fetches with range requests, in
+/// compressed bytes. Larger lists are skipped and the page count omitted.
+const MAX_INSPECT_PAGES_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Largest archive `get_wacz_page` and `get_accession_content` download,
+/// and `list_wacz_pages` and `inspect_wacz` when the server ignores ranges,
+/// in bytes. Larger archives are refused part way.
+const MAX_WACZ_PAGES_DOWNLOAD_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Redirects followed when `crawl_diagnostics` checks a seed URL, as in
+/// `preview_crawl_target`'s default.
+const DIAGNOSTIC_MAX_REDIRECTS: u32 = 10;
+
+/// Most matches `search` returns per category.
+const MAX_SEARCH_LIMIT: i64 = 20;
+
+/// Most buckets `accessions_timeline` returns; a narrower range or wider
+/// bucket is needed beyond this.
+const MAX_TIMELINE_BUCKETS: usize = 400;
+
+/// Timeout of the request made by `health_check`.
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Bytes of an error response body kept in `SdaError::Http`; the rest is
+/// not read.
+const MAX_ERROR_BODY_BYTES: usize = 4 * 1024;
+
+/// Pagination state carried between iterations of `accessions_stream`.
+struct AccessionPager {
+    /// Filters and page size applied to every page request.
+    args: ListAccessionsArgs,
+    /// Next page to request, or `None` once the last page has been fetched.
+    next_page: Option<i64>,
+    /// Items from the current page not yet yielded.
+    buffer: VecDeque<AccessionsWithMetadataResponse>,
+    /// `num_pages` reported by the first page fetched.
+    num_pages: Option<i64>,
+}
+
+impl AccessionPager {
+    /// Records the `num_pages` of a fetched page, returning the previously
+    /// seen value if it differs.
+    ///
+    /// The API only supports offset pagination, so accessions added or
+    /// removed mid-fetch shift later pages and items can be skipped or
+    /// repeated; a changing page count is the visible symptom.
+    fn observe_num_pages(&mut self, num_pages: i64) -> Option<i64> {
+        match self.num_pages.replace(num_pages) {
+            Some(previous) if previous != num_pages => Some(previous),
+            _ => None,
+        }
+    }
+}
+
+/// Members of every collection, cached by `accession_collections`.
+#[derive(Serialize, Deserialize)]
+struct MembershipIndex {
+    /// Each collection with the IDs of its accessions.
+    collections: Vec<(CollectionResponse, Vec<i64>)>,
+}
+
+/// Outcome of a completed WACZ download.
+#[derive(Debug)]
+pub struct WaczDownload {
+    /// Number of bytes written to disk.
+    pub bytes: u64,
+    /// Hex-encoded SHA-256 of the downloaded bytes.
+    pub sha256: String,
+    /// Whether the size was checked against the server's `Content-Length`.
+    pub length_verified: bool,
+}
+
+/// A subject name looked up by `lookup_subject_names`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NamedSubject {
+    /// ID of the existing subject with the name.
+    Existing(i64),
+    /// A name no subject has yet, created by `create_named_subjects`.
+    Missing(String),
+}
+
+/// A temporary file that is deleted when dropped.
+struct TempFile(std::path::PathBuf);
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
 
 /// Client for interacting with the Sudan Digital Archive API.
 #[derive(Clone)]
 pub struct SdaClient {
-    /// Internal HTTP client.
-    client: Client,
+    /// Internal HTTP client, sending every request through the layers in
+    /// `middleware`.
+    client: ClientWithMiddleware,
+    /// Base URL of the SDA API.
+    base_url: String,
+    /// The layer adding the API key, also in the client's stack.
+    api_key: Arc<middleware::ApiKey>,
+    /// Timeout of requests that start a crawl.
+    crawl_timeout: Duration,
+    /// Timeout of WACZ downloads.
+    download_timeout: Duration,
+    /// Cache of read-mostly subject and collection responses, shared by all clones.
+    cache: Arc<ResponseCache>,
+    /// Largest JSON response body that is read into memory.
+    max_response_bytes: u64,
+    /// Whether decoded responses are checked for fields the models do not know.
+    strict_schema: bool,
+}
+
+/// Builder for `SdaClient`.
+///
+/// Every setting other than the base URL and API key has a sensible default,
+/// so new options can be added without changing existing call sites.
+pub struct SdaClientBuilder {
     /// Base URL of the SDA API.
     base_url: String,
     /// API key for authentication.
     api_key: String,
+    /// Timeout of ordinary requests.
+    timeout: Duration,
+    /// Timeout of requests that start a crawl.
+    crawl_timeout: Duration,
+    /// Timeout of WACZ downloads.
+    download_timeout: Duration,
+    /// Number of times a rate-limited (429) request is retried.
+    max_retries: u32,
+    /// Upper bound on the wait before retrying a rate-limited request.
+    max_retry_wait: Duration,
+    /// `User-Agent` header value.
+    user_agent: String,
+    /// Outbound proxy for all API traffic.
+    proxy: Option<Proxy>,
+    /// Maximum number of requests in flight at once.
+    max_concurrency: usize,
+    /// Maximum number of requests started per second; `0` means unlimited.
+    rate_limit_rps: u32,
+    /// Maximum number of idle pooled connections per host.
+    pool_max_idle_per_host: usize,
+    /// How long an idle pooled connection is kept open.
+    pool_idle_timeout: Duration,
+    /// Time to live of cached subject and collection responses.
+    cache_ttl: Duration,
+    /// Largest JSON response body that is read into memory.
+    max_response_bytes: u64,
+    /// Extra trusted root certificates, added to the built-in roots.
+    root_certificates: Vec<Certificate>,
+    /// Skip TLS certificate verification entirely.
+    accept_invalid_certs: bool,
+    /// Extra headers added to every request sent to the API.
+    extra_headers: HeaderMap,
+    /// Whether decoded responses are checked for fields the models do not know.
+    strict_schema: bool,
+    /// Layers added after the built-in ones.
+    middleware: Vec<Arc<dyn Middleware>>,
+    /// Where exchanges are recorded, if anywhere.
+    capture: Option<Arc<DebugCapture>>,
 }
 
-impl SdaClient {
-    /// Creates a new `SdaClient` with the given base URL and API key.
-    pub fn new(base_url: String, api_key: String) -> Self {
+impl Default for SdaClientBuilder {
+    fn default() -> Self {
         Self {
-            client: Client::new(),
+            base_url: String::new(),
+            api_key: String::new(),
+            timeout: DEFAULT_TIMEOUT,
+            crawl_timeout: DEFAULT_CRAWL_TIMEOUT,
+            download_timeout: DEFAULT_DOWNLOAD_TIMEOUT,
+            max_retries: DEFAULT_MAX_RETRIES,
+            max_retry_wait: DEFAULT_MAX_RETRY_WAIT,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            proxy: None,
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            rate_limit_rps: 0,
+            pool_max_idle_per_host: DEFAULT_POOL_MAX_IDLE_PER_HOST,
+            pool_idle_timeout: DEFAULT_POOL_IDLE_TIMEOUT,
+            cache_ttl: DEFAULT_CACHE_TTL,
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            root_certificates: Vec::new(),
+            accept_invalid_certs: false,
+            extra_headers: HeaderMap::new(),
+            strict_schema: cfg!(debug_assertions),
+            middleware: Vec::new(),
+            capture: None,
+        }
+    }
+}
+
+impl SdaClientBuilder {
+    /// Creates a builder with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the base URL of the SDA API. Trailing slashes are ignored, so
+    /// `https://host/sda-api/` and `https://host/sda-api` are equivalent.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Sets the API key sent in the `x-api-key` header.
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = api_key.into();
+        self
+    }
+
+    /// Sets the timeout of ordinary requests: reads, updates and deletes.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the timeout of requests that start a crawl.
+    pub fn crawl_timeout(mut self, timeout: Duration) -> Self {
+        self.crawl_timeout = timeout;
+        self
+    }
+
+    /// Sets the timeout of a whole WACZ download, reading the body included.
+    pub fn download_timeout(mut self, timeout: Duration) -> Self {
+        self.download_timeout = timeout;
+        self
+    }
+
+    /// Sets how many times a rate-limited (429) request is retried.
+    #[allow(dead_code)]
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets the maximum time to wait before retrying a rate-limited request.
+    pub fn max_retry_wait(mut self, max_retry_wait: Duration) -> Self {
+        self.max_retry_wait = max_retry_wait;
+        self
+    }
+
+    /// Sets the `User-Agent` header value.
+    #[allow(dead_code)]
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Routes all API traffic through the given proxy.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Sets how many idle connections per host are kept for reuse. `0` disables pooling.
+    pub fn pool_max_idle_per_host(mut self, max_idle: usize) -> Self {
+        self.pool_max_idle_per_host = max_idle;
+        self
+    }
+
+    /// Sets how long an idle pooled connection is kept open before it is closed.
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = timeout;
+        self
+    }
+
+    /// Sets the maximum number of requests in flight at once. Values below 1 are treated as 1.
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    /// Caps how many requests are started per second, spacing them evenly
+    /// instead of letting them go out in bursts. `0` removes the cap.
+    pub fn rate_limit_rps(mut self, rate_limit_rps: u32) -> Self {
+        self.rate_limit_rps = rate_limit_rps;
+        self
+    }
+
+    /// Sets how long subject and collection responses are cached. Zero disables the cache.
+    pub fn cache_ttl(mut self, cache_ttl: Duration) -> Self {
+        self.cache_ttl = cache_ttl;
+        self
+    }
+
+    /// Trusts `certificate` as an additional root, e.g. the CA of an internal mirror.
+    pub fn add_root_certificate(mut self, certificate: Certificate) -> Self {
+        self.root_certificates.push(certificate);
+        self
+    }
+
+    /// Disables TLS certificate verification. Dangerous: any server can
+    /// impersonate the API, so only use this for testing.
+    pub fn danger_accept_invalid_certs(mut self, accept_invalid_certs: bool) -> Self {
+        self.accept_invalid_certs = accept_invalid_certs;
+        self
+    }
+
+    /// Sets the largest JSON response body that is read; larger responses fail.
+    pub fn max_response_bytes(mut self, max_response_bytes: u64) -> Self {
+        self.max_response_bytes = max_response_bytes;
+        self
+    }
+
+    /// Adds a header sent on every API request, e.g. one required by a
+    /// gateway in front of a mirror. Repeating a name sends every value.
+    /// Headers a request sets itself, such as `x-api-key`, `X-Request-Id`
+    /// and `Range`, take precedence over these.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.extra_headers.append(name, value);
+        self
+    }
+
+    /// Checks every decoded response against the raw JSON, warning about
+    /// fields the models do not know and quoting the body in decode errors.
+    /// On by default in debug builds.
+    pub fn strict_schema(mut self, strict_schema: bool) -> Self {
+        self.strict_schema = strict_schema;
+        self
+    }
+
+    /// Adds a layer run around every request, after the built-in ones, e.g.
+    /// to trace requests or capture their bodies. Layers run in the order
+    /// they are added.
+    pub fn middleware(mut self, layer: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(layer));
+        self
+    }
+
+    /// Records every request and its response to a file in the capture's
+    /// directory, for reproducing API problems.
+    pub fn debug_capture(mut self, capture: impl Into<Arc<DebugCapture>>) -> Self {
+        self.capture = Some(capture.into());
+        self
+    }
+
+    /// Builds the `SdaClient`, failing if the base URL is not an absolute
+    /// `http` or `https` URL or the HTTP client cannot be configured.
+    pub fn build(self) -> Result<SdaClient> {
+        check_base_url(&self.base_url)?;
+        // Advertise gzip and brotli so large listings travel compressed;
+        // bodies are decompressed transparently while they are read, so the
+        // per-request timeout covers decompression too.
+        let mut http = Client::builder()
+            .user_agent(self.user_agent.clone())
+            .gzip(true)
+            .brotli(true)
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .pool_idle_timeout(self.pool_idle_timeout);
+        if let Some(proxy) = self.proxy.clone() {
+            http = http.proxy(proxy);
+        }
+        if !self.root_certificates.is_empty() {
+            http = http.tls_certs_merge(self.root_certificates.clone());
+        }
+        if self.accept_invalid_certs {
+            http = http.tls_danger_accept_invalid_certs(true);
+        }
+        let client = http
+            .build()
+            .map_err(|e| SdaError::network("Failed to build HTTP client", e))?;
+        self.assemble(client)
+    }
+
+    /// Combines the builder's settings with a ready HTTP client, wrapping
+    /// it in the layers described in the `middleware` module.
+    fn assemble(self, client: Client) -> Result<SdaClient> {
+        let base_url = self.base_url.trim_end_matches('/').to_string();
+        let api_key = middleware::ApiKey::new(&base_url, &self.api_key).map_err(|_| {
+            SdaError::Validation("The API key is not a valid header value".to_string())
+        })?;
+        let api_key = Arc::new(api_key);
+        let limits = middleware::Limits {
+            timeout: self.timeout,
+            concurrency: Arc::new(Semaphore::new(self.max_concurrency)),
+            // A burst of one spaces requests evenly, so auto-pagination and
+            // batches never send a second's worth of requests at once.
+            rate_limiter: NonZeroU32::new(self.rate_limit_rps).map(|rps| {
+                Arc::new(RateLimiter::direct(
+                    Quota::per_second(rps).allow_burst(NonZeroU32::MIN),
+                ))
+            }),
+        };
+        let mut stack = reqwest_middleware::ClientBuilder::new(client)
+            .with(middleware::RetryRateLimited {
+                max_retries: self.max_retries,
+                max_retry_wait: self.max_retry_wait,
+            })
+            .with_arc(api_key.clone())
+            .with(middleware::ExtraHeaders::new(&base_url, self.extra_headers))
+            .with(middleware::RequestId)
+            .with(limits);
+        for layer in self.middleware {
+            stack = stack.with_arc(layer);
+        }
+        if let Some(capture) = self.capture {
+            stack = stack.with(middleware::Capture::new(&base_url, capture));
+        }
+        Ok(SdaClient {
+            client: stack.with(middleware::Metrics).build(),
             base_url,
             api_key,
-        }
+            crawl_timeout: self.crawl_timeout,
+            download_timeout: self.download_timeout,
+            cache: Arc::new(ResponseCache::new(self.cache_ttl)),
+            max_response_bytes: self.max_response_bytes,
+            strict_schema: self.strict_schema,
+        })
+    }
+}
+
+impl SdaClient {
+    /// Creates a new `SdaClient` with the given base URL and API key.
+    ///
+    /// All other settings use their defaults; use `SdaClient::builder` to
+    /// customise them. Fails like `SdaClientBuilder::build`, e.g. for an
+    /// invalid base URL.
+    #[allow(dead_code)]
+    pub fn new(base_url: String, api_key: String) -> Result<Self> {
+        SdaClient::builder()
+            .base_url(base_url)
+            .api_key(api_key)
+            .build()
+    }
+
+    /// Returns a builder for configuring a new `SdaClient`.
+    pub fn builder() -> SdaClientBuilder {
+        SdaClientBuilder::new()
+    }
+
+    /// Returns the base URL requests are sent to.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
     }
 
     /// Returns the authentication header as a key-value tuple.
     fn auth_header(&self) -> (&str, &str) {
-        ("x-api-key", &self.api_key)
+        let (name, value) = self.api_key.header();
+        // The key was parsed from a `&str`, so it is always valid text.
+        (name, value.to_str().unwrap_or_default())
+    }
+
+    /// Identifies this client's API and key in a saved cache, so the state
+    /// file is only loaded back by the same deployment.
+    pub fn cache_owner(&self) -> String {
+        crate::cache::owner_fingerprint(&self.base_url, self.auth_header().1)
+    }
+
+    /// Returns the response cache, shared by all clones of the client.
+    pub fn cache(&self) -> &ResponseCache {
+        &self.cache
+    }
+
+    /// Probes the API with an authenticated request for a single accession.
+    ///
+    /// Never fails: an unreachable API, a timeout after
+    /// `HEALTH_CHECK_TIMEOUT` or an error status (e.g. 401 for a bad API key)
+    /// is reported in the returned `HealthReport`. The request is sent once,
+    /// so a rate-limited API is reported as such instead of waited out.
+    pub async fn health_check(&self) -> HealthReport {
+        let checked_at = Utc::now().to_rfc3339();
+        let started = Instant::now();
+        let url = format!("{}/api/v1/accessions", self.base_url);
+        let builder = self
+            .client
+            .get(&url)
+            .query(&[("page", FIRST_PAGE), ("per_page", 1)])
+            .timeout(HEALTH_CHECK_TIMEOUT)
+            .with_extension(middleware::NoRetry);
+        let result = match self.send(builder, "Failed to reach the SDA API").await {
+            Ok(response) => {
+                let status = response.status().as_u16();
+                Self::handle_response(response, "SDA API health check failed")
+                    .await
+                    .map(|_| status)
+                    .map_err(|e| (Some(status), e))
+            }
+            Err(e) => Err((None, e)),
+        };
+        let latency_ms = started.elapsed().as_millis() as u64;
+        match result {
+            Ok(status) => HealthReport {
+                healthy: true,
+                status: Some(status),
+                latency_ms,
+                error: None,
+                checked_at,
+            },
+            Err((status, e)) => HealthReport {
+                healthy: false,
+                status,
+                latency_ms,
+                error: Some(e.to_string()),
+                checked_at,
+            },
+        }
+    }
+
+    /// Compares this server's clock with the API's, read from the `Date`
+    /// header of one small request.
+    ///
+    /// The request is sent once, and the local time is taken halfway through
+    /// that attempt as timed by the innermost layer, so network latency and
+    /// waits for the client's limits do not count as skew. Any response
+    /// carries a `Date`, so an error status (e.g. a bad API key or a 429)
+    /// does not prevent the check.
+    pub async fn check_time_sync(&self, max_skew: Duration) -> Result<TimeSyncResponse> {
+        let context = "Failed to reach the SDA API";
+        let url = format!("{}/api/v1/accessions", self.base_url);
+        let request = self
+            .client
+            .get(&url)
+            .query(&[("page", FIRST_PAGE), ("per_page", 1)])
+            .timeout(HEALTH_CHECK_TIMEOUT)
+            .build()
+            .map_err(|e| SdaError::network(context, e))?;
+        let mut extensions = Extensions::new();
+        extensions.insert(middleware::NoRetry);
+        let response = self
+            .client
+            .execute_with_extensions(request, &mut extensions)
+            .await
+            .map_err(|e| middleware_error(context, e))?;
+        let middleware::Attempt { sent, round_trip } = extensions
+            .get::<middleware::Attempt>()
+            .copied()
+            .ok_or_else(|| SdaError::Middleware {
+                context: context.to_string(),
+                message: "the request was not timed".to_string(),
+            })?;
+        let local = sent + round_trip / 2;
+        let api = response
+            .headers()
+            .get(DATE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| DateTime::parse_from_rfc2822(v).ok())
+            .map(|date| date.with_timezone(&Utc))
+            .ok_or_else(|| {
+                SdaError::Validation("The SDA API response has no valid Date header".to_string())
+            })?;
+        Ok(time_sync_report(api, local, round_trip, max_skew))
+    }
+
+    /// Sends a request through the middleware stack described in the
+    /// `middleware` module, which retries 429 responses unless the request
+    /// carries the `NoRetry` extension.
+    ///
+    /// The final response is returned as-is so that `handle_response` can
+    /// report a persistent 429 with its body.
+    async fn send(&self, builder: RequestBuilder, context: &str) -> Result<reqwest::Response> {
+        builder
+            .send()
+            .await
+            .map_err(|e| middleware_error(context, e))
     }
 
     /// Helper function to handle HTTP responses and capture error bodies.
     ///
     /// This is preferred over `error_for_status()` because it captures
     /// the response body (e.g., validation error details) and includes it
-    /// in the error message, making debugging much easier.
+    /// in the returned `SdaError::Http`, making debugging much easier.
+    /// Only the first `MAX_ERROR_BODY_BYTES` of the body are read.
     async fn handle_response(
         response: reqwest::Response,
         context: &str,
     ) -> Result<reqwest::Response> {
         if !response.status().is_success() {
             let status = response.status();
-            let body = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "<failed to read error body>".to_string());
-            let msg = if body.is_empty() {
-                format!("{}: HTTP {}", context, status)
-            } else {
-                format!("{}: HTTP {} - {}", context, status, body)
-            };
-            return Err(anyhow::anyhow!(msg));
+            let body = Self::read_error_body(response).await;
+            return Err(SdaError::Http {
+                context: context.to_string(),
+                status,
+                body,
+            });
         }
         Ok(response)
     }
 
+    /// Reads the start of an error response body, marking truncation as
+    /// `schema_check::body_snippet` does.
+    async fn read_error_body(mut response: reqwest::Response) -> String {
+        let mut body = Vec::new();
+        let mut truncated = false;
+        loop {
+            match response.chunk().await {
+                Ok(Some(chunk)) => {
+                    body.extend_from_slice(&chunk);
+                    if body.len() > MAX_ERROR_BODY_BYTES {
+                        body.truncate(MAX_ERROR_BODY_BYTES);
+                        truncated = true;
+                        break;
+                    }
+                }
+                Ok(None) => break,
+                Err(_) if body.is_empty() => return "<failed to read error body>".to_string(),
+                Err(_) => break,
+            }
+        }
+        let mut text = String::from_utf8_lossy(&body).into_owned();
+        if truncated {
+            text.push('…');
+        }
+        text
+    }
+
+    /// Reads a response body, failing once it exceeds `max_response_bytes`.
+    ///
+    /// The body is streamed so an oversized response is abandoned as soon as
+    /// the cap is crossed instead of being buffered in full; an advertised
+    /// `Content-Length` above the cap fails before anything is read.
+    async fn read_body(&self, response: reqwest::Response, context: &str) -> Result<Vec<u8>> {
+        let limit = self.max_response_bytes;
+        let too_large = || SdaError::ResponseTooLarge {
+            context: context.to_string(),
+            limit,
+        };
+        if response.content_length().is_some_and(|len| len > limit) {
+            return Err(too_large());
+        }
+        let mut body = Vec::new();
+        let mut chunks = response.bytes_stream();
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk.map_err(|e| SdaError::network(context, e))?;
+            if body.len() as u64 + chunk.len() as u64 > limit {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body)
+    }
+
+    /// Reads a successful response body that should be JSON.
+    ///
+    /// A non-empty body whose declared `Content-Type` is not JSON fails with
+    /// `SdaError::UnexpectedContentType`, quoting the type and the start of
+    /// the body, instead of a baffling decode error. A missing
+    /// `Content-Type` is accepted.
+    async fn read_json_body(&self, response: reqwest::Response, context: &str) -> Result<Vec<u8>> {
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned());
+        let body = self.read_body(response, context).await?;
+        if let Some(content_type) = content_type
+            && !is_json_content_type(&content_type)
+            && !body.trim_ascii().is_empty()
+        {
+            return Err(SdaError::UnexpectedContentType {
+                context: context.to_string(),
+                content_type,
+                snippet: schema_check::body_snippet(&body),
+            });
+        }
+        Ok(body)
+    }
+
+    /// Reads a successful response body and decodes it as JSON.
+    ///
+    /// Decoding via `serde_json` rather than `Response::json` keeps decode
+    /// failures distinguishable from transport failures.
+    async fn parse_json<T: DeserializeOwned + Serialize>(
+        &self,
+        response: reqwest::Response,
+        context: &str,
+    ) -> Result<T> {
+        let bytes = self.read_json_body(response, context).await?;
+        self.decode_json(&bytes, context)
+    }
+
+    /// Decodes a successful response body as JSON.
+    ///
+    /// An empty (or whitespace-only) body, as sent with `204 No Content`, is
+    /// reported as `SdaError::EmptyResponse` rather than a confusing "EOF while
+    /// parsing" decode error.
+    ///
+    /// In strict schema mode, fields the model does not know are logged as a
+    /// warning, and a decode error quotes the start of the body.
+    fn decode_json<T: DeserializeOwned + Serialize>(
+        &self,
+        body: &[u8],
+        context: &str,
+    ) -> Result<T> {
+        if body.trim_ascii().is_empty() {
+            return Err(SdaError::EmptyResponse {
+                context: context.to_string(),
+            });
+        }
+        if !self.strict_schema {
+            return serde_json::from_slice(body).map_err(|e| SdaError::decode(context, e));
+        }
+        let decoded = serde_json::from_slice(body).map_err(|e| {
+            SdaError::decode(
+                format!("{} (body: {})", context, schema_check::body_snippet(body)),
+                e,
+            )
+        })?;
+        if let (Ok(raw), Ok(known)) = (
+            serde_json::from_slice::<serde_json::Value>(body),
+            serde_json::to_value(&decoded),
+        ) {
+            let unknown = schema_check::unknown_fields(&raw, &known);
+            if !unknown.is_empty() {
+                tracing::warn!(
+                    context,
+                    model = std::any::type_name::<T>(),
+                    fields = %unknown.join(", "),
+                    "SDA API response has fields the model does not know; the API may have changed"
+                );
+            }
+        }
+        Ok(decoded)
+    }
+
+    /// Sends a GET request through the response cache.
+    ///
+    /// A fresh cached body for the same URL (including query string) is
+    /// parsed without contacting the API; otherwise the request is sent and a
+    /// successful body is cached before parsing.
+    async fn get_cached<T: DeserializeOwned + Serialize>(
+        &self,
+        builder: RequestBuilder,
+        send_context: &str,
+        error_context: &str,
+        parse_context: &str,
+    ) -> Result<T> {
+        let key = builder
+            .try_clone()
+            .and_then(|b| b.build().ok())
+            .map(|request| request.url().to_string());
+        if let Some(body) = key.as_deref().and_then(|k| self.cache.get(k)) {
+            tracing::debug!(url = key.as_deref(), "Serving response from cache");
+            return self.decode_json(&body, parse_context);
+        }
+
+        let response = self.send(builder, send_context).await?;
+        let response = Self::handle_response(response, error_context).await?;
+        let body = self.read_json_body(response, parse_context).await?;
+        let parsed = self.decode_json(&body, parse_context)?;
+        if let Some(key) = key {
+            self.cache.insert(key, body);
+        }
+        Ok(parsed)
+    }
+
     /// Builds a query vector for accession-related requests.
+    ///
+    /// The result is canonical: parameters are sorted by name, keeping the
+    /// order of repeated ones, and a value repeated for the same name (such
+    /// as a subject ID listed twice) is sent once. Equal filters therefore
+    /// always produce the same URL.
     fn build_accession_query(
         &self,
         args: ListAccessionsArgs,
@@ -70,6 +845,7 @@ impl SdaClient {
             query.push(("per_page", args.per_page.to_string()));
         }
         match args.lang {
+            _ if args.include_both_languages => {}
             MetadataLanguage::English => query.push(("lang", "english".to_string())),
             MetadataLanguage::Arabic => query.push(("lang", "arabic".to_string())),
             MetadataLanguage::None => {}
@@ -117,88 +893,396 @@ impl SdaClient {
         if !args.url_filter.is_empty() {
             query.push(("url_filter", args.url_filter));
         }
-        if !args.date_from.is_empty() {
-            query.push(("date_from", args.date_from));
-        }
-        if !args.date_to.is_empty() {
-            query.push(("date_to", args.date_to));
-        }
+        push_date_filters(&mut query, &args.date_from, &args.date_to)?;
         if !args.location.is_empty() {
             query.push(("location", args.location));
         }
-        if args.is_private {
-            query.push(("is_private", "true".to_string()));
+        if !args.sort_by.is_empty() {
+            if !SORTABLE_ACCESSION_FIELDS.contains(&args.sort_by.as_str()) {
+                return Err(SdaError::Validation(format!(
+                    "Unsupported sort_by field '{}'; expected one of: {}",
+                    args.sort_by,
+                    SORTABLE_ACCESSION_FIELDS.join(", ")
+                )));
+            }
+            query.push(("sort_by", args.sort_by));
+            let order = match args.sort_order {
+                SortOrder::Asc => "asc",
+                SortOrder::Desc => "desc",
+            };
+            query.push(("sort_order", order.to_string()));
+        }
+        let mut seen = HashSet::new();
+        query.retain(|(key, value)| seen.insert((*key, value.clone())));
+        query.sort_by_key(|(key, _)| *key);
+        Ok(query)
+    }
+
+    /// Builds a query vector for collection listing requests.
+    fn build_collection_query(
+        &self,
+        args: ListCollectionsArgs,
+    ) -> Result<Vec<(&'static str, String)>> {
+        let mut query = vec![];
+        if args.page != -1 {
+            query.push(("page", args.page.to_string()));
         }
+        if args.per_page != -1 {
+            query.push(("per_page", args.per_page.to_string()));
+        }
+        match args.lang {
+            MetadataLanguage::English => query.push(("lang", "english".to_string())),
+            MetadataLanguage::Arabic => query.push(("lang", "arabic".to_string())),
+            MetadataLanguage::None => {}
+        }
+        for s in args.metadata_subjects {
+            query.push(("metadata_subjects", s.to_string()));
+        }
+        if !args.query_term.is_empty() {
+            query.push(("query_term", args.query_term));
+        }
+        push_date_filters(&mut query, &args.date_from, &args.date_to)?;
         Ok(query)
     }
 
     /// Creates a new accession (starts a crawl).
+    ///
+    /// Subject IDs are normalized with `validation::normalize_subject_ids`.
+    /// The request is then checked with `validation::check_crawl_request` and
+    /// its subjects looked up; if anything is wrong, the call fails with
+    /// `SdaError::InvalidFields` listing every problem, before the crawl is
+    /// submitted. The request's `idempotency_key`, if any, is sent as the
+    /// `Idempotency-Key` header. The crawl timeout applies instead of the
+    /// ordinary one.
     pub async fn create_accession_crawl(
         &self,
-        request: CreateAccessionCrawlRequest,
+        mut request: CreateAccessionCrawlRequest,
     ) -> Result<String> {
+        request.metadata_subjects = validation::normalize_subject_ids(&request.metadata_subjects);
+        let problems = self.crawl_request_problems(&request).await;
+        if !problems.is_empty() {
+            return Err(SdaError::InvalidFields {
+                context: "Invalid crawl request; no crawl was started".to_string(),
+                problems,
+            });
+        }
         let url = format!("{}/api/v1/accessions/crawl", self.base_url);
-        let response = self
+        let mut builder = self
             .client
             .post(&url)
-            .header(self.auth_header().0, self.auth_header().1)
             .json(&request)
-            .send()
-            .await
-            .context("Failed to send create accession crawl request")?;
+            .timeout(self.crawl_timeout);
+        if let Some(key) = &request.idempotency_key {
+            builder = builder.header(IDEMPOTENCY_KEY, key);
+        }
+        let response = self
+            .send(builder, "Failed to send create accession crawl request")
+            .await?;
 
         let response =
             Self::handle_response(response, "Server returned error for create accession crawl")
                 .await?;
 
-        response
-            .text()
+        response.text().await.map_err(|e| {
+            SdaError::network("Failed to parse create accession crawl response text", e)
+        })
+    }
+
+    /// Creates an accession from a WACZ already uploaded to the archive's
+    /// bucket, named by the request's `s3_filename`, instead of crawling.
+    ///
+    /// The request is checked with `validation::check_crawl_request` first,
+    /// and must have an `s3_filename`. An error status from the API names the
+    /// file, since a wrong key is the usual cause.
+    pub async fn register_accession_from_s3(
+        &self,
+        request: CreateAccessionCrawlRequest,
+    ) -> Result<String> {
+        let s3_filename = request.s3_filename.clone().unwrap_or_default();
+        let mut problems = validation::check_crawl_request(&request);
+        if s3_filename.is_empty() {
+            problems.push(ValidationProblem {
+                field: "s3_filename".to_string(),
+                message: "s3_filename must not be empty".to_string(),
+            });
+        }
+        if !problems.is_empty() {
+            return Err(SdaError::InvalidFields {
+                context: "Invalid registration request; no accession was created".to_string(),
+                problems,
+            });
+        }
+        self.create_accession_crawl(request)
+            .await
+            .map_err(|e| match e {
+                SdaError::Http { status, body, .. } => SdaError::Http {
+                    context: format!(
+                        "Server rejected the registration of s3_filename '{}'",
+                        s3_filename
+                    ),
+                    status,
+                    body,
+                },
+                e => e,
+            })
+    }
+
+    /// Looks up each subject ID in `lang` through the response cache.
+    async fn lookup_subjects(
+        &self,
+        ids: &[i64],
+        lang: &MetadataLanguage,
+    ) -> Vec<(i64, Result<DublinMetadataSubjectResponse>)> {
+        stream::iter(ids.iter().copied())
+            .map(|id| async move { (id, self.get_subject(id, lang.clone()).await) })
+            .buffered(BATCH_CONCURRENCY)
+            .collect()
             .await
-            .context("Failed to parse create accession crawl response text")
+    }
+
+    /// Reports each subject ID that does not exist in `lang`, so a crawl is
+    /// never started with a bad reference.
+    ///
+    /// A lookup that fails for another reason is logged and left for the API
+    /// to judge.
+    async fn missing_subjects(
+        &self,
+        ids: &[i64],
+        lang: &MetadataLanguage,
+    ) -> Vec<ValidationProblem> {
+        let mut problems = Vec::new();
+        for (id, result) in self.lookup_subjects(ids, lang).await {
+            match result {
+                Ok(_) => {}
+                Err(SdaError::Http { status, .. }) if status == StatusCode::NOT_FOUND => problems
+                    .push(ValidationProblem {
+                        field: "metadata_subjects".to_string(),
+                        message: format!(
+                            "Subject {} does not exist; use list_subjects to find valid IDs",
+                            id
+                        ),
+                    }),
+                Err(e) => tracing::warn!(subject_id = id, "Could not check subject: {}", e),
+            }
+        }
+        problems
+    }
+
+    /// Returns the problems `create_accession_crawl` would refuse `request`
+    /// for: the local checks and subject IDs that do not exist.
+    pub async fn crawl_request_problems(
+        &self,
+        request: &CreateAccessionCrawlRequest,
+    ) -> Vec<ValidationProblem> {
+        let mut problems = validation::check_crawl_request(request);
+        let subjects = validation::normalize_subject_ids(&request.metadata_subjects);
+        problems.extend(
+            self.missing_subjects(&subjects, &request.metadata_language)
+                .await,
+        );
+        problems
+    }
+
+    /// Checks a crawl request without submitting it.
+    ///
+    /// Runs the local checks from `validation::check_crawl_request` and looks
+    /// up every subject ID in the request's language, reporting IDs that do
+    /// not exist or could not be checked.
+    pub async fn validate_crawl_request(
+        &self,
+        request: &CreateAccessionCrawlRequest,
+    ) -> CrawlValidationReport {
+        let mut problems = validation::check_crawl_request(request);
+        let lookups = self
+            .lookup_subjects(&request.metadata_subjects, &request.metadata_language)
+            .await;
+        for (id, result) in lookups {
+            let message = match result {
+                Ok(_) => continue,
+                Err(SdaError::Http { status, .. }) if status == StatusCode::NOT_FOUND => {
+                    format!("Subject {} does not exist", id)
+                }
+                Err(e) => format!("Could not check subject {}: {}", id, e),
+            };
+            problems.push(ValidationProblem {
+                field: "metadata_subjects".to_string(),
+                message,
+            });
+        }
+        CrawlValidationReport {
+            valid: problems.is_empty(),
+            problems,
+        }
     }
 
     /// Fetches a list of public accessions.
+    ///
+    /// `args.is_private` must be unset; private accessions are listed with
+    /// `list_private_accessions`.
     pub async fn list_accessions(
         &self,
         args: ListAccessionsArgs,
     ) -> Result<ListAccessionsResponse> {
+        if args.is_private {
+            return Err(SdaError::Validation(
+                "list_accessions only lists public accessions and does not accept is_private=true; \
+                 use list_private_accessions to list private accessions"
+                    .to_string(),
+            ));
+        }
         let url = format!("{}/api/v1/accessions", self.base_url);
         let query = self.build_accession_query(args)?;
 
+        let builder = self.client.get(&url).query(&query);
         let response = self
-            .client
-            .get(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .query(&query)
-            .send()
-            .await
-            .context("Failed to send list accessions request")?;
+            .send(builder, "Failed to send list accessions request")
+            .await?;
 
         let response =
             Self::handle_response(response, "Server returned error for list accessions").await?;
 
-        response
-            .json()
+        self.parse_json(response, "Failed to parse list accessions response")
             .await
-            .context("Failed to parse list accessions response")
+    }
+
+    /// Streams accessions one at a time across page boundaries.
+    ///
+    /// Pages are fetched lazily as the stream is polled, starting from
+    /// `args.page` (or the first page when unset), so memory use stays flat
+    /// regardless of archive size. Private accessions are listed when
+    /// `args.is_private` is set. The stream ends after the last page or
+    /// after yielding the first error.
+    ///
+    /// The API has no cursor pagination, so writes during a long fetch can
+    /// cause items to be skipped or duplicated. A warning is logged when
+    /// `num_pages` changes between pages.
+    pub fn accessions_stream(
+        &self,
+        args: ListAccessionsArgs,
+    ) -> impl Stream<Item = Result<AccessionsWithMetadataResponse>> + '_ {
+        let start = if args.page == -1 {
+            FIRST_PAGE
+        } else {
+            args.page
+        };
+        let pager = AccessionPager {
+            args,
+            next_page: Some(start),
+            buffer: VecDeque::new(),
+            num_pages: None,
+        };
+
+        stream::unfold(pager, move |mut pager| async move {
+            loop {
+                if let Some(item) = pager.buffer.pop_front() {
+                    return Some((Ok(item), pager));
+                }
+                let page = pager.next_page?;
+                let args = ListAccessionsArgs {
+                    page,
+                    ..pager.args.clone()
+                };
+                let result = if args.is_private {
+                    self.list_private_accessions(args).await
+                } else {
+                    self.list_accessions(args).await
+                };
+                match result {
+                    Ok(response) => {
+                        if let Some(previous) = pager.observe_num_pages(response.num_pages) {
+                            tracing::warn!(
+                                page = response.page,
+                                previous_num_pages = previous,
+                                num_pages = response.num_pages,
+                                "Accessions changed while paginating; items may be skipped or repeated"
+                            );
+                        }
+                        pager.next_page = if response.items.is_empty()
+                            || response.page + 1 >= response.num_pages
+                        {
+                            None
+                        } else {
+                            Some(response.page + 1)
+                        };
+                        pager.buffer.extend(response.items);
+                    }
+                    Err(e) => {
+                        pager.next_page = None;
+                        return Some((Err(e), pager));
+                    }
+                }
+            }
+        })
+    }
+
+    /// Finds every accession captured from `url`, newest crawl first.
+    ///
+    /// The trimmed URL is passed to the API's `url_filter` as given, so it
+    /// matches seeds stored with the same spelling; results are then
+    /// narrowed to exact seed URL matches, since the filter may also match
+    /// similar URLs. That comparison normalizes both sides (lowercase scheme
+    /// and host, `/` for an empty path). Re-crawls of the same URL are all
+    /// returned, sorted by `crawl_timestamp` descending.
+    pub async fn find_accessions_by_url(
+        &self,
+        url: &str,
+        is_private: bool,
+    ) -> Result<Vec<AccessionsWithMetadataResponse>> {
+        let target = normalize_url(url)?;
+        let args = ListAccessionsArgs {
+            url_filter: url.trim().to_string(),
+            is_private,
+            ..Default::default()
+        };
+        let mut matches: Vec<AccessionsWithMetadataResponse> = self
+            .accessions_stream(args)
+            .try_filter(|item| {
+                let exact = normalize_url(&item.seed_url).is_ok_and(|seed| seed == target);
+                futures::future::ready(exact)
+            })
+            .try_collect()
+            .await?;
+        matches.sort_by(|a, b| b.crawl_timestamp.cmp(&a.crawl_timestamp));
+        Ok(matches)
+    }
+
+    /// Returns the newest `Complete` or `Pending` accession of `url` crawled
+    /// within `window` of now.
+    ///
+    /// URLs are matched as in `find_accessions_by_url`. Failed crawls
+    /// (`Error` or `BadCrawl`) and accessions whose `crawl_timestamp` cannot
+    /// be parsed are ignored, so they never block a new crawl.
+    pub async fn find_recent_accession(
+        &self,
+        url: &str,
+        is_private: bool,
+        window: Duration,
+    ) -> Result<Option<AccessionsWithMetadataResponse>> {
+        let cutoff = Utc::now().naive_utc() - window;
+        let matches = self.find_accessions_by_url(url, is_private).await?;
+        Ok(matches.into_iter().find(|accession| {
+            matches!(
+                accession.crawl_status,
+                CrawlStatus::Complete | CrawlStatus::Pending
+            ) && parse_crawl_timestamp(&accession.crawl_timestamp).is_some_and(|t| t >= cutoff)
+        }))
     }
 
     /// Fetches a list of private accessions.
+    ///
+    /// `is_private=true` is always sent, whatever `args.is_private` says.
     pub async fn list_private_accessions(
         &self,
         args: ListAccessionsArgs,
     ) -> Result<ListAccessionsResponse> {
         let url = format!("{}/api/v1/accessions/private", self.base_url);
-        let query = self.build_accession_query(args)?;
+        let mut query = self.build_accession_query(args)?;
+        query.push(("is_private", "true".to_string()));
 
+        let builder = self.client.get(&url).query(&query);
         let response = self
-            .client
-            .get(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .query(&query)
-            .send()
-            .await
-            .context("Failed to send list private accessions request")?;
+            .send(builder, "Failed to send list private accessions request")
+            .await?;
 
         let response = Self::handle_response(
             response,
@@ -206,85 +1290,1166 @@ impl SdaClient {
         )
         .await?;
 
-        response
-            .json()
+        self.parse_json(response, "Failed to parse list private accessions response")
             .await
-            .context("Failed to parse list private accessions response")
     }
 
     /// Retrieves a single public accession by its ID.
-    pub async fn get_accession(&self, id: i32) -> Result<GetOneAccessionResponse> {
-        let url = format!("{}/api/v1/accessions/{}", self.base_url, id);
+    pub async fn get_accession(&self, id: i64) -> Result<GetOneAccessionResponse> {
+        Ok(self.fetch_accession(id, false).await?.0)
+    }
+
+    /// Retrieves a public or private accession together with its `ETag`
+    /// header, if the API sent one.
+    async fn fetch_accession(
+        &self,
+        id: i64,
+        private: bool,
+    ) -> Result<(GetOneAccessionResponse, Option<String>)> {
+        let (path, name) = if private {
+            ("accessions/private", "get private accession")
+        } else {
+            ("accessions", "get accession")
+        };
+        let url = format!("{}/api/v1/{}/{}", self.base_url, path, id);
+        let parse_context = format!("Failed to parse {} response", name);
+        let cached = self.cache.get_validated(&url);
+        let mut builder = self.client.get(&url);
+        if let Some((etag, _)) = &cached {
+            builder = builder.header(IF_NONE_MATCH, etag);
+        }
         let response = self
-            .client
-            .get(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .send()
-            .await
-            .context("Failed to send get accession request")?;
+            .send(builder, &format!("Failed to send {} request", name))
+            .await?;
+        if response.status() == StatusCode::NOT_MODIFIED
+            && let Some((etag, body)) = cached
+        {
+            tracing::debug!(url, "Accession not modified; reusing cached body");
+            let mut accession: GetOneAccessionResponse = self.decode_json(&body, &parse_context)?;
+            accession.etag = Some(etag.clone());
+            return Ok((accession, Some(etag)));
+        }
 
         let response =
-            Self::handle_response(response, "Server returned error for get accession").await?;
+            Self::handle_response(response, &format!("Server returned error for {}", name)).await?;
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+
+        let body = self.read_json_body(response, &parse_context).await?;
+        let mut accession: GetOneAccessionResponse = self.decode_json(&body, &parse_context)?;
+        accession.etag = etag.clone();
+        if let Some(etag) = &etag {
+            self.cache.insert_validated(url, etag.clone(), body);
+        }
+        Ok((accession, etag))
+    }
 
-        response
-            .json()
+    /// Drops the stored bodies of accession `id`, so its next fetch is
+    /// unconditional.
+    fn forget_accession(&self, id: i64) {
+        for path in ["accessions", "accessions/private"] {
+            self.cache
+                .remove(&format!("{}/api/v1/{}/{}", self.base_url, path, id));
+        }
+    }
+
+    /// Retrieves several public accessions concurrently.
+    ///
+    /// Requests are issued with bounded concurrency. Duplicate IDs are fetched
+    /// once, and a failure for one ID is recorded in the response's `errors`
+    /// map instead of failing the whole batch.
+    pub async fn get_accessions(&self, ids: Vec<i64>) -> GetAccessionsResponse {
+        let results: Vec<(i64, Result<GetOneAccessionResponse>)> = stream::iter(unique_ids(ids))
+            .map(|id| async move { (id, self.get_accession(id).await) })
+            .buffered(BATCH_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut items = Vec::new();
+        let mut errors = BTreeMap::new();
+        for (id, result) in results {
+            match result {
+                Ok(accession) => items.push(accession),
+                Err(e) => {
+                    errors.insert(id, e.to_string());
+                }
+            }
+        }
+        GetAccessionsResponse { items, errors }
+    }
+
+    /// Retrieves several accessions, public or private, concurrently,
+    /// returning each ID with its result in the order given.
+    pub async fn get_any_accessions(
+        &self,
+        ids: &[i64],
+    ) -> Vec<(i64, Result<GetOneAccessionResponse>)> {
+        stream::iter(ids.iter().copied())
+            .map(|id| async move { (id, self.get_any_accession(id).await.map(|(a, _)| a)) })
+            .buffered(BATCH_CONCURRENCY)
+            .collect()
             .await
-            .context("Failed to parse get accession response")
     }
 
     /// Retrieves a single private accession by its ID.
-    pub async fn get_private_accession(&self, id: i32) -> Result<GetOneAccessionResponse> {
-        let url = format!("{}/api/v1/accessions/private/{}", self.base_url, id);
-        let response = self
-            .client
-            .get(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .send()
+    pub async fn get_private_accession(&self, id: i64) -> Result<GetOneAccessionResponse> {
+        Ok(self.fetch_accession(id, true).await?.0)
+    }
+
+    /// Downloads an accession's archive file (WACZ or WARC) to `dest`.
+    ///
+    /// `url` is the pre-signed URL from
+    /// `GetOneAccessionResponse::archive_url`, so no
+    /// API key is sent with it. The body is streamed to disk while its SHA-256
+    /// is computed, and the byte count is checked against `Content-Length`
+    /// when the server sends one. The body is written to a temporary file
+    /// beside `dest` that is renamed into place once complete, so `dest` never
+    /// holds a partial download: on any error, including a connection lost
+    /// mid-stream or a cancelled call, the temporary file is removed. The
+    /// download timeout covers the whole transfer, not just the response
+    /// headers.
+    ///
+    /// `on_progress` is called after each chunk is written with the bytes
+    /// written so far and the advertised length, if any.
+    pub async fn download_wacz(
+        &self,
+        url: &str,
+        dest: &Path,
+        on_progress: impl Fn(u64, Option<u64>) + Send,
+    ) -> Result<WaczDownload> {
+        self.download_wacz_capped(url, dest, u64::MAX, on_progress)
             .await
-            .context("Failed to send get private accession request")?;
+    }
 
+    /// Downloads an archive file as `download_wacz` does, failing with
+    /// `SdaError::ResponseTooLarge` once it exceeds `limit` bytes, or at once
+    /// when its advertised `Content-Length` does.
+    async fn download_wacz_capped(
+        &self,
+        url: &str,
+        dest: &Path,
+        limit: u64,
+        on_progress: impl Fn(u64, Option<u64>) + Send,
+    ) -> Result<WaczDownload> {
+        let builder = self.client.get(url).timeout(self.download_timeout);
+        let response = self
+            .send(builder, "Failed to send WACZ download request")
+            .await?;
         let response =
-            Self::handle_response(response, "Server returned error for get private accession")
+            Self::handle_response(response, "Server returned error for WACZ download").await?;
+        let expected_length = response.content_length();
+        let too_large = || SdaError::ResponseTooLarge {
+            context: "Failed to download WACZ".to_string(),
+            limit,
+        };
+        if expected_length.is_some_and(|len| len > limit) {
+            return Err(too_large());
+        }
+
+        let context = format!("Failed to write WACZ to {}", dest.display());
+        let file_name = dest
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let partial =
+            TempFile(dest.with_file_name(format!(".{}.{}.part", file_name, uuid::Uuid::new_v4())));
+        let mut file = tokio::fs::File::create(&partial.0)
+            .await
+            .map_err(|e| SdaError::io(&context, e))?;
+        let mut hasher = Sha256::new();
+        let mut bytes = 0u64;
+        let mut body = response.bytes_stream();
+        while let Some(chunk) = body.next().await {
+            let chunk = chunk.map_err(|e| SdaError::network("Failed to read WACZ download", e))?;
+            bytes = bytes.saturating_add(chunk.len() as u64);
+            if bytes > limit {
+                return Err(too_large());
+            }
+            hasher.update(&chunk);
+            file.write_all(&chunk)
+                .await
+                .map_err(|e| SdaError::io(&context, e))?;
+            on_progress(bytes, expected_length);
+        }
+        file.flush().await.map_err(|e| SdaError::io(&context, e))?;
+        drop(file);
+
+        verify_length(expected_length, bytes)?;
+        tokio::fs::rename(&partial.0, dest)
+            .await
+            .map_err(|e| SdaError::io(&context, e))?;
+        Ok(WaczDownload {
+            bytes,
+            sha256: format!("{:x}", hasher.finalize()),
+            length_verified: expected_length.is_some(),
+        })
+    }
+
+    /// Writes every accession matching `filters` to `dest` as
+    /// newline-delimited JSON, one accession per line.
+    ///
+    /// Items are written as `accessions_stream` yields them, so memory use
+    /// stays flat however many accessions match. If fetching or writing
+    /// fails part way, the incomplete file is removed before the error is
+    /// returned. `on_item` is called after each line with the count so far.
+    pub async fn export_accessions_ndjson(
+        &self,
+        filters: ListAccessionsArgs,
+        dest: &Path,
+        on_item: impl Fn(u64) + Send,
+    ) -> Result<u64> {
+        let context = format!("Failed to write accessions to {}", dest.display());
+        let file = tokio::fs::File::create(dest)
+            .await
+            .map_err(|e| SdaError::io(&context, e))?;
+        let mut writer = tokio::io::BufWriter::new(file);
+        let mut items = 0u64;
+        let result: Result<()> = async {
+            let mut accessions = std::pin::pin!(self.accessions_stream(filters));
+            while let Some(accession) = accessions.next().await {
+                let mut line = serde_json::to_vec(&accession?)
+                    .map_err(|e| SdaError::decode("Failed to serialize accession", e))?;
+                line.push(b'\n');
+                writer
+                    .write_all(&line)
+                    .await
+                    .map_err(|e| SdaError::io(&context, e))?;
+                items += 1;
+                on_item(items);
+            }
+            writer.flush().await.map_err(|e| SdaError::io(&context, e))
+        }
+        .await;
+
+        if let Err(e) = result {
+            drop(writer);
+            let _ = tokio::fs::remove_file(dest).await;
+            return Err(e);
+        }
+        Ok(items)
+    }
+
+    /// Reads capture metadata from an accession's WACZ file.
+    ///
+    /// Only the zip's tail, its central directory, `datapackage.json` and
+    /// `pages/pages.jsonl` are fetched, with HTTP range requests against
+    /// `wacz_url`. If the server ignores ranges, the whole file is
+    /// downloaded to a temporary file instead. Fails with a validation
+    /// error for WARC accessions.
+    pub async fn inspect_wacz(&self, id: i64) -> Result<WaczInspection> {
+        let url = self.wacz_url(id, "WACZ metadata").await?;
+        let context = format!("Failed to inspect WACZ of accession {}", id);
+        let archive_error = |message: String| SdaError::Archive {
+            context: context.clone(),
+            message,
+        };
+
+        let Some((entries, total, mut fetched)) = self.fetch_wacz_directory(&url, &context).await?
+        else {
+            return self.inspect_downloaded_wacz(id, &url).await;
+        };
+        let find = |name: &str| entries.iter().find(|e| e.name == name);
+        let datapackage = find(wacz::DATAPACKAGE_PATH)
+            .ok_or_else(|| archive_error(format!("The WACZ has no {}", wacz::DATAPACKAGE_PATH)))?;
+        let (datapackage, bytes) = self.fetch_zip_entry(&url, datapackage, &context).await?;
+        fetched += bytes;
+        let pages = match find(wacz::PAGES_PATH) {
+            Some(entry) if entry.compressed_size <= MAX_INSPECT_PAGES_BYTES => {
+                let (pages, bytes) = self.fetch_zip_entry(&url, entry, &context).await?;
+                fetched += bytes;
+                Some(pages)
+            }
+            _ => None,
+        };
+        let metadata =
+            wacz::summarize_metadata(&datapackage, pages.as_deref()).map_err(archive_error)?;
+        Ok(wacz_inspection(id, total, fetched, true, metadata))
+    }
+
+    /// Lists the pages captured in an accession's WACZ file, `per_page` at a
+    /// time.
+    ///
+    /// Only the zip's tail, its central directory and the page lists
+    /// (`pages/pages.jsonl`, then `pages/extraPages.jsonl`) are fetched, with
+    /// HTTP range requests against `wacz_url`, as in `inspect_wacz`. Page
+    /// lists larger than `MAX_INSPECT_PAGES_BYTES` are refused. If the server
+    /// ignores ranges, the whole file, up to `MAX_WACZ_PAGES_DOWNLOAD_BYTES`,
+    /// is downloaded to a temporary file instead.
+    pub async fn list_wacz_pages(
+        &self,
+        id: i64,
+        page: i64,
+        per_page: i64,
+    ) -> Result<WaczPagesResponse> {
+        let url = self.wacz_url(id, "WACZ page list").await?;
+        let context = format!("Failed to list pages of WACZ of accession {}", id);
+
+        let (pages, used_range_requests) = match self.fetch_wacz_directory(&url, &context).await? {
+            Some((entries, _, _)) => {
+                let mut lists = Vec::new();
+                for name in [wacz::PAGES_PATH, wacz::EXTRA_PAGES_PATH] {
+                    let Some(entry) = entries.iter().find(|e| e.name == name) else {
+                        lists.push(None);
+                        continue;
+                    };
+                    if entry.compressed_size > MAX_INSPECT_PAGES_BYTES {
+                        return Err(SdaError::Archive {
+                            context,
+                            message: format!(
+                                "{} is {} bytes compressed, more than the {} bytes read",
+                                name, entry.compressed_size, MAX_INSPECT_PAGES_BYTES
+                            ),
+                        });
+                    }
+                    lists.push(Some(self.fetch_zip_entry(&url, entry, &context).await?.0));
+                }
+                (
+                    wacz::list_pages(lists[0].as_deref(), lists[1].as_deref()),
+                    true,
+                )
+            }
+            None => {
+                let temp = TempFile(std::env::temp_dir().join(format!(
+                    "sda-accession-{}-{}.wacz",
+                    id,
+                    uuid::Uuid::new_v4()
+                )));
+                self.download_wacz_capped(&url, &temp.0, MAX_WACZ_PAGES_DOWNLOAD_BYTES, |_, _| {})
+                    .await?;
+                let path = temp.0.clone();
+                let pages = tokio::task::spawn_blocking(move || wacz::read_page_list(&path))
+                    .await
+                    .map_err(|e| SdaError::Archive {
+                        context: context.clone(),
+                        message: e.to_string(),
+                    })?
+                    .map_err(|message| SdaError::Archive { context, message })?;
+                (pages, false)
+            }
+        };
+
+        let page_count = pages.len();
+        let items = pages
+            .into_iter()
+            .skip(
+                page.checked_mul(per_page)
+                    .and_then(|skip| usize::try_from(skip).ok())
+                    .unwrap_or(usize::MAX),
+            )
+            .take(usize::try_from(per_page).unwrap_or(usize::MAX))
+            .map(|page| WaczPage {
+                id: page.id,
+                url: page.url,
+                title: page.title,
+                ts: page.ts,
+                seed: page.seed,
+            })
+            .collect();
+        Ok(WaczPagesResponse {
+            accession_id: id,
+            page_count,
+            items,
+            page,
+            per_page,
+            num_pages: (page_count as u64).div_ceil(per_page as u64) as i64,
+            used_range_requests,
+        })
+    }
+
+    /// Reads one page captured in an accession's WACZ file, by its id in
+    /// the page lists.
+    ///
+    /// The archive is downloaded to a temporary file, which is removed
+    /// afterwards, and archives larger than `MAX_WACZ_PAGES_DOWNLOAD_BYTES`
+    /// are refused. It is read with `wacz::read_page`: as text, or with `html`
+    /// as the captured body. At most `max_chars` characters are returned.
+    pub async fn get_wacz_page(
+        &self,
+        id: i64,
+        page_id: &str,
+        max_chars: usize,
+        html: bool,
+    ) -> Result<WaczPageContent> {
+        let url = self.wacz_url(id, "WACZ pages").await?;
+        let temp = TempFile(std::env::temp_dir().join(format!(
+            "sda-accession-{}-{}.wacz",
+            id,
+            uuid::Uuid::new_v4()
+        )));
+        self.download_wacz_capped(&url, &temp.0, MAX_WACZ_PAGES_DOWNLOAD_BYTES, |_, _| {})
+            .await?;
+
+        let path = temp.0.clone();
+        let wanted = page_id.to_string();
+        let context = format!("Failed to read page '{}' of accession {}", page_id, id);
+        let (listed, page) =
+            tokio::task::spawn_blocking(move || wacz::read_page(&path, &wanted, max_chars, html))
+                .await
+                .map_err(|e| SdaError::Archive {
+                    context: context.clone(),
+                    message: e.to_string(),
+                })?
+                .map_err(|message| SdaError::Archive { context, message })?;
+        Ok(WaczPageContent {
+            accession_id: id,
+            page_id: listed.id,
+            url: page.url,
+            title: page.title,
+            ts: listed.ts,
+            content_type: page.content_type,
+            html,
+            content: page.text,
+            total_chars: page.total_chars,
+            truncated: page.truncated,
+        })
+    }
+
+    /// Returns the `wacz_url` of accession `id`, public or private, failing
+    /// with a validation error naming `what` for WARC accessions.
+    async fn wacz_url(&self, id: i64, what: &str) -> Result<String> {
+        let (accession, _) = self.get_any_accession(id).await?;
+        if accession.accession.dublin_metadata_format != DublinMetadataFormat::Wacz {
+            return Err(SdaError::Validation(format!(
+                "Accession {} is archived as WARC, not WACZ, so it has no {}",
+                id, what
+            )));
+        }
+        Ok(accession.wacz_url)
+    }
+
+    /// Fetches the central directory of the zip at `url` with range
+    /// requests. Returns its entries, the file's size and the bytes
+    /// fetched, or `None` when the server ignores ranges.
+    async fn fetch_wacz_directory(
+        &self,
+        url: &str,
+        context: &str,
+    ) -> Result<Option<(Vec<ZipEntry>, u64, u64)>> {
+        let archive_error = |message: String| SdaError::Archive {
+            context: context.to_string(),
+            message,
+        };
+        let tail_range = format!("bytes=-{}", remote_zip::TAIL_BYTES);
+        let Some((tail, total)) = self.fetch_range(url, &tail_range).await? else {
+            return Ok(None);
+        };
+        let mut fetched = tail.len() as u64;
+        let tail_offset = total.saturating_sub(fetched);
+        let directory =
+            remote_zip::locate_central_directory(&tail, tail_offset).map_err(archive_error)?;
+        let entries = if directory.offset >= tail_offset {
+            let start = usize::try_from(directory.offset - tail_offset).ok();
+            let size = usize::try_from(directory.size).ok();
+            let bytes = start
+                .zip(size)
+                .and_then(|(start, size)| tail.get(start..start.checked_add(size)?))
+                .ok_or_else(|| {
+                    archive_error("Central directory runs past the end of the file".to_string())
+                })?;
+            remote_zip::parse_central_directory(bytes)
+        } else {
+            let bytes = self
+                .fetch_exact_range(url, directory.offset, directory.size, context)
                 .await?;
+            fetched += bytes.len() as u64;
+            remote_zip::parse_central_directory(&bytes)
+        }
+        .map_err(archive_error)?;
+        Ok(Some((entries, total, fetched)))
+    }
 
-        response
-            .json()
+    /// Inspects a WACZ by downloading it in full, up to
+    /// `MAX_WACZ_PAGES_DOWNLOAD_BYTES`, for servers without range support.
+    async fn inspect_downloaded_wacz(&self, id: i64, url: &str) -> Result<WaczInspection> {
+        let temp = TempFile(std::env::temp_dir().join(format!(
+            "sda-accession-{}-{}.wacz",
+            id,
+            uuid::Uuid::new_v4()
+        )));
+        let download = self
+            .download_wacz_capped(url, &temp.0, MAX_WACZ_PAGES_DOWNLOAD_BYTES, |_, _| {})
+            .await?;
+        let path = temp.0.clone();
+        let context = format!("Failed to inspect WACZ of accession {}", id);
+        let metadata = tokio::task::spawn_blocking(move || wacz::read_metadata(&path))
+            .await
+            .map_err(|e| SdaError::Archive {
+                context: context.clone(),
+                message: e.to_string(),
+            })?
+            .map_err(|message| SdaError::Archive { context, message })?;
+        Ok(wacz_inspection(
+            id,
+            download.bytes,
+            download.bytes,
+            false,
+            metadata,
+        ))
+    }
+
+    /// Fetches part of a file with a `Range` request, e.g. `bytes=-100`.
+    ///
+    /// Returns the bytes and the file's total size, or `None` when the
+    /// server ignores the range (answering 200 instead of 206) or does not
+    /// report the total size. No API key is sent.
+    async fn fetch_range(&self, url: &str, range: &str) -> Result<Option<(Vec<u8>, u64)>> {
+        let builder = self.client.get(url).header(RANGE, range);
+        let response = self
+            .send(builder, "Failed to send WACZ range request")
+            .await?;
+        let response =
+            Self::handle_response(response, "Server returned error for WACZ range request").await?;
+        if response.status() != StatusCode::PARTIAL_CONTENT {
+            return Ok(None);
+        }
+        let total = response
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.rsplit_once('/'))
+            .and_then(|(_, total)| total.parse().ok());
+        let Some(total) = total else {
+            return Ok(None);
+        };
+        let bytes = self
+            .read_body(response, "Failed to read WACZ range response")
+            .await?;
+        Ok(Some((bytes, total)))
+    }
+
+    /// Fetches `len` bytes starting at `offset`, failing if the server
+    /// stops honouring ranges or returns a different length.
+    async fn fetch_exact_range(
+        &self,
+        url: &str,
+        offset: u64,
+        len: u64,
+        context: &str,
+    ) -> Result<Vec<u8>> {
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+        let last = offset
+            .checked_add(len)
+            .and_then(|end| end.checked_sub(1))
+            .ok_or_else(|| SdaError::Archive {
+                context: context.to_string(),
+                message: format!("{} bytes at offset {} run past any file", len, offset),
+            })?;
+        let range = format!("bytes={}-{}", offset, last);
+        match self.fetch_range(url, &range).await? {
+            Some((bytes, _)) if bytes.len() as u64 == len => Ok(bytes),
+            _ => Err(SdaError::Archive {
+                context: context.to_string(),
+                message: format!("The server did not return bytes {}", &range[6..]),
+            }),
+        }
+    }
+
+    /// Fetches and decompresses one zip entry. Returns its contents and the
+    /// number of bytes fetched.
+    async fn fetch_zip_entry(
+        &self,
+        url: &str,
+        entry: &ZipEntry,
+        context: &str,
+    ) -> Result<(Vec<u8>, u64)> {
+        let archive_error = |message: String| SdaError::Archive {
+            context: context.to_string(),
+            message,
+        };
+        let header = self
+            .fetch_exact_range(
+                url,
+                entry.header_offset,
+                remote_zip::LOCAL_HEADER_BYTES,
+                context,
+            )
+            .await?;
+        let data_offset = remote_zip::local_data_offset(&header)
+            .and_then(|offset| {
+                entry
+                    .header_offset
+                    .checked_add(offset)
+                    .ok_or_else(|| format!("{} starts past any file", entry.name))
+            })
+            .map_err(archive_error)?;
+        let data = self
+            .fetch_exact_range(url, data_offset, entry.compressed_size, context)
+            .await?;
+        let fetched = (header.len() + data.len()) as u64;
+        let contents = remote_zip::decompress(entry, &data).map_err(archive_error)?;
+        Ok((contents, fetched))
+    }
+
+    /// Reads the text of the main page captured for an accession.
+    ///
+    /// The archive is downloaded to a temporary file, which is removed
+    /// afterwards (also if the call is cancelled), and read with
+    /// `wacz::read_main_page`. Archives larger than
+    /// `MAX_WACZ_PAGES_DOWNLOAD_BYTES` are refused. At most `max_chars`
+    /// characters are returned.
+    pub async fn get_accession_content(
+        &self,
+        id: i64,
+        is_private: bool,
+        max_chars: usize,
+    ) -> Result<AccessionContentResponse> {
+        let accession = self.fetch_accession(id, is_private).await?.0;
+        let temp = TempFile(std::env::temp_dir().join(format!(
+            "sda-accession-{}-{}.archive",
+            id,
+            uuid::Uuid::new_v4()
+        )));
+        self.download_wacz_capped(
+            accession.archive_url(),
+            &temp.0,
+            MAX_WACZ_PAGES_DOWNLOAD_BYTES,
+            |_, _| {},
+        )
+        .await?;
+
+        let path = temp.0.clone();
+        let page = tokio::task::spawn_blocking(move || wacz::read_main_page(&path, max_chars))
             .await
-            .context("Failed to parse get private accession response")
+            .map_err(|e| SdaError::Archive {
+                context: format!("Failed to read archive of accession {}", id),
+                message: e.to_string(),
+            })?
+            .map_err(|message| SdaError::Archive {
+                context: format!("Failed to read archive of accession {}", id),
+                message,
+            })?;
+        Ok(AccessionContentResponse {
+            accession_id: id,
+            url: page.url,
+            title: page.title,
+            content_type: page.content_type,
+            text: page.text,
+            total_chars: page.total_chars,
+            truncated: page.truncated,
+        })
     }
 
     /// Updates an existing accession.
+    ///
+    /// With `if_match`, the `ETag` read earlier is sent as `If-Match` so the
+    /// API can reject the write if the accession changed in between; a
+    /// `412 Precondition Failed` is reported as `SdaError::Conflict`.
     pub async fn update_accession(
         &self,
-        id: i32,
+        id: i64,
         request: UpdateAccessionRequest,
+        if_match: Option<String>,
     ) -> Result<GetOneAccessionResponse> {
         let url = format!("{}/api/v1/accessions/{}", self.base_url, id);
+        let mut builder = self.client.put(&url).json(&request);
+        if let Some(etag) = if_match {
+            builder = builder.header(IF_MATCH, etag);
+        }
         let response = self
-            .client
-            .put(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to send update accession request")?;
+            .send(builder, "Failed to send update accession request")
+            .await?;
+        self.forget_accession(id);
+        if response.status() == StatusCode::PRECONDITION_FAILED {
+            return Err(conflict(id));
+        }
 
         let response =
             Self::handle_response(response, "Server returned error for update accession").await?;
 
-        response
-            .json()
+        self.parse_json(response, "Failed to parse update accession response")
             .await
-            .context("Failed to parse update accession response")
     }
 
-    /// Lists metadata subjects with language parameter and optional pagination.
+    /// Stops a crawl that has not finished by deleting its `Pending`
+    /// accession, since the API has no endpoint to cancel a crawl.
+    ///
+    /// An accession whose crawl already finished is left alone. The delete
+    /// carries the accession's `ETag` as `If-Match`, so a crawl that
+    /// finishes between the check and the delete answers `412` and is
+    /// reported as not cancelled, with its new status. Without an `ETag`
+    /// that race cannot be ruled out, so nothing is deleted.
+    pub async fn cancel_crawl(&self, id: i64) -> Result<CancelCrawlResponse> {
+        let (current, etag) = self.get_any_accession(id).await?;
+        let not_cancelled = |status: CrawlStatus, message: String| CancelCrawlResponse {
+            accession_id: id,
+            cancelled: false,
+            crawl_status: status,
+            message,
+        };
+        if !matches!(current.accession.crawl_status, CrawlStatus::Pending) {
+            let status = current.accession.crawl_status;
+            return Ok(not_cancelled(
+                status.clone(),
+                format!(
+                    "The crawl of accession {} already finished with status {:?}; it was left unchanged.",
+                    id, status
+                ),
+            ));
+        }
+        let Some(etag) = etag else {
+            return Ok(not_cancelled(
+                CrawlStatus::Pending,
+                format!(
+                    "The API sent no ETag for accession {}, so it cannot be deleted only while its crawl is still pending; it was left unchanged.",
+                    id
+                ),
+            ));
+        };
+
+        let url = format!("{}/api/v1/accessions/{}", self.base_url, id);
+        let builder = self.client.delete(&url).header(IF_MATCH, etag);
+        let response = self
+            .send(builder, "Failed to send delete accession request")
+            .await?;
+        self.forget_accession(id);
+        if response.status() == StatusCode::PRECONDITION_FAILED {
+            let (current, _) = self.get_any_accession(id).await?;
+            let status = current.accession.crawl_status;
+            return Ok(not_cancelled(
+                status.clone(),
+                format!(
+                    "The crawl of accession {} finished with status {:?} while it was being cancelled; it was left unchanged.",
+                    id, status
+                ),
+            ));
+        }
+        Self::handle_response(response, "Server returned error for delete accession").await?;
+
+        Ok(CancelCrawlResponse {
+            accession_id: id,
+            cancelled: true,
+            crawl_status: CrawlStatus::Pending,
+            message: format!(
+                "Accession {} was pending and has been deleted. The API cannot stop a crawl job itself, so one already running may still finish upstream.",
+                id
+            ),
+        })
+    }
+
+    /// Checks that an accession is unchanged before it is overwritten.
+    ///
+    /// Fetches the accession and fails with `SdaError::Conflict` if its
+    /// `crawl_timestamp` differs from `expected_crawl_timestamp`. Returns the
+    /// accession's `ETag`, if any, for use as `If-Match` on the update.
+    pub async fn check_accession_unchanged(
+        &self,
+        id: i64,
+        expected_crawl_timestamp: &str,
+    ) -> Result<Option<String>> {
+        let (current, etag) = self.get_any_accession(id).await?;
+        check_crawl_timestamp(&current.accession, expected_crawl_timestamp)?;
+        Ok(etag)
+    }
+
+    /// Builds the full update request for a partial accession update.
+    ///
+    /// Fetches the accession (falling back to the private endpoint when the
+    /// public one returns 404) and fills every field missing from `patch`
+    /// with its current value, so the result can be sent with
+    /// `update_accession` without clobbering unrelated fields. A non-empty
+    /// `expected_crawl_timestamp` is checked as in
+    /// `check_accession_unchanged`. The accession's `ETag`, if any, is
+    /// returned alongside the request.
+    pub async fn prepare_accession_patch(
+        &self,
+        id: i64,
+        patch: UpdateAccessionPatch,
+        expected_crawl_timestamp: &str,
+    ) -> Result<(UpdateAccessionRequest, Option<String>)> {
+        let (current, etag) = self.get_any_accession(id).await?;
+        check_crawl_timestamp(&current.accession, expected_crawl_timestamp)?;
+        Ok((merge_accession_patch(&current.accession, patch), etag))
+    }
+
+    /// Lists accessions crawled within `args.since` of now, newest first.
+    ///
+    /// The API's `date_from` filter works in whole days, so the returned page
+    /// is also trimmed to accessions whose `crawl_timestamp` falls inside the
+    /// window; `num_pages` still counts the whole days.
+    pub async fn recent_accessions(
+        &self,
+        args: RecentAccessionsArgs,
+    ) -> Result<RecentAccessionsResponse> {
+        let lookback = dates::parse_lookback(&args.since)
+            .map_err(|e| SdaError::Validation(format!("since: {}", e)))?;
+        let cutoff = Utc::now() - lookback;
+        let list_args = ListAccessionsArgs {
+            page: args.page,
+            per_page: args.per_page,
+            include_both_languages: true,
+            date_from: cutoff.format("%Y-%m-%d").to_string(),
+            sort_by: "crawl_timestamp".to_string(),
+            sort_order: SortOrder::Desc,
+            ..Default::default()
+        };
+        let response = if args.is_private {
+            self.list_private_accessions(list_args).await?
+        } else {
+            self.list_accessions(list_args).await?
+        };
+
+        let items = response
+            .items
+            .into_iter()
+            .filter(|item| {
+                parse_crawl_timestamp(&item.crawl_timestamp)
+                    .is_none_or(|crawled| crawled >= cutoff.naive_utc())
+            })
+            .map(RecentAccession::from)
+            .collect();
+        Ok(RecentAccessionsResponse {
+            since: cutoff.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            items,
+            num_pages: response.num_pages,
+            page: response.page,
+            per_page: response.per_page,
+        })
+    }
+
+    /// Counts accessions per day, week or month of crawl date.
+    ///
+    /// Accessions are paged through newest first, stopping at the first one
+    /// crawled before `date_from`, so only the range and everything newer
+    /// is fetched. Every bucket overlapping the range is returned, empty or
+    /// not; the first and last may be partial.
+    pub async fn accessions_timeline(
+        &self,
+        args: AccessionsTimelineArgs,
+    ) -> Result<AccessionsTimelineResponse> {
+        let parse = |field: &str, input: &str, bound: dates::Bound| -> Result<NaiveDate> {
+            let date =
+                dates::normalize_filter_date(field, input, bound).map_err(SdaError::Validation)?;
+            NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                .map_err(|_| SdaError::Validation(format!("{} must not be empty", field)))
+        };
+        let from = parse("date_from", &args.date_from, dates::Bound::Start)?;
+        let to = if args.date_to.trim().is_empty() {
+            Utc::now().date_naive()
+        } else {
+            parse("date_to", &args.date_to, dates::Bound::End)?
+        };
+        if from > to {
+            return Err(SdaError::Validation(format!(
+                "date_from {} is after date_to {}",
+                from, to
+            )));
+        }
+        let mut starts = Vec::new();
+        let mut start = dates::bucket_start(from, args.bucket).map_err(SdaError::Validation)?;
+        while start <= to {
+            if starts.len() == MAX_TIMELINE_BUCKETS {
+                return Err(SdaError::Validation(format!(
+                    "{} to {} spans more than {} buckets; use a shorter range or a wider bucket",
+                    from, to, MAX_TIMELINE_BUCKETS
+                )));
+            }
+            starts.push(start);
+            start = dates::next_bucket(start, args.bucket).map_err(SdaError::Validation)?;
+        }
+
+        let mut counts = vec![0u64; starts.len()];
+        let list_args = ListAccessionsArgs {
+            include_both_languages: true,
+            sort_by: "crawl_timestamp".to_string(),
+            sort_order: SortOrder::Desc,
+            is_private: args.is_private,
+            ..Default::default()
+        };
+        let mut accessions = std::pin::pin!(self.accessions_stream(list_args));
+        while let Some(accession) = accessions.next().await {
+            let accession = accession?;
+            let Some(crawled) = parse_crawl_timestamp(&accession.crawl_timestamp) else {
+                continue;
+            };
+            let day = crawled.date();
+            if day < from {
+                break;
+            }
+            if day <= to {
+                counts[starts.partition_point(|start| *start <= day) - 1] += 1;
+            }
+        }
+
+        Ok(AccessionsTimelineResponse {
+            date_from: from.to_string(),
+            date_to: to.to_string(),
+            bucket: args.bucket,
+            total: counts.iter().sum(),
+            buckets: starts
+                .into_iter()
+                .zip(counts)
+                .map(|(start, count)| TimelineCount {
+                    start: start.to_string(),
+                    count,
+                })
+                .collect(),
+        })
+    }
+
+    /// Searches public accessions, public collections and subjects for
+    /// `query_term` concurrently, returning each category's top matches.
+    ///
+    /// A category whose first page is full is counted with a second,
+    /// one-item request, since the API only reports page counts. A category
+    /// that fails is reported with its error; the search fails only when
+    /// every category does.
+    pub async fn search(&self, args: SearchArgs) -> Result<SearchResponse> {
+        let query_term = args.query_term.trim().to_string();
+        if query_term.is_empty() {
+            return Err(SdaError::Validation(
+                "query_term must not be empty".to_string(),
+            ));
+        }
+        let limit = args.limit.clamp(1, MAX_SEARCH_LIMIT);
+        let subject_lang = match &args.lang {
+            MetadataLanguage::None if query_term.chars().any(language::is_arabic_letter) => {
+                MetadataLanguage::Arabic
+            }
+            MetadataLanguage::None => MetadataLanguage::English,
+            lang => lang.clone(),
+        };
+
+        let accessions = async {
+            let list = |per_page| {
+                self.list_accessions(ListAccessionsArgs {
+                    per_page,
+                    lang: args.lang.clone(),
+                    query_term: query_term.clone(),
+                    ..Default::default()
+                })
+            };
+            let page = list(limit).await?;
+            let total = match page.num_pages {
+                n if n > 1 => list(1).await?.num_pages,
+                _ => page.items.len() as i64,
+            };
+            Ok((
+                total,
+                page.items.into_iter().map(RecentAccession::from).collect(),
+            ))
+        };
+        let collections = async {
+            let list = |per_page| {
+                self.list_collections(ListCollectionsArgs {
+                    per_page,
+                    lang: args.lang.clone(),
+                    query_term: query_term.clone(),
+                    ..Default::default()
+                })
+            };
+            let page = list(limit).await?;
+            let total = match page.num_pages {
+                n if n > 1 => list(1).await?.num_pages,
+                _ => page.items.len() as i64,
+            };
+            Ok((total, page.items))
+        };
+        let subjects = async {
+            let list = |per_page| {
+                self.list_subjects(
+                    subject_lang.clone(),
+                    None,
+                    Some(per_page),
+                    None,
+                    query_term.clone(),
+                )
+            };
+            let page = list(limit).await?;
+            let total = match page.num_pages {
+                n if n > 1 => list(1).await?.num_pages,
+                _ => page.items.len() as i64,
+            };
+            Ok((total, page.items))
+        };
+        let (accessions, collections, subjects) = futures::join!(accessions, collections, subjects);
+
+        let (accessions, collections, subjects) = match (accessions, collections, subjects) {
+            (Err(e), Err(_), Err(_)) => return Err(e),
+            results => results,
+        };
+        Ok(SearchResponse {
+            query_term,
+            accessions: search_matches(accessions),
+            collections: search_matches(collections),
+            subjects: search_matches(subjects),
+        })
+    }
+
+    /// Explains a public or private accession's crawl outcome with
+    /// `diagnostics::diagnose`.
+    ///
+    /// With `check_target`, the seed URL of a failed crawl is also requested
+    /// directly with `preview::preview_crawl_target` to look for the cause,
+    /// unless `policy` refuses it.
+    pub async fn crawl_diagnostics(
+        &self,
+        id: i64,
+        check_target: bool,
+        policy: &CrawlPolicy,
+    ) -> Result<CrawlDiagnostics> {
+        let (response, _) = self.get_any_accession(id).await?;
+        let accession = response.accession;
+        let failed = matches!(
+            accession.crawl_status,
+            CrawlStatus::Error | CrawlStatus::BadCrawl
+        );
+        let target = if check_target && failed {
+            preview::preview_crawl_target(&accession.seed_url, DIAGNOSTIC_MAX_REDIRECTS, policy)
+                .await
+                .ok()
+        } else {
+            None
+        };
+        Ok(diagnostics::diagnose(
+            &accession,
+            parse_crawl_timestamp(&accession.crawl_timestamp),
+            Utc::now().naive_utc(),
+            target,
+        ))
+    }
+
+    /// Compares the metadata of two public or private accessions, e.g. an
+    /// original capture and its re-crawl.
+    pub async fn diff_accessions(&self, old_id: i64, new_id: i64) -> Result<AccessionDiffResponse> {
+        if old_id == new_id {
+            return Err(SdaError::Validation(
+                "old_id and new_id must be different accessions".to_string(),
+            ));
+        }
+        let ((old, _), (new, _)) = futures::try_join!(
+            self.get_any_accession(old_id),
+            self.get_any_accession(new_id)
+        )?;
+        Ok(diff::accession_diff(&old.accession, &new.accession))
+    }
+
+    /// Sets the privacy of several accessions concurrently.
+    ///
+    /// Each accession is read, and unless it already has the requested
+    /// privacy, written back with only `is_private` changed, as in
+    /// `prepare_accession_patch`. Duplicate IDs are processed once, and a
+    /// failure for one ID is recorded in its result instead of aborting the
+    /// batch.
+    pub async fn set_accessions_privacy(
+        &self,
+        ids: Vec<i64>,
+        is_private: bool,
+    ) -> SetAccessionsPrivacyResponse {
+        let results = self
+            .accession_privacy_changes(ids, is_private)
+            .collect()
+            .await;
+        SetAccessionsPrivacyResponse::new(is_private, results)
+    }
+
+    /// Sets the privacy of several accessions as `set_accessions_privacy`
+    /// does, at most `BATCH_CONCURRENCY` at a time, yielding each distinct
+    /// ID's result in request order.
+    ///
+    /// Dropping the stream stops reading further accessions; changes already
+    /// sent may still be applied.
+    pub fn accession_privacy_changes(
+        &self,
+        ids: Vec<i64>,
+        is_private: bool,
+    ) -> impl Stream<Item = AccessionPrivacyResult> + '_ {
+        stream::iter(unique_ids(ids))
+            .map(move |id| self.set_accession_privacy(id, is_private))
+            .buffered(BATCH_CONCURRENCY)
+    }
+
+    /// Sets one accession's privacy for `set_accessions_privacy`, recording
+    /// its previous privacy whenever it could be read.
+    async fn set_accession_privacy(&self, id: i64, is_private: bool) -> AccessionPrivacyResult {
+        let failed = |was_private, e: SdaError| AccessionPrivacyResult {
+            id,
+            status: PrivacyChangeStatus::Failed,
+            was_private,
+            error: Some(e.to_string()),
+        };
+        let (current, etag) = match self.get_any_accession(id).await {
+            Ok(found) => found,
+            Err(e) => return failed(None, e),
+        };
+        let was_private = Some(current.accession.is_private);
+        let status = if current.accession.is_private == is_private {
+            PrivacyChangeStatus::Unchanged
+        } else {
+            let patch = UpdateAccessionPatch {
+                is_private: Some(is_private),
+                ..Default::default()
+            };
+            let request = merge_accession_patch(&current.accession, patch);
+            if let Err(e) = self.update_accession(id, request, etag).await {
+                return failed(was_private, e);
+            }
+            PrivacyChangeStatus::Updated
+        };
+        AccessionPrivacyResult {
+            id,
+            status,
+            was_private,
+            error: None,
+        }
+    }
+
+    /// Builds a crawl request that re-archives an existing accession.
+    ///
+    /// The accession is fetched like in `prepare_accession_patch`, and its
+    /// seed URL, format and metadata are copied into a new crawl request,
+    /// with any fields set in `overrides` taking precedence.
+    pub async fn prepare_recrawl(
+        &self,
+        id: i64,
+        overrides: UpdateAccessionPatch,
+    ) -> Result<CreateAccessionCrawlRequest> {
+        let (current, _) = self.get_any_accession(id).await?;
+        let metadata = merge_accession_patch(&current.accession, overrides);
+        Ok(recrawl_request(&current.accession, metadata))
+    }
+
+    /// Starts several crawls, at most `BATCH_CONCURRENCY` at a time, yielding
+    /// each API response or error in request order.
+    ///
+    /// Dropping the stream stops starting crawls; requests already sent may
+    /// still start theirs.
+    pub fn create_accession_crawls(
+        &self,
+        requests: Vec<CreateAccessionCrawlRequest>,
+    ) -> impl Stream<Item = Result<String>> + '_ {
+        stream::iter(requests)
+            .map(|request| self.create_accession_crawl(request))
+            .buffered(BATCH_CONCURRENCY)
+    }
+
+    /// Retrieves an accession and its `ETag` by ID, falling back to the
+    /// private endpoint when the public one returns 404.
+    async fn get_any_accession(
+        &self,
+        id: i64,
+    ) -> Result<(GetOneAccessionResponse, Option<String>)> {
+        match self.fetch_accession(id, false).await {
+            Err(SdaError::Http { status, .. }) if status == StatusCode::NOT_FOUND => {
+                self.fetch_accession(id, true).await
+            }
+            other => other,
+        }
+    }
+
+    /// Lists metadata subjects with language parameter, optional pagination
+    /// and an optional search term.
     pub async fn list_subjects(
         &self,
         lang: MetadataLanguage,
         page: Option<i64>,
         per_page: Option<i64>,
-        in_collection_id: Option<i32>,
+        in_collection_id: Option<i64>,
+        query_term: String,
     ) -> Result<ListSubjectsResponse> {
         let url = format!("{}/api/v1/subjects", self.base_url);
         let mut query = vec![];
@@ -307,91 +2472,346 @@ impl SdaClient {
         {
             query.push(("in_collection_id", cid.to_string()));
         }
+        if !query_term.is_empty() {
+            query.push(("query_term", query_term));
+        }
 
-        let response = self
-            .client
-            .get(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .query(&query)
-            .send()
-            .await
-            .context("Failed to send list subjects request")?;
+        let builder = self.client.get(&url).query(&query);
+        self.get_cached(
+            builder,
+            "Failed to send list subjects request",
+            "Server returned error for list subjects",
+            "Failed to parse list subjects response",
+        )
+        .await
+    }
 
-        let response =
-            Self::handle_response(response, "Server returned error for list subjects").await?;
+    /// Lists every subject in `lang`, following pagination.
+    pub async fn list_all_subjects(
+        &self,
+        lang: MetadataLanguage,
+    ) -> Result<Vec<DublinMetadataSubjectResponse>> {
+        let mut items = Vec::new();
+        let mut page = FIRST_PAGE;
+        loop {
+            let response = self
+                .list_subjects(lang.clone(), Some(page), None, None, String::new())
+                .await?;
+            let last = response.items.is_empty() || response.page + 1 >= response.num_pages;
+            items.extend(response.items);
+            if last {
+                return Ok(items);
+            }
+            page = response.page + 1;
+        }
+    }
 
-        response
-            .json()
-            .await
-            .context("Failed to parse list subjects response")
+    /// Loads the subject vocabulary in both languages into the response
+    /// cache, returning how many subjects were loaded.
+    ///
+    /// The unpaginated first page an agent's plain `list_subjects` call asks
+    /// for is fetched as well as every explicit page, so both are served
+    /// from the cache until it expires.
+    pub async fn prefetch_subjects(&self) -> Result<usize> {
+        let load = |lang: MetadataLanguage| async move {
+            self.list_subjects(lang.clone(), None, None, None, String::new())
+                .await?;
+            self.list_all_subjects(lang).await.map(|items| items.len())
+        };
+        let (english, arabic) = tokio::try_join!(
+            load(MetadataLanguage::English),
+            load(MetadataLanguage::Arabic)
+        )?;
+        Ok(english + arabic)
     }
 
     /// Creates a new metadata subject.
+    ///
+    /// The name is checked with `validation::check_subject_name` first.
     pub async fn create_subject(&self, request: CreateSubjectRequest) -> Result<String> {
+        validation::check_subject_name(&request.metadata_subject).map_err(SdaError::Validation)?;
         let url = format!("{}/api/v1/subjects", self.base_url);
+        let builder = self.client.post(&url).json(&request);
         let response = self
-            .client
-            .post(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to send create subject request")?;
+            .send(builder, "Failed to send create subject request")
+            .await?;
 
         let response =
             Self::handle_response(response, "Server returned error for create subject").await?;
+        self.cache.clear();
 
         response
             .text()
             .await
-            .context("Failed to parse create subject response text")
+            .map_err(|e| SdaError::network("Failed to parse create subject response text", e))
+    }
+
+    /// Creates several metadata subjects concurrently.
+    ///
+    /// Requests naming the same subject (same language, same name ignoring
+    /// case and surrounding whitespace) are sent once. With `skip_existing`,
+    /// each subject is first looked up by name and left alone if it already
+    /// exists. A failure for one subject is recorded in its result instead of
+    /// aborting the batch.
+    pub async fn create_subjects(
+        &self,
+        requests: Vec<CreateSubjectRequest>,
+        skip_existing: bool,
+    ) -> CreateSubjectsResponse {
+        let mut unique: Vec<CreateSubjectRequest> = Vec::with_capacity(requests.len());
+        for request in requests {
+            let key = subject_key(&request.metadata_subject);
+            if !unique
+                .iter()
+                .any(|r| r.lang == request.lang && subject_key(&r.metadata_subject) == key)
+            {
+                unique.push(request);
+            }
+        }
+
+        let results = stream::iter(unique)
+            .map(|request| async move {
+                let mut result = CreateSubjectResult {
+                    request,
+                    created_id: None,
+                    existing_id: None,
+                    error: None,
+                };
+                let outcome = async {
+                    validation::check_subject_name(&result.request.metadata_subject)
+                        .map_err(SdaError::Validation)?;
+                    if skip_existing && let Some(id) = self.find_subject_id(&result.request).await?
+                    {
+                        return Ok((None, Some(id)));
+                    }
+                    let body = self.create_subject(result.request.clone()).await?;
+                    let created: DublinMetadataSubjectResponse = self
+                        .decode_json(body.as_bytes(), "Failed to parse create subject response")?;
+                    Ok::<_, SdaError>((Some(created.id), None))
+                }
+                .await;
+                match outcome {
+                    Ok((created_id, existing_id)) => {
+                        result.created_id = created_id;
+                        result.existing_id = existing_id;
+                    }
+                    Err(e) => result.error = Some(e.to_string()),
+                }
+                result
+            })
+            .buffered(BATCH_CONCURRENCY)
+            .collect()
+            .await;
+
+        CreateSubjectsResponse { results }
+    }
+
+    /// Returns the ID of the subject named like `request`, creating it if
+    /// there is none.
+    ///
+    /// Names are matched as in `find_subject_id`. If the create is rejected
+    /// with 409 Conflict, another caller created the subject in the meantime,
+    /// so the lookup is retried with the cache cleared before giving up.
+    pub async fn find_or_create_subject(
+        &self,
+        request: CreateSubjectRequest,
+    ) -> Result<FindOrCreateSubjectResponse> {
+        validation::check_subject_name(&request.metadata_subject).map_err(SdaError::Validation)?;
+        if let Some(id) = self.find_subject_id(&request).await? {
+            return Ok(FindOrCreateSubjectResponse { id, created: false });
+        }
+        match self.create_subject(request.clone()).await {
+            Ok(body) => {
+                let created: DublinMetadataSubjectResponse =
+                    self.decode_json(body.as_bytes(), "Failed to parse create subject response")?;
+                Ok(FindOrCreateSubjectResponse {
+                    id: created.id,
+                    created: true,
+                })
+            }
+            Err(SdaError::Http {
+                status: StatusCode::CONFLICT,
+                ..
+            }) => {
+                self.cache.clear();
+                match self.find_subject_id(&request).await? {
+                    Some(id) => Ok(FindOrCreateSubjectResponse { id, created: false }),
+                    None => Err(SdaError::Conflict(format!(
+                        "Subject '{}' could not be created (409 Conflict) and was not found on lookup",
+                        request.metadata_subject.trim()
+                    ))),
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the ID of the subject whose name matches `request`, if any.
+    ///
+    /// Names are compared ignoring case and surrounding whitespace.
+    pub async fn find_subject_id(&self, request: &CreateSubjectRequest) -> Result<Option<i64>> {
+        Ok(self
+            .subject_ids_named(request.lang.clone(), &request.metadata_subject)
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    /// Returns the IDs of every subject in `lang` named `name`, searching
+    /// all pages of the `query_term` results.
+    async fn subject_ids_named(&self, lang: MetadataLanguage, name: &str) -> Result<Vec<i64>> {
+        let key = subject_key(name);
+        let mut ids = Vec::new();
+        let mut page = FIRST_PAGE;
+        loop {
+            let response = self
+                .list_subjects(
+                    lang.clone(),
+                    Some(page),
+                    None,
+                    None,
+                    name.trim().to_string(),
+                )
+                .await?;
+            let last = response.items.is_empty() || response.page + 1 >= response.num_pages;
+            ids.extend(
+                response
+                    .items
+                    .into_iter()
+                    .filter(|s| subject_key(&s.subject) == key)
+                    .map(|s| s.id),
+            );
+            if last {
+                return Ok(ids);
+            }
+            page = response.page + 1;
+        }
+    }
+
+    /// Looks up subject `names` in language `lang`, in order, without
+    /// creating any.
+    ///
+    /// Names are matched as in `find_subject_id`. A name matching several
+    /// subjects is always an error; a name matching none is an error in
+    /// `Lookup` mode and `Missing` in `FindOrCreate` mode, once it passes
+    /// `validation::check_subject_name`. Every unresolved name is reported
+    /// at once, as `InvalidFields` on `metadata_subject_names`.
+    pub async fn lookup_subject_names(
+        &self,
+        names: &[String],
+        lang: MetadataLanguage,
+        mode: SubjectNameMode,
+    ) -> Result<Vec<NamedSubject>> {
+        let field = "metadata_subject_names";
+        if lang == MetadataLanguage::None {
+            return Err(SdaError::Validation(format!(
+                "{} needs metadata_language set to english or arabic, the language of the names",
+                field
+            )));
+        }
+        let mut subjects = Vec::with_capacity(names.len());
+        let mut problems = Vec::new();
+        let mut problem = |message: String| {
+            problems.push(ValidationProblem {
+                field: field.to_string(),
+                message,
+            })
+        };
+        for name in names {
+            let name = name.trim();
+            if name.is_empty() {
+                continue;
+            }
+            let matches = self.subject_ids_named(lang.clone(), name).await?;
+            match (matches.as_slice(), mode) {
+                ([id], _) => subjects.push(NamedSubject::Existing(*id)),
+                ([], SubjectNameMode::FindOrCreate) => match validation::check_subject_name(name) {
+                    Ok(()) => subjects.push(NamedSubject::Missing(name.to_string())),
+                    Err(message) => problem(format!("'{}': {}", name, message)),
+                },
+                ([], SubjectNameMode::Lookup) => problem(format!(
+                    "No {:?} subject is named '{}'; check the name with list_subjects, or set subject_name_mode to find_or_create to create it",
+                    lang, name
+                )),
+                (several, _) => problem(format!(
+                    "'{}' is ambiguous: subjects {:?} share the name; pass the intended ID in metadata_subjects instead",
+                    name, several
+                )),
+            }
+        }
+        if !problems.is_empty() {
+            return Err(SdaError::InvalidFields {
+                context: "Could not resolve subject names".to_string(),
+                problems,
+            });
+        }
+        Ok(subjects)
+    }
+
+    /// Creates the `Missing` subjects found by `lookup_subject_names` and
+    /// returns every subject's ID, in order.
+    pub async fn create_named_subjects(
+        &self,
+        subjects: Vec<NamedSubject>,
+        lang: MetadataLanguage,
+    ) -> Result<Vec<i64>> {
+        let mut ids = Vec::with_capacity(subjects.len());
+        for subject in subjects {
+            let id = match subject {
+                NamedSubject::Existing(id) => id,
+                NamedSubject::Missing(name) => {
+                    let request = CreateSubjectRequest {
+                        lang: lang.clone(),
+                        metadata_subject: name,
+                    };
+                    self.find_or_create_subject(request).await?.id
+                }
+            };
+            ids.push(id);
+        }
+        Ok(ids)
     }
 
     /// Deletes a metadata subject by its ID.
-    pub async fn delete_subject(&self, id: i32, request: DeleteSubjectRequest) -> Result<()> {
+    pub async fn delete_subject(&self, id: i64, request: DeleteSubjectRequest) -> Result<()> {
         let url = format!("{}/api/v1/subjects/{}", self.base_url, id);
+        let builder = self.client.delete(&url).json(&request);
         let response = self
-            .client
-            .delete(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to send delete subject request")?;
+            .send(builder, "Failed to send delete subject request")
+            .await?;
 
         Self::handle_response(response, "Server returned error for delete subject").await?;
+        self.cache.clear();
         Ok(())
     }
 
     /// Updates a metadata subject by its ID.
+    ///
+    /// The new name is checked with `validation::check_subject_name` first.
     pub async fn update_subject(
         &self,
-        id: i32,
+        id: i64,
         request: UpdateSubjectRequest,
     ) -> Result<DublinMetadataSubjectResponse> {
+        validation::check_subject_name(&request.metadata_subject).map_err(SdaError::Validation)?;
         let url = format!("{}/api/v1/subjects/{}", self.base_url, id);
+        let builder = self.client.put(&url).json(&request);
         let response = self
-            .client
-            .put(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to send update subject request")?;
+            .send(builder, "Failed to send update subject request")
+            .await?;
 
         let response =
             Self::handle_response(response, "Server returned error for update subject").await?;
+        self.cache.clear();
 
-        response
-            .json()
+        self.parse_json(response, "Failed to parse update subject response")
             .await
-            .context("Failed to parse update subject response")
     }
 
     /// Retrieves a single metadata subject by its ID.
     pub async fn get_subject(
         &self,
-        id: i32,
+        id: i64,
         lang: MetadataLanguage,
     ) -> Result<DublinMetadataSubjectResponse> {
         let url = format!("{}/api/v1/subjects/{}", self.base_url, id);
@@ -403,25 +2823,14 @@ impl SdaClient {
             MetadataLanguage::None => {}
         }
 
-        let response = self
-            .client
-            .get(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .query(&query)
-            .send()
-            .await
-            .context(format!("Failed to send get subject request for ID {}", id))?;
-
-        let response = Self::handle_response(
-            response,
+        let builder = self.client.get(&url).query(&query);
+        self.get_cached(
+            builder,
+            &format!("Failed to send get subject request for ID {}", id),
             &format!("Server returned error for get subject {}", id),
+            "Failed to parse get subject response",
         )
-        .await?;
-
-        response
-            .json()
-            .await
-            .context("Failed to parse get subject response")
+        .await
     }
 
     /// Lists public collections.
@@ -430,36 +2839,18 @@ impl SdaClient {
         args: ListCollectionsArgs,
     ) -> Result<ListCollectionsResponse> {
         let url = format!("{}/api/v1/collections", self.base_url);
-        let mut query = vec![];
-
-        if args.page != -1 {
-            query.push(("page", args.page.to_string()));
-        }
-        if args.per_page != -1 {
-            query.push(("per_page", args.per_page.to_string()));
-        }
-        match args.lang {
-            MetadataLanguage::English => query.push(("lang", "english".to_string())),
-            MetadataLanguage::Arabic => query.push(("lang", "arabic".to_string())),
-            MetadataLanguage::None => {}
-        }
+        let query = self.build_collection_query(args)?;
 
+        let builder = self.client.get(&url).query(&query);
         let response = self
-            .client
-            .get(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .query(&query)
-            .send()
-            .await
-            .context("Failed to send list collections request")?;
+            .send(builder, "Failed to send list collections request")
+            .await?;
 
         let response =
             Self::handle_response(response, "Server returned error for list collections").await?;
 
-        response
-            .json()
+        self.parse_json(response, "Failed to parse list collections response")
             .await
-            .context("Failed to parse list collections response")
     }
 
     /// Lists private collections.
@@ -468,29 +2859,23 @@ impl SdaClient {
         args: ListPrivateCollectionsArgs,
     ) -> Result<ListCollectionsResponse> {
         let url = format!("{}/api/v1/collections/private", self.base_url);
-        let mut query = vec![];
-
-        if args.page != -1 {
-            query.push(("page", args.page.to_string()));
-        }
-        if args.per_page != -1 {
-            query.push(("per_page", args.per_page.to_string()));
-        }
-        match args.lang {
-            MetadataLanguage::English => query.push(("lang", "english".to_string())),
-            MetadataLanguage::Arabic => query.push(("lang", "arabic".to_string())),
-            MetadataLanguage::None => {}
-        }
-        query.push(("is_private", args.is_private.to_string()));
-
+        let is_private = args.is_private;
+        let mut query = self.build_collection_query(ListCollectionsArgs {
+            page: args.page,
+            per_page: args.per_page,
+            lang: args.lang,
+            metadata_subjects: args.metadata_subjects,
+            query_term: args.query_term,
+            date_from: args.date_from,
+            date_to: args.date_to,
+            count_only: args.count_only,
+        })?;
+        query.push(("is_private", is_private.to_string()));
+
+        let builder = self.client.get(&url).query(&query);
         let response = self
-            .client
-            .get(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .query(&query)
-            .send()
-            .await
-            .context("Failed to send list private collections request")?;
+            .send(builder, "Failed to send list private collections request")
+            .await?;
 
         let response = Self::handle_response(
             response,
@@ -498,16 +2883,17 @@ impl SdaClient {
         )
         .await?;
 
-        response
-            .json()
-            .await
-            .context("Failed to parse list private collections response")
+        self.parse_json(
+            response,
+            "Failed to parse list private collections response",
+        )
+        .await
     }
 
     /// Retrieves a single collection by its ID.
     pub async fn get_collection(
         &self,
-        id: i32,
+        id: i64,
         lang: MetadataLanguage,
     ) -> Result<CollectionResponse> {
         let url = format!("{}/api/v1/collections/{}", self.base_url, id);
@@ -519,235 +2905,644 @@ impl SdaClient {
             MetadataLanguage::None => {}
         }
 
-        let response = self
-            .client
-            .get(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .query(&query)
-            .send()
-            .await
-            .context(format!(
-                "Failed to send get collection request for ID {}",
-                id
-            ))?;
-
-        let response = Self::handle_response(
-            response,
+        let builder = self.client.get(&url).query(&query);
+        self.get_cached(
+            builder,
+            &format!("Failed to send get collection request for ID {}", id),
             &format!("Server returned error for get collection {}", id),
+            "Failed to parse get collection response",
         )
-        .await?;
-
-        response
-            .json()
-            .await
-            .context("Failed to parse get collection response")
+        .await
     }
 
     /// Creates a new collection.
+    ///
+    /// The title and description are checked with
+    /// `validation::check_collection_text` first.
     pub async fn create_collection(&self, request: CreateCollectionRequest) -> Result<String> {
+        validation::check_collection_text(&request.title, &request.description)
+            .map_err(SdaError::Validation)?;
         let url = format!("{}/api/v1/collections", self.base_url);
+        let builder = self.client.post(&url).json(&request);
         let response = self
-            .client
-            .post(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to send create collection request")?;
+            .send(builder, "Failed to send create collection request")
+            .await?;
 
         let response =
             Self::handle_response(response, "Server returned error for create collection").await?;
+        self.cache.clear();
 
         response
             .text()
             .await
-            .context("Failed to parse create collection response text")
+            .map_err(|e| SdaError::network("Failed to parse create collection response text", e))
     }
 
     /// Updates an existing collection.
+    ///
+    /// The title and description are checked with
+    /// `validation::check_collection_text` first.
     pub async fn update_collection(
         &self,
-        id: i32,
+        id: i64,
         request: UpdateCollectionRequest,
     ) -> Result<CollectionResponse> {
+        validation::check_collection_text(&request.title, &request.description)
+            .map_err(SdaError::Validation)?;
         let url = format!("{}/api/v1/collections/{}", self.base_url, id);
+        let builder = self.client.put(&url).json(&request);
         let response = self
-            .client
-            .put(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .json(&request)
-            .send()
-            .await
-            .context(format!(
-                "Failed to send update collection request for ID {}",
-                id
-            ))?;
+            .send(
+                builder,
+                &format!("Failed to send update collection request for ID {}", id),
+            )
+            .await?;
 
         let response = Self::handle_response(
             response,
             &format!("Server returned error for update collection {}", id),
         )
         .await?;
+        self.cache.clear();
 
-        response
-            .json()
+        self.parse_json(response, "Failed to parse update collection response")
             .await
-            .context("Failed to parse update collection response")
     }
 
-    /// Lists contributors.
-    pub async fn list_contributors(
+    /// Builds a collection update that adds and removes subjects.
+    ///
+    /// The collection is fetched fresh, bypassing the response cache, and
+    /// its current `subject_ids` are edited with `edit_subject_ids`, so the
+    /// result can be sent with `update_collection` without dropping
+    /// subjects the caller did not know about. Fails with a validation
+    /// error if the API does not report the collection's subjects.
+    pub async fn prepare_collection_subjects_update(
         &self,
+        id: i64,
         lang: MetadataLanguage,
-        page: Option<i64>,
-        per_page: Option<i64>,
-        query_term: String,
-    ) -> Result<ListContributorsResponse> {
-        let url = format!("{}/api/v1/contributors", self.base_url);
-        let mut query = vec![];
+        add: &[i64],
+        remove: &[i64],
+    ) -> Result<UpdateCollectionRequest> {
+        let current = self.fetch_collection_uncached(id, &lang).await?;
+        let Some(subject_ids) = current.subject_ids else {
+            return Err(SdaError::Validation(format!(
+                "The API did not report the subjects of collection {}, so they cannot be edited safely; use update_collection with the full subject_ids list instead",
+                id
+            )));
+        };
+        Ok(UpdateCollectionRequest {
+            lang,
+            title: current.title,
+            is_private: current.is_private,
+            subject_ids: edit_subject_ids(&subject_ids, add, remove),
+            description: current.description.unwrap_or_default(),
+        })
+    }
+
+    /// Builds a collection update that changes only the fields set in `patch`.
+    ///
+    /// The collection is fetched fresh, bypassing the response cache, and
+    /// every field the patch leaves unset keeps its current value, so the
+    /// result can be sent with `update_collection`. Unless the patch sets
+    /// `subject_ids`, fails with a validation error if the API does not
+    /// report the collection's subjects, rather than clearing them.
+    pub async fn prepare_collection_patch(
+        &self,
+        id: i64,
+        lang: MetadataLanguage,
+        patch: UpdateCollectionPatch,
+    ) -> Result<UpdateCollectionRequest> {
+        let current = self.fetch_collection_uncached(id, &lang).await?;
+        let Some(subject_ids) = patch.subject_ids.or(current.subject_ids) else {
+            return Err(SdaError::Validation(format!(
+                "The API did not report the subjects of collection {}, so they cannot be kept safely; set subject_ids in the patch",
+                id
+            )));
+        };
+        Ok(UpdateCollectionRequest {
+            lang,
+            title: patch.title.unwrap_or(current.title),
+            is_private: patch.is_private.unwrap_or(current.is_private),
+            subject_ids,
+            description: patch
+                .description
+                .or(current.description)
+                .unwrap_or_default(),
+        })
+    }
 
+    /// Retrieves a collection, bypassing the response cache, so a
+    /// read-modify-write starts from its current state.
+    async fn fetch_collection_uncached(
+        &self,
+        id: i64,
+        lang: &MetadataLanguage,
+    ) -> Result<CollectionResponse> {
+        let url = format!("{}/api/v1/collections/{}", self.base_url, id);
+        let mut query = vec![];
         match lang {
             MetadataLanguage::English => query.push(("lang", "english".to_string())),
             MetadataLanguage::Arabic => query.push(("lang", "arabic".to_string())),
             MetadataLanguage::None => {}
         }
-        if let Some(p) = page {
-            query.push(("page", p.to_string()));
-        }
-        if let Some(pp) = per_page {
-            query.push(("per_page", pp.to_string()));
-        }
-        if !query_term.is_empty() {
-            query.push(("query_term", query_term));
-        }
-
+        let builder = self.client.get(&url).query(&query);
         let response = self
-            .client
-            .get(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .query(&query)
-            .send()
-            .await
-            .context("Failed to send list contributors request")?;
-
-        let response =
-            Self::handle_response(response, "Server returned error for list contributors").await?;
-
-        response
-            .json()
+            .send(
+                builder,
+                &format!("Failed to send get collection request for ID {}", id),
+            )
+            .await?;
+        let response = Self::handle_response(
+            response,
+            &format!("Server returned error for get collection {}", id),
+        )
+        .await?;
+        self.parse_json(response, "Failed to parse get collection response")
             .await
-            .context("Failed to parse list contributors response")
     }
 
-    /// Gets a single contributor by ID.
-    pub async fn get_contributor(
+    /// Lists the accessions that are members of a collection.
+    pub async fn list_collection_accessions(
         &self,
-        id: i32,
-        lang: MetadataLanguage,
-    ) -> Result<ContributorResponse> {
-        let url = format!("{}/api/v1/contributors/{}", self.base_url, id);
+        args: ListCollectionAccessionsArgs,
+    ) -> Result<ListAccessionsResponse> {
+        let url = format!(
+            "{}/api/v1/collections/{}/accessions",
+            self.base_url, args.collection_id
+        );
         let mut query = vec![];
-
-        match lang {
+        if args.page != -1 {
+            query.push(("page", args.page.to_string()));
+        }
+        if args.per_page != -1 {
+            query.push(("per_page", args.per_page.to_string()));
+        }
+        match args.lang {
             MetadataLanguage::English => query.push(("lang", "english".to_string())),
             MetadataLanguage::Arabic => query.push(("lang", "arabic".to_string())),
             MetadataLanguage::None => {}
         }
 
+        let builder = self.client.get(&url).query(&query);
         let response = self
-            .client
-            .get(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .query(&query)
-            .send()
-            .await
-            .context(format!(
-                "Failed to send get contributor request for ID {}",
-                id
-            ))?;
+            .send(
+                builder,
+                &format!(
+                    "Failed to send list accessions request for collection {}",
+                    args.collection_id
+                ),
+            )
+            .await?;
 
         let response = Self::handle_response(
             response,
-            &format!("Server returned error for get contributor {}", id),
+            &format!(
+                "Server returned error for list accessions of collection {}",
+                args.collection_id
+            ),
         )
         .await?;
 
-        response
-            .json()
-            .await
-            .context("Failed to parse get contributor response")
+        self.parse_json(
+            response,
+            "Failed to parse list collection accessions response",
+        )
+        .await
     }
 
-    /// Creates a new contributor.
-    pub async fn create_contributor(&self, request: CreateContributorRequest) -> Result<String> {
-        let url = format!("{}/api/v1/contributors", self.base_url);
-        let response = self
-            .client
-            .post(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to send create contributor request")?;
-
-        let response =
-            Self::handle_response(response, "Server returned error for create contributor").await?;
-
-        response
-            .text()
-            .await
-            .context("Failed to parse create contributor response text")
+    /// Lists every accession in a collection, following pagination.
+    pub async fn list_all_collection_accessions(
+        &self,
+        collection_id: i64,
+        lang: MetadataLanguage,
+    ) -> Result<Vec<AccessionsWithMetadataResponse>> {
+        let mut items = Vec::new();
+        let mut page = FIRST_PAGE;
+        loop {
+            let response = self
+                .list_collection_accessions(ListCollectionAccessionsArgs {
+                    collection_id,
+                    page,
+                    per_page: -1,
+                    lang: lang.clone(),
+                    verbosity: Verbosity::Full,
+                })
+                .await?;
+            let last = response.items.is_empty() || response.page + 1 >= response.num_pages;
+            items.extend(response.items);
+            if last {
+                return Ok(items);
+            }
+            page = response.page + 1;
+        }
     }
 
-    /// Updates a contributor.
-    pub async fn update_contributor(
+    /// Lists every collection, public then private, following pagination.
+    async fn list_all_collections(
         &self,
-        id: i32,
-        request: UpdateContributorRequest,
-    ) -> Result<ContributorResponse> {
-        let url = format!("{}/api/v1/contributors/{}", self.base_url, id);
-        let response = self
-            .client
-            .put(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .json(&request)
-            .send()
-            .await
-            .context(format!(
-                "Failed to send update contributor request for ID {}",
-                id
-            ))?;
-
-        let response = Self::handle_response(
-            response,
-            &format!("Server returned error for update contributor {}", id),
-        )
-        .await?;
-
-        response
-            .json()
-            .await
-            .context("Failed to parse update contributor response")
+        lang: &MetadataLanguage,
+    ) -> Result<Vec<CollectionResponse>> {
+        let mut collections = Vec::new();
+        for is_private in [false, true] {
+            let mut page = FIRST_PAGE;
+            loop {
+                let response = self
+                    .list_private_collections(ListPrivateCollectionsArgs {
+                        page,
+                        lang: lang.clone(),
+                        is_private,
+                        ..Default::default()
+                    })
+                    .await?;
+                let last = response.items.is_empty() || response.page + 1 >= response.num_pages;
+                collections.extend(response.items);
+                if last {
+                    break;
+                }
+                page = response.page + 1;
+            }
+        }
+        Ok(collections)
     }
 
-    /// Deletes a contributor.
-    pub async fn delete_contributor(
+    /// Lists the collections, public and private, that accession `id` is a
+    /// member of, with the number of collections checked and whether the
+    /// answer came from the cache.
+    ///
+    /// The API only lists membership from the collection side, so every
+    /// collection's accessions are listed, a few collections at a time. The
+    /// memberships found are cached like collection responses, so looking
+    /// up other accessions soon after needs no new scan; `refresh` skips
+    /// the cache, and any change made through this server clears it.
+    pub async fn accession_collections(
         &self,
-        id: i32,
-        request: DeleteContributorRequest,
-    ) -> Result<()> {
+        id: i64,
+        lang: MetadataLanguage,
+        refresh: bool,
+    ) -> Result<(Vec<CollectionResponse>, usize, bool)> {
+        // The fragment is never sent, so no request shares this key.
+        let key = format!(
+            "{}/api/v1/collections#members-{}",
+            self.base_url,
+            serde_json::to_string(&lang).unwrap_or_default()
+        );
+        let cached = (!refresh)
+            .then(|| self.cache.get(&key))
+            .flatten()
+            .and_then(|body| serde_json::from_slice::<MembershipIndex>(&body).ok());
+        let from_cache = cached.is_some();
+        let index = match cached {
+            Some(index) => index,
+            None => {
+                let collections = self.list_all_collections(&lang).await?;
+                let ids: Vec<i64> = collections.iter().map(|c| c.id).collect();
+                let members: Vec<_> = stream::iter(ids)
+                    .map(|collection_id| {
+                        self.list_all_collection_accessions(collection_id, lang.clone())
+                    })
+                    .buffered(BATCH_CONCURRENCY)
+                    .try_collect()
+                    .await?;
+                let index = MembershipIndex {
+                    collections: collections
+                        .into_iter()
+                        .zip(members)
+                        .map(|(collection, items)| {
+                            (collection, items.into_iter().map(|a| a.id).collect())
+                        })
+                        .collect(),
+                };
+                if let Ok(body) = serde_json::to_vec(&index) {
+                    self.cache.insert(key, body);
+                }
+                index
+            }
+        };
+        let scanned = index.collections.len();
+        let collections = index
+            .collections
+            .into_iter()
+            .filter(|(_, members)| members.contains(&id))
+            .map(|(collection, _)| collection)
+            .collect();
+        Ok((collections, scanned, from_cache))
+    }
+
+    /// Gathers a collection, its subjects and its member accessions into an
+    /// export manifest.
+    ///
+    /// Subject names are looked up with `get_subject` and left empty when
+    /// that fails. Each member's details (including `wacz_url`) are fetched
+    /// from the public or private endpoint as appropriate, with bounded
+    /// concurrency. With `archive_dir`, each archive is also downloaded
+    /// there as `{id}.wacz` (or `.warc`). A member whose details or archive
+    /// cannot be fetched is recorded in `errors`; an accession whose archive
+    /// alone failed is still listed, without `archive_file`.
+    pub async fn export_collection(
+        &self,
+        collection_id: i64,
+        lang: MetadataLanguage,
+        archive_dir: Option<&Path>,
+    ) -> Result<CollectionExport> {
+        let collection = self.get_collection(collection_id, lang.clone()).await?;
+        let members = self
+            .list_all_collection_accessions(collection_id, lang.clone())
+            .await?;
+
+        let subject_ids = collection.subject_ids.clone().unwrap_or_default();
+        let subjects = stream::iter(subject_ids)
+            .map(|id| {
+                let lang = lang.clone();
+                async move {
+                    CollectionExportSubject {
+                        id,
+                        subject: self.get_subject(id, lang).await.ok().map(|s| s.subject),
+                    }
+                }
+            })
+            .buffered(BATCH_CONCURRENCY)
+            .collect()
+            .await;
+
+        let results: Vec<_> = stream::iter(members)
+            .map(|member| async move {
+                let id = member.id;
+                (id, self.export_accession(member, archive_dir).await)
+            })
+            .buffered(BATCH_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut accessions = Vec::new();
+        let mut errors = BTreeMap::new();
+        for (id, result) in results {
+            match result {
+                Ok((accession, archive_error)) => {
+                    accessions.push(accession);
+                    if let Some(e) = archive_error {
+                        errors.insert(id, e.to_string());
+                    }
+                }
+                Err(e) => {
+                    errors.insert(id, e.to_string());
+                }
+            }
+        }
+        Ok(CollectionExport {
+            exported_at: Utc::now().to_rfc3339(),
+            collection,
+            subjects,
+            accessions,
+            errors,
+        })
+    }
+
+    /// Fetches one collection member for `export_collection`, downloading its
+    /// archive into `archive_dir` when given. A failed download is returned
+    /// alongside the accession rather than as the result.
+    async fn export_accession(
+        &self,
+        member: AccessionsWithMetadataResponse,
+        archive_dir: Option<&Path>,
+    ) -> Result<(ExportedAccession, Option<SdaError>)> {
+        let details = self.fetch_accession(member.id, member.is_private).await?.0;
+        let archive_url = details.archive_url().to_string();
+        let extension = match details.accession.dublin_metadata_format {
+            DublinMetadataFormat::Wacz => "wacz",
+            DublinMetadataFormat::Warc => "warc",
+        };
+        let mut exported = ExportedAccession {
+            accession: details.accession,
+            wacz_url: details.wacz_url,
+            archive_file: None,
+            sha256: None,
+        };
+        let Some(dir) = archive_dir else {
+            return Ok((exported, None));
+        };
+        let name = format!("{}.{}", member.id, extension);
+        match self
+            .download_wacz(&archive_url, &dir.join(&name), |_, _| {})
+            .await
+        {
+            Ok(download) => {
+                exported.archive_file = Some(format!("{}/{}", export::BUNDLE_ARCHIVE_DIR, name));
+                exported.sha256 = Some(download.sha256);
+                Ok((exported, None))
+            }
+            Err(e) => Ok((exported, Some(e))),
+        }
+    }
+
+    /// Adds an accession to a collection.
+    ///
+    /// Returns `false` without error when the accession is already a member
+    /// (the API responds with 409 Conflict).
+    pub async fn add_accession_to_collection(
+        &self,
+        collection_id: i64,
+        accession_id: i64,
+    ) -> Result<bool> {
+        let url = format!(
+            "{}/api/v1/collections/{}/accessions/{}",
+            self.base_url, collection_id, accession_id
+        );
+        let builder = self.client.post(&url);
+        let response = self
+            .send(
+                builder,
+                &format!(
+                    "Failed to send add accession {} to collection {} request",
+                    accession_id, collection_id
+                ),
+            )
+            .await?;
+
+        if response.status() == StatusCode::CONFLICT {
+            return Ok(false);
+        }
+        Self::handle_response(
+            response,
+            &format!(
+                "Server returned error for add accession {} to collection {}",
+                accession_id, collection_id
+            ),
+        )
+        .await?;
+        self.cache.clear();
+        Ok(true)
+    }
+
+    /// Removes an accession from a collection.
+    ///
+    /// Returns `false` without error when the accession is not a member
+    /// (the API responds with 404 Not Found). Since the API answers 404 for
+    /// a missing collection too, the collection is then looked up, and a
+    /// missing one is reported as not found.
+    pub async fn remove_accession_from_collection(
+        &self,
+        collection_id: i64,
+        accession_id: i64,
+    ) -> Result<bool> {
+        let url = format!(
+            "{}/api/v1/collections/{}/accessions/{}",
+            self.base_url, collection_id, accession_id
+        );
+        let builder = self.client.delete(&url);
+        let response = self
+            .send(
+                builder,
+                &format!(
+                    "Failed to send remove accession {} from collection {} request",
+                    accession_id, collection_id
+                ),
+            )
+            .await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            self.get_collection(collection_id, MetadataLanguage::None)
+                .await?;
+            return Ok(false);
+        }
+        Self::handle_response(
+            response,
+            &format!(
+                "Server returned error for remove accession {} from collection {}",
+                accession_id, collection_id
+            ),
+        )
+        .await?;
+        self.cache.clear();
+        Ok(true)
+    }
+
+    /// Lists contributors.
+    pub async fn list_contributors(
+        &self,
+        lang: MetadataLanguage,
+        page: Option<i64>,
+        per_page: Option<i64>,
+        query_term: String,
+    ) -> Result<ListContributorsResponse> {
+        let url = format!("{}/api/v1/contributors", self.base_url);
+        let mut query = vec![];
+
+        match lang {
+            MetadataLanguage::English => query.push(("lang", "english".to_string())),
+            MetadataLanguage::Arabic => query.push(("lang", "arabic".to_string())),
+            MetadataLanguage::None => {}
+        }
+        if let Some(p) = page {
+            query.push(("page", p.to_string()));
+        }
+        if let Some(pp) = per_page {
+            query.push(("per_page", pp.to_string()));
+        }
+        if !query_term.is_empty() {
+            query.push(("query_term", query_term));
+        }
+
+        let builder = self.client.get(&url).query(&query);
+        let response = self
+            .send(builder, "Failed to send list contributors request")
+            .await?;
+
+        let response =
+            Self::handle_response(response, "Server returned error for list contributors").await?;
+
+        self.parse_json(response, "Failed to parse list contributors response")
+            .await
+    }
+
+    /// Gets a single contributor by ID.
+    pub async fn get_contributor(
+        &self,
+        id: i64,
+        lang: MetadataLanguage,
+    ) -> Result<ContributorResponse> {
         let url = format!("{}/api/v1/contributors/{}", self.base_url, id);
+        let mut query = vec![];
+
+        match lang {
+            MetadataLanguage::English => query.push(("lang", "english".to_string())),
+            MetadataLanguage::Arabic => query.push(("lang", "arabic".to_string())),
+            MetadataLanguage::None => {}
+        }
+
+        let builder = self.client.get(&url).query(&query);
         let response = self
-            .client
-            .delete(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .json(&request)
-            .send()
+            .send(
+                builder,
+                &format!("Failed to send get contributor request for ID {}", id),
+            )
+            .await?;
+
+        let response = Self::handle_response(
+            response,
+            &format!("Server returned error for get contributor {}", id),
+        )
+        .await?;
+
+        self.parse_json(response, "Failed to parse get contributor response")
             .await
-            .context(format!(
-                "Failed to send delete contributor request for ID {}",
-                id
-            ))?;
+    }
+
+    /// Creates a new contributor.
+    pub async fn create_contributor(&self, request: CreateContributorRequest) -> Result<String> {
+        let url = format!("{}/api/v1/contributors", self.base_url);
+        let builder = self.client.post(&url).json(&request);
+        let response = self
+            .send(builder, "Failed to send create contributor request")
+            .await?;
+
+        let response =
+            Self::handle_response(response, "Server returned error for create contributor").await?;
+
+        response
+            .text()
+            .await
+            .map_err(|e| SdaError::network("Failed to parse create contributor response text", e))
+    }
+
+    /// Updates a contributor.
+    pub async fn update_contributor(
+        &self,
+        id: i64,
+        request: UpdateContributorRequest,
+    ) -> Result<ContributorResponse> {
+        let url = format!("{}/api/v1/contributors/{}", self.base_url, id);
+        let builder = self.client.put(&url).json(&request);
+        let response = self
+            .send(
+                builder,
+                &format!("Failed to send update contributor request for ID {}", id),
+            )
+            .await?;
+
+        let response = Self::handle_response(
+            response,
+            &format!("Server returned error for update contributor {}", id),
+        )
+        .await?;
+
+        self.parse_json(response, "Failed to parse update contributor response")
+            .await
+    }
+
+    /// Deletes a contributor.
+    pub async fn delete_contributor(
+        &self,
+        id: i64,
+        request: DeleteContributorRequest,
+    ) -> Result<()> {
+        let url = format!("{}/api/v1/contributors/{}", self.base_url, id);
+        let builder = self.client.delete(&url).json(&request);
+        let response = self
+            .send(
+                builder,
+                &format!("Failed to send delete contributor request for ID {}", id),
+            )
+            .await?;
 
         Self::handle_response(response, "Server returned error for delete contributor").await?;
         Ok(())
@@ -779,29 +3574,23 @@ impl SdaClient {
             query.push(("query_term", query_term));
         }
 
+        let builder = self.client.get(&url).query(&query);
         let response = self
-            .client
-            .get(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .query(&query)
-            .send()
-            .await
-            .context("Failed to send list contributor roles request")?;
+            .send(builder, "Failed to send list contributor roles request")
+            .await?;
 
         let response =
             Self::handle_response(response, "Server returned error for list contributor roles")
                 .await?;
 
-        response
-            .json()
+        self.parse_json(response, "Failed to parse list contributor roles response")
             .await
-            .context("Failed to parse list contributor roles response")
     }
 
     /// Gets a single contributor role by ID.
     pub async fn get_contributor_role(
         &self,
-        id: i32,
+        id: i64,
         lang: MetadataLanguage,
     ) -> Result<ContributorRoleResponse> {
         let url = format!("{}/api/v1/contributors/roles/{}", self.base_url, id);
@@ -813,17 +3602,13 @@ impl SdaClient {
             MetadataLanguage::None => {}
         }
 
+        let builder = self.client.get(&url).query(&query);
         let response = self
-            .client
-            .get(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .query(&query)
-            .send()
-            .await
-            .context(format!(
-                "Failed to send get contributor role request for ID {}",
-                id
-            ))?;
+            .send(
+                builder,
+                &format!("Failed to send get contributor role request for ID {}", id),
+            )
+            .await?;
 
         let response = Self::handle_response(
             response,
@@ -831,10 +3616,8 @@ impl SdaClient {
         )
         .await?;
 
-        response
-            .json()
+        self.parse_json(response, "Failed to parse get contributor role response")
             .await
-            .context("Failed to parse get contributor role response")
     }
 
     /// Creates a new contributor role.
@@ -843,14 +3626,10 @@ impl SdaClient {
         request: CreateContributorRoleRequest,
     ) -> Result<String> {
         let url = format!("{}/api/v1/contributors/roles", self.base_url);
+        let builder = self.client.post(&url).json(&request);
         let response = self
-            .client
-            .post(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to send create contributor role request")?;
+            .send(builder, "Failed to send create contributor role request")
+            .await?;
 
         let response = Self::handle_response(
             response,
@@ -858,30 +3637,28 @@ impl SdaClient {
         )
         .await?;
 
-        response
-            .text()
-            .await
-            .context("Failed to parse create contributor role response text")
+        response.text().await.map_err(|e| {
+            SdaError::network("Failed to parse create contributor role response text", e)
+        })
     }
 
     /// Updates a contributor role.
     pub async fn update_contributor_role(
         &self,
-        id: i32,
+        id: i64,
         request: UpdateContributorRoleRequest,
     ) -> Result<ContributorRoleResponse> {
         let url = format!("{}/api/v1/contributors/roles/{}", self.base_url, id);
+        let builder = self.client.put(&url).json(&request);
         let response = self
-            .client
-            .put(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .json(&request)
-            .send()
-            .await
-            .context(format!(
-                "Failed to send update contributor role request for ID {}",
-                id
-            ))?;
+            .send(
+                builder,
+                &format!(
+                    "Failed to send update contributor role request for ID {}",
+                    id
+                ),
+            )
+            .await?;
 
         let response = Self::handle_response(
             response,
@@ -889,30 +3666,27 @@ impl SdaClient {
         )
         .await?;
 
-        response
-            .json()
+        self.parse_json(response, "Failed to parse update contributor role response")
             .await
-            .context("Failed to parse update contributor role response")
     }
 
     /// Deletes a contributor role.
     pub async fn delete_contributor_role(
         &self,
-        id: i32,
+        id: i64,
         request: DeleteContributorRoleRequest,
     ) -> Result<()> {
         let url = format!("{}/api/v1/contributors/roles/{}", self.base_url, id);
+        let builder = self.client.delete(&url).json(&request);
         let response = self
-            .client
-            .delete(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .json(&request)
-            .send()
-            .await
-            .context(format!(
-                "Failed to send delete contributor role request for ID {}",
-                id
-            ))?;
+            .send(
+                builder,
+                &format!(
+                    "Failed to send delete contributor role request for ID {}",
+                    id
+                ),
+            )
+            .await?;
 
         Self::handle_response(
             response,
@@ -948,26 +3722,20 @@ impl SdaClient {
             query.push(("query_term", query_term));
         }
 
+        let builder = self.client.get(&url).query(&query);
         let response = self
-            .client
-            .get(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .query(&query)
-            .send()
-            .await
-            .context("Failed to send list creators request")?;
+            .send(builder, "Failed to send list creators request")
+            .await?;
 
         let response =
             Self::handle_response(response, "Server returned error for list creators").await?;
 
-        response
-            .json()
+        self.parse_json(response, "Failed to parse list creators response")
             .await
-            .context("Failed to parse list creators response")
     }
 
     /// Gets a single creator by ID.
-    pub async fn get_creator(&self, id: i32, lang: MetadataLanguage) -> Result<CreatorResponse> {
+    pub async fn get_creator(&self, id: i64, lang: MetadataLanguage) -> Result<CreatorResponse> {
         let url = format!("{}/api/v1/creators/{}", self.base_url, id);
         let mut query = vec![];
 
@@ -977,14 +3745,13 @@ impl SdaClient {
             MetadataLanguage::None => {}
         }
 
+        let builder = self.client.get(&url).query(&query);
         let response = self
-            .client
-            .get(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .query(&query)
-            .send()
-            .await
-            .context(format!("Failed to send get creator request for ID {}", id))?;
+            .send(
+                builder,
+                &format!("Failed to send get creator request for ID {}", id),
+            )
+            .await?;
 
         let response = Self::handle_response(
             response,
@@ -992,23 +3759,17 @@ impl SdaClient {
         )
         .await?;
 
-        response
-            .json()
+        self.parse_json(response, "Failed to parse get creator response")
             .await
-            .context("Failed to parse get creator response")
     }
 
     /// Creates a new creator.
     pub async fn create_creator(&self, request: CreateCreatorRequest) -> Result<String> {
         let url = format!("{}/api/v1/creators", self.base_url);
+        let builder = self.client.post(&url).json(&request);
         let response = self
-            .client
-            .post(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to send create creator request")?;
+            .send(builder, "Failed to send create creator request")
+            .await?;
 
         let response =
             Self::handle_response(response, "Server returned error for create creator").await?;
@@ -1016,27 +3777,23 @@ impl SdaClient {
         response
             .text()
             .await
-            .context("Failed to parse create creator response text")
+            .map_err(|e| SdaError::network("Failed to parse create creator response text", e))
     }
 
     /// Updates a creator.
     pub async fn update_creator(
         &self,
-        id: i32,
+        id: i64,
         request: UpdateCreatorRequest,
     ) -> Result<CreatorResponse> {
         let url = format!("{}/api/v1/creators/{}", self.base_url, id);
+        let builder = self.client.put(&url).json(&request);
         let response = self
-            .client
-            .put(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .json(&request)
-            .send()
-            .await
-            .context(format!(
-                "Failed to send update creator request for ID {}",
-                id
-            ))?;
+            .send(
+                builder,
+                &format!("Failed to send update creator request for ID {}", id),
+            )
+            .await?;
 
         let response = Self::handle_response(
             response,
@@ -1044,26 +3801,20 @@ impl SdaClient {
         )
         .await?;
 
-        response
-            .json()
+        self.parse_json(response, "Failed to parse update creator response")
             .await
-            .context("Failed to parse update creator response")
     }
 
     /// Deletes a creator.
-    pub async fn delete_creator(&self, id: i32, request: DeleteCreatorRequest) -> Result<()> {
+    pub async fn delete_creator(&self, id: i64, request: DeleteCreatorRequest) -> Result<()> {
         let url = format!("{}/api/v1/creators/{}", self.base_url, id);
+        let builder = self.client.delete(&url).json(&request);
         let response = self
-            .client
-            .delete(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .json(&request)
-            .send()
-            .await
-            .context(format!(
-                "Failed to send delete creator request for ID {}",
-                id
-            ))?;
+            .send(
+                builder,
+                &format!("Failed to send delete creator request for ID {}", id),
+            )
+            .await?;
 
         Self::handle_response(response, "Server returned error for delete creator").await?;
         Ok(())
@@ -1095,26 +3846,20 @@ impl SdaClient {
             query.push(("query_term", query_term));
         }
 
+        let builder = self.client.get(&url).query(&query);
         let response = self
-            .client
-            .get(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .query(&query)
-            .send()
-            .await
-            .context("Failed to send list locations request")?;
+            .send(builder, "Failed to send list locations request")
+            .await?;
 
         let response =
             Self::handle_response(response, "Server returned error for list locations").await?;
 
-        response
-            .json()
+        self.parse_json(response, "Failed to parse list locations response")
             .await
-            .context("Failed to parse list locations response")
     }
 
     /// Gets a single location by ID.
-    pub async fn get_location(&self, id: i32, lang: MetadataLanguage) -> Result<LocationResponse> {
+    pub async fn get_location(&self, id: i64, lang: MetadataLanguage) -> Result<LocationResponse> {
         let url = format!("{}/api/v1/locations/{}", self.base_url, id);
         let mut query = vec![];
 
@@ -1124,14 +3869,13 @@ impl SdaClient {
             MetadataLanguage::None => {}
         }
 
+        let builder = self.client.get(&url).query(&query);
         let response = self
-            .client
-            .get(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .query(&query)
-            .send()
-            .await
-            .context(format!("Failed to send get location request for ID {}", id))?;
+            .send(
+                builder,
+                &format!("Failed to send get location request for ID {}", id),
+            )
+            .await?;
 
         let response = Self::handle_response(
             response,
@@ -1139,23 +3883,17 @@ impl SdaClient {
         )
         .await?;
 
-        response
-            .json()
+        self.parse_json(response, "Failed to parse get location response")
             .await
-            .context("Failed to parse get location response")
     }
 
     /// Creates a new location.
     pub async fn create_location(&self, request: CreateLocationRequest) -> Result<String> {
         let url = format!("{}/api/v1/locations", self.base_url);
+        let builder = self.client.post(&url).json(&request);
         let response = self
-            .client
-            .post(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to send create location request")?;
+            .send(builder, "Failed to send create location request")
+            .await?;
 
         let response =
             Self::handle_response(response, "Server returned error for create location").await?;
@@ -1163,27 +3901,23 @@ impl SdaClient {
         response
             .text()
             .await
-            .context("Failed to parse create location response text")
+            .map_err(|e| SdaError::network("Failed to parse create location response text", e))
     }
 
     /// Updates a location.
     pub async fn update_location(
         &self,
-        id: i32,
+        id: i64,
         request: UpdateLocationRequest,
     ) -> Result<LocationResponse> {
         let url = format!("{}/api/v1/locations/{}", self.base_url, id);
+        let builder = self.client.put(&url).json(&request);
         let response = self
-            .client
-            .put(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .json(&request)
-            .send()
-            .await
-            .context(format!(
-                "Failed to send update location request for ID {}",
-                id
-            ))?;
+            .send(
+                builder,
+                &format!("Failed to send update location request for ID {}", id),
+            )
+            .await?;
 
         let response = Self::handle_response(
             response,
@@ -1191,26 +3925,20 @@ impl SdaClient {
         )
         .await?;
 
-        response
-            .json()
+        self.parse_json(response, "Failed to parse update location response")
             .await
-            .context("Failed to parse update location response")
     }
 
     /// Deletes a location.
-    pub async fn delete_location(&self, id: i32, request: DeleteLocationRequest) -> Result<()> {
+    pub async fn delete_location(&self, id: i64, request: DeleteLocationRequest) -> Result<()> {
         let url = format!("{}/api/v1/locations/{}", self.base_url, id);
+        let builder = self.client.delete(&url).json(&request);
         let response = self
-            .client
-            .delete(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .json(&request)
-            .send()
-            .await
-            .context(format!(
-                "Failed to send delete location request for ID {}",
-                id
-            ))?;
+            .send(
+                builder,
+                &format!("Failed to send delete location request for ID {}", id),
+            )
+            .await?;
 
         Self::handle_response(response, "Server returned error for delete location").await?;
         Ok(())
@@ -1219,7 +3947,7 @@ impl SdaClient {
     /// Lists relations for an accession.
     pub async fn list_relations(
         &self,
-        accession_id: i32,
+        accession_id: i64,
         lang: MetadataLanguage,
     ) -> Result<ListRelationsResponse> {
         let url = format!(
@@ -1234,17 +3962,16 @@ impl SdaClient {
             MetadataLanguage::None => {}
         }
 
+        let builder = self.client.get(&url).query(&query);
         let response = self
-            .client
-            .get(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .query(&query)
-            .send()
-            .await
-            .context(format!(
-                "Failed to send list relations request for accession {}",
-                accession_id
-            ))?;
+            .send(
+                builder,
+                &format!(
+                    "Failed to send list relations request for accession {}",
+                    accession_id
+                ),
+            )
+            .await?;
 
         let response = Self::handle_response(
             response,
@@ -1252,17 +3979,15 @@ impl SdaClient {
         )
         .await?;
 
-        response
-            .json()
+        self.parse_json(response, "Failed to parse list relations response")
             .await
-            .context("Failed to parse list relations response")
     }
 
     /// Gets a single relation by ID.
     pub async fn get_relation(
         &self,
-        accession_id: i32,
-        relation_id: i32,
+        accession_id: i64,
+        relation_id: i64,
         lang: MetadataLanguage,
     ) -> Result<RelationResponse> {
         let url = format!(
@@ -1277,17 +4002,16 @@ impl SdaClient {
             MetadataLanguage::None => {}
         }
 
+        let builder = self.client.get(&url).query(&query);
         let response = self
-            .client
-            .get(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .query(&query)
-            .send()
-            .await
-            .context(format!(
-                "Failed to send get relation request for accession {} relation {}",
-                accession_id, relation_id
-            ))?;
+            .send(
+                builder,
+                &format!(
+                    "Failed to send get relation request for accession {} relation {}",
+                    accession_id, relation_id
+                ),
+            )
+            .await?;
 
         let response = Self::handle_response(
             response,
@@ -1298,33 +4022,30 @@ impl SdaClient {
         )
         .await?;
 
-        response
-            .json()
+        self.parse_json(response, "Failed to parse get relation response")
             .await
-            .context("Failed to parse get relation response")
     }
 
     /// Creates a new relation.
     pub async fn create_relation(
         &self,
-        accession_id: i32,
+        accession_id: i64,
         request: CreateRelationRequest,
     ) -> Result<String> {
         let url = format!(
             "{}/api/v1/accessions/{}/relation",
             self.base_url, accession_id
         );
+        let builder = self.client.post(&url).json(&request);
         let response = self
-            .client
-            .post(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .json(&request)
-            .send()
-            .await
-            .context(format!(
-                "Failed to send create relation request for accession {}",
-                accession_id
-            ))?;
+            .send(
+                builder,
+                &format!(
+                    "Failed to send create relation request for accession {}",
+                    accession_id
+                ),
+            )
+            .await?;
 
         let response =
             Self::handle_response(response, "Server returned error for create relation").await?;
@@ -1332,14 +4053,14 @@ impl SdaClient {
         response
             .text()
             .await
-            .context("Failed to parse create relation response text")
+            .map_err(|e| SdaError::network("Failed to parse create relation response text", e))
     }
 
     /// Deletes a relation.
     pub async fn delete_relation(
         &self,
-        accession_id: i32,
-        relation_id: i32,
+        accession_id: i64,
+        relation_id: i64,
         lang: MetadataLanguage,
     ) -> Result<()> {
         let url = format!(
@@ -1355,17 +4076,16 @@ impl SdaClient {
             },
         )];
 
+        let builder = self.client.delete(&url).query(&query);
         let response = self
-            .client
-            .delete(&url)
-            .header(self.auth_header().0, self.auth_header().1)
-            .query(&query)
-            .send()
-            .await
-            .context(format!(
-                "Failed to send delete relation request for accession {} relation {}",
-                accession_id, relation_id
-            ))?;
+            .send(
+                builder,
+                &format!(
+                    "Failed to send delete relation request for accession {} relation {}",
+                    accession_id, relation_id
+                ),
+            )
+            .await?;
 
         Self::handle_response(
             response,
@@ -1379,158 +4099,4378 @@ impl SdaClient {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_update_subject_url_construction() {
-        let client = SdaClient::new(
-            "https://api.example.com".to_string(),
-            "test-key".to_string(),
-        );
-        let id = 42;
-        let expected_url = "https://api.example.com/api/v1/subjects/42";
+/// Describes the skew between the API's clock at `api` and the local one at
+/// `local`, warning when it exceeds `max_skew`.
+fn time_sync_report(
+    api: DateTime<Utc>,
+    local: DateTime<Utc>,
+    round_trip: Duration,
+    max_skew: Duration,
+) -> TimeSyncResponse {
+    // The Date header is truncated to the second, so round to whole seconds.
+    let skew_seconds = ((api - local).num_milliseconds() as f64 / 1000.0).round() as i64;
+    let in_sync = skew_seconds.unsigned_abs() <= max_skew.as_secs();
+    let warning = (!in_sync).then(|| {
+        let direction = if skew_seconds > 0 {
+            "ahead of"
+        } else {
+            "behind"
+        };
+        format!(
+            "The SDA API's clock is {}s {} this server's. Date filters such as date_from=today are computed with this server's clock, so accessions near day boundaries may be missed; sync the clocks (e.g. with NTP on the host).",
+            skew_seconds.unsigned_abs(),
+            direction
+        )
+    });
+    TimeSyncResponse {
+        local_time: local.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        api_time: api.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        skew_seconds,
+        round_trip_ms: round_trip.as_millis() as u64,
+        max_skew_seconds: max_skew.as_secs(),
+        in_sync,
+        warning,
+    }
+}
 
-        // Test URL construction by checking format string
-        let constructed_url = format!("{}/api/v1/subjects/{}", client.base_url, id);
-        assert_eq!(constructed_url, expected_url);
+/// Turns the outcome of searching one category into its `search` section.
+fn search_matches<T>(result: Result<(i64, Vec<T>)>) -> SearchMatches<T> {
+    match result {
+        Ok((total, items)) => SearchMatches {
+            total,
+            items,
+            error: None,
+        },
+        Err(e) => SearchMatches {
+            total: 0,
+            items: Vec::new(),
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+/// Returns `ids` without repeats, keeping the order of first occurrence.
+pub fn unique_ids(ids: Vec<i64>) -> Vec<i64> {
+    let mut unique = Vec::with_capacity(ids.len());
+    for id in ids {
+        if !unique.contains(&id) {
+            unique.push(id);
+        }
+    }
+    unique
+}
+
+/// Returns `current` with `remove` taken out and any new IDs from `add`
+/// appended, dropping duplicates and otherwise keeping the original order.
+fn edit_subject_ids(current: &[i64], add: &[i64], remove: &[i64]) -> Vec<i64> {
+    let mut ids: Vec<i64> = Vec::with_capacity(current.len() + add.len());
+    for &id in current.iter().chain(add) {
+        if !remove.contains(&id) && !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    ids
+}
+
+/// Merges a partial update into an accession's current metadata.
+///
+/// Language-specific fields are read from the patch's language. When it is
+/// unset, the language is detected from a patched title or description, and
+/// failing that the accession's English metadata (Arabic if it has none) is
+/// used.
+fn merge_accession_patch(
+    current: &AccessionsWithMetadataResponse,
+    patch: UpdateAccessionPatch,
+) -> UpdateAccessionRequest {
+    let patched_text = [&patch.metadata_title, &patch.metadata_description]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    let lang = match language::or_detect(patch.metadata_language.clone(), &patched_text) {
+        MetadataLanguage::None if !current.has_english_metadata && current.has_arabic_metadata => {
+            MetadataLanguage::Arabic
+        }
+        MetadataLanguage::None => MetadataLanguage::English,
+        lang => lang,
+    };
+    let arabic = lang == MetadataLanguage::Arabic;
+    let pick = |en: &Option<String>, ar: &Option<String>| {
+        if arabic { ar } else { en }.clone().unwrap_or_default()
+    };
+    let pick_ids = |en: &Option<Vec<i64>>, ar: &Option<Vec<i64>>| {
+        if arabic { ar } else { en }.clone().unwrap_or_default()
+    };
+    let pick_id = |en: Option<i64>, ar: Option<i64>| if arabic { ar } else { en };
+
+    UpdateAccessionRequest {
+        is_private: patch.is_private.unwrap_or(current.is_private),
+        metadata_description: patch
+            .metadata_description
+            .unwrap_or_else(|| pick(&current.description_en, &current.description_ar)),
+        metadata_subjects: patch
+            .metadata_subjects
+            .unwrap_or_else(|| pick_ids(&current.subjects_en_ids, &current.subjects_ar_ids)),
+        metadata_time: patch
+            .metadata_time
+            .unwrap_or_else(|| current.dublin_metadata_date.clone()),
+        metadata_title: patch
+            .metadata_title
+            .unwrap_or_else(|| pick(&current.title_en, &current.title_ar)),
+        metadata_contributor_ids: patch
+            .metadata_contributor_ids
+            .unwrap_or_else(|| pick_ids(&current.contributor_en_ids, &current.contributor_ar_ids)),
+        metadata_contributor_role_ids: patch.metadata_contributor_role_ids.unwrap_or_else(|| {
+            pick_ids(
+                &current.contributor_role_en_ids,
+                &current.contributor_role_ar_ids,
+            )
+            .into_iter()
+            .map(Some)
+            .collect()
+        }),
+        metadata_creator_id: patch
+            .metadata_creator_id
+            .or_else(|| pick_id(current.creator_en_id, current.creator_ar_id)),
+        metadata_location_id: patch
+            .metadata_location_id
+            .or_else(|| pick_id(current.location_en_id, current.location_ar_id)),
+        metadata_language: lang,
+    }
+}
+
+/// Maps an error from the middleware stack to an `SdaError`, giving errors
+/// raised by the built-in layers the request's context.
+fn middleware_error(context: &str, error: reqwest_middleware::Error) -> SdaError {
+    match error {
+        reqwest_middleware::Error::Reqwest(e) => SdaError::network(context, e),
+        reqwest_middleware::Error::Middleware(e) => match e.downcast::<SdaError>() {
+            Ok(SdaError::RateLimited { waited, .. }) => SdaError::RateLimited {
+                context: context.to_string(),
+                waited,
+            },
+            Ok(e) => e,
+            Err(e) => SdaError::Middleware {
+                context: context.to_string(),
+                message: format!("{:#}", e),
+            },
+        },
+    }
+}
+
+/// Error returned when an accession changed since the caller read it.
+fn conflict(id: i64) -> SdaError {
+    SdaError::Conflict(format!(
+        "Conflict: accession {} changed since you read it; fetch it again and reapply your changes",
+        id
+    ))
+}
+
+/// Fails with a conflict if `expected` is set and differs from the
+/// accession's current `crawl_timestamp`.
+fn check_crawl_timestamp(current: &AccessionsWithMetadataResponse, expected: &str) -> Result<()> {
+    if expected.is_empty() || current.crawl_timestamp == expected {
+        return Ok(());
+    }
+    Err(SdaError::Conflict(format!(
+        "Conflict: accession {} changed since you read it (crawl_timestamp is now {}, expected {}); fetch it again and reapply your changes",
+        current.id, current.crawl_timestamp, expected
+    )))
+}
+
+/// Builds a crawl request re-archiving an accession already fetched with its
+/// current metadata, as `SdaClient::prepare_recrawl` does without overrides.
+pub fn unchanged_recrawl_request(
+    current: &AccessionsWithMetadataResponse,
+) -> CreateAccessionCrawlRequest {
+    recrawl_request(current, merge_accession_patch(current, Default::default()))
+}
+
+/// Turns an accession and its merged metadata into a crawl request for the
+/// same seed URL. An empty description is sent as no description.
+fn recrawl_request(
+    current: &AccessionsWithMetadataResponse,
+    metadata: UpdateAccessionRequest,
+) -> CreateAccessionCrawlRequest {
+    CreateAccessionCrawlRequest {
+        url: current.seed_url.clone(),
+        metadata_language: metadata.metadata_language,
+        metadata_title: metadata.metadata_title,
+        metadata_time: metadata.metadata_time,
+        metadata_subjects: metadata.metadata_subjects,
+        is_private: metadata.is_private,
+        metadata_format: current.dublin_metadata_format,
+        browser_profile: None,
+        metadata_description: Some(metadata.metadata_description).filter(|d| !d.is_empty()),
+        s3_filename: None,
+        metadata_contributor_ids: metadata.metadata_contributor_ids,
+        metadata_contributor_role_ids: metadata.metadata_contributor_role_ids,
+        metadata_creator_id: metadata.metadata_creator_id,
+        metadata_location_id: metadata.metadata_location_id,
+        send_email_notification: false,
+        idempotency_key: None,
+    }
+}
+
+/// Parses an API `crawl_timestamp` as UTC, with or without fractional
+/// seconds or a timezone offset.
+fn parse_crawl_timestamp(timestamp: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%dT%H:%M:%S%.f")
+        .ok()
+        .or_else(|| {
+            DateTime::parse_from_rfc3339(timestamp)
+                .ok()
+                .map(|t| t.naive_utc())
+        })
+}
+
+/// Checks that `base_url` is an absolute `http` or `https` URL.
+fn check_base_url(base_url: &str) -> Result<()> {
+    let parsed = reqwest::Url::parse(base_url)
+        .map_err(|e| SdaError::Validation(format!("Invalid base URL '{}': {}", base_url, e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(SdaError::Validation(format!(
+            "Invalid base URL '{}': expected an http or https URL",
+            base_url
+        )));
+    }
+    Ok(())
+}
+
+/// Parses a `--header` argument of the form `name=value` into a header to
+/// send on every API request.
+///
+/// The API key header is refused, since `--api-key` already sets it and a
+/// second copy would be ignored.
+pub fn parse_extra_header(s: &str) -> std::result::Result<(HeaderName, HeaderValue), String> {
+    let (name, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected `name=value`, got '{}'", s))?;
+    let name = HeaderName::from_bytes(name.trim().as_bytes())
+        .map_err(|_| format!("invalid header name '{}'", name.trim()))?;
+    if name == "x-api-key" {
+        return Err("the API key header is set with --api-key".to_string());
+    }
+    let value = HeaderValue::from_str(value.trim())
+        .map_err(|_| format!("invalid value for header '{}'", name))?;
+    Ok((name, value))
+}
+
+/// Normalizes a URL for exact matching: surrounding whitespace is trimmed and
+/// the scheme and host are lowercased. Paths and query strings are kept as is.
+fn normalize_url(url: &str) -> Result<String> {
+    let trimmed = url.trim();
+    reqwest::Url::parse(trimmed)
+        .map(|parsed| parsed.to_string())
+        .map_err(|e| SdaError::Validation(format!("Invalid URL '{}': {}", trimmed, e)))
+}
+
+/// Builds the `inspect_wacz` response from the metadata read from a WACZ.
+fn wacz_inspection(
+    id: i64,
+    total_bytes: u64,
+    bytes_fetched: u64,
+    used_range_requests: bool,
+    metadata: wacz::WaczMetadata,
+) -> WaczInspection {
+    WaczInspection {
+        accession_id: id,
+        total_bytes,
+        bytes_fetched,
+        used_range_requests,
+        wacz_version: metadata.wacz_version,
+        title: metadata.title,
+        software: metadata.software,
+        created: metadata.created,
+        main_page_url: metadata.main_page_url,
+        main_page_date: metadata.main_page_date,
+        page_count: metadata.page_count,
+        first_capture: metadata.first_capture,
+        last_capture: metadata.last_capture,
+        resources: metadata
+            .resources
+            .into_iter()
+            .map(|(path, bytes)| WaczResource { path, bytes })
+            .collect(),
+    }
+}
+
+/// Adds the `date_from` / `date_to` filters, normalized with
+/// `dates::normalize_filter_date`, to a list query. Empty values are skipped.
+fn push_date_filters(
+    query: &mut Vec<(&'static str, String)>,
+    date_from: &str,
+    date_to: &str,
+) -> Result<()> {
+    for (field, value, bound) in [
+        ("date_from", date_from, dates::Bound::Start),
+        ("date_to", date_to, dates::Bound::End),
+    ] {
+        let date =
+            dates::normalize_filter_date(field, value, bound).map_err(SdaError::Validation)?;
+        if !date.is_empty() {
+            query.push((field, date));
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether a `Content-Type` header value declares JSON, such as
+/// `application/json; charset=utf-8` or `application/problem+json`.
+fn is_json_content_type(content_type: &str) -> bool {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    essence == "application/json"
+        || (essence.starts_with("application/") && essence.ends_with("+json"))
+}
+
+/// Normalizes a subject name for duplicate detection: surrounding whitespace
+/// is trimmed and the name is lowercased.
+fn subject_key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Checks a downloaded byte count against the advertised `Content-Length`.
+fn verify_length(expected: Option<u64>, actual: u64) -> Result<()> {
+    match expected {
+        Some(expected) if expected != actual => Err(SdaError::Integrity(format!(
+            "Downloaded {} bytes but the server advertised {}; the file is truncated or corrupt",
+            actual, expected
+        ))),
+        _ => Ok(()),
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request_id;
+    use reqwest::header::HeaderValue;
 
     #[test]
-    fn test_auth_header_format() {
-        let client = SdaClient::new(
-            "https://api.example.com".to_string(),
-            "my-api-key".to_string(),
+    fn test_time_sync_report_rounds_skew_and_warns_over_threshold() {
+        let local = DateTime::parse_from_rfc3339("2025-03-01T23:59:58.600Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let api = DateTime::parse_from_rfc3339("2025-03-02T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let max_skew = Duration::from_secs(30);
+
+        let report = time_sync_report(api, local, Duration::from_millis(80), max_skew);
+        assert_eq!(report.skew_seconds, 1);
+        assert!(report.in_sync);
+        assert!(report.warning.is_none());
+        assert_eq!(report.round_trip_ms, 80);
+
+        let behind = time_sync_report(
+            api - chrono::Duration::minutes(5),
+            local,
+            Duration::ZERO,
+            max_skew,
         );
-        let (header_name, header_value) = client.auth_header();
+        assert_eq!(behind.skew_seconds, -299);
+        assert!(!behind.in_sync);
+        assert!(behind.warning.unwrap().contains("299s behind"));
+    }
 
-        assert_eq!(header_name, "x-api-key");
-        assert_eq!(header_value, "my-api-key");
+    /// Counters shared with a `spawn_test_server` instance.
+    struct TestServer {
+        /// Base URL of the server.
+        url: String,
+        /// Number of requests received.
+        hits: Arc<std::sync::atomic::AtomicUsize>,
+        /// Highest number of requests handled at the same time.
+        peak: Arc<std::sync::atomic::AtomicUsize>,
     }
 
-    #[test]
-    fn test_build_accession_query_with_empty_args() {
-        let client = SdaClient::new(
-            "https://api.example.com".to_string(),
-            "test-key".to_string(),
+    /// Starts a local HTTP server answering every request with `body` after `delay`.
+    async fn spawn_test_server(body: &'static str, delay: Duration) -> TestServer {
+        spawn_test_server_with_status("200 OK", body, delay).await
+    }
+
+    /// Like `spawn_test_server`, but answering with the given status line, e.g. `412 Precondition Failed`.
+    async fn spawn_test_server_with_status(
+        status: &'static str,
+        body: &'static str,
+        delay: Duration,
+    ) -> TestServer {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::AsyncReadExt;
+        use tokio::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let server = TestServer {
+            url: format!("http://{}", addr),
+            hits: hits.clone(),
+            peak: peak.clone(),
+        };
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let (hits, peak, in_flight) = (hits.clone(), peak.clone(), in_flight.clone());
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 4096];
+                    let _ = socket.read(&mut buf).await;
+                    hits.fetch_add(1, Ordering::SeqCst);
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(delay).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    let response = format!(
+                        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        body.len(),
+                        body
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        server
+    }
+
+    #[tokio::test]
+    async fn test_send_limits_concurrent_requests() {
+        let server = spawn_test_server("", Duration::from_millis(50)).await;
+        let client = SdaClient::builder()
+            .base_url(&server.url)
+            .api_key("test-key")
+            .max_concurrency(2)
+            .build()
+            .unwrap();
+        let url = format!("{}/", server.url);
+        let requests = (0..6).map(|_| client.send(client.client.get(&url), "test request"));
+        let responses = futures::future::join_all(requests).await;
+
+        assert!(responses.iter().all(|r| r.is_ok()));
+        assert_eq!(server.peak.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_spaces_requests() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(4)
+            .mount(&server)
+            .await;
+        let client = SdaClient::builder()
+            .base_url(server.uri())
+            .api_key("test-key")
+            .rate_limit_rps(20)
+            .build()
+            .unwrap();
+
+        let started = Instant::now();
+        let requests = (0..4).map(|_| client.send(client.client.get(server.uri()), "test request"));
+        for response in futures::future::join_all(requests).await {
+            response.unwrap();
+        }
+        // The first request goes out at once, the other three 50 ms apart.
+        assert!(started.elapsed() >= Duration::from_millis(140));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_wait_is_bounded_by_request_timeout() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = SdaClient::builder()
+            .base_url(server.uri())
+            .api_key("test-key")
+            .rate_limit_rps(1)
+            .build()
+            .unwrap();
+
+        let request = || {
+            client
+                .client
+                .get(server.uri())
+                .timeout(Duration::from_millis(50))
+        };
+        client.send(request(), "first").await.unwrap();
+        let result = client.send(request(), "second").await;
+        assert!(matches!(result, Err(SdaError::RateLimited { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_crawls_get_a_longer_timeout_than_reads() {
+        let server = MockServer::start().await;
+        let slow = ResponseTemplate::new(200)
+            .set_body_string("{}")
+            .set_delay(Duration::from_millis(200));
+        Mock::given(method("GET"))
+            .respond_with(slow.clone())
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/accessions/crawl"))
+            .respond_with(slow)
+            .mount(&server)
+            .await;
+        let client = SdaClient::builder()
+            .base_url(server.uri())
+            .api_key("test-key")
+            .timeout(Duration::from_millis(50))
+            .crawl_timeout(Duration::from_secs(5))
+            .build()
+            .unwrap();
+
+        let read = client.get_subject(1, MetadataLanguage::English).await;
+        assert!(matches!(read, Err(SdaError::Network { .. })));
+        let request = CreateAccessionCrawlRequest {
+            url: "https://example.com".to_string(),
+            metadata_language: MetadataLanguage::English,
+            metadata_title: "Example".to_string(),
+            metadata_time: "2024-05-01T00:00:00".to_string(),
+            metadata_subjects: vec![],
+            is_private: false,
+            metadata_format: DublinMetadataFormat::Wacz,
+            browser_profile: None,
+            metadata_description: None,
+            s3_filename: None,
+            metadata_contributor_ids: vec![],
+            metadata_contributor_role_ids: vec![],
+            metadata_creator_id: None,
+            metadata_location_id: None,
+            send_email_notification: false,
+            idempotency_key: None,
+        };
+        assert_eq!(client.create_accession_crawl(request).await.unwrap(), "{}");
+    }
+
+    #[tokio::test]
+    async fn test_download_wacz_leaves_no_file_when_the_body_fails_mid_stream() {
+        use tokio::io::AsyncReadExt;
+        use tokio::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = socket.read(&mut buf).await;
+            // Promise 1000 bytes, send 10, then drop the connection.
+            let _ = socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 1000\r\n\r\n0123456789")
+                .await;
+        });
+        let client = SdaClient::builder()
+            .base_url(format!("http://{}", addr))
+            .api_key("test-key")
+            .build()
+            .unwrap();
+        let dir = crate::export::TempDir::create("sda-download-test").unwrap();
+        let dest = dir.path().join("5.wacz");
+
+        let url = format!("http://{}/files/5.wacz", addr);
+        assert!(client.download_wacz(&url, &dest, |_, _| {}).await.is_err());
+        assert!(!dest.exists());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_download_wacz_capped_refuses_archives_over_the_limit() {
+        use wiremock::matchers::{method, path};
+        let api = wiremock::MockServer::start().await;
+        wiremock::Mock::given(method("GET"))
+            .and(path("/files/5.wacz"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_bytes(vec![0u8; 100]))
+            .mount(&api)
+            .await;
+        let client = SdaClient::builder()
+            .base_url(api.uri())
+            .api_key("test-key")
+            .build()
+            .unwrap();
+        let dir = crate::export::TempDir::create("sda-download-test").unwrap();
+        let dest = dir.path().join("5.wacz");
+        let url = format!("{}/files/5.wacz", api.uri());
+
+        let err = client
+            .download_wacz_capped(&url, &dest, 99, |_, _| {})
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, SdaError::ResponseTooLarge { limit: 99, .. }),
+            "{}",
+            err
         );
-        let args = ListAccessionsArgs::default();
+        assert!(!dest.exists());
+        let download = client
+            .download_wacz_capped(&url, &dest, 100, |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(download.bytes, 100);
+    }
 
-        let result = client.build_accession_query(args).unwrap();
+    #[tokio::test]
+    async fn test_send_propagates_request_id_header() {
+        use tokio::io::AsyncReadExt;
+        use tokio::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let request = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let n = socket.read(&mut buf).await.unwrap();
+            socket
+                .write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&buf[..n]).to_lowercase()
+        });
+        let client = SdaClient::builder()
+            .base_url(format!("http://{}", addr))
+            .api_key("test-key")
+            .build()
+            .unwrap();
+
+        let url = format!("http://{}/", addr);
+        request_id::scope(
+            "abc-123".to_string(),
+            client.send(client.client.get(&url), "test request"),
+        )
+        .await
+        .unwrap();
+        assert!(request.await.unwrap().contains("x-request-id: abc-123"));
+    }
+
+    #[tokio::test]
+    async fn test_client_negotiates_and_decompresses_gzip() {
+        use tokio::io::AsyncReadExt;
+        use tokio::net::TcpListener;
+
+        // `{"id":3,"subject":"Khartoum"}`, gzip-compressed.
+        const GZIPPED: [u8; 49] = [
+            31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 171, 86, 202, 76, 81, 178, 50, 214, 81, 42, 46, 77,
+            202, 74, 77, 46, 81, 178, 82, 242, 206, 72, 44, 42, 201, 47, 205, 85, 170, 5, 0, 178,
+            83, 17, 108, 29, 0, 0, 0,
+        ];
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let request = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let n = socket.read(&mut buf).await.unwrap();
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                GZIPPED.len()
+            );
+            socket.write_all(head.as_bytes()).await.unwrap();
+            socket.write_all(&GZIPPED).await.unwrap();
+            String::from_utf8_lossy(&buf[..n]).to_lowercase()
+        });
+        let client = SdaClient::builder()
+            .base_url(format!("http://{}", addr))
+            .api_key("test-key")
+            .cache_ttl(Duration::ZERO)
+            .build()
+            .unwrap();
+
+        let subject = client
+            .get_subject(3, MetadataLanguage::English)
+            .await
+            .unwrap();
+        assert_eq!(subject.subject, "Khartoum");
+        let request = request.await.unwrap();
+        let accept_encoding = request
+            .lines()
+            .find(|l| l.starts_with("accept-encoding:"))
+            .unwrap();
+        assert!(accept_encoding.contains("gzip"));
+        assert!(accept_encoding.contains("br"));
+    }
+
+    #[tokio::test]
+    async fn test_list_collection_accessions_parses_response() {
+        let server = spawn_test_server(
+            r#"{"items":[],"num_pages":0,"page":0,"per_page":10}"#,
+            Duration::ZERO,
+        )
+        .await;
+        let client = SdaClient::builder()
+            .base_url(&server.url)
+            .api_key("test-key")
+            .build()
+            .unwrap();
+
+        let response = client
+            .list_collection_accessions(ListCollectionAccessionsArgs {
+                collection_id: 7,
+                page: 0,
+                per_page: 10,
+                lang: MetadataLanguage::English,
+                verbosity: Verbosity::Full,
+            })
+            .await
+            .unwrap();
+        assert!(response.items.is_empty());
+        assert_eq!(response.per_page, 10);
+    }
+
+    #[tokio::test]
+    async fn test_response_larger_than_limit_is_rejected() {
+        let body = r#"{"id":3,"subject":"Khartoum"}"#;
+        let server = spawn_test_server(body, Duration::ZERO).await;
+        let client = |limit| {
+            SdaClient::builder()
+                .base_url(&server.url)
+                .api_key("test-key")
+                .cache_ttl(Duration::ZERO)
+                .max_response_bytes(limit)
+                .build()
+                .unwrap()
+        };
+
+        let result = client(10).get_subject(3, MetadataLanguage::English).await;
+        assert!(matches!(
+            result,
+            Err(SdaError::ResponseTooLarge { limit: 10, .. })
+        ));
+
+        let subject = client(body.len() as u64)
+            .get_subject(3, MetadataLanguage::English)
+            .await
+            .unwrap();
+        assert_eq!(subject.id, 3);
+    }
+
+    #[tokio::test]
+    async fn test_update_accession_reports_precondition_failure_as_conflict() {
+        let server =
+            spawn_test_server_with_status("412 Precondition Failed", "", Duration::ZERO).await;
+        let client = SdaClient::builder()
+            .base_url(&server.url)
+            .api_key("test-key")
+            .build()
+            .unwrap();
+        let request = merge_accession_patch(&accession_with_metadata(), Default::default());
+
+        let result = client
+            .update_accession(5, request, Some("\"v1\"".to_string()))
+            .await;
+        let err = result.unwrap_err();
+        assert!(matches!(err, SdaError::Conflict(_)));
         assert!(
-            result.is_empty(),
-            "Empty args should produce empty query vector"
+            err.to_string()
+                .contains("accession 5 changed since you read it")
         );
     }
 
-    #[test]
-    fn test_build_accession_query_with_pagination() {
-        let client = SdaClient::new(
-            "https://api.example.com".to_string(),
-            "test-key".to_string(),
+    #[tokio::test]
+    async fn test_empty_success_body_is_reported_clearly() {
+        let server = spawn_test_server("", Duration::ZERO).await;
+        let client = SdaClient::builder()
+            .base_url(&server.url)
+            .api_key("test-key")
+            .build()
+            .unwrap();
+
+        let result = client
+            .update_subject(
+                3,
+                UpdateSubjectRequest {
+                    lang: MetadataLanguage::English,
+                    metadata_subject: "Khartoum".to_string(),
+                },
+            )
+            .await;
+        assert!(matches!(result, Err(SdaError::EmptyResponse { .. })));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Failed to parse update subject response: the API returned an empty response body"
         );
-        let mut args = ListAccessionsArgs::default();
-        args.page = 2;
-        args.per_page = 25;
 
-        let result = client.build_accession_query(args).unwrap();
-        assert_eq!(result.len(), 2);
+        let result = client.get_subject(3, MetadataLanguage::English).await;
+        assert!(matches!(result, Err(SdaError::EmptyResponse { .. })));
+    }
 
-        // Check that pagination parameters are included
-        let page_param = result.iter().find(|(key, _)| *key == "page");
-        let per_page_param = result.iter().find(|(key, _)| *key == "per_page");
+    #[tokio::test]
+    async fn test_create_subjects_deduplicates_within_batch() {
+        let server = spawn_test_server(r#"{"id":5,"subject":"Khartoum"}"#, Duration::ZERO).await;
+        let client = SdaClient::builder()
+            .base_url(&server.url)
+            .api_key("test-key")
+            .build()
+            .unwrap();
+        let subject = |lang, name: &str| CreateSubjectRequest {
+            lang,
+            metadata_subject: name.to_string(),
+        };
 
-        assert!(page_param.is_some());
-        assert_eq!(page_param.unwrap().1, "2");
-        assert!(per_page_param.is_some());
-        assert_eq!(per_page_param.unwrap().1, "25");
+        let response = client
+            .create_subjects(
+                vec![
+                    subject(MetadataLanguage::English, "Khartoum"),
+                    subject(MetadataLanguage::English, " khartoum "),
+                    subject(MetadataLanguage::Arabic, "Khartoum"),
+                ],
+                false,
+            )
+            .await;
+
+        assert_eq!(server.hits.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert_eq!(response.results.len(), 2);
+        assert_eq!(response.results[0].request.lang, MetadataLanguage::English);
+        assert_eq!(response.results[1].request.lang, MetadataLanguage::Arabic);
+        assert!(response.results.iter().all(|r| r.created_id == Some(5)));
     }
 
-    #[test]
-    fn test_build_accession_query_with_language_filter() {
-        let client = SdaClient::new(
-            "https://api.example.com".to_string(),
-            "test-key".to_string(),
+    #[tokio::test]
+    async fn test_create_subjects_skips_existing_and_reports_failures() {
+        let server = spawn_test_server(
+            r#"{"items":[{"id":3,"subject":"Khartoum"}],"num_pages":1,"page":0,"per_page":10}"#,
+            Duration::ZERO,
+        )
+        .await;
+        let client = SdaClient::builder()
+            .base_url(&server.url)
+            .api_key("test-key")
+            .build()
+            .unwrap();
+        let subject = |name: &str| CreateSubjectRequest {
+            lang: MetadataLanguage::English,
+            metadata_subject: name.to_string(),
+        };
+
+        let response = client
+            .create_subjects(vec![subject("khartoum"), subject("Omdurman")], true)
+            .await;
+
+        let existing = &response.results[0];
+        assert_eq!(existing.existing_id, Some(3));
+        assert_eq!(existing.created_id, None);
+        assert_eq!(existing.error, None);
+        // Not found, so it was created, but this server answers with a subject
+        // list, which cannot be parsed as the created subject.
+        let failed = &response.results[1];
+        assert_eq!(failed.request.metadata_subject, "Omdurman");
+        assert_eq!(failed.created_id, None);
+        assert!(
+            failed
+                .error
+                .as_deref()
+                .unwrap()
+                .starts_with("Failed to parse create subject response")
         );
-        let mut args = ListAccessionsArgs::default();
-        args.lang = MetadataLanguage::Arabic;
+    }
 
-        let result = client.build_accession_query(args).unwrap();
-        assert_eq!(result.len(), 1);
+    #[tokio::test]
+    async fn test_list_subjects_is_served_from_cache_until_mutation() {
+        use std::sync::atomic::Ordering;
 
-        let lang_param = result.iter().find(|(key, _)| *key == "lang");
-        assert!(lang_param.is_some());
-        assert_eq!(lang_param.unwrap().1, "arabic");
+        let server = spawn_test_server(
+            r#"{"items":[],"num_pages":0,"page":0,"per_page":10}"#,
+            Duration::ZERO,
+        )
+        .await;
+        let client = SdaClient::builder()
+            .base_url(&server.url)
+            .api_key("test-key")
+            .build()
+            .unwrap();
+        let list =
+            || client.list_subjects(MetadataLanguage::English, None, None, None, String::new());
+
+        list().await.unwrap();
+        list().await.unwrap();
+        assert_eq!(server.hits.load(Ordering::SeqCst), 1);
+
+        client
+            .list_subjects(MetadataLanguage::Arabic, None, None, None, String::new())
+            .await
+            .unwrap();
+        assert_eq!(server.hits.load(Ordering::SeqCst), 2);
+
+        client.cache.clear();
+        list().await.unwrap();
+        assert_eq!(server.hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_zero_cache_ttl_always_hits_api() {
+        use std::sync::atomic::Ordering;
+
+        let server = spawn_test_server(
+            r#"{"items":[],"num_pages":0,"page":0,"per_page":10}"#,
+            Duration::ZERO,
+        )
+        .await;
+        let client = SdaClient::builder()
+            .base_url(&server.url)
+            .api_key("test-key")
+            .cache_ttl(Duration::ZERO)
+            .build()
+            .unwrap();
+
+        for _ in 0..2 {
+            client
+                .list_subjects(MetadataLanguage::English, None, None, None, String::new())
+                .await
+                .unwrap();
+        }
+        assert_eq!(server.hits.load(Ordering::SeqCst), 2);
+    }
+
+    fn accession_with_metadata() -> AccessionsWithMetadataResponse {
+        AccessionsWithMetadataResponse {
+            title_en: Some("English title".to_string()),
+            description_en: Some("English description".to_string()),
+            location_en_id: Some(3),
+            subjects_en: Some(vec!["Protests".to_string()]),
+            subjects_en_ids: Some(vec![1]),
+            contributors_en: Some(vec!["Someone".to_string()]),
+            contributor_en_ids: Some(vec![7]),
+            contributor_roles_en: Some(vec!["Photographer".to_string()]),
+            contributor_role_en_ids: Some(vec![2]),
+            title_ar: Some("عنوان".to_string()),
+            subjects_ar_ids: Some(vec![9]),
+            has_arabic_metadata: true,
+            ..AccessionsWithMetadataResponse::sample(5)
+        }
     }
 
     #[test]
-    fn test_build_accession_query_ignores_default_pagination() {
-        let client = SdaClient::new(
-            "https://api.example.com".to_string(),
-            "test-key".to_string(),
-        );
-        let mut args = ListAccessionsArgs::default();
-        args.page = -1; // Default value
-        args.per_page = -1; // Default value
-        args.lang = MetadataLanguage::English;
+    fn test_merge_accession_patch_keeps_unpatched_fields() {
+        let patch = UpdateAccessionPatch {
+            is_private: Some(true),
+            ..Default::default()
+        };
 
-        let result = client.build_accession_query(args).unwrap();
-        assert_eq!(result.len(), 1); // Only language should be included
+        let request = merge_accession_patch(&accession_with_metadata(), patch);
+        assert_eq!(
+            request,
+            UpdateAccessionRequest {
+                is_private: true,
+                metadata_description: "English description".to_string(),
+                metadata_language: MetadataLanguage::English,
+                metadata_subjects: vec![1],
+                metadata_time: "2024-05-01T00:00:00".to_string(),
+                metadata_title: "English title".to_string(),
+                metadata_contributor_ids: vec![7],
+                metadata_contributor_role_ids: vec![Some(2)],
+                metadata_creator_id: None,
+                metadata_location_id: Some(3),
+            }
+        );
+    }
 
-        let page_param = result.iter().find(|(key, _)| *key == "page");
-        let per_page_param = result.iter().find(|(key, _)| *key == "per_page");
+    #[test]
+    fn test_merge_accession_patch_uses_requested_language() {
+        let patch = UpdateAccessionPatch {
+            metadata_language: MetadataLanguage::Arabic,
+            metadata_description: Some("وصف".to_string()),
+            ..Default::default()
+        };
 
-        assert!(page_param.is_none());
-        assert!(per_page_param.is_none());
+        let request = merge_accession_patch(&accession_with_metadata(), patch);
+        assert_eq!(request.metadata_language, MetadataLanguage::Arabic);
+        assert_eq!(request.metadata_title, "عنوان");
+        assert_eq!(request.metadata_description, "وصف");
+        assert_eq!(request.metadata_subjects, vec![9]);
+        assert!(request.metadata_contributor_ids.is_empty());
+        assert_eq!(request.metadata_location_id, None);
+        assert!(!request.is_private);
     }
 
     #[test]
-    fn test_collection_url_construction() {
-        let client = SdaClient::new(
-            "https://api.example.com".to_string(),
-            "test-key".to_string(),
-        );
-        let id = 123;
-        let expected_url = "https://api.example.com/api/v1/collections/123";
+    fn test_recrawl_request_copies_seed_url_and_overrides() {
+        let current = accession_with_metadata();
+        let patch = UpdateAccessionPatch {
+            metadata_title: Some("Refreshed title".to_string()),
+            metadata_description: Some(String::new()),
+            ..Default::default()
+        };
 
-        // Test URL construction by checking format string
-        let constructed_url = format!("{}/api/v1/collections/{}", client.base_url, id);
-        assert_eq!(constructed_url, expected_url);
+        let request = recrawl_request(&current, merge_accession_patch(&current, patch));
+        assert_eq!(request.url, current.seed_url);
+        assert_eq!(request.metadata_title, "Refreshed title");
+        assert_eq!(request.metadata_description, None);
+        assert_eq!(request.metadata_subjects, vec![1]);
+        assert_eq!(request.metadata_location_id, Some(3));
+        assert_eq!(request.metadata_time, "2024-05-01T00:00:00");
+        assert!(request.browser_profile.is_none());
+        assert!(!request.send_email_notification);
     }
 
     #[test]
-    fn test_list_collections_args_default() {
-        let args = ListCollectionsArgs::default();
+    fn test_check_crawl_timestamp() {
+        let current = accession_with_metadata();
+        assert!(check_crawl_timestamp(&current, "").is_ok());
+        assert!(check_crawl_timestamp(&current, &current.crawl_timestamp).is_ok());
+
+        let err = check_crawl_timestamp(&current, "2020-01-01T00:00:00").unwrap_err();
+        assert!(matches!(err, SdaError::Conflict(_)));
+        assert!(err.to_string().contains("expected 2020-01-01T00:00:00"));
+    }
 
-        assert_eq!(args.page, -1);
-        assert_eq!(args.per_page, -1);
-        assert_eq!(args.lang, MetadataLanguage::None);
+    #[test]
+    fn test_pager_detects_num_pages_change() {
+        let mut pager = AccessionPager {
+            args: ListAccessionsArgs::default(),
+            next_page: Some(FIRST_PAGE),
+            buffer: VecDeque::new(),
+            num_pages: None,
+        };
+
+        assert_eq!(pager.observe_num_pages(3), None);
+        assert_eq!(pager.observe_num_pages(3), None);
+        assert_eq!(pager.observe_num_pages(4), Some(3));
+        assert_eq!(pager.observe_num_pages(4), None);
     }
 
     #[test]
-    fn test_list_private_collections_args_default() {
-        let args = ListPrivateCollectionsArgs::default();
+    fn test_edit_subject_ids_dedupes_and_keeps_order() {
+        assert_eq!(edit_subject_ids(&[3, 1, 3, 2], &[5, 1], &[]), [3, 1, 2, 5]);
+        assert_eq!(edit_subject_ids(&[3, 1, 2], &[], &[1, 9]), [3, 2]);
+        assert_eq!(edit_subject_ids(&[], &[4, 4], &[]), [4]);
+    }
 
-        assert_eq!(args.page, -1);
-        assert_eq!(args.per_page, -1);
-        assert_eq!(args.lang, MetadataLanguage::None);
-        assert_eq!(args.is_private, true);
+    #[tokio::test]
+    async fn test_prepare_collection_subjects_update_requires_subject_ids() {
+        let server = spawn_test_server(
+            r#"{"id":2,"title":"Elections","is_private":false,"description":null}"#,
+            Duration::ZERO,
+        )
+        .await;
+        let client = SdaClient::builder()
+            .base_url(&server.url)
+            .api_key("test-key")
+            .build()
+            .unwrap();
+
+        let result = client
+            .prepare_collection_subjects_update(2, MetadataLanguage::English, &[7], &[])
+            .await;
+        assert!(matches!(result, Err(SdaError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_prepare_collection_subjects_update_merges_current_subjects() {
+        let server = spawn_test_server(
+            r#"{"id":2,"title":"Elections","is_private":true,"description":null,"subject_ids":[1,2]}"#,
+            Duration::ZERO,
+        )
+        .await;
+        let client = SdaClient::builder()
+            .base_url(&server.url)
+            .api_key("test-key")
+            .build()
+            .unwrap();
+
+        let request = client
+            .prepare_collection_subjects_update(2, MetadataLanguage::English, &[7], &[1])
+            .await
+            .unwrap();
+        assert_eq!(request.subject_ids, [2, 7]);
+        assert_eq!(request.title, "Elections");
+        assert!(request.is_private);
+        assert_eq!(request.description, "");
+    }
+
+    #[tokio::test]
+    async fn test_prepare_collection_patch_keeps_unset_fields() {
+        let server = spawn_test_server(
+            r#"{"id":2,"title":"Elections","is_private":true,"description":"Polls","subject_ids":[1,2]}"#,
+            Duration::ZERO,
+        )
+        .await;
+        let client = SdaClient::builder()
+            .base_url(&server.url)
+            .api_key("test-key")
+            .build()
+            .unwrap();
+
+        let patch = UpdateCollectionPatch {
+            is_private: Some(false),
+            ..Default::default()
+        };
+        let request = client
+            .prepare_collection_patch(2, MetadataLanguage::English, patch)
+            .await
+            .unwrap();
+        assert!(!request.is_private);
+        assert_eq!(request.title, "Elections");
+        assert_eq!(request.description, "Polls");
+        assert_eq!(request.subject_ids, [1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_prepare_collection_patch_needs_subject_ids_to_keep() {
+        let server = spawn_test_server(
+            r#"{"id":2,"title":"Elections","is_private":false,"description":null}"#,
+            Duration::ZERO,
+        )
+        .await;
+        let client = SdaClient::builder()
+            .base_url(&server.url)
+            .api_key("test-key")
+            .build()
+            .unwrap();
+
+        let result = client
+            .prepare_collection_patch(2, MetadataLanguage::English, Default::default())
+            .await;
+        assert!(matches!(result, Err(SdaError::Validation(_))));
+
+        let patch = UpdateCollectionPatch {
+            subject_ids: Some(vec![4]),
+            title: Some("Elections 2024".to_string()),
+            ..Default::default()
+        };
+        let request = client
+            .prepare_collection_patch(2, MetadataLanguage::English, patch)
+            .await
+            .unwrap();
+        assert_eq!(request.subject_ids, [4]);
+        assert_eq!(request.title, "Elections 2024");
     }
 
     #[test]
-    fn test_create_collection_request_serialization() {
-        let request = CreateCollectionRequest {
-            lang: MetadataLanguage::English,
-            title: "Test Collection".to_string(),
-            is_private: true,
-            subject_ids: vec![1, 2, 3],
-            description: "A test description".to_string(),
+    fn test_archive_url_follows_metadata_format() {
+        let mut response = GetOneAccessionResponse {
+            accession: accession_with_metadata(),
+            wacz_url: "https://files.example.com/5.wacz".to_string(),
+            warc_url: Some("https://files.example.com/5.warc".to_string()),
+            etag: None,
         };
+        assert_eq!(response.archive_url(), "https://files.example.com/5.wacz");
 
-        let json = serde_json::to_string(&request).unwrap();
-        assert!(json.contains("Test Collection"));
-        assert!(json.contains("A test description"));
-        assert!(json.contains("true"));
+        response.accession.dublin_metadata_format = DublinMetadataFormat::Warc;
+        assert_eq!(response.archive_url(), "https://files.example.com/5.warc");
+
+        response.warc_url = None;
+        assert_eq!(response.archive_url(), "https://files.example.com/5.wacz");
+    }
+
+    #[tokio::test]
+    async fn test_mock_create_accession_crawl_reports_every_problem() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/subjects/1"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let request: CreateAccessionCrawlRequest = serde_json::from_value(serde_json::json!({
+            "url": "ftp://example.com",
+            "metadata_language": "english",
+            "metadata_title": " ",
+            "metadata_time": "2026-02-01T00:00:00Z",
+            "metadata_subjects": [1],
+            "is_private": false,
+            "metadata_format": "warc",
+            "metadata_contributor_ids": [4],
+            "metadata_contributor_role_ids": [],
+            "send_email_notification": false,
+        }))
+        .unwrap();
+
+        let err = mock_client(&server)
+            .create_accession_crawl(request)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("supported formats: \"wacz\""));
+        let SdaError::InvalidFields { problems, .. } = err else {
+            panic!("expected invalid fields, got {:?}", err);
+        };
+        let fields: Vec<&str> = problems.iter().map(|p| p.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "url",
+                "metadata_format",
+                "metadata_title",
+                "metadata_time",
+                "metadata_contributor_role_ids",
+                "metadata_subjects"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_or_create_subject_returns_existing_match() {
+        let server = spawn_test_server(
+            r#"{"items":[{"id":3,"subject":"Darfur "}],"num_pages":1,"page":0,"per_page":10}"#,
+            Duration::ZERO,
+        )
+        .await;
+        let client = SdaClient::builder()
+            .base_url(&server.url)
+            .api_key("test-key")
+            .build()
+            .unwrap();
+
+        let response = client
+            .find_or_create_subject(CreateSubjectRequest {
+                lang: MetadataLanguage::English,
+                metadata_subject: "darfur".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.id, 3);
+        assert!(!response.created);
+        assert_eq!(server.hits.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_parse_crawl_timestamp() {
+        let expected =
+            NaiveDateTime::parse_from_str("2024-05-01T12:00:00", "%Y-%m-%dT%H:%M:%S").unwrap();
+        assert_eq!(parse_crawl_timestamp("2024-05-01T12:00:00"), Some(expected));
+        assert_eq!(
+            parse_crawl_timestamp("2024-05-01T12:00:00.000"),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_crawl_timestamp("2024-05-01T14:00:00+02:00"),
+            Some(expected)
+        );
+        assert_eq!(parse_crawl_timestamp("yesterday"), None);
+    }
+
+    #[tokio::test]
+    async fn test_find_recent_accession_respects_window() {
+        let body = serde_json::json!({
+            "items": [accession_with_metadata()],
+            "num_pages": 1,
+            "page": 0,
+            "per_page": 10,
+        })
+        .to_string();
+        let server = spawn_test_server(Box::leak(body.into_boxed_str()), Duration::ZERO).await;
+        let client = SdaClient::builder()
+            .base_url(&server.url)
+            .api_key("test-key")
+            .build()
+            .unwrap();
+        // The sample accession was crawled in 2024.
+        let recent = client
+            .find_recent_accession("https://example.com", false, Duration::from_secs(3600))
+            .await
+            .unwrap();
+        assert!(recent.is_none());
+
+        let recent = client
+            .find_recent_accession(
+                "https://EXAMPLE.com/",
+                false,
+                Duration::from_secs(100 * 365 * 24 * 3600),
+            )
+            .await
+            .unwrap();
+        assert_eq!(recent.map(|a| a.id), Some(5));
+    }
+
+    #[tokio::test]
+    async fn test_mock_find_recent_accession_ignores_failed_crawls() {
+        let server = MockServer::start().await;
+        let mut failed = accession_with_metadata();
+        failed.id = 6;
+        failed.crawl_status = CrawlStatus::Error;
+        failed.crawl_timestamp = Utc::now()
+            .naive_utc()
+            .format("%Y-%m-%dT%H:%M:%S")
+            .to_string();
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [failed],
+                "num_pages": 1,
+                "page": 0,
+                "per_page": 50,
+            })))
+            .mount(&server)
+            .await;
+
+        let recent = mock_client(&server)
+            .find_recent_accession("https://example.com", false, Duration::from_secs(3600))
+            .await
+            .unwrap();
+        assert!(recent.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_mock_find_accessions_by_url_sends_the_url_as_given() {
+        let server = MockServer::start().await;
+        let mut accession = accession_with_metadata();
+        accession.seed_url = "https://Example.com/page".to_string();
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions"))
+            .and(query_param("url_filter", "https://Example.com/page"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [accession],
+                "num_pages": 1,
+                "page": 0,
+                "per_page": 50,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let matches = mock_client(&server)
+            .find_accessions_by_url(" https://Example.com/page ", false)
+            .await
+            .unwrap();
+        assert_eq!(matches.len(), 1);
+    }
+
+    #[test]
+    fn test_normalize_url() {
+        assert_eq!(
+            normalize_url("  HTTPS://Example.COM/News/Item?id=1 ").unwrap(),
+            "https://example.com/News/Item?id=1"
+        );
+        assert_eq!(
+            normalize_url("https://example.com").unwrap(),
+            normalize_url("https://EXAMPLE.com/").unwrap()
+        );
+        assert!(matches!(
+            normalize_url("not a url"),
+            Err(SdaError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_length() {
+        assert!(verify_length(Some(10), 10).is_ok());
+        assert!(verify_length(None, 10).is_ok());
+
+        let err = verify_length(Some(10), 7).unwrap_err();
+        assert!(matches!(err, SdaError::Integrity(_)));
+        assert!(err.to_string().contains("Downloaded 7 bytes"));
+    }
+
+    #[test]
+    fn test_sha256_hex_encoding() {
+        let digest = Sha256::digest(b"abc");
+        assert_eq!(
+            format!("{:x}", digest),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_update_subject_url_construction() {
+        let client = SdaClient::new(
+            "https://api.example.com".to_string(),
+            "test-key".to_string(),
+        )
+        .unwrap();
+        let id = 42;
+        let expected_url = "https://api.example.com/api/v1/subjects/42";
+
+        // Test URL construction by checking format string
+        let constructed_url = format!("{}/api/v1/subjects/{}", client.base_url, id);
+        assert_eq!(constructed_url, expected_url);
+    }
+
+    #[test]
+    fn test_builder_applies_settings() {
+        let client = SdaClient::builder()
+            .base_url("https://api.example.com")
+            .api_key("builder-key")
+            .timeout(Duration::from_secs(5))
+            .crawl_timeout(Duration::from_secs(7))
+            .max_retries(1)
+            .max_retry_wait(Duration::from_secs(10))
+            .user_agent("custom-agent/1.0")
+            .build()
+            .unwrap();
+
+        assert_eq!(client.base_url, "https://api.example.com");
+        assert_eq!(client.crawl_timeout, Duration::from_secs(7));
+        assert_eq!(client.auth_header().1, "builder-key");
+    }
+
+    #[tokio::test]
+    async fn test_base_url_trailing_slash_is_trimmed() {
+        let client = SdaClient::builder()
+            .base_url("https://x.com/sda-api/")
+            .api_key("test-key")
+            .build()
+            .unwrap();
+        assert_eq!(client.base_url(), "https://x.com/sda-api");
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/sda-api/api/v1/subjects/7"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "id": 7, "subject": "Protest" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = SdaClient::builder()
+            .base_url(format!("{}/sda-api/", server.uri()))
+            .api_key("test-key")
+            .build()
+            .unwrap();
+        client
+            .get_subject(7, MetadataLanguage::English)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_extra_headers_sent_to_api_without_replacing_auth() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/subjects/7"))
+            .and(header("x-api-key", "test-key"))
+            .and(header("x-gateway-token", "abc"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "id": 7, "subject": "Protest" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = SdaClient::builder()
+            .base_url(server.uri())
+            .api_key("test-key")
+            .header(
+                HeaderName::from_static("x-gateway-token"),
+                HeaderValue::from_static("abc"),
+            )
+            .header(
+                HeaderName::from_static("x-api-key"),
+                HeaderValue::from_static("other-key"),
+            )
+            .build()
+            .unwrap();
+        client
+            .get_subject(7, MetadataLanguage::English)
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests[0].headers.get_all("x-api-key").iter().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_extra_headers_not_sent_to_other_hosts() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+        let client = SdaClient::builder()
+            .base_url(format!("{}/sda", server.uri()))
+            .api_key("test-key")
+            .header(
+                HeaderName::from_static("x-gateway-token"),
+                HeaderValue::from_static("abc"),
+            )
+            .build()
+            .unwrap();
+        for url in [
+            format!("{}/sda/x", server.uri()),
+            format!("{}/sdax", server.uri()),
+        ] {
+            client
+                .send(client.client.get(&url), "test request")
+                .await
+                .unwrap();
+        }
+
+        let requests = server.received_requests().await.unwrap();
+        assert!(requests[0].headers.contains_key("x-gateway-token"));
+        assert!(!requests[1].headers.contains_key("x-gateway-token"));
+    }
+
+    #[test]
+    fn test_parse_extra_header() {
+        let (name, value) = parse_extra_header("X-Gateway-Token = abc=def").unwrap();
+        assert_eq!(name, "x-gateway-token");
+        assert_eq!(value, "abc=def");
+        assert_eq!(parse_extra_header("X-Empty=").unwrap().1, "");
+
+        for invalid in [
+            "no-equals",
+            "=value",
+            "bad name=x",
+            "x-bad=line\nbreak",
+            "X-API-Key=k",
+        ] {
+            assert!(
+                parse_extra_header(invalid).is_err(),
+                "{:?} should be rejected",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_build_rejects_invalid_base_url() {
+        for base_url in ["", "api.example.com/sda-api", "ftp://example.com"] {
+            let result = SdaClient::builder()
+                .base_url(base_url)
+                .api_key("test-key")
+                .build();
+            assert!(
+                matches!(result, Err(SdaError::Validation(_))),
+                "{:?} should be rejected",
+                base_url
+            );
+        }
+    }
+
+    #[test]
+    fn test_new_uses_builder_defaults() {
+        let client = SdaClient::new(
+            "https://api.example.com".to_string(),
+            "test-key".to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(client.crawl_timeout, DEFAULT_CRAWL_TIMEOUT);
+        assert_eq!(client.download_timeout, DEFAULT_DOWNLOAD_TIMEOUT);
+    }
+
+    /// Counts requests and remembers the last status, to check custom layers run.
+    #[derive(Clone, Default)]
+    struct Recorder {
+        sent: Arc<std::sync::atomic::AtomicUsize>,
+        status: Arc<std::sync::Mutex<Option<u16>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Middleware for Recorder {
+        async fn handle(
+            &self,
+            mut request: reqwest::Request,
+            extensions: &mut http::Extensions,
+            next: reqwest_middleware::Next<'_>,
+        ) -> reqwest_middleware::Result<reqwest::Response> {
+            self.sent.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            request
+                .headers_mut()
+                .insert("x-traced", HeaderValue::from_static("yes"));
+            let result = next.run(request, extensions).await;
+            *self.status.lock().unwrap() = result.as_ref().ok().map(|r| r.status().as_u16());
+            result
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_api_key_and_custom_middleware_apply_to_requests() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/subjects/3"))
+            .and(header("x-api-key", "my-api-key"))
+            .and(header("x-traced", "yes"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"id": 3, "subject": "Darfur"})),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let recorder = Recorder::default();
+        let client = SdaClient::builder()
+            .base_url(server.uri())
+            .api_key("my-api-key")
+            .max_retries(0)
+            .middleware(recorder.clone())
+            .build()
+            .unwrap();
+        client
+            .get_subject(3, MetadataLanguage::English)
+            .await
+            .unwrap();
+        assert_eq!(recorder.sent.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(*recorder.status.lock().unwrap(), Some(200));
+    }
+
+    #[test]
+    fn test_auth_header_format() {
+        let client = SdaClient::new(
+            "https://api.example.com".to_string(),
+            "my-api-key".to_string(),
+        )
+        .unwrap();
+        let (header_name, header_value) = client.auth_header();
+
+        assert_eq!(header_name, "x-api-key");
+        assert_eq!(header_value, "my-api-key");
+    }
+
+    #[test]
+    fn test_build_accession_query_with_empty_args() {
+        let client = SdaClient::new(
+            "https://api.example.com".to_string(),
+            "test-key".to_string(),
+        )
+        .unwrap();
+        let args = ListAccessionsArgs::default();
+
+        let result = client.build_accession_query(args).unwrap();
+        assert!(
+            result.is_empty(),
+            "Empty args should produce empty query vector"
+        );
+    }
+
+    #[test]
+    #[allow(clippy::field_reassign_with_default)]
+    fn test_build_accession_query_with_pagination() {
+        let client = SdaClient::new(
+            "https://api.example.com".to_string(),
+            "test-key".to_string(),
+        )
+        .unwrap();
+        let mut args = ListAccessionsArgs::default();
+        args.page = 2;
+        args.per_page = 25;
+
+        let result = client.build_accession_query(args).unwrap();
+        assert_eq!(result.len(), 2);
+
+        // Check that pagination parameters are included
+        let page_param = result.iter().find(|(key, _)| *key == "page");
+        let per_page_param = result.iter().find(|(key, _)| *key == "per_page");
+
+        assert!(page_param.is_some());
+        assert_eq!(page_param.unwrap().1, "2");
+        assert!(per_page_param.is_some());
+        assert_eq!(per_page_param.unwrap().1, "25");
+    }
+
+    #[test]
+    fn test_build_accession_query_is_sorted_and_deduplicated() {
+        let client = SdaClient::new(
+            "https://api.example.com".to_string(),
+            "test-key".to_string(),
+        )
+        .unwrap();
+        let args = ListAccessionsArgs {
+            page: 0,
+            per_page: 50,
+            lang: MetadataLanguage::English,
+            metadata_subjects: vec![7, 5, 7, 5],
+            metadata_subjects_inclusive_filter: true,
+            query_term: "flood".to_string(),
+            metadata_creators: vec![2],
+            ..Default::default()
+        };
+
+        let result = client.build_accession_query(args).unwrap();
+        assert_eq!(
+            result,
+            vec![
+                ("lang", "english".to_string()),
+                ("metadata_creators", "2".to_string()),
+                ("metadata_subjects", "7".to_string()),
+                ("metadata_subjects", "5".to_string()),
+                ("metadata_subjects_inclusive_filter", "true".to_string()),
+                ("page", "0".to_string()),
+                ("per_page", "50".to_string()),
+                ("query_term", "flood".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::field_reassign_with_default)]
+    fn test_build_accession_query_with_language_filter() {
+        let client = SdaClient::new(
+            "https://api.example.com".to_string(),
+            "test-key".to_string(),
+        )
+        .unwrap();
+        let mut args = ListAccessionsArgs::default();
+        args.lang = MetadataLanguage::Arabic;
+
+        let result = client.build_accession_query(args).unwrap();
+        assert_eq!(result.len(), 1);
+
+        let lang_param = result.iter().find(|(key, _)| *key == "lang");
+        assert!(lang_param.is_some());
+        assert_eq!(lang_param.unwrap().1, "arabic");
+    }
+
+    #[test]
+    fn test_build_accession_query_drops_language_when_including_both() {
+        let client = SdaClient::new(
+            "https://api.example.com".to_string(),
+            "test-key".to_string(),
+        )
+        .unwrap();
+        let args = ListAccessionsArgs {
+            lang: MetadataLanguage::Arabic,
+            include_both_languages: true,
+            ..Default::default()
+        };
+
+        let result = client.build_accession_query(args).unwrap();
+        assert!(result.iter().all(|(key, _)| *key != "lang"));
+    }
+
+    #[test]
+    #[allow(clippy::field_reassign_with_default)]
+    fn test_build_accession_query_ignores_default_pagination() {
+        let client = SdaClient::new(
+            "https://api.example.com".to_string(),
+            "test-key".to_string(),
+        )
+        .unwrap();
+        let mut args = ListAccessionsArgs::default();
+        args.page = -1; // Default value
+        args.per_page = -1; // Default value
+        args.lang = MetadataLanguage::English;
+
+        let result = client.build_accession_query(args).unwrap();
+        assert_eq!(result.len(), 1); // Only language should be included
+
+        let page_param = result.iter().find(|(key, _)| *key == "page");
+        let per_page_param = result.iter().find(|(key, _)| *key == "per_page");
+
+        assert!(page_param.is_none());
+        assert!(per_page_param.is_none());
+    }
+
+    #[test]
+    fn test_build_accession_query_with_sorting() {
+        let client = SdaClient::new(
+            "https://api.example.com".to_string(),
+            "test-key".to_string(),
+        )
+        .unwrap();
+        let args = ListAccessionsArgs {
+            sort_by: "crawl_timestamp".to_string(),
+            sort_order: SortOrder::Desc,
+            ..Default::default()
+        };
+
+        let result = client.build_accession_query(args).unwrap();
+        assert_eq!(
+            result,
+            vec![
+                ("sort_by", "crawl_timestamp".to_string()),
+                ("sort_order", "desc".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_accession_query_ignores_sort_order_without_sort_by() {
+        let client = SdaClient::new(
+            "https://api.example.com".to_string(),
+            "test-key".to_string(),
+        )
+        .unwrap();
+        let args = ListAccessionsArgs {
+            sort_order: SortOrder::Desc,
+            ..Default::default()
+        };
+
+        let result = client.build_accession_query(args).unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn test_build_accession_query_rejects_unknown_sort_field() {
+        let client = SdaClient::new(
+            "https://api.example.com".to_string(),
+            "test-key".to_string(),
+        )
+        .unwrap();
+        let args = ListAccessionsArgs {
+            sort_by: "title_en".to_string(),
+            ..Default::default()
+        };
+
+        let err = client.build_accession_query(args).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Unsupported sort_by field 'title_en'")
+        );
+    }
+
+    #[test]
+    fn test_collection_url_construction() {
+        let client = SdaClient::new(
+            "https://api.example.com".to_string(),
+            "test-key".to_string(),
+        )
+        .unwrap();
+        let id = 123;
+        let expected_url = "https://api.example.com/api/v1/collections/123";
+
+        // Test URL construction by checking format string
+        let constructed_url = format!("{}/api/v1/collections/{}", client.base_url, id);
+        assert_eq!(constructed_url, expected_url);
+    }
+
+    #[test]
+    fn test_build_collection_query_with_empty_args() {
+        let client = SdaClient::new(
+            "https://api.example.com".to_string(),
+            "test-key".to_string(),
+        )
+        .unwrap();
+
+        let result = client
+            .build_collection_query(ListCollectionsArgs::default())
+            .unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn test_build_collection_query_with_filters() {
+        let client = SdaClient::new(
+            "https://api.example.com".to_string(),
+            "test-key".to_string(),
+        )
+        .unwrap();
+        let args = ListCollectionsArgs {
+            metadata_subjects: vec![3, 7],
+            query_term: "Darfur".to_string(),
+            date_from: "2024-01-01".to_string(),
+            date_to: "2024-12-31".to_string(),
+            ..Default::default()
+        };
+
+        let result = client.build_collection_query(args).unwrap();
+        assert_eq!(
+            result,
+            vec![
+                ("metadata_subjects", "3".to_string()),
+                ("metadata_subjects", "7".to_string()),
+                ("query_term", "Darfur".to_string()),
+                ("date_from", "2024-01-01".to_string()),
+                ("date_to", "2024-12-31".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_collections_args_default() {
+        let args = ListCollectionsArgs::default();
+
+        assert_eq!(args.page, -1);
+        assert_eq!(args.per_page, -1);
+        assert_eq!(args.lang, MetadataLanguage::None);
+    }
+
+    #[test]
+    #[allow(clippy::bool_assert_comparison)]
+    fn test_list_private_collections_args_default() {
+        let args = ListPrivateCollectionsArgs::default();
+
+        assert_eq!(args.page, -1);
+        assert_eq!(args.per_page, -1);
+        assert_eq!(args.lang, MetadataLanguage::None);
+        assert_eq!(args.is_private, true);
+    }
+
+    #[test]
+    fn test_create_collection_request_serialization() {
+        let request = CreateCollectionRequest {
+            lang: MetadataLanguage::English,
+            title: "Test Collection".to_string(),
+            is_private: true,
+            subject_ids: vec![1, 2, 3],
+            description: "A test description".to_string(),
+        };
+
+        let json = serde_json::to_string(&request).unwrap();
+        assert!(json.contains("Test Collection"));
+        assert!(json.contains("A test description"));
+        assert!(json.contains("true"));
+    }
+
+    // Request/response contract tests against a mock SDA API. Each mock
+    // matches on method, path, auth header and query, and `expect(1)` makes
+    // the mock server fail the test on drop if the request never arrived.
+
+    use wiremock::matchers::{
+        body_json, body_partial_json, header, method, path, query_param, query_param_is_missing,
+    };
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Builds a client pointed at `server` with caching and retries disabled.
+    fn mock_client(server: &MockServer) -> SdaClient {
+        SdaClient::builder()
+            .base_url(server.uri())
+            .api_key("test-key")
+            .cache_ttl(Duration::ZERO)
+            .max_retries(0)
+            .build()
+            .unwrap()
+    }
+
+    fn one_accession_json() -> serde_json::Value {
+        serde_json::json!({
+            "accession": accession_with_metadata(),
+            "wacz_url": "https://example.com/5.wacz",
+        })
+    }
+
+    #[tokio::test]
+    async fn test_mock_list_accessions() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions"))
+            .and(header("x-api-key", "test-key"))
+            .and(query_param("page", "2"))
+            .and(query_param("per_page", "10"))
+            .and(query_param("lang", "english"))
+            .and(query_param("query_term", "Khartoum"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [accession_with_metadata()],
+                "num_pages": 3,
+                "page": 2,
+                "per_page": 10,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let response = mock_client(&server)
+            .list_accessions(ListAccessionsArgs {
+                page: 2,
+                per_page: 10,
+                lang: MetadataLanguage::English,
+                query_term: "Khartoum".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.num_pages, 3);
+        assert_eq!(response.items[0].id, 5);
+        assert_eq!(response.items[0].seed_url, "https://example.com");
+    }
+
+    #[tokio::test]
+    async fn test_mock_get_accessions_fetches_each_id_once_and_reports_failures() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/5"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(one_accession_json()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/6"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let response = mock_client(&server).get_accessions(vec![5, 6, 5]).await;
+        let ids: Vec<i64> = response.items.iter().map(|a| a.accession.id).collect();
+        assert_eq!(ids, [5]);
+        assert_eq!(response.errors.keys().copied().collect::<Vec<_>>(), [6]);
+        assert!(response.errors[&6].contains("404"), "{:?}", response.errors);
+    }
+
+    #[tokio::test]
+    async fn test_mock_get_public_and_private_accession() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/5"))
+            .and(header("x-api-key", "test-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(one_accession_json()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/private/5"))
+            .and(header("x-api-key", "test-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(one_accession_json()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let public = client.get_accession(5).await.unwrap();
+        assert_eq!(public.accession.id, 5);
+        assert_eq!(public.wacz_url, "https://example.com/5.wacz");
+        let private = client.get_private_accession(5).await.unwrap();
+        assert_eq!(private.accession.id, 5);
+    }
+
+    #[tokio::test]
+    async fn test_mock_get_accession_with_id_beyond_i32() {
+        let id = i64::from(i32::MAX) + 10;
+        let mut body = one_accession_json();
+        body["accession"]["id"] = id.into();
+        body["accession"]["subjects_en_ids"] = serde_json::json!([id + 1]);
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!("/api/v1/accessions/{}", id)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let response = client.get_accession(id).await.unwrap();
+        assert_eq!(response.accession.id, 2_147_483_657);
+        assert_eq!(response.accession.subjects_en_ids, Some(vec![id + 1]));
+        let args: IdArgs = serde_json::from_str(r#"{"id": 2147483657}"#).unwrap();
+        assert_eq!(args.id, id);
+    }
+
+    #[tokio::test]
+    async fn test_mock_create_accession_crawl() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/accessions/crawl"))
+            .and(header("x-api-key", "test-key"))
+            .and(header(IDEMPOTENCY_KEY, "crawl-1"))
+            .and(body_json(serde_json::json!({
+                "url": "https://example.com",
+                "metadata_language": "english",
+                "metadata_title": "Example",
+                "metadata_time": "2024-05-01T00:00:00",
+                "metadata_subjects": [1],
+                "is_private": true,
+                "metadata_format": "wacz",
+                "browser_profile": null,
+                "metadata_description": null,
+                "s3_filename": null,
+                "metadata_contributor_ids": [],
+                "metadata_contributor_role_ids": [],
+                "metadata_creator_id": null,
+                "metadata_location_id": null,
+                "send_email_notification": false,
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_string("crawl started"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/subjects/1"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "id": 1, "subject": "War" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let body = mock_client(&server)
+            .create_accession_crawl(CreateAccessionCrawlRequest {
+                url: "https://example.com".to_string(),
+                metadata_language: MetadataLanguage::English,
+                metadata_title: "Example".to_string(),
+                metadata_time: "2024-05-01T00:00:00".to_string(),
+                metadata_subjects: vec![1, 0, 1],
+                is_private: true,
+                metadata_format: DublinMetadataFormat::Wacz,
+                browser_profile: None,
+                metadata_description: None,
+                s3_filename: None,
+                metadata_contributor_ids: vec![],
+                metadata_contributor_role_ids: vec![],
+                metadata_creator_id: None,
+                metadata_location_id: None,
+                send_email_notification: false,
+                idempotency_key: Some("crawl-1".to_string()),
+            })
+            .await
+            .unwrap();
+        assert_eq!(body, "crawl started");
+    }
+
+    #[tokio::test]
+    async fn test_mock_create_accession_crawls_reports_each_outcome_in_order() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/accessions/crawl"))
+            .and(body_partial_json(serde_json::json!({
+                "url": "https://www.facebook.com/ok",
+                "browser_profile": "facebook",
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({"id": 41})))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/accessions/crawl"))
+            .respond_with(ResponseTemplate::new(500).set_body_string("crawler down"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/subjects/1"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "id": 1, "subject": "War" })),
+            )
+            .mount(&server)
+            .await;
+
+        let mut failed = accession_with_metadata();
+        let requests: Vec<_> = [
+            "https://www.facebook.com/down",
+            "https://www.facebook.com/ok",
+        ]
+        .into_iter()
+        .map(|url| {
+            failed.seed_url = url.to_string();
+            let mut request = unchanged_recrawl_request(&failed);
+            request.browser_profile = Some(BrowserProfile::Facebook);
+            request
+        })
+        .collect();
+        assert_eq!(requests[1].metadata_subjects, vec![1]);
+
+        let client = mock_client(&server);
+        let results: Vec<_> = client.create_accession_crawls(requests).collect().await;
+        assert_eq!(results.len(), 2);
+        assert!(
+            matches!(&results[0], Err(SdaError::Http { .. })),
+            "{:?}",
+            results[0]
+        );
+        assert_eq!(results[1].as_deref().unwrap(), r#"{"id":41}"#);
+    }
+
+    #[tokio::test]
+    async fn test_mock_create_accession_crawl_rejects_unknown_subjects() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/subjects/1"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "id": 1, "subject": "War" })),
+            )
+            .mount(&server)
+            .await;
+        for id in [9, 12] {
+            Mock::given(method("GET"))
+                .and(path(format!("/api/v1/subjects/{}", id)))
+                .respond_with(ResponseTemplate::new(404))
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("POST"))
+            .and(path("/api/v1/accessions/crawl"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let request: CreateAccessionCrawlRequest = serde_json::from_value(serde_json::json!({
+            "url": "https://example.com",
+            "metadata_language": "english",
+            "metadata_title": "Example",
+            "metadata_time": "2026-02-01T00:00:00",
+            "metadata_subjects": [9, 1, 12, 9],
+            "is_private": false,
+            "metadata_format": "wacz",
+            "metadata_contributor_ids": [],
+            "metadata_contributor_role_ids": [],
+            "send_email_notification": false,
+        }))
+        .unwrap();
+        match mock_client(&server).create_accession_crawl(request).await {
+            Err(SdaError::InvalidFields { problems, .. }) => {
+                let messages: Vec<&str> = problems.iter().map(|p| p.message.as_str()).collect();
+                assert_eq!(messages.len(), 2, "{:?}", messages);
+                assert!(messages[0].starts_with("Subject 9 does not exist"));
+                assert!(messages[1].starts_with("Subject 12 does not exist"));
+            }
+            other => panic!("expected invalid fields, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_cancel_crawl() {
+        let server = MockServer::start().await;
+        let with_status = |status: &str| {
+            let mut json = one_accession_json();
+            json["accession"]["crawl_status"] = serde_json::json!(status);
+            json
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/5"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("etag", "\"v1\"")
+                    .set_body_json(with_status("Pending")),
+            )
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/5"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(with_status("Complete")))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/api/v1/accessions/5"))
+            .and(header("if-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(204))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        // The crawl finishes before the second cancel's delete lands.
+        Mock::given(method("DELETE"))
+            .and(path("/api/v1/accessions/5"))
+            .respond_with(ResponseTemplate::new(412))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let cancelled = client.cancel_crawl(5).await.unwrap();
+        assert!(cancelled.cancelled);
+
+        let raced = client.cancel_crawl(5).await.unwrap();
+        assert!(!raced.cancelled);
+        assert!(matches!(raced.crawl_status, CrawlStatus::Complete));
+        assert!(raced.message.contains("while it was being cancelled"));
+
+        let finished = client.cancel_crawl(5).await.unwrap();
+        assert!(!finished.cancelled);
+        assert!(finished.message.contains("already finished"));
+    }
+
+    #[tokio::test]
+    async fn test_mock_cancel_crawl_without_etag_deletes_nothing() {
+        let server = MockServer::start().await;
+        let mut pending = one_accession_json();
+        pending["accession"]["crawl_status"] = serde_json::json!("Pending");
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/5"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(pending))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let response = mock_client(&server).cancel_crawl(5).await.unwrap();
+        assert!(!response.cancelled);
+        assert!(matches!(response.crawl_status, CrawlStatus::Pending));
+        assert!(response.message.contains("no ETag"), "{}", response.message);
+    }
+
+    #[tokio::test]
+    async fn test_mock_get_accession_revalidates_with_etag() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/5"))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/5"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("etag", "\"v1\"")
+                    .set_body_json(one_accession_json()),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/accessions/5"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(one_accession_json()))
+            .mount(&server)
+            .await;
+
+        let client = SdaClient::builder()
+            .base_url(server.uri())
+            .api_key("test-key")
+            .cache_ttl(Duration::from_secs(60))
+            .max_retries(0)
+            .build()
+            .unwrap();
+        let first = client.get_accession(5).await.unwrap();
+        let revalidated = client.get_accession(5).await.unwrap();
+        assert_eq!(revalidated.accession.id, first.accession.id);
+        assert_eq!(revalidated.wacz_url, first.wacz_url);
+        assert_eq!(first.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(revalidated.etag.as_deref(), Some("\"v1\""));
+
+        // An update drops the stored body, so the next fetch is unconditional.
+        let request = merge_accession_patch(&first.accession, Default::default());
+        client.update_accession(5, request, None).await.unwrap();
+        client.get_accession(5).await.unwrap();
+    }
+
+    /// Looks up subject `names`, then creates the missing ones.
+    async fn resolve_subject_names(
+        client: &SdaClient,
+        names: &[String],
+        lang: MetadataLanguage,
+        mode: SubjectNameMode,
+    ) -> Result<Vec<i64>> {
+        let subjects = client
+            .lookup_subject_names(names, lang.clone(), mode)
+            .await?;
+        client.create_named_subjects(subjects, lang).await
+    }
+
+    #[tokio::test]
+    async fn test_mock_resolve_subject_names() {
+        let server = MockServer::start().await;
+        let subjects = |items: serde_json::Value| {
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": items, "num_pages": 1, "page": 0, "per_page": 10,
+            }))
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/v1/subjects"))
+            .and(query_param("query_term", "darfur"))
+            .respond_with(subjects(serde_json::json!([
+                {"id": 3, "subject": "Darfur"},
+                {"id": 8, "subject": "Darfur genocide"},
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/subjects"))
+            .and(query_param("query_term", "Floods"))
+            .respond_with(subjects(serde_json::json!([
+                {"id": 4, "subject": "Floods"},
+                {"id": 5, "subject": "floods "},
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/subjects"))
+            .respond_with(subjects(serde_json::json!([])))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/subjects"))
+            .and(body_json(
+                serde_json::json!({"lang": "english", "metadata_subject": "Displacement"}),
+            ))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "id": 9, "subject": "Displacement",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        let err = resolve_subject_names(
+            &client,
+            &names(&["darfur", "Floods", "Displacement"]),
+            MetadataLanguage::English,
+            SubjectNameMode::Lookup,
+        )
+        .await
+        .unwrap_err();
+        let SdaError::InvalidFields { problems, .. } = err else {
+            panic!("expected invalid fields, got {:?}", err);
+        };
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].message.contains("ambiguous"), "{:?}", problems);
+        assert!(
+            problems[1].message.contains("Displacement"),
+            "{:?}",
+            problems
+        );
+        // A bad name fails the call before the valid new one is created.
+        let err = resolve_subject_names(
+            &client,
+            &names(&["Displacement", "Bad\u{7}name"]),
+            MetadataLanguage::English,
+            SubjectNameMode::FindOrCreate,
+        )
+        .await
+        .unwrap_err();
+        let SdaError::InvalidFields { problems, .. } = err else {
+            panic!("expected invalid fields, got {:?}", err);
+        };
+        assert_eq!(problems.len(), 1);
+        assert!(
+            problems[0].message.contains("control characters"),
+            "{:?}",
+            problems
+        );
+
+        let ids = resolve_subject_names(
+            &client,
+            &names(&["darfur", " ", "Displacement"]),
+            MetadataLanguage::English,
+            SubjectNameMode::FindOrCreate,
+        )
+        .await
+        .unwrap();
+        assert_eq!(ids, [3, 9]);
+        assert!(matches!(
+            resolve_subject_names(
+                &client,
+                &names(&["darfur"]),
+                MetadataLanguage::None,
+                SubjectNameMode::Lookup
+            )
+            .await,
+            Err(SdaError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_mock_find_or_create_subject_looks_up_again_on_conflict() {
+        let server = MockServer::start().await;
+        let subjects = |items: serde_json::Value| {
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": items, "num_pages": 1, "page": 0, "per_page": 10,
+            }))
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/v1/subjects"))
+            .and(query_param("query_term", "Darfur"))
+            .respond_with(subjects(serde_json::json!([])))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        // Another caller created the subject between the lookup and the create.
+        Mock::given(method("GET"))
+            .and(path("/api/v1/subjects"))
+            .and(query_param("query_term", "Darfur"))
+            .respond_with(subjects(
+                serde_json::json!([{"id": 12, "subject": "darfur"}]),
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/subjects"))
+            .respond_with(subjects(serde_json::json!([])))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/subjects"))
+            .respond_with(ResponseTemplate::new(409).set_body_string("duplicate subject"))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let request = |name: &str| CreateSubjectRequest {
+            lang: MetadataLanguage::English,
+            metadata_subject: name.to_string(),
+        };
+        let found = client
+            .find_or_create_subject(request("Darfur"))
+            .await
+            .unwrap();
+        assert_eq!(found.id, 12);
+        assert!(!found.created);
+
+        let err = client
+            .find_or_create_subject(request("Kordofan"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SdaError::Conflict(_)), "{:?}", err);
+        assert!(err.to_string().contains("Kordofan"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_mock_find_subject_id_searches_every_page() {
+        let server = MockServer::start().await;
+        for (page, items) in [
+            (0, serde_json::json!([{"id": 1, "subject": "Nile delta"}])),
+            (1, serde_json::json!([{"id": 2, "subject": "nile"}])),
+        ] {
+            Mock::given(method("GET"))
+                .and(path("/api/v1/subjects"))
+                .and(query_param("query_term", "Nile"))
+                .and(query_param("page", page.to_string()))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "items": items, "num_pages": 2, "page": page, "per_page": 1,
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = mock_client(&server);
+        let request = CreateSubjectRequest {
+            lang: MetadataLanguage::English,
+            metadata_subject: " Nile ".to_string(),
+        };
+        assert_eq!(client.find_subject_id(&request).await.unwrap(), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_mock_register_accession_from_s3() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/accessions/crawl"))
+            .respond_with(ResponseTemplate::new(422).set_body_string("no such object"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let request = |s3_filename: &str| CreateAccessionCrawlRequest {
+            url: "https://example.com".to_string(),
+            metadata_language: MetadataLanguage::English,
+            metadata_title: "Example".to_string(),
+            metadata_time: "2024-05-01T00:00:00".to_string(),
+            metadata_subjects: vec![],
+            is_private: false,
+            metadata_format: DublinMetadataFormat::Wacz,
+            browser_profile: None,
+            metadata_description: None,
+            s3_filename: Some(s3_filename.to_string()),
+            metadata_contributor_ids: vec![],
+            metadata_contributor_role_ids: vec![],
+            metadata_creator_id: None,
+            metadata_location_id: None,
+            send_email_notification: false,
+            idempotency_key: None,
+        };
+        let client = mock_client(&server);
+
+        // Rejected locally, without a request.
+        let result = client.register_accession_from_s3(request("")).await;
+        assert!(matches!(
+            result,
+            Err(SdaError::InvalidFields { problems, .. }) if problems[0].field == "s3_filename"
+        ));
+
+        let err = client
+            .register_accession_from_s3(request("captures/site.wacz"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("captures/site.wacz"), "{}", err);
+        assert!(err.to_string().contains("no such object"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_mock_update_accession_sends_if_match() {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/accessions/5"))
+            .and(header("x-api-key", "test-key"))
+            .and(header("if-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(200).set_body_json(one_accession_json()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let response = mock_client(&server)
+            .update_accession(
+                5,
+                UpdateAccessionRequest {
+                    is_private: false,
+                    metadata_description: String::new(),
+                    metadata_language: MetadataLanguage::English,
+                    metadata_subjects: vec![1],
+                    metadata_time: "2024-05-01T00:00:00".to_string(),
+                    metadata_title: "Example".to_string(),
+                    metadata_contributor_ids: vec![],
+                    metadata_contributor_role_ids: vec![],
+                    metadata_creator_id: None,
+                    metadata_location_id: None,
+                },
+                Some("\"v1\"".to_string()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.accession.id, 5);
+    }
+
+    #[tokio::test]
+    async fn test_mock_subject_endpoints() {
+        let server = MockServer::start().await;
+        let subject = serde_json::json!({ "id": 7, "subject": "Protest" });
+        Mock::given(method("GET"))
+            .and(path("/api/v1/subjects"))
+            .and(header("x-api-key", "test-key"))
+            .and(query_param("lang", "arabic"))
+            .and(query_param("page", "1"))
+            .and(query_param("in_collection_id", "4"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [subject],
+                "num_pages": 1,
+                "page": 1,
+                "per_page": 20,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/subjects/7"))
+            .and(query_param("lang", "english"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&subject))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/subjects"))
+            .and(header("x-api-key", "test-key"))
+            .and(body_json(serde_json::json!({
+                "lang": "english",
+                "metadata_subject": "Protest",
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_string("{\"id\":7}"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/subjects/7"))
+            .and(body_json(serde_json::json!({
+                "lang": "english",
+                "metadata_subject": "Protest",
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&subject))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/api/v1/subjects/7"))
+            .and(body_json(serde_json::json!({ "lang": "english" })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let list = client
+            .list_subjects(
+                MetadataLanguage::Arabic,
+                Some(1),
+                None,
+                Some(4),
+                String::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(list.items[0].subject, "Protest");
+        let one = client
+            .get_subject(7, MetadataLanguage::English)
+            .await
+            .unwrap();
+        assert_eq!(one.id, 7);
+        let created = client
+            .create_subject(CreateSubjectRequest {
+                lang: MetadataLanguage::English,
+                metadata_subject: "Protest".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(created, "{\"id\":7}");
+        let updated = client
+            .update_subject(
+                7,
+                UpdateSubjectRequest {
+                    lang: MetadataLanguage::English,
+                    metadata_subject: "Protest".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.subject, "Protest");
+        client
+            .delete_subject(
+                7,
+                DeleteSubjectRequest {
+                    lang: MetadataLanguage::English,
+                },
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_collection_endpoints() {
+        let server = MockServer::start().await;
+        let collection = serde_json::json!({
+            "id": 4,
+            "title": "Revolution",
+            "is_private": false,
+            "description": null,
+            "subject_ids": [7],
+        });
+        Mock::given(method("GET"))
+            .and(path("/api/v1/collections"))
+            .and(header("x-api-key", "test-key"))
+            .and(query_param("lang", "english"))
+            .and(query_param("metadata_subjects", "7"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [collection],
+                "num_pages": 1,
+                "page": 1,
+                "per_page": 20,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/collections/4"))
+            .and(query_param("lang", "english"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&collection))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/collections"))
+            .and(body_json(serde_json::json!({
+                "lang": "english",
+                "title": "Revolution",
+                "is_private": false,
+                "subject_ids": [7],
+                "description": "",
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_string("{\"id\":4}"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/collections/4"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&collection))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/collections/4/accessions"))
+            .and(query_param("per_page", "5"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [accession_with_metadata()],
+                "num_pages": 1,
+                "page": 1,
+                "per_page": 5,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/collections/4/accessions/5"))
+            .and(header("x-api-key", "test-key"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/api/v1/collections/4/accessions/5"))
+            .and(header("x-api-key", "test-key"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let list = client
+            .list_collections(ListCollectionsArgs {
+                lang: MetadataLanguage::English,
+                metadata_subjects: vec![7],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(list.items[0].subject_ids, Some(vec![7]));
+        let one = client
+            .get_collection(4, MetadataLanguage::English)
+            .await
+            .unwrap();
+        assert_eq!(one.title, "Revolution");
+        let request = CreateCollectionRequest {
+            lang: MetadataLanguage::English,
+            title: "Revolution".to_string(),
+            is_private: false,
+            subject_ids: vec![7],
+            description: String::new(),
+        };
+        assert_eq!(
+            client.create_collection(request).await.unwrap(),
+            "{\"id\":4}"
+        );
+        let updated = client
+            .update_collection(
+                4,
+                UpdateCollectionRequest {
+                    lang: MetadataLanguage::English,
+                    title: "Revolution".to_string(),
+                    is_private: false,
+                    subject_ids: vec![7],
+                    description: String::new(),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.id, 4);
+        let accessions = client
+            .list_collection_accessions(ListCollectionAccessionsArgs {
+                collection_id: 4,
+                page: -1,
+                per_page: 5,
+                lang: MetadataLanguage::None,
+                verbosity: Verbosity::Full,
+            })
+            .await
+            .unwrap();
+        assert_eq!(accessions.items[0].id, 5);
+        assert!(client.add_accession_to_collection(4, 5).await.unwrap());
+        assert!(client.remove_accession_from_collection(4, 5).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_mock_remove_from_collection_tells_missing_collection_apart() {
+        let server = MockServer::start().await;
+        for collection in [4, 9] {
+            Mock::given(method("DELETE"))
+                .and(path(format!(
+                    "/api/v1/collections/{}/accessions/5",
+                    collection
+                )))
+                .respond_with(ResponseTemplate::new(404))
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/api/v1/collections/4"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": 4,
+                "title": "Revolution",
+                "is_private": false,
+                "description": null,
+                "subject_ids": [7],
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/collections/9"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        assert!(!client.remove_accession_from_collection(4, 5).await.unwrap());
+        let err = client
+            .remove_accession_from_collection(9, 5)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            SdaError::Http {
+                status: StatusCode::NOT_FOUND,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_mock_contributor_endpoints() {
+        let server = MockServer::start().await;
+        let contributor = serde_json::json!({ "id": 2, "contributor": "Amal" });
+        Mock::given(method("GET"))
+            .and(path("/api/v1/contributors"))
+            .and(header("x-api-key", "test-key"))
+            .and(query_param("lang", "english"))
+            .and(query_param("per_page", "50"))
+            .and(query_param("query_term", "Am"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [contributor],
+                "num_pages": 1,
+                "page": 1,
+                "per_page": 50,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/contributors/2"))
+            .and(query_param("lang", "arabic"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&contributor))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/contributors"))
+            .and(body_json(serde_json::json!({
+                "lang": "english",
+                "contributor": "Amal",
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_string("created"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/contributors/2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&contributor))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/api/v1/contributors/2"))
+            .and(body_json(serde_json::json!({ "lang": "english" })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let list = client
+            .list_contributors(MetadataLanguage::English, None, Some(50), "Am".to_string())
+            .await
+            .unwrap();
+        assert_eq!(list.items[0].contributor, "Amal");
+        let one = client
+            .get_contributor(2, MetadataLanguage::Arabic)
+            .await
+            .unwrap();
+        assert_eq!(one.id, 2);
+        let created = client
+            .create_contributor(CreateContributorRequest {
+                lang: MetadataLanguage::English,
+                contributor: "Amal".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(created, "created");
+        client
+            .update_contributor(
+                2,
+                UpdateContributorRequest {
+                    lang: MetadataLanguage::English,
+                    contributor: "Amal".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        client
+            .delete_contributor(
+                2,
+                DeleteContributorRequest {
+                    lang: MetadataLanguage::English,
+                },
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_contributor_role_endpoints() {
+        let server = MockServer::start().await;
+        let role = serde_json::json!({ "id": 3, "role": "Photographer" });
+        Mock::given(method("GET"))
+            .and(path("/api/v1/contributors/roles"))
+            .and(header("x-api-key", "test-key"))
+            .and(query_param("lang", "english"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [role],
+                "num_pages": 1,
+                "page": 1,
+                "per_page": 20,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/contributors/roles/3"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&role))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/contributors/roles"))
+            .and(body_json(serde_json::json!({
+                "lang": "english",
+                "role": "Photographer",
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_string("created"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/contributors/roles/3"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&role))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/api/v1/contributors/roles/3"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let list = client
+            .list_contributor_roles(MetadataLanguage::English, None, None, String::new())
+            .await
+            .unwrap();
+        assert_eq!(list.items[0].role, "Photographer");
+        let one = client
+            .get_contributor_role(3, MetadataLanguage::English)
+            .await
+            .unwrap();
+        assert_eq!(one.id, 3);
+        client
+            .create_contributor_role(CreateContributorRoleRequest {
+                lang: MetadataLanguage::English,
+                role: "Photographer".to_string(),
+            })
+            .await
+            .unwrap();
+        client
+            .update_contributor_role(
+                3,
+                UpdateContributorRoleRequest {
+                    lang: MetadataLanguage::English,
+                    role: "Photographer".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        client
+            .delete_contributor_role(
+                3,
+                DeleteContributorRoleRequest {
+                    lang: MetadataLanguage::English,
+                },
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_creator_endpoints() {
+        let server = MockServer::start().await;
+        let creator = serde_json::json!({ "id": 8, "creator": "Radio Dabanga" });
+        Mock::given(method("GET"))
+            .and(path("/api/v1/creators"))
+            .and(header("x-api-key", "test-key"))
+            .and(query_param("page", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [creator],
+                "num_pages": 2,
+                "page": 2,
+                "per_page": 20,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/creators/8"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&creator))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/creators"))
+            .and(body_json(serde_json::json!({
+                "lang": "english",
+                "creator": "Radio Dabanga",
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_string("created"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/creators/8"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&creator))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/api/v1/creators/8"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let list = client
+            .list_creators(MetadataLanguage::English, Some(2), None, String::new())
+            .await
+            .unwrap();
+        assert_eq!(list.items[0].creator, "Radio Dabanga");
+        let one = client
+            .get_creator(8, MetadataLanguage::English)
+            .await
+            .unwrap();
+        assert_eq!(one.id, 8);
+        client
+            .create_creator(CreateCreatorRequest {
+                lang: MetadataLanguage::English,
+                creator: "Radio Dabanga".to_string(),
+            })
+            .await
+            .unwrap();
+        client
+            .update_creator(
+                8,
+                UpdateCreatorRequest {
+                    lang: MetadataLanguage::English,
+                    creator: "Radio Dabanga".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        client
+            .delete_creator(
+                8,
+                DeleteCreatorRequest {
+                    lang: MetadataLanguage::English,
+                },
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_location_endpoints() {
+        let server = MockServer::start().await;
+        let location = serde_json::json!({ "id": 9, "location": "Omdurman" });
+        Mock::given(method("GET"))
+            .and(path("/api/v1/locations"))
+            .and(header("x-api-key", "test-key"))
+            .and(query_param("lang", "english"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [location],
+                "num_pages": 1,
+                "page": 1,
+                "per_page": 20,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/locations/9"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&location))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/locations"))
+            .and(body_json(serde_json::json!({
+                "lang": "english",
+                "location": "Omdurman",
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_string("created"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/locations/9"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&location))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/api/v1/locations/9"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let list = client
+            .list_locations(MetadataLanguage::English, None, None, String::new())
+            .await
+            .unwrap();
+        assert_eq!(list.items[0].location, "Omdurman");
+        let one = client
+            .get_location(9, MetadataLanguage::English)
+            .await
+            .unwrap();
+        assert_eq!(one.id, 9);
+        client
+            .create_location(CreateLocationRequest {
+                lang: MetadataLanguage::English,
+                location: "Omdurman".to_string(),
+            })
+            .await
+            .unwrap();
+        client
+            .update_location(
+                9,
+                UpdateLocationRequest {
+                    lang: MetadataLanguage::English,
+                    location: "Omdurman".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        client
+            .delete_location(
+                9,
+                DeleteLocationRequest {
+                    lang: MetadataLanguage::English,
+                },
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_relation_endpoints() {
+        let server = MockServer::start().await;
+        let relation = serde_json::json!({
+            "id": 11,
+            "related_accession_id": 6,
+            "relation_type": "has_part",
+        });
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/5/relation"))
+            .and(header("x-api-key", "test-key"))
+            .and(query_param("lang", "english"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "items": [relation] })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/5/relation/11"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&relation))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/accessions/5/relation"))
+            .and(body_json(serde_json::json!({
+                "related_accession_id": 6,
+                "relation_type": "has_part",
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_string("created"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/api/v1/accessions/5/relation/11"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let list = client
+            .list_relations(5, MetadataLanguage::English)
+            .await
+            .unwrap();
+        assert_eq!(list.items[0].relation_type, "has_part");
+        let one = client
+            .get_relation(5, 11, MetadataLanguage::English)
+            .await
+            .unwrap();
+        assert_eq!(one.related_accession_id, 6);
+        client
+            .create_relation(
+                5,
+                CreateRelationRequest {
+                    related_accession_id: 6,
+                    relation_type: DublinMetadataRelationType::HasPart,
+                },
+            )
+            .await
+            .unwrap();
+        client
+            .delete_relation(5, 11, MetadataLanguage::English)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_error_status_keeps_response_body() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/5"))
+            .respond_with(ResponseTemplate::new(422).set_body_string("invalid id"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/6"))
+            .respond_with(ResponseTemplate::new(500).set_body_string("database down"))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        match client.get_accession(5).await {
+            Err(SdaError::Http {
+                context,
+                status,
+                body,
+            }) => {
+                assert_eq!(context, "Server returned error for get accession");
+                assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+                assert_eq!(body, "invalid id");
+            }
+            other => panic!("expected HTTP error, got {:?}", other),
+        }
+        match client.get_accession(6).await {
+            Err(SdaError::Http { status, body, .. }) => {
+                assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+                assert_eq!(body, "database down");
+            }
+            other => panic!("expected HTTP error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_error_body_is_capped() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/5"))
+            .respond_with(ResponseTemplate::new(422).set_body_string("x".repeat(1024 * 1024)))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        match client.get_accession(5).await {
+            Err(SdaError::Http { body, .. }) => {
+                assert_eq!(body.len(), MAX_ERROR_BODY_BYTES + '…'.len_utf8());
+                assert!(body.ends_with('…'));
+            }
+            other => panic!("expected HTTP error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_malformed_body_is_decode_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/collections/4"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw("{\"id\": \"four\"}", "application/json"),
+            )
+            .mount(&server)
+            .await;
+
+        let err = mock_client(&server)
+            .get_collection(4, MetadataLanguage::English)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SdaError::Decode { .. }), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_mock_prefetch_subjects_serves_later_calls_from_cache() {
+        let server = MockServer::start().await;
+        for lang in ["english", "arabic"] {
+            Mock::given(method("GET"))
+                .and(path("/api/v1/subjects"))
+                .and(query_param("lang", lang))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "items": [{"id": 1, "subject": "War"}, {"id": 2, "subject": "Health"}],
+                    "num_pages": 1,
+                    "page": 0,
+                    "per_page": 50,
+                })))
+                .expect(2)
+                .mount(&server)
+                .await;
+        }
+
+        let client = SdaClient::builder()
+            .base_url(server.uri())
+            .api_key("test-key")
+            .max_retries(0)
+            .build()
+            .unwrap();
+        assert_eq!(client.prefetch_subjects().await.unwrap(), 4);
+        let first = client
+            .list_subjects(MetadataLanguage::Arabic, None, None, None, String::new())
+            .await
+            .unwrap();
+        assert_eq!(first.items.len(), 2);
+        assert_eq!(
+            client
+                .list_all_subjects(MetadataLanguage::English)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mock_html_page_is_content_type_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/collections/4"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                "<html><title>Sign in to the guest Wi-Fi</title></html>",
+                "text/html; charset=utf-8",
+            ))
+            .mount(&server)
+            .await;
+
+        match mock_client(&server)
+            .get_collection(4, MetadataLanguage::English)
+            .await
+        {
+            Err(SdaError::UnexpectedContentType {
+                content_type,
+                snippet,
+                ..
+            }) => {
+                assert_eq!(content_type, "text/html; charset=utf-8");
+                assert!(snippet.contains("guest Wi-Fi"), "{}", snippet);
+            }
+            other => panic!("expected content type error, got {:?}", other),
+        }
+        assert!(is_json_content_type("application/json; charset=utf-8"));
+        assert!(is_json_content_type("Application/Problem+JSON"));
+        assert!(!is_json_content_type("text/plain"));
+    }
+
+    #[tokio::test]
+    async fn test_mock_strict_schema_quotes_body_in_decode_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/collections/4"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw("{\"id\": \"four\"}", "application/json"),
+            )
+            .mount(&server)
+            .await;
+
+        let client = |strict_schema| {
+            SdaClient::builder()
+                .base_url(server.uri())
+                .api_key("test-key")
+                .max_retries(0)
+                .strict_schema(strict_schema)
+                .build()
+                .unwrap()
+        };
+        match client(true)
+            .get_collection(4, MetadataLanguage::English)
+            .await
+        {
+            Err(SdaError::Decode { context, .. }) => {
+                assert!(context.contains("{\"id\": \"four\"}"), "{}", context)
+            }
+            other => panic!("expected decode error, got {:?}", other),
+        }
+        match client(false)
+            .get_collection(4, MetadataLanguage::English)
+            .await
+        {
+            Err(SdaError::Decode { context, .. }) => assert!(!context.contains("four")),
+            other => panic!("expected decode error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_public_listing_never_sends_is_private() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions"))
+            .and(query_param_is_missing("is_private"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [],
+                "num_pages": 0,
+                "page": 0,
+                "per_page": 20,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        client
+            .list_accessions(ListAccessionsArgs::default())
+            .await
+            .unwrap();
+        let err = client
+            .list_accessions(ListAccessionsArgs {
+                is_private: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        match err {
+            SdaError::Validation(message) => {
+                assert!(
+                    message.contains("use list_private_accessions"),
+                    "{}",
+                    message
+                )
+            }
+            other => panic!("expected validation error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_private_listing_always_sends_is_private() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/private"))
+            .and(query_param("is_private", "true"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [],
+                "num_pages": 0,
+                "page": 0,
+                "per_page": 20,
+            })))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        for is_private in [false, true] {
+            client
+                .list_private_accessions(ListAccessionsArgs {
+                    is_private,
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+        }
+        let requests = server.received_requests().await.unwrap();
+        for request in requests {
+            let flags = request
+                .url
+                .query_pairs()
+                .filter(|(key, _)| key == "is_private")
+                .count();
+            assert_eq!(flags, 1, "is_private sent more than once: {}", request.url);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_export_collection_downloads_archives() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/collections/4"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": 4,
+                "title": "Revolution",
+                "is_private": false,
+                "description": null,
+                "subject_ids": [7, 8],
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/subjects/7"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "id": 7, "subject": "Protest" })),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/subjects/8"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/collections/4/accessions"))
+            .and(query_param("page", "0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [accession_with_metadata()],
+                "num_pages": 1,
+                "page": 0,
+                "per_page": 20,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/5"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "accession": accession_with_metadata(),
+                "wacz_url": format!("{}/files/5.wacz", server.uri()),
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/files/5.wacz"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"wacz bytes".to_vec()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = export::TempDir::create("sda-client-export-test").unwrap();
+        let export = mock_client(&server)
+            .export_collection(4, MetadataLanguage::English, Some(dir.path()))
+            .await
+            .unwrap();
+
+        assert_eq!(export.collection.title, "Revolution");
+        assert_eq!(export.subjects[0].subject.as_deref(), Some("Protest"));
+        assert_eq!(export.subjects[1].subject, None);
+        assert!(export.errors.is_empty(), "{:?}", export.errors);
+        let accession = &export.accessions[0];
+        assert_eq!(accession.accession.id, 5);
+        assert_eq!(accession.archive_file.as_deref(), Some("archives/5.wacz"));
+        assert_eq!(
+            std::fs::read(dir.path().join("5.wacz")).unwrap(),
+            b"wacz bytes"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mock_set_accessions_privacy() {
+        let server = MockServer::start().await;
+        let mut already_private = accession_with_metadata();
+        already_private.id = 6;
+        already_private.is_private = true;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/5"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(one_accession_json()))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/6"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "accession": already_private,
+                "wacz_url": "https://example.com/6.wacz",
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/7"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/private/7"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/accessions/5"))
+            .and(body_json(merge_accession_patch(
+                &accession_with_metadata(),
+                UpdateAccessionPatch {
+                    is_private: Some(true),
+                    ..Default::default()
+                },
+            )))
+            .respond_with(ResponseTemplate::new(200).set_body_json(one_accession_json()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let response = mock_client(&server)
+            .set_accessions_privacy(vec![5, 6, 7, 5], true)
+            .await;
+
+        assert!(response.is_private);
+        assert_eq!(
+            (response.updated, response.unchanged, response.failed),
+            (1, 1, 1)
+        );
+        let statuses: Vec<_> = response.results.iter().map(|r| (r.id, &r.status)).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (5, &PrivacyChangeStatus::Updated),
+                (6, &PrivacyChangeStatus::Unchanged),
+                (7, &PrivacyChangeStatus::Failed),
+            ]
+        );
+        assert!(response.results[2].error.is_some());
+        assert!(response.results[0].error.is_none());
+        let was_private: Vec<_> = response.results.iter().map(|r| r.was_private).collect();
+        assert_eq!(was_private, [Some(false), Some(true), None]);
+    }
+
+    #[tokio::test]
+    async fn test_mock_export_accessions_ndjson_writes_one_line_per_accession() {
+        let server = MockServer::start().await;
+        let mut second = accession_with_metadata();
+        second.id = 6;
+        for (page, items) in [("0", vec![accession_with_metadata()]), ("1", vec![second])] {
+            Mock::given(method("GET"))
+                .and(path("/api/v1/accessions"))
+                .and(query_param("page", page))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "items": items,
+                    "num_pages": 2,
+                    "page": page.parse::<i64>().unwrap(),
+                    "per_page": 1,
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        let dest = std::env::temp_dir().join(format!("sda-test-{}.ndjson", uuid::Uuid::new_v4()));
+        let _cleanup = TempFile(dest.clone());
+
+        let seen = std::sync::atomic::AtomicU64::new(0);
+        let items = mock_client(&server)
+            .export_accessions_ndjson(ListAccessionsArgs::default(), &dest, |n| {
+                seen.store(n, std::sync::atomic::Ordering::SeqCst)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(items, 2);
+        assert_eq!(seen.load(std::sync::atomic::Ordering::SeqCst), 2);
+        let text = std::fs::read_to_string(&dest).unwrap();
+        let ids: Vec<i64> = text
+            .lines()
+            .map(|line| {
+                serde_json::from_str::<serde_json::Value>(line).unwrap()["id"]
+                    .as_i64()
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(ids, [5, 6]);
+    }
+
+    #[tokio::test]
+    async fn test_mock_export_accessions_ndjson_removes_partial_file() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions"))
+            .and(query_param("page", "0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [accession_with_metadata()],
+                "num_pages": 2,
+                "page": 0,
+                "per_page": 1,
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions"))
+            .and(query_param("page", "1"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+        let dest = std::env::temp_dir().join(format!("sda-test-{}.ndjson", uuid::Uuid::new_v4()));
+
+        let result = mock_client(&server)
+            .export_accessions_ndjson(ListAccessionsArgs::default(), &dest, |_| {})
+            .await;
+
+        assert!(matches!(result, Err(SdaError::Http { .. })));
+        assert!(!dest.exists());
+    }
+
+    #[tokio::test]
+    async fn test_mock_recent_accessions() {
+        let server = MockServer::start().await;
+        let now = Utc::now();
+        let mut recent = accession_with_metadata();
+        recent.title_en = None;
+        recent.crawl_timestamp = (now - chrono::Duration::hours(2))
+            .format("%Y-%m-%dT%H:%M:%S")
+            .to_string();
+        let mut older = accession_with_metadata();
+        older.id = 4;
+        older.crawl_timestamp = (now - chrono::Duration::hours(30))
+            .format("%Y-%m-%dT%H:%M:%S")
+            .to_string();
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/private"))
+            .and(query_param(
+                "date_from",
+                (now - chrono::Duration::hours(24))
+                    .format("%Y-%m-%d")
+                    .to_string(),
+            ))
+            .and(query_param("sort_by", "crawl_timestamp"))
+            .and(query_param("sort_order", "desc"))
+            .and(query_param_is_missing("lang"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [recent, older],
+                "num_pages": 1,
+                "page": 0,
+                "per_page": 20,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let response = client
+            .recent_accessions(RecentAccessionsArgs {
+                since: "24h".to_string(),
+                is_private: true,
+                page: -1,
+                per_page: -1,
+            })
+            .await
+            .unwrap();
+
+        // The day-granular filter also matched the 30-hour-old accession.
+        assert_eq!(response.items.len(), 1);
+        assert_eq!(response.items[0].id, 5);
+        assert_eq!(response.items[0].title.as_deref(), Some("عنوان"));
+
+        let result = client
+            .recent_accessions(RecentAccessionsArgs {
+                since: "soon".to_string(),
+                is_private: false,
+                page: -1,
+                per_page: -1,
+            })
+            .await;
+        assert!(matches!(result, Err(SdaError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_mock_accession_collections_scans_members_once() {
+        let server = MockServer::start().await;
+        for (is_private, id) in [("false", 1), ("true", 2)] {
+            Mock::given(method("GET"))
+                .and(path("/api/v1/collections/private"))
+                .and(query_param("is_private", is_private))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "items": [{
+                        "id": id,
+                        "title": format!("Collection {}", id),
+                        "is_private": is_private == "true",
+                        "description": null,
+                    }],
+                    "num_pages": 1,
+                    "page": 0,
+                    "per_page": 20,
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        for (collection, accession) in [(1, 5), (2, 9)] {
+            let mut member = accession_with_metadata();
+            member.id = accession;
+            Mock::given(method("GET"))
+                .and(path(format!(
+                    "/api/v1/collections/{}/accessions",
+                    collection
+                )))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "items": [member],
+                    "num_pages": 1,
+                    "page": 0,
+                    "per_page": 20,
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = SdaClient::builder()
+            .base_url(server.uri())
+            .api_key("test-key")
+            .cache_ttl(Duration::from_secs(60))
+            .max_retries(0)
+            .build()
+            .unwrap();
+        let (collections, scanned, cached) = client
+            .accession_collections(5, MetadataLanguage::English, false)
+            .await
+            .unwrap();
+        assert_eq!(collections.iter().map(|c| c.id).collect::<Vec<_>>(), [1]);
+        assert_eq!((scanned, cached), (2, false));
+
+        // Other accessions are answered from the memberships already found.
+        let (collections, _, cached) = client
+            .accession_collections(9, MetadataLanguage::English, false)
+            .await
+            .unwrap();
+        assert_eq!(collections[0].id, 2);
+        assert!(collections[0].is_private);
+        assert!(cached);
+        let (collections, _, _) = client
+            .accession_collections(7, MetadataLanguage::English, false)
+            .await
+            .unwrap();
+        assert!(collections.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mock_list_private_collections_sends_is_private() {
+        let server = MockServer::start().await;
+        for value in ["true", "false"] {
+            Mock::given(method("GET"))
+                .and(path("/api/v1/collections/private"))
+                .and(query_param("is_private", value))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "items": [],
+                    "num_pages": 0,
+                    "page": 0,
+                    "per_page": 20,
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = mock_client(&server);
+        let args: ListPrivateCollectionsArgs = serde_json::from_str("{}").unwrap();
+        client.list_private_collections(args).await.unwrap();
+        client
+            .list_private_collections(ListPrivateCollectionsArgs {
+                is_private: false,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_accessions_timeline_stops_before_date_from() {
+        let server = MockServer::start().await;
+        let crawled = |id: i64, timestamp: &str| {
+            let mut accession = accession_with_metadata();
+            accession.id = id;
+            accession.crawl_timestamp = timestamp.to_string();
+            accession
+        };
+        let page = |page: i64, items: Vec<AccessionsWithMetadataResponse>| {
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": items,
+                "num_pages": 3,
+                "page": page,
+                "per_page": 3,
+            }))
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions"))
+            .and(query_param("sort_by", "crawl_timestamp"))
+            .and(query_param("sort_order", "desc"))
+            .and(query_param("page", "0"))
+            .respond_with(page(
+                0,
+                vec![
+                    crawled(5, "2024-05-20T09:00:00"),
+                    crawled(4, "2024-05-02T09:00:00"),
+                    crawled(3, "2024-04-30T23:59:59"),
+                ],
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions"))
+            .and(query_param("page", "1"))
+            .respond_with(page(
+                1,
+                vec![
+                    crawled(2, "2024-04-10T09:00:00"),
+                    crawled(1, "2024-03-31T09:00:00"),
+                ],
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions"))
+            .and(query_param("page", "2"))
+            .respond_with(page(2, vec![]))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let timeline = mock_client(&server)
+            .accessions_timeline(AccessionsTimelineArgs {
+                date_from: "2024-04".to_string(),
+                date_to: "2024-05-15".to_string(),
+                bucket: TimelineBucket::Month,
+                is_private: false,
+            })
+            .await
+            .unwrap();
+        assert_eq!(timeline.date_from, "2024-04-01");
+        assert_eq!(timeline.total, 3);
+        assert_eq!(
+            timeline.buckets,
+            [
+                TimelineCount {
+                    start: "2024-04-01".to_string(),
+                    count: 2
+                },
+                TimelineCount {
+                    start: "2024-05-01".to_string(),
+                    count: 1
+                },
+            ]
+        );
+
+        let err = mock_client(&server)
+            .accessions_timeline(AccessionsTimelineArgs {
+                date_from: "2000".to_string(),
+                date_to: "2024".to_string(),
+                bucket: TimelineBucket::Day,
+                is_private: false,
+            })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("wider bucket"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_mock_search_spans_categories() {
+        let server = MockServer::start().await;
+        let page = |items: serde_json::Value, num_pages: i64| {
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": items,
+                "num_pages": num_pages,
+                "page": 0,
+                "per_page": 1,
+            }))
+        };
+        // A full first page is counted with a one-item request.
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions"))
+            .and(query_param("query_term", "دارفور"))
+            .and(query_param("per_page", "1"))
+            .respond_with(page(serde_json::json!([accession_with_metadata()]), 12))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/collections"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/subjects"))
+            .and(query_param("lang", "arabic"))
+            .and(query_param("query_term", "دارفور"))
+            .respond_with(page(serde_json::json!([{"id": 3, "subject": "دارفور"}]), 1))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let response = mock_client(&server)
+            .search(SearchArgs {
+                query_term: " دارفور ".to_string(),
+                lang: MetadataLanguage::None,
+                limit: 1,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(response.query_term, "دارفور");
+        assert_eq!(response.accessions.total, 12);
+        assert_eq!(response.accessions.items[0].id, 5);
+        assert!(response.collections.error.is_some());
+        assert_eq!(response.subjects.total, 1);
+        assert_eq!(response.subjects.items[0].id, 3);
+    }
+
+    #[tokio::test]
+    async fn test_mock_diff_accessions() {
+        let server = MockServer::start().await;
+        let mut recrawl = accession_with_metadata();
+        recrawl.id = 9;
+        recrawl.is_private = true;
+        recrawl.crawl_timestamp = "2025-01-10T08:00:00".to_string();
+        recrawl.subjects_en = Some(vec!["Khartoum".to_string()]);
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/5"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(one_accession_json()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/9"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/private/9"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "accession": recrawl,
+                "wacz_url": "https://example.com/9.wacz",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let diff = client.diff_accessions(5, 9).await.unwrap();
+
+        assert!(diff.same_seed_url);
+        assert!(!diff.identical);
+        assert_eq!(diff.subjects_added, ["Khartoum"]);
+        assert_eq!(diff.subjects_removed, ["Protests"]);
+        let fields: Vec<&str> = diff.changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, ["crawl_timestamp", "is_private", "subjects_en"]);
+        assert!(matches!(
+            client.diff_accessions(5, 5).await,
+            Err(SdaError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_mock_check_time_sync_reads_date_header_of_error_response() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions"))
+            .respond_with(
+                ResponseTemplate::new(401)
+                    .insert_header("date", "Wed, 21 Oct 2015 07:28:00 GMT")
+                    .set_body_string("bad key"),
+            )
+            .mount(&server)
+            .await;
+        let client = mock_client(&server);
+
+        let report = client
+            .check_time_sync(Duration::from_secs(30))
+            .await
+            .unwrap();
+        assert_eq!(report.api_time, "2015-10-21T07:28:00Z");
+        assert!(report.skew_seconds < 0);
+        assert!(!report.in_sync);
+        assert!(report.warning.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_mock_check_time_sync_sends_one_attempt() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions"))
+            .respond_with(
+                ResponseTemplate::new(429)
+                    .insert_header("date", "Wed, 21 Oct 2015 07:28:00 GMT")
+                    .insert_header("retry-after", "0"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = SdaClient::builder()
+            .base_url(server.uri())
+            .api_key("test-key")
+            .max_retries(3)
+            .build()
+            .unwrap();
+
+        let report = client
+            .check_time_sync(Duration::from_secs(30))
+            .await
+            .unwrap();
+        assert_eq!(report.api_time, "2015-10-21T07:28:00Z");
+        assert!(report.round_trip_ms < HEALTH_CHECK_TIMEOUT.as_millis() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_mock_health_check_reports_status() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions"))
+            .and(header("x-api-key", "test-key"))
+            .and(query_param("per_page", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [],
+                "num_pages": 0,
+                "page": 0,
+                "per_page": 1,
+            })))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions"))
+            .respond_with(ResponseTemplate::new(401).set_body_string("bad key"))
+            .mount(&server)
+            .await;
+        let client = mock_client(&server);
+
+        let healthy = client.health_check().await;
+        assert!(healthy.healthy);
+        assert_eq!(healthy.status, Some(200));
+        assert!(healthy.error.is_none());
+
+        let unhealthy = client.health_check().await;
+        assert!(!unhealthy.healthy);
+        assert_eq!(unhealthy.status, Some(401));
+        assert!(unhealthy.error.unwrap().contains("bad key"));
+    }
+
+    #[tokio::test]
+    async fn test_mock_health_check_does_not_retry_rate_limits() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "30"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = SdaClient::builder()
+            .base_url(server.uri())
+            .api_key("test-key")
+            .max_retries(3)
+            .build()
+            .unwrap();
+
+        let report = client.health_check().await;
+        assert!(!report.healthy);
+        assert_eq!(report.status, Some(429));
+        assert!(report.latency_ms < 30_000);
+    }
+
+    #[tokio::test]
+    async fn test_health_check_reports_unreachable_api() {
+        // Bind and drop a listener so the port is closed.
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let client = SdaClient::builder()
+            .base_url(format!("http://127.0.0.1:{}", port))
+            .api_key("test-key")
+            .build()
+            .unwrap();
+
+        let report = client.health_check().await;
+        assert!(!report.healthy);
+        assert_eq!(report.status, None);
+        assert!(
+            report
+                .error
+                .unwrap()
+                .starts_with("Failed to reach the SDA API")
+        );
+    }
+
+    /// Serves `body` honouring `Range: bytes=a-b` and `bytes=-n` requests.
+    struct RangeResponder(Vec<u8>);
+
+    impl wiremock::Respond for RangeResponder {
+        fn respond(&self, request: &wiremock::Request) -> ResponseTemplate {
+            let total = self.0.len();
+            let range = request
+                .headers
+                .get("range")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("bytes="))
+                .unwrap();
+            let (start, end) = match range.split_once('-').unwrap() {
+                ("", suffix) => (total.saturating_sub(suffix.parse().unwrap()), total - 1),
+                (start, end) => (start.parse().unwrap(), end.parse().unwrap()),
+            };
+            ResponseTemplate::new(206)
+                .insert_header(
+                    "content-range",
+                    format!("bytes {}-{}/{}", start, end, total).as_str(),
+                )
+                .set_body_bytes(self.0[start..=end].to_vec())
+        }
+    }
+
+    fn sample_wacz() -> Vec<u8> {
+        use std::io::Write;
+
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+        // Stored, so the archive is much larger than the tail fetched first.
+        let stored = options.compression_method(zip::CompressionMethod::Stored);
+        zip.start_file("archive/data.warc.gz", stored).unwrap();
+        zip.write_all(&[7u8; 200_000]).unwrap();
+        zip.start_file("pages/pages.jsonl", options).unwrap();
+        zip.write_all(
+            b"{\"format\":\"json-pages-1.0\"}\n{\"url\":\"https://example.com/\",\"ts\":\"2024-05-01T12:00:00Z\"}\n",
+        )
+        .unwrap();
+        zip.start_file("datapackage.json", options).unwrap();
+        zip.write_all(br#"{"wacz_version":"1.1.1","software":"crawler","resources":[]}"#)
+            .unwrap();
+        zip.finish().unwrap().into_inner()
+    }
+
+    async fn mount_wacz_accession(server: &MockServer) {
+        Mock::given(method("GET"))
+            .and(path("/api/v1/accessions/5"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "accession": accession_with_metadata(),
+                "wacz_url": format!("{}/files/5.wacz", server.uri()),
+            })))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_mock_inspect_wacz_uses_range_requests() {
+        let server = MockServer::start().await;
+        mount_wacz_accession(&server).await;
+        let wacz = sample_wacz();
+        Mock::given(method("GET"))
+            .and(path("/files/5.wacz"))
+            .respond_with(RangeResponder(wacz.clone()))
+            .mount(&server)
+            .await;
+
+        let inspection = mock_client(&server).inspect_wacz(5).await.unwrap();
+        assert!(inspection.used_range_requests);
+        assert_eq!(inspection.total_bytes, wacz.len() as u64);
+        assert!(inspection.bytes_fetched < inspection.total_bytes);
+        assert_eq!(inspection.wacz_version.as_deref(), Some("1.1.1"));
+        assert_eq!(inspection.page_count, Some(1));
+        assert_eq!(
+            inspection.first_capture.as_deref(),
+            Some("2024-05-01T12:00:00Z")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mock_list_wacz_pages_uses_range_requests() {
+        let server = MockServer::start().await;
+        mount_wacz_accession(&server).await;
+        Mock::given(method("GET"))
+            .and(path("/files/5.wacz"))
+            .respond_with(RangeResponder(sample_wacz()))
+            .mount(&server)
+            .await;
+
+        let client = mock_client(&server);
+        let pages = client.list_wacz_pages(5, 0, 100).await.unwrap();
+        assert!(pages.used_range_requests);
+        assert_eq!(pages.page_count, 1);
+        assert_eq!(pages.num_pages, 1);
+        assert_eq!(
+            pages.items,
+            vec![WaczPage {
+                id: "1".to_string(),
+                url: "https://example.com/".to_string(),
+                title: None,
+                ts: Some("2024-05-01T12:00:00Z".to_string()),
+                seed: true,
+            }]
+        );
+        assert!(
+            client
+                .list_wacz_pages(5, 1, 100)
+                .await
+                .unwrap()
+                .items
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mock_inspect_wacz_falls_back_to_full_download() {
+        let server = MockServer::start().await;
+        mount_wacz_accession(&server).await;
+        let wacz = sample_wacz();
+        Mock::given(method("GET"))
+            .and(path("/files/5.wacz"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(wacz.clone()))
+            .mount(&server)
+            .await;
+
+        let inspection = mock_client(&server).inspect_wacz(5).await.unwrap();
+        assert!(!inspection.used_range_requests);
+        assert_eq!(inspection.bytes_fetched, wacz.len() as u64);
+        assert_eq!(inspection.software.as_deref(), Some("crawler"));
+        assert_eq!(inspection.page_count, Some(1));
     }
 }
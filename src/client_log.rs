@@ -0,0 +1,253 @@
+//! Forwarding of server log events to the MCP client.
+//!
+//! The server normally logs only to stderr, which remote clients never see.
+//! `ClientLog` implements the MCP `logging` capability: once the client sends
+//! `logging/setLevel`, `tracing` events from this crate at or above that
+//! level are sent to it as `notifications/message`. Nothing is forwarded
+//! until a level has been set.
+//!
+//! Notifications go through the rmcp peer, which serializes them with every
+//! other outgoing message, so they never interleave with protocol frames on
+//! stdout. Events from other crates (including rmcp itself) are not
+//! forwarded, which also keeps sending a notification from logging another.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use rmcp::model::{LoggingLevel, LoggingMessageNotificationParam};
+use rmcp::service::{Peer, RoleServer};
+use serde_json::{Map, Value};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Prefix of the tracing targets forwarded to the client.
+const TARGET_PREFIX: &str = env!("CARGO_CRATE_NAME");
+
+/// Shared handle to the client log level and notification queue.
+#[derive(Clone)]
+pub struct ClientLog {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    /// Minimum level requested by the client; `None` until `logging/setLevel`.
+    level: Mutex<Option<LoggingLevel>>,
+    /// Queue of notifications waiting to be sent, in event order.
+    sender: UnboundedSender<LoggingMessageNotificationParam>,
+    /// Receiving end of the queue, taken when a peer is attached.
+    receiver: Mutex<Option<UnboundedReceiver<LoggingMessageNotificationParam>>>,
+}
+
+impl Default for ClientLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClientLog {
+    /// Creates a handle with forwarding disabled.
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            inner: Arc::new(Inner {
+                level: Mutex::new(None),
+                sender,
+                receiver: Mutex::new(Some(receiver)),
+            }),
+        }
+    }
+
+    /// Returns a `tracing` layer that queues this crate's events for the
+    /// client.
+    ///
+    /// The target filter is attached to the layer only, so it neither hides
+    /// other crates' events from stderr nor enables their debug callsites.
+    pub fn layer<S>(&self) -> impl Layer<S>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        ClientLogLayer { log: self.clone() }.with_filter(filter_fn(|metadata| {
+            metadata.target().starts_with(TARGET_PREFIX)
+        }))
+    }
+
+    /// Sets the minimum level of forwarded events.
+    pub fn set_level(&self, level: LoggingLevel) {
+        *self.inner.level.lock().unwrap_or_else(|e| e.into_inner()) = Some(level);
+    }
+
+    /// Starts sending queued notifications to `peer`.
+    ///
+    /// Only the first call has an effect; later peers are ignored.
+    pub fn attach(&self, peer: Peer<RoleServer>) {
+        let Some(mut receiver) = self
+            .inner
+            .receiver
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+        else {
+            return;
+        };
+        tokio::spawn(async move {
+            while let Some(param) = receiver.recv().await {
+                // A failed send means the client is gone; logging it here
+                // would only queue another notification.
+                if peer.notify_logging_message(param).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Returns whether an event at `level` should be forwarded.
+    fn accepts(&self, level: LoggingLevel) -> bool {
+        match *self.inner.level.lock().unwrap_or_else(|e| e.into_inner()) {
+            Some(min) => severity(level) >= severity(min),
+            None => false,
+        }
+    }
+}
+
+/// `tracing` layer created by `ClientLog::layer`.
+struct ClientLogLayer {
+    log: ClientLog,
+}
+
+impl<S: Subscriber> Layer<S> for ClientLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let level = logging_level(*metadata.level());
+        if !self.log.accepts(level) {
+            return;
+        }
+        let mut fields = JsonFields(Map::new());
+        event.record(&mut fields);
+        let _ = self.log.inner.sender.send(LoggingMessageNotificationParam {
+            level,
+            logger: Some(metadata.target().to_string()),
+            data: Value::Object(fields.0),
+        });
+    }
+}
+
+/// Maps a `tracing` level onto the MCP (syslog) levels.
+fn logging_level(level: Level) -> LoggingLevel {
+    match level {
+        Level::ERROR => LoggingLevel::Error,
+        Level::WARN => LoggingLevel::Warning,
+        Level::INFO => LoggingLevel::Info,
+        Level::DEBUG | Level::TRACE => LoggingLevel::Debug,
+    }
+}
+
+/// Orders MCP levels from least to most severe.
+fn severity(level: LoggingLevel) -> u8 {
+    match level {
+        LoggingLevel::Debug => 0,
+        LoggingLevel::Info => 1,
+        LoggingLevel::Notice => 2,
+        LoggingLevel::Warning => 3,
+        LoggingLevel::Error => 4,
+        LoggingLevel::Critical => 5,
+        LoggingLevel::Alert => 6,
+        LoggingLevel::Emergency => 7,
+    }
+}
+
+/// Collects event fields into a JSON object.
+struct JsonFields(Map<String, Value>);
+
+impl Visit for JsonFields {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value).into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn drain(log: &ClientLog) -> Vec<LoggingMessageNotificationParam> {
+        let mut guard = log.inner.receiver.lock().unwrap();
+        let receiver = guard.as_mut().unwrap();
+        let mut items = vec![];
+        while let Ok(item) = receiver.try_recv() {
+            items.push(item);
+        }
+        items
+    }
+
+    #[test]
+    fn test_nothing_is_forwarded_before_set_level() {
+        let log = ClientLog::new();
+        let subscriber = tracing_subscriber::registry().with(log.layer());
+        tracing::subscriber::with_default(subscriber, || tracing::error!("boom"));
+        assert!(drain(&log).is_empty());
+    }
+
+    #[test]
+    fn test_events_below_level_are_dropped() {
+        let log = ClientLog::new();
+        log.set_level(LoggingLevel::Warning);
+        let subscriber = tracing_subscriber::registry().with(log.layer());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("ignored");
+            tracing::warn!(attempt = 2, "retrying request");
+        });
+
+        let items = drain(&log);
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].level, LoggingLevel::Warning);
+        assert_eq!(items[0].data["message"], "retrying request");
+        assert_eq!(items[0].data["attempt"], 2);
+        assert!(
+            items[0]
+                .logger
+                .as_deref()
+                .unwrap()
+                .starts_with(TARGET_PREFIX)
+        );
+    }
+
+    #[test]
+    fn test_other_targets_are_not_forwarded() {
+        let log = ClientLog::new();
+        log.set_level(LoggingLevel::Debug);
+        let subscriber = tracing_subscriber::registry().with(log.layer());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::error!(target: "rmcp::service", "transport closed");
+        });
+        assert!(drain(&log).is_empty());
+    }
+
+    #[test]
+    fn test_level_mapping_and_ordering() {
+        assert_eq!(logging_level(Level::TRACE), LoggingLevel::Debug);
+        assert_eq!(logging_level(Level::WARN), LoggingLevel::Warning);
+        assert!(severity(LoggingLevel::Error) > severity(LoggingLevel::Warning));
+        assert!(severity(LoggingLevel::Emergency) > severity(LoggingLevel::Critical));
+    }
+}
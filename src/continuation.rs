@@ -0,0 +1,142 @@
+//! Splitting oversized tool results into chunks fetched one call at a time.
+//!
+//! With `--max-result-bytes`, a tool result whose text is longer than the
+//! limit is cut into chunks of at most that many bytes, split on character
+//! boundaries. The first chunk is returned at once with a continuation token;
+//! the rest stay in a `TokenStore` for a while and are fetched with
+//! `continue_result`. A token names one chunk of one result, so fetching the
+//! same token again returns the same chunk, and a client can retry a lost
+//! response safely.
+
+use std::time::Duration;
+
+use crate::model::ResultContinuation;
+use crate::token_store::{self, TokenStore};
+
+/// How long the chunks of a truncated result are kept without being fetched.
+pub const CONTINUATION_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Chunks of one truncated result.
+struct Pending {
+    /// Every chunk, including the first one already returned.
+    chunks: Vec<String>,
+    /// Length in bytes of the whole result.
+    total_bytes: usize,
+}
+
+/// Truncated results awaiting `continue_result`, keyed by result ID.
+pub struct ContinuationStore {
+    /// Largest chunk in bytes.
+    max_bytes: usize,
+    /// Pending results by ID.
+    pending: TokenStore<Pending>,
+}
+
+impl ContinuationStore {
+    /// Creates a store cutting results longer than `max_bytes`.
+    pub fn new(max_bytes: usize, ttl: Duration) -> Self {
+        Self {
+            max_bytes: max_bytes.max(1),
+            pending: TokenStore::new(ttl),
+        }
+    }
+
+    /// Returns `text` unchanged if it fits, or else its first chunk and the
+    /// continuation describing the rest.
+    pub fn truncate(&self, text: String) -> (String, Option<ResultContinuation>) {
+        if text.len() <= self.max_bytes {
+            return (text, None);
+        }
+        let chunks = split_chunks(&text, self.max_bytes);
+        let first = chunks[0].clone();
+        let count = chunks.len();
+        let id = self.pending.insert(Pending {
+            chunks,
+            total_bytes: text.len(),
+        });
+        (first, Some(continuation(&id, 0, count, text.len())))
+    }
+
+    /// Returns the chunk named by `token` and the continuation to the next
+    /// one, or `None` if the token is unknown or has expired.
+    pub fn chunk(&self, token: &str) -> Option<(String, ResultContinuation)> {
+        self.pending.read(token, |id, index, result| {
+            let chunk = result.chunks.get(index)?.clone();
+            Some((
+                chunk,
+                continuation(id, index, result.chunks.len(), result.total_bytes),
+            ))
+        })
+    }
+}
+
+/// Describes chunk `index` of `chunks` of result `id`.
+fn continuation(id: &str, index: usize, chunks: usize, total_bytes: usize) -> ResultContinuation {
+    ResultContinuation {
+        chunk: index + 1,
+        chunks,
+        total_bytes,
+        continuation_token: (index + 1 < chunks).then(|| token_store::token(id, index + 1)),
+    }
+}
+
+/// Cuts `text` into pieces of at most `max_bytes` bytes, never splitting a
+/// character.
+fn split_chunks(text: &str, max_bytes: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let mut end = rest.floor_char_boundary(max_bytes.min(rest.len()));
+        if end == 0 {
+            // A single character wider than the limit still has to go somewhere.
+            end = rest.ceil_char_boundary(1);
+        }
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk.to_string());
+        rest = tail;
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_chunks_keeps_characters_whole() {
+        let text = "abالسودانcd";
+        let chunks = split_chunks(text, 3);
+        assert!(chunks.iter().all(|c| c.len() <= 3), "{:?}", chunks);
+        assert_eq!(chunks.concat(), text);
+    }
+
+    #[test]
+    fn test_truncated_result_is_fetched_chunk_by_chunk() {
+        let store = ContinuationStore::new(4, CONTINUATION_TTL);
+        assert_eq!(
+            store.truncate("fits".to_string()),
+            ("fits".to_string(), None)
+        );
+
+        let (first, continuation) = store.truncate("0123456789".to_string());
+        let continuation = continuation.unwrap();
+        assert_eq!(first, "0123");
+        assert_eq!((continuation.chunk, continuation.chunks), (1, 3));
+        let mut text = first;
+        let mut token = continuation.continuation_token;
+        while let Some(next) = token {
+            let (chunk, continuation) = store.chunk(&next).unwrap();
+            // Fetching a token twice returns the same chunk.
+            assert_eq!(store.chunk(&next).unwrap().0, chunk);
+            text.push_str(&chunk);
+            token = continuation.continuation_token;
+        }
+        assert_eq!(text, "0123456789");
+        assert!(store.chunk("unknown.1").is_none());
+
+        let expired = ContinuationStore::new(4, Duration::ZERO);
+        let (_, continuation) = expired.truncate("0123456789".to_string());
+        let token = continuation.unwrap().continuation_token.unwrap();
+        assert!(expired.chunk(&token).is_none());
+    }
+}
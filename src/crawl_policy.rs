@@ -0,0 +1,193 @@
+//! Which sites agents may ask the archive to crawl.
+//!
+//! Operators exposing the server to untrusted agents can restrict crawls
+//! with `--allow-domain` and `--deny-domain`. A rule is either an exact host
+//! (`example.com`) or a wildcard for its subdomains (`*.example.com`, which
+//! does not match `example.com` itself). Deny rules win over allow rules,
+//! and with no allow rules every host that is not denied may be crawled.
+
+use std::fmt;
+
+use reqwest::Url;
+
+/// A host, or all subdomains of a domain, named in a crawl rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DomainPattern {
+    /// Lowercase domain, without the `*.` prefix.
+    domain: String,
+    /// Whether the pattern matches subdomains of `domain` rather than `domain` itself.
+    subdomains: bool,
+}
+
+impl DomainPattern {
+    /// Parses `example.com` or `*.example.com`.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let lower = input.trim().trim_end_matches('.').to_lowercase();
+        let (domain, subdomains) = match lower.strip_prefix("*.") {
+            Some(domain) => (domain, true),
+            None => (lower.as_str(), false),
+        };
+        let valid = !domain.is_empty()
+            && domain.split('.').all(|label| {
+                !label.is_empty()
+                    && !label.starts_with('-')
+                    && !label.ends_with('-')
+                    && label
+                        .bytes()
+                        .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+            });
+        if !valid {
+            return Err(format!(
+                "invalid domain '{}': expected a host name such as example.com or *.example.com, without scheme, port or path",
+                input
+            ));
+        }
+        Ok(Self {
+            domain: domain.to_string(),
+            subdomains,
+        })
+    }
+
+    /// Returns whether `host` (lowercase) matches the pattern.
+    fn matches(&self, host: &str) -> bool {
+        if self.subdomains {
+            host.strip_suffix(&self.domain)
+                .is_some_and(|prefix| prefix.len() > 1 && prefix.ends_with('.'))
+        } else {
+            host == self.domain
+        }
+    }
+}
+
+impl fmt::Display for DomainPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.subdomains {
+            write!(f, "*.{}", self.domain)
+        } else {
+            f.write_str(&self.domain)
+        }
+    }
+}
+
+/// The allow and deny rules applied to crawl URLs.
+#[derive(Debug, Clone, Default)]
+pub struct CrawlPolicy {
+    allow: Vec<DomainPattern>,
+    deny: Vec<DomainPattern>,
+}
+
+impl CrawlPolicy {
+    /// Creates a policy from allow and deny rules; both empty allows everything.
+    pub fn new(allow: Vec<DomainPattern>, deny: Vec<DomainPattern>) -> Self {
+        Self { allow, deny }
+    }
+
+    /// Returns whether no rules are configured.
+    pub fn is_unrestricted(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+
+    /// Checks that `url` may be crawled, explaining which rule refused it if not.
+    pub fn check(&self, url: &str) -> Result<(), String> {
+        if self.is_unrestricted() {
+            return Ok(());
+        }
+        let host = Url::parse(url.trim())
+            .ok()
+            .and_then(|url| {
+                url.host_str()
+                    .map(|h| h.trim_end_matches('.').to_lowercase())
+            })
+            .ok_or_else(|| format!("'{}' has no host to check against the domain rules", url))?;
+        if let Some(rule) = self.deny.iter().find(|rule| rule.matches(&host)) {
+            return Err(format!(
+                "crawling {} is not permitted on this server (denied by --deny-domain {})",
+                host, rule
+            ));
+        }
+        if !self.allow.is_empty() && !self.allow.iter().any(|rule| rule.matches(&host)) {
+            let allowed: Vec<String> = self.allow.iter().map(ToString::to_string).collect();
+            return Err(format!(
+                "crawling {} is not permitted on this server; allowed domains: {}",
+                host,
+                allowed.join(", ")
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(patterns: &[&str]) -> Vec<DomainPattern> {
+        patterns
+            .iter()
+            .map(|p| DomainPattern::parse(p).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_domain_pattern() {
+        assert_eq!(
+            DomainPattern::parse(" *.Example.COM. ")
+                .unwrap()
+                .to_string(),
+            "*.example.com"
+        );
+        assert_eq!(
+            DomainPattern::parse("sudan.gov.sd").unwrap().to_string(),
+            "sudan.gov.sd"
+        );
+        for invalid in [
+            "",
+            "*",
+            "*.",
+            "https://example.com",
+            "example.com/path",
+            "example.com:80",
+            "a..b",
+            "-a.com",
+            "*.*.com",
+        ] {
+            assert!(
+                DomainPattern::parse(invalid).is_err(),
+                "{:?} should be rejected",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_wildcard_matches_subdomains_only() {
+        let policy = CrawlPolicy::new(rules(&["*.example.com"]), Vec::new());
+        assert!(policy.check("https://news.example.com/a").is_ok());
+        assert!(policy.check("https://a.b.example.com/").is_ok());
+        assert!(policy.check("https://example.com/").is_err());
+        assert!(policy.check("https://badexample.com/").is_err());
+    }
+
+    #[test]
+    fn test_deny_wins_over_allow() {
+        let policy = CrawlPolicy::new(
+            rules(&["example.com", "*.example.com"]),
+            rules(&["private.example.com"]),
+        );
+        assert!(policy.check("https://EXAMPLE.com/page").is_ok());
+        let err = policy.check("https://private.example.com/").unwrap_err();
+        assert!(err.contains("--deny-domain private.example.com"));
+        let err = policy.check("https://other.org/").unwrap_err();
+        assert!(err.contains("allowed domains: example.com, *.example.com"));
+    }
+
+    #[test]
+    fn test_deny_only_and_unrestricted_policies() {
+        let policy = CrawlPolicy::new(Vec::new(), rules(&["*.evil.test"]));
+        assert!(policy.check("https://example.com/").is_ok());
+        assert!(policy.check("http://x.evil.test/").is_err());
+        assert!(policy.check("not a url").is_err());
+
+        assert!(CrawlPolicy::default().check("not a url").is_ok());
+    }
+}
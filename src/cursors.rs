@@ -0,0 +1,101 @@
+//! Server-side cursors over accession listings.
+//!
+//! `open_accession_cursor` keeps the filters of a listing on the server and
+//! answers with its first page and a `cursor_id` for the second;
+//! `next_accession_page` takes that `cursor_id` and answers with the page and
+//! the `cursor_id` of the page after it. An agent thus never counts pages or
+//! repeats its filters. Because a `cursor_id` carries its page's position,
+//! sending one again after a lost reply gets that page once more instead of
+//! skipping to the next. Cursors live in a `TokenStore`; one is dropped after
+//! its last page, on `close_cursor`, or when unused for `CURSOR_TTL`. Pages
+//! are fetched when asked for, so accessions added or removed in between
+//! shift later pages just as they would with `page` numbers.
+
+use std::time::Duration;
+
+use crate::model::{ListAccessionsArgs, Verbosity};
+use crate::token_store::{self, TokenStore};
+
+/// How long a cursor is kept without being used.
+pub const CURSOR_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Filters and starting page of one listing.
+#[derive(Debug, Clone)]
+pub struct Cursor {
+    /// Filters sent with every page request; `page` is the first page.
+    pub args: ListAccessionsArgs,
+    /// Accession fields kept in each item, if limited.
+    pub fields: Vec<String>,
+    /// Detail level of each item.
+    pub verbosity: Verbosity,
+}
+
+/// Open cursors, named in tokens by the cursor's ID and a page offset.
+pub struct CursorStore {
+    /// Open cursors by ID.
+    cursors: TokenStore<Cursor>,
+}
+
+impl CursorStore {
+    /// Creates a store dropping cursors unused for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            cursors: TokenStore::new(ttl),
+        }
+    }
+
+    /// Stores `cursor` and returns the token of its first page.
+    pub fn open(&self, cursor: Cursor) -> String {
+        token_store::token(&self.cursors.insert(cursor), 0)
+    }
+
+    /// Returns the cursor named by `token`, positioned at the page the token
+    /// names, with the token of the page after it. Returns `None` if the
+    /// token is malformed, or the cursor is unknown or has expired.
+    pub fn page(&self, token: &str) -> Option<(Cursor, String)> {
+        self.cursors.read(token, |id, offset, cursor| {
+            let mut cursor = cursor.clone();
+            cursor.args.page = cursor.args.page.checked_add(i64::try_from(offset).ok()?)?;
+            Some((cursor, token_store::token(id, offset + 1)))
+        })
+    }
+
+    /// Drops the cursor named by `token`, returning whether it was open.
+    pub fn close(&self, token: &str) -> bool {
+        self.cursors.remove(token)
+    }
+
+    /// Whether no cursor is open.
+    #[cfg(test)]
+    pub fn is_empty(&self) -> bool {
+        self.cursors.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_tokens_name_one_page_each() {
+        let store = CursorStore::new(CURSOR_TTL);
+        let first = store.open(Cursor {
+            args: ListAccessionsArgs {
+                page: 2,
+                query_term: "Khartoum".to_string(),
+                ..Default::default()
+            },
+            fields: Vec::new(),
+            verbosity: Verbosity::Summary,
+        });
+        let (cursor, second) = store.page(&first).unwrap();
+        assert_eq!(cursor.args.page, 2);
+        let (cursor, third) = store.page(&second).unwrap();
+        assert_eq!(cursor.args.page, 3);
+        assert_eq!(cursor.args.query_term, "Khartoum");
+        assert_eq!(store.page(&second).unwrap().1, third);
+
+        assert!(store.close(&third));
+        assert!(store.page(&second).is_none());
+    }
+}
@@ -0,0 +1,352 @@
+//! Parsing of the `date_from` / `date_to` list filters.
+//!
+//! Agents often write dates loosely ("2024", "last month"), which the API
+//! rejects with a 422. `normalize_filter_date` turns the accepted forms into
+//! the `YYYY-MM-DD` dates the API expects. Inputs naming a whole period
+//! resolve to its first day for `date_from` and its last day for `date_to`,
+//! so `date_from = date_to = "2024-05"` covers all of May.
+//!
+//! `bucket_start` and `next_bucket` step through the day, week or month
+//! buckets of `accessions_timeline`.
+//!
+//! `parse_lookback` reads the shorter `since` durations ("24h", "7d") used by
+//! `recent_accessions`.
+
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, Utc, Weekday};
+
+use crate::model::TimelineBucket;
+
+/// Format of the dates sent to the API.
+const API_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Human-readable list of accepted inputs, used in error messages.
+const ACCEPTED_FORMS: &str = "YYYY-MM-DD, YYYY-MM-DDTHH:MM:SS, YYYY-MM, YYYY, \
+     today, yesterday, this week/month/year, last week/month/year, \
+     or N days/weeks/months/years ago";
+
+/// Which end of a date range a filter value is for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bound {
+    /// `date_from`: periods resolve to their first day.
+    Start,
+    /// `date_to`: periods resolve to their last day.
+    End,
+}
+
+/// Normalizes a `date_from` / `date_to` value relative to today's UTC date.
+///
+/// Empty input stays empty (no filter).
+pub fn normalize_filter_date(field: &str, input: &str, bound: Bound) -> Result<String, String> {
+    normalize_date(field, input, bound, Utc::now().date_naive())
+}
+
+/// Normalizes a filter value relative to `today`.
+fn normalize_date(
+    field: &str,
+    input: &str,
+    bound: Bound,
+    today: NaiveDate,
+) -> Result<String, String> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Ok(String::new());
+    }
+    let (first, last) = parse_period(&trimmed.to_lowercase(), today).ok_or_else(|| {
+        format!(
+            "{} '{}' is not a recognised date; use {}",
+            field, input, ACCEPTED_FORMS
+        )
+    })?;
+    let date = match bound {
+        Bound::Start => first,
+        Bound::End => last,
+    };
+    Ok(date.format(API_DATE_FORMAT).to_string())
+}
+
+/// Resolves `input` (lowercase, trimmed) to the first and last day of the
+/// period it names.
+fn parse_period(input: &str, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    if let Ok(date) = NaiveDate::parse_from_str(input, API_DATE_FORMAT) {
+        return Some((date, date));
+    }
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(input, "%Y-%m-%dt%H:%M:%S") {
+        return Some((datetime.date(), datetime.date()));
+    }
+    if let Some((year, month)) = input.split_once('-') {
+        let first = NaiveDate::from_ymd_opt(parse_year(year)?, month.parse().ok()?, 1)?;
+        return Some((first, end_of_month(first)?));
+    }
+    if let Some(year) = parse_year(input) {
+        return Some((
+            NaiveDate::from_ymd_opt(year, 1, 1)?,
+            NaiveDate::from_ymd_opt(year, 12, 31)?,
+        ));
+    }
+    match input {
+        "today" => return Some((today, today)),
+        "yesterday" => {
+            let day = today.pred_opt()?;
+            return Some((day, day));
+        }
+        _ => {}
+    }
+    if let Some(unit) = input.strip_prefix("this ") {
+        return period_containing(unit, today);
+    }
+    if let Some(unit) = input.strip_prefix("last ") {
+        let earlier = shift_back(today, 1, unit)?;
+        return period_containing(unit, earlier);
+    }
+    if let Some(rest) = input.strip_suffix(" ago") {
+        let (count, unit) = rest.split_once(' ')?;
+        let day = shift_back(today, count.parse().ok()?, unit)?;
+        return Some((day, day));
+    }
+    None
+}
+
+/// Parses a four-digit year.
+fn parse_year(input: &str) -> Option<i32> {
+    if input.len() == 4 && input.bytes().all(|b| b.is_ascii_digit()) {
+        input.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Returns the last day of the month containing `date`.
+fn end_of_month(date: NaiveDate) -> Option<NaiveDate> {
+    let first = date.with_day(1)?;
+    first.checked_add_months(Months::new(1))?.pred_opt()
+}
+
+/// Returns the first and last day of the week (Monday to Sunday), month or
+/// year containing `date`.
+fn period_containing(unit: &str, date: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    match unit {
+        "week" => {
+            let monday = date - Duration::days(date.weekday().num_days_from_monday().into());
+            Some((monday, monday + Duration::days(6)))
+        }
+        "month" => Some((date.with_day(1)?, end_of_month(date)?)),
+        "year" => Some((
+            NaiveDate::from_ymd_opt(date.year(), 1, 1)?,
+            NaiveDate::from_ymd_opt(date.year(), 12, 31)?,
+        )),
+        _ => None,
+    }
+}
+
+/// Moves `date` back by `count` days, weeks, months or years. Singular and
+/// plural unit names are both accepted.
+fn shift_back(date: NaiveDate, count: u32, unit: &str) -> Option<NaiveDate> {
+    match unit.strip_suffix('s').unwrap_or(unit) {
+        "day" => date.checked_sub_signed(Duration::days(count.into())),
+        "week" => date.checked_sub_signed(Duration::weeks(count.into())),
+        "month" => date.checked_sub_months(Months::new(count)),
+        "year" => date.checked_sub_months(Months::new(count.checked_mul(12)?)),
+        _ => None,
+    }
+}
+
+/// Parses a look-back duration such as "24h", "7d", "2w" or "3 days".
+///
+/// Hours, days and weeks are accepted, as single letters or (singular or
+/// plural) words; the count must be a positive whole number.
+pub fn parse_lookback(input: &str) -> Result<Duration, String> {
+    let trimmed = input.trim().to_lowercase();
+    let split = trimmed
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(trimmed.len());
+    let (count, unit) = trimmed.split_at(split);
+    let duration = count
+        .parse::<i64>()
+        .ok()
+        .filter(|&count| count > 0)
+        .and_then(
+            |count| match unit.trim().strip_suffix('s').unwrap_or(unit.trim()) {
+                "h" | "hour" => Duration::try_hours(count),
+                "d" | "day" => Duration::try_days(count),
+                "w" | "week" => Duration::try_weeks(count),
+                _ => None,
+            },
+        );
+    duration.ok_or_else(|| {
+        format!(
+            "'{}' is not a recognised duration; use a number followed by h, d or w, e.g. \"24h\", \"7d\" or \"2w\"",
+            input
+        )
+    })
+}
+
+/// Returns the first day of the bucket containing `date`, or an error if
+/// it falls outside the dates chrono can represent.
+pub fn bucket_start(date: NaiveDate, bucket: TimelineBucket) -> Result<NaiveDate, String> {
+    let start = match bucket {
+        TimelineBucket::Day => Some(date),
+        TimelineBucket::Week => date.week(Weekday::Mon).checked_first_day(),
+        TimelineBucket::Month => NaiveDate::from_ymd_opt(date.year(), date.month(), 1),
+    };
+    start.ok_or_else(|| format!("No {:?} bucket contains {}", bucket, date))
+}
+
+/// Returns the first day of the bucket after the one starting on `start`,
+/// or an error past the last date chrono can represent.
+pub fn next_bucket(start: NaiveDate, bucket: TimelineBucket) -> Result<NaiveDate, String> {
+    let next = match bucket {
+        TimelineBucket::Day => start.checked_add_signed(Duration::days(1)),
+        TimelineBucket::Week => start.checked_add_signed(Duration::weeks(1)),
+        TimelineBucket::Month => start.checked_add_months(Months::new(1)),
+    };
+    next.ok_or_else(|| format!("No {:?} bucket follows {}", bucket, start))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A Thursday.
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, 14).unwrap()
+    }
+
+    fn start(input: &str) -> Result<String, String> {
+        normalize_date("date_from", input, Bound::Start, today())
+    }
+
+    fn end(input: &str) -> Result<String, String> {
+        normalize_date("date_to", input, Bound::End, today())
+    }
+
+    #[test]
+    fn test_empty_input_means_no_filter() {
+        assert_eq!(start("").unwrap(), "");
+        assert_eq!(end("   ").unwrap(), "");
+    }
+
+    #[test]
+    fn test_iso_dates_pass_through() {
+        assert_eq!(start("2024-05-01").unwrap(), "2024-05-01");
+        assert_eq!(end(" 2024-05-01 ").unwrap(), "2024-05-01");
+        assert_eq!(start("2024-05-01T12:30:00").unwrap(), "2024-05-01");
+    }
+
+    #[test]
+    fn test_year_and_month_cover_the_whole_period() {
+        assert_eq!(start("2024").unwrap(), "2024-01-01");
+        assert_eq!(end("2024").unwrap(), "2024-12-31");
+        assert_eq!(start("2024-02").unwrap(), "2024-02-01");
+        assert_eq!(end("2024-02").unwrap(), "2024-02-29");
+        assert_eq!(end("2023-02").unwrap(), "2023-02-28");
+        assert_eq!(end("2023-12").unwrap(), "2023-12-31");
+    }
+
+    #[test]
+    fn test_today_and_yesterday() {
+        assert_eq!(start("today").unwrap(), "2024-03-14");
+        assert_eq!(end("Today").unwrap(), "2024-03-14");
+        assert_eq!(start("yesterday").unwrap(), "2024-03-13");
+    }
+
+    #[test]
+    fn test_relative_offsets() {
+        assert_eq!(start("7 days ago").unwrap(), "2024-03-07");
+        assert_eq!(start("1 day ago").unwrap(), "2024-03-13");
+        assert_eq!(start("2 weeks ago").unwrap(), "2024-02-29");
+        assert_eq!(start("1 month ago").unwrap(), "2024-02-14");
+        assert_eq!(start("3 years ago").unwrap(), "2021-03-14");
+    }
+
+    #[test]
+    fn test_this_and_last_periods() {
+        assert_eq!(start("this week").unwrap(), "2024-03-11");
+        assert_eq!(end("this week").unwrap(), "2024-03-17");
+        assert_eq!(start("last week").unwrap(), "2024-03-04");
+        assert_eq!(end("last week").unwrap(), "2024-03-10");
+        assert_eq!(start("this month").unwrap(), "2024-03-01");
+        assert_eq!(start("last month").unwrap(), "2024-02-01");
+        assert_eq!(end("last month").unwrap(), "2024-02-29");
+        assert_eq!(start("last year").unwrap(), "2023-01-01");
+        assert_eq!(end("this year").unwrap(), "2024-12-31");
+    }
+
+    #[test]
+    fn test_unparseable_inputs_are_rejected() {
+        for input in [
+            "next week",
+            "soon",
+            "24",
+            "2024-13",
+            "2024-02-30",
+            "05/01/2024",
+            "ten days ago",
+            "7 fortnights ago",
+            "last decade",
+        ] {
+            assert!(start(input).is_err(), "{} should be rejected", input);
+        }
+    }
+
+    #[test]
+    fn test_error_lists_accepted_forms() {
+        let err = start("whenever").unwrap_err();
+        assert!(err.starts_with("date_from 'whenever' is not a recognised date"));
+        assert!(err.contains("YYYY-MM-DD"));
+        assert!(err.contains("N days/weeks/months/years ago"));
+    }
+
+    #[test]
+    fn test_buckets() {
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        assert_eq!(bucket_start(today(), TimelineBucket::Day).unwrap(), today());
+        assert_eq!(
+            bucket_start(today(), TimelineBucket::Week).unwrap(),
+            date("2024-03-11")
+        );
+        assert_eq!(
+            bucket_start(today(), TimelineBucket::Month).unwrap(),
+            date("2024-03-01")
+        );
+        assert_eq!(
+            next_bucket(date("2024-01-31"), TimelineBucket::Day).unwrap(),
+            date("2024-02-01")
+        );
+        assert_eq!(
+            next_bucket(date("2024-12-30"), TimelineBucket::Week).unwrap(),
+            date("2025-01-06")
+        );
+        assert_eq!(
+            next_bucket(date("2024-12-01"), TimelineBucket::Month).unwrap(),
+            date("2025-01-01")
+        );
+        assert!(next_bucket(NaiveDate::MAX, TimelineBucket::Month).is_err());
+        assert!(bucket_start(NaiveDate::MIN, TimelineBucket::Week).is_err());
+    }
+
+    #[test]
+    fn test_parse_lookback() {
+        assert_eq!(parse_lookback("24h"), Ok(Duration::hours(24)));
+        assert_eq!(parse_lookback(" 7D "), Ok(Duration::days(7)));
+        assert_eq!(parse_lookback("2w"), Ok(Duration::weeks(2)));
+        assert_eq!(parse_lookback("3 days"), Ok(Duration::days(3)));
+        assert_eq!(parse_lookback("1 hour"), Ok(Duration::hours(1)));
+
+        for invalid in [
+            "",
+            "7",
+            "d",
+            "0d",
+            "-1d",
+            "1.5d",
+            "7 months",
+            "99999999999999w",
+        ] {
+            assert!(
+                parse_lookback(invalid).is_err(),
+                "{:?} should be rejected",
+                invalid
+            );
+        }
+    }
+}
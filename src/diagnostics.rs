@@ -0,0 +1,252 @@
+//! Explanations of crawl outcomes.
+//!
+//! The SDA API reports only an accession's `crawl_status`, not the crawler's
+//! logs or an error reason. `diagnose` turns the status into guidance, and
+//! for failed crawls adds what a fresh check of the seed URL with
+//! `preview::preview_crawl_target` reveals, such as a page that is gone, a
+//! site blocking crawlers or one that needs a browser profile.
+
+use chrono::{Duration, NaiveDateTime};
+use reqwest::Url;
+
+use crate::model::{
+    AccessionsWithMetadataResponse, BrowserProfile, CrawlDiagnostics, CrawlStatus,
+    CrawlTargetPreview,
+};
+use crate::preview;
+
+/// How long a crawl can stay pending before it is reported as possibly stuck.
+const STALE_PENDING: Duration = Duration::hours(6);
+
+/// Explains `accession`'s crawl status at time `now`.
+///
+/// `crawled_at` is the parsed `crawl_timestamp`, used to spot crawls stuck
+/// in Pending. `target` is a fresh check of the seed URL, if one was made;
+/// it is only used for failed crawls.
+pub fn diagnose(
+    accession: &AccessionsWithMetadataResponse,
+    crawled_at: Option<NaiveDateTime>,
+    now: NaiveDateTime,
+    target: Option<CrawlTargetPreview>,
+) -> CrawlDiagnostics {
+    let failed = matches!(
+        accession.crawl_status,
+        CrawlStatus::Error | CrawlStatus::BadCrawl
+    );
+    let mut suggestions = Vec::new();
+    if failed {
+        if let Some(target) = &target {
+            suggestions.extend(target_suggestions(target));
+        }
+        let profile = target
+            .as_ref()
+            .and_then(|t| t.suggested_browser_profile.clone())
+            .or_else(|| {
+                Url::parse(&accession.seed_url)
+                    .ok()
+                    .and_then(|url| preview::suggest_browser_profile(&url))
+            });
+        if let Some(BrowserProfile::Facebook) = profile {
+            suggestions.push(
+                "This is a Facebook page, which needs the facebook browser profile: recrawl it with recrawl_accession and browser_profile set to facebook.".to_string(),
+            );
+        }
+    }
+
+    let explanation = match accession.crawl_status {
+        CrawlStatus::Complete => "The crawl finished successfully.",
+        CrawlStatus::Pending => "The crawl is queued or still running.",
+        CrawlStatus::Error => {
+            suggestions.push(
+                "Recrawl with recrawl_accession once the cause is fixed; crawls that failed on a network error or timeout often succeed on a second try.".to_string(),
+            );
+            "The crawler could not complete the crawl, e.g. because the site was unreachable, timed out or refused the crawler, or the crawler itself failed."
+        }
+        CrawlStatus::BadCrawl => {
+            suggestions.push(
+                "Check the page in a browser: if it needs a login, shows a cookie wall or bot check, or only renders with heavy JavaScript, it may not be archivable without a browser profile. Only a facebook profile is available.".to_string(),
+            );
+            "The crawl ran, but the capture was judged unusable, often because the page needs a login or a browser profile, showed a cookie wall or bot check, or did not render."
+        }
+    };
+    if matches!(accession.crawl_status, CrawlStatus::Pending)
+        && let Some(crawled_at) = crawled_at
+        && now - crawled_at > STALE_PENDING
+    {
+        suggestions.push(format!(
+            "The crawl has been pending for {} hours, much longer than crawls normally take, so it may be stuck. Ask an operator to check the crawler job (crawl_id and job_run_id), or recrawl it with recrawl_accession.",
+            (now - crawled_at).num_hours()
+        ));
+    }
+
+    CrawlDiagnostics {
+        accession_id: accession.id,
+        seed_url: accession.seed_url.clone(),
+        crawl_status: accession.crawl_status.clone(),
+        crawl_timestamp: accession.crawl_timestamp.clone(),
+        crawl_id: accession.crawl_id.clone(),
+        job_run_id: accession.job_run_id.clone(),
+        failed,
+        explanation: explanation.to_string(),
+        suggestions,
+        target: if failed { target } else { None },
+    }
+}
+
+/// Likely causes of a failed crawl visible in a fresh check of its seed URL.
+fn target_suggestions(target: &CrawlTargetPreview) -> Vec<String> {
+    let mut suggestions = Vec::new();
+    if let Some(error) = &target.error {
+        suggestions.push(format!(
+            "The site could not be reached from this server either ({}); it may be down, or the URL may be wrong. Try again later.",
+            error
+        ));
+    }
+    if target.redirect_limit_reached {
+        suggestions.push(
+            "The URL redirects in a loop or through too many hops, which can stop the crawler."
+                .to_string(),
+        );
+    } else if target.redirected && target.final_url != target.url {
+        suggestions.push(format!(
+            "The URL redirects to {}; crawling that URL directly may work better.",
+            target.final_url
+        ));
+    }
+    match target.status {
+        Some(404 | 410) => suggestions.push(format!(
+            "The page now returns HTTP {}, so it has probably been removed or moved; check the URL.",
+            target.status.unwrap_or_default()
+        )),
+        Some(401 | 403) => suggestions.push(format!(
+            "The page returns HTTP {}: it needs a login or blocks automated requests.",
+            target.status.unwrap_or_default()
+        )),
+        Some(429) => suggestions.push(
+            "The site is rate limiting requests (HTTP 429); wait before recrawling.".to_string(),
+        ),
+        Some(status) if status >= 500 => suggestions.push(format!(
+            "The site returns a server error (HTTP {}); recrawl once it recovers.",
+            status
+        )),
+        _ => {}
+    }
+    if let Some(rule) = target
+        .robots_txt
+        .as_ref()
+        .filter(|robots| robots.allowed == Some(false))
+        .map(|robots| robots.matched_rule.as_deref().unwrap_or("Disallow"))
+    {
+        suggestions.push(format!(
+            "The site's robots.txt disallows this URL for all crawlers ({}), which the crawler may have honoured.",
+            rule
+        ));
+    }
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::RobotsTxtCheck;
+
+    fn accession(status: CrawlStatus, seed_url: &str) -> AccessionsWithMetadataResponse {
+        AccessionsWithMetadataResponse {
+            crawl_status: status,
+            crawl_id: Some("c1".to_string()),
+            job_run_id: Some("j1".to_string()),
+            seed_url: seed_url.to_string(),
+            title_en: None,
+            has_english_metadata: false,
+            ..AccessionsWithMetadataResponse::sample(5)
+        }
+    }
+
+    fn at(timestamp: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%dT%H:%M:%S").unwrap()
+    }
+
+    fn target(status: Option<u16>) -> CrawlTargetPreview {
+        CrawlTargetPreview {
+            url: "https://example.com/a".to_string(),
+            final_url: "https://example.com/a".to_string(),
+            status,
+            reachable: status.is_some_and(|s| (200..300).contains(&s)),
+            content_type: None,
+            redirected: false,
+            redirects: Vec::new(),
+            redirect_limit_reached: false,
+            error: None,
+            robots_txt: None,
+            suggested_browser_profile: None,
+        }
+    }
+
+    #[test]
+    fn test_complete_crawl_has_no_suggestions() {
+        let diagnostics = diagnose(
+            &accession(CrawlStatus::Complete, "https://example.com"),
+            Some(at("2024-05-01T12:00:00")),
+            at("2024-05-02T12:00:00"),
+            Some(target(Some(404))),
+        );
+        assert!(!diagnostics.failed);
+        assert!(diagnostics.suggestions.is_empty());
+        assert!(diagnostics.target.is_none());
+        assert_eq!(diagnostics.crawl_id.as_deref(), Some("c1"));
+    }
+
+    #[test]
+    fn test_bad_crawl_of_facebook_page_suggests_profile() {
+        let diagnostics = diagnose(
+            &accession(CrawlStatus::BadCrawl, "https://www.facebook.com/page"),
+            None,
+            at("2024-05-02T12:00:00"),
+            None,
+        );
+        assert!(diagnostics.failed);
+        assert!(diagnostics.suggestions[0].contains("browser_profile set to facebook"));
+    }
+
+    #[test]
+    fn test_failed_crawl_reports_target_problems_first() {
+        let mut gone = target(Some(404));
+        gone.robots_txt = Some(RobotsTxtCheck {
+            url: "https://example.com/robots.txt".to_string(),
+            found: true,
+            allowed: Some(false),
+            matched_rule: Some("Disallow: /".to_string()),
+        });
+        let diagnostics = diagnose(
+            &accession(CrawlStatus::Error, "https://example.com/a"),
+            None,
+            at("2024-05-02T12:00:00"),
+            Some(gone),
+        );
+        assert_eq!(diagnostics.suggestions.len(), 3);
+        assert!(diagnostics.suggestions[0].contains("HTTP 404"));
+        assert!(diagnostics.suggestions[1].contains("Disallow: /"));
+        assert!(diagnostics.suggestions[2].contains("recrawl_accession"));
+        assert!(diagnostics.target.is_some());
+    }
+
+    #[test]
+    fn test_long_pending_crawl_may_be_stuck() {
+        let pending = accession(CrawlStatus::Pending, "https://example.com");
+        let fresh = diagnose(
+            &pending,
+            Some(at("2024-05-01T12:00:00")),
+            at("2024-05-01T13:00:00"),
+            None,
+        );
+        assert!(fresh.suggestions.is_empty());
+
+        let stale = diagnose(
+            &pending,
+            Some(at("2024-05-01T12:00:00")),
+            at("2024-05-02T12:00:00"),
+            None,
+        );
+        assert!(stale.suggestions[0].contains("pending for 24 hours"));
+    }
+}
@@ -0,0 +1,160 @@
+//! Field-by-field comparison of accession metadata.
+//!
+//! Accessions are compared as `serde_json::Value`s rather than field by
+//! field, so fields added to the API model are picked up without changes
+//! here.
+
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+
+use crate::model::{AccessionDiffResponse, AccessionsWithMetadataResponse, FieldChange};
+
+/// Fields whose added and removed items are reported as subject changes.
+const SUBJECT_FIELDS: [&str; 2] = ["subjects_en", "subjects_ar"];
+
+/// Compares two accessions, ignoring their IDs.
+pub fn accession_diff(
+    old: &AccessionsWithMetadataResponse,
+    new: &AccessionsWithMetadataResponse,
+) -> AccessionDiffResponse {
+    let mut old_value = serde_json::to_value(old).unwrap_or_default();
+    let mut new_value = serde_json::to_value(new).unwrap_or_default();
+    for value in [&mut old_value, &mut new_value] {
+        if let Value::Object(fields) = value {
+            fields.remove("id");
+        }
+    }
+    let changes = diff_values(&old_value, &new_value);
+
+    let subject_items = |items: &[Value]| -> Vec<String> {
+        items
+            .iter()
+            .filter_map(|item| item.as_str().map(str::to_string))
+            .collect()
+    };
+    let subject_changes = changes
+        .iter()
+        .filter(|change| SUBJECT_FIELDS.contains(&change.field.as_str()));
+    AccessionDiffResponse {
+        old_id: old.id,
+        new_id: new.id,
+        same_seed_url: old.seed_url == new.seed_url,
+        identical: changes.is_empty(),
+        subjects_added: subject_changes
+            .clone()
+            .flat_map(|change| subject_items(&change.added))
+            .collect(),
+        subjects_removed: subject_changes
+            .flat_map(|change| subject_items(&change.removed))
+            .collect(),
+        changes,
+    }
+}
+
+/// Lists the fields that differ between two JSON values.
+///
+/// Objects are compared key by key, with nested keys joined by dots; a key
+/// missing on one side counts as `null`. Differing lists are reported whole,
+/// together with the items added and removed.
+pub fn diff_values(old: &Value, new: &Value) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    diff_into("", old, new, &mut changes);
+    changes
+}
+
+fn diff_into(field: &str, old: &Value, new: &Value, changes: &mut Vec<FieldChange>) {
+    if old == new {
+        return;
+    }
+    if let (Value::Object(old_fields), Value::Object(new_fields)) = (old, new) {
+        let keys: BTreeSet<&String> = old_fields.keys().chain(new_fields.keys()).collect();
+        for key in keys {
+            let nested = if field.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", field, key)
+            };
+            diff_into(
+                &nested,
+                old_fields.get(key).unwrap_or(&Value::Null),
+                new_fields.get(key).unwrap_or(&Value::Null),
+                changes,
+            );
+        }
+        return;
+    }
+
+    let items = |value: &Value| value.as_array().cloned().unwrap_or_default();
+    let (old_items, new_items) = (items(old), items(new));
+    changes.push(FieldChange {
+        field: field.to_string(),
+        old: old.clone(),
+        new: new.clone(),
+        added: new_items
+            .iter()
+            .filter(|item| !old_items.contains(item))
+            .cloned()
+            .collect(),
+        removed: old_items
+            .iter()
+            .filter(|item| !new_items.contains(item))
+            .cloned()
+            .collect(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_values_reports_changed_missing_and_nested_fields() {
+        let old = json!({
+            "title_en": "Old",
+            "is_private": false,
+            "location_en": "Khartoum",
+            "relations_en": { "url": "a", "kind": "same" },
+        });
+        let new = json!({
+            "title_en": "New",
+            "is_private": false,
+            "relations_en": { "url": "b", "kind": "same" },
+            "creator_en": "SDA",
+        });
+
+        let changes = diff_values(&old, &new);
+        let fields: Vec<&str> = changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            ["creator_en", "location_en", "relations_en.url", "title_en"]
+        );
+        assert_eq!(changes[0].old, Value::Null);
+        assert_eq!(changes[1].new, Value::Null);
+        assert_eq!(changes[3].old, json!("Old"));
+        assert_eq!(changes[3].new, json!("New"));
+    }
+
+    #[test]
+    fn test_diff_values_lists_added_and_removed_items() {
+        let changes = diff_values(
+            &json!({ "subjects_en": ["Protest", "Khartoum"] }),
+            &json!({ "subjects_en": ["Khartoum", "Darfur"] }),
+        );
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].added, [json!("Darfur")]);
+        assert_eq!(changes[0].removed, [json!("Protest")]);
+
+        // A list appearing where there was none counts every item as added.
+        let changes = diff_values(&json!({}), &json!({ "subjects_ar": ["احتجاج"] }));
+        assert_eq!(changes[0].added, [json!("احتجاج")]);
+        assert!(changes[0].removed.is_empty());
+    }
+
+    #[test]
+    fn test_diff_values_of_equal_values_is_empty() {
+        let value = json!({ "title_en": "Same", "subjects_en": ["A"] });
+        assert!(diff_values(&value, &value).is_empty());
+    }
+}
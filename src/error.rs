@@ -0,0 +1,203 @@
+//! Error types for the Sudan Digital Archive API client.
+//!
+//! `SdaError` keeps the distinction between transport failures, HTTP status
+//! errors, decoding problems and client-side validation so that the MCP layer
+//! can map each of them to the appropriate MCP error code.
+
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use thiserror::Error;
+
+use crate::model::ValidationProblem;
+
+/// Errors returned by `SdaClient`.
+#[derive(Debug, Error)]
+pub enum SdaError {
+    /// The API responded with a non-success HTTP status.
+    #[error("{context}: HTTP {status}{}", body_suffix(.body))]
+    Http {
+        /// Description of the operation that failed.
+        context: String,
+        /// HTTP status returned by the API.
+        status: StatusCode,
+        /// Response body, usually containing validation details.
+        body: String,
+    },
+    /// The request could not be sent or the response could not be read.
+    #[error("{context}: {source}")]
+    Network {
+        /// Description of the operation that failed.
+        context: String,
+        /// Underlying transport error.
+        #[source]
+        source: reqwest::Error,
+    },
+    /// The response body could not be decoded into the expected model.
+    #[error("{context}: {source}")]
+    Decode {
+        /// Description of the operation that failed.
+        context: String,
+        /// Underlying deserialization error.
+        #[source]
+        source: serde_json::Error,
+    },
+    /// The API reported success but sent no body where one was expected.
+    #[error("{context}: the API returned an empty response body")]
+    EmptyResponse {
+        /// Description of the operation that failed.
+        context: String,
+    },
+    /// A successful response that should be JSON declared another content
+    /// type, as when a proxy or captive portal answers with an HTML page.
+    #[error(
+        "{context}: expected a JSON response but got Content-Type {content_type}; a proxy, captive portal or wrong --base-url may be answering instead of the SDA API. Body starts: {snippet}"
+    )]
+    UnexpectedContentType {
+        /// Description of the operation that failed.
+        context: String,
+        /// The `Content-Type` the response declared.
+        content_type: String,
+        /// The start of the response body.
+        snippet: String,
+    },
+    /// The response body was larger than the configured limit.
+    #[error("{context}: response body exceeds the {limit} byte limit")]
+    ResponseTooLarge {
+        /// Description of the operation that failed.
+        context: String,
+        /// The configured limit, in bytes.
+        limit: u64,
+    },
+    /// The resource changed since the caller read it, so a write was refused.
+    #[error("{0}")]
+    Conflict(String),
+    /// The request was rejected locally before being sent to the API.
+    #[error("{0}")]
+    Validation(String),
+    /// The request was rejected locally, with every problem found.
+    #[error("{context}: {}", problem_list(.problems))]
+    InvalidFields {
+        /// Description of the operation that failed.
+        context: String,
+        /// Each invalid field and what is wrong with it.
+        problems: Vec<ValidationProblem>,
+    },
+    /// The request waited longer than its timeout for the client-side rate limit.
+    #[error(
+        "{context}: gave up after waiting {} ms for the client rate limit (--rate-limit-rps)",
+        .waited.as_millis()
+    )]
+    RateLimited {
+        /// Description of the operation that failed.
+        context: String,
+        /// How long the request waited.
+        waited: Duration,
+    },
+    /// A local file could not be written.
+    #[error("{context}: {source}")]
+    Io {
+        /// Description of the operation that failed.
+        context: String,
+        /// Underlying I/O error.
+        #[source]
+        source: std::io::Error,
+    },
+    /// Downloaded content did not match what the server advertised.
+    #[error("{0}")]
+    Integrity(String),
+    /// A downloaded WACZ or WARC file could not be read.
+    #[error("{context}: {message}")]
+    Archive {
+        /// Description of the operation that failed.
+        context: String,
+        /// What was wrong with the archive.
+        message: String,
+    },
+    /// A custom middleware layer rejected the request.
+    #[error("{context}: {message}")]
+    Middleware {
+        /// Description of the operation that failed.
+        context: String,
+        /// The layer's error.
+        message: String,
+    },
+}
+
+/// Result type returned by `SdaClient` methods.
+pub type Result<T, E = SdaError> = std::result::Result<T, E>;
+
+impl SdaError {
+    /// Creates a `Network` error with the given context.
+    pub fn network(context: impl Into<String>, source: reqwest::Error) -> Self {
+        Self::Network {
+            context: context.into(),
+            source,
+        }
+    }
+
+    /// Creates an `Io` error with the given context.
+    pub fn io(context: impl Into<String>, source: std::io::Error) -> Self {
+        Self::Io {
+            context: context.into(),
+            source,
+        }
+    }
+
+    /// Creates a `Decode` error with the given context.
+    pub fn decode(context: impl Into<String>, source: serde_json::Error) -> Self {
+        Self::Decode {
+            context: context.into(),
+            source,
+        }
+    }
+}
+
+/// Formats validation problems as `field: message` pairs.
+fn problem_list(problems: &[ValidationProblem]) -> String {
+    problems
+        .iter()
+        .map(|p| format!("{}: {}", p.field, p.message))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Formats the optional body suffix of an HTTP error message.
+fn body_suffix(body: &str) -> String {
+    if body.is_empty() {
+        String::new()
+    } else {
+        format!(" - {}", body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_error_display_includes_body() {
+        let err = SdaError::Http {
+            context: "Server returned error for list accessions".to_string(),
+            status: StatusCode::UNPROCESSABLE_ENTITY,
+            body: "invalid lang".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Server returned error for list accessions: HTTP 422 Unprocessable Entity - invalid lang"
+        );
+    }
+
+    #[test]
+    fn test_http_error_display_without_body() {
+        let err = SdaError::Http {
+            context: "Server returned error for get accession".to_string(),
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            body: String::new(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Server returned error for get accession: HTTP 500 Internal Server Error"
+        );
+    }
+}
@@ -0,0 +1,368 @@
+//! Export formats for archive records.
+//!
+//! This module turns API models into flat or standard document formats
+//! (CSV, Dublin Core XML, collection zip bundles) for use outside of MCP
+//! clients.
+
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use zip::CompressionMethod;
+use zip::write::SimpleFileOptions;
+
+use crate::model::AccessionsWithMetadataResponse;
+
+/// Column headers of the accessions CSV export.
+const ACCESSION_CSV_HEADERS: [&str; 10] = [
+    "id",
+    "seed_url",
+    "crawl_status",
+    "crawl_timestamp",
+    "is_private",
+    "title_en",
+    "title_ar",
+    "subjects_en",
+    "subjects_ar",
+    "dublin_metadata_date",
+];
+
+/// Name of the manifest inside a collection export zip.
+pub const BUNDLE_MANIFEST: &str = "collection.json";
+
+/// Directory holding the WACZ/WARC archives inside a collection export zip.
+pub const BUNDLE_ARCHIVE_DIR: &str = "archives";
+
+/// Separator used when joining multi-valued fields into one CSV cell.
+const LIST_SEPARATOR: &str = ";";
+
+/// Returns the serialized name of a unit enum variant, e.g. `Complete`.
+fn variant_name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+/// Joins an optional list into one cell, emitting an empty string for `None`.
+fn join_list(values: &Option<Vec<String>>) -> String {
+    values
+        .as_ref()
+        .map(|v| v.join(LIST_SEPARATOR))
+        .unwrap_or_default()
+}
+
+/// Builds the CSV row for a single accession, in `ACCESSION_CSV_HEADERS` order.
+fn accession_csv_record(item: &AccessionsWithMetadataResponse) -> [String; 10] {
+    [
+        item.id.to_string(),
+        item.seed_url.clone(),
+        variant_name(&item.crawl_status),
+        item.crawl_timestamp.clone(),
+        item.is_private.to_string(),
+        item.title_en.clone().unwrap_or_default(),
+        item.title_ar.clone().unwrap_or_default(),
+        join_list(&item.subjects_en),
+        join_list(&item.subjects_ar),
+        item.dublin_metadata_date.clone(),
+    ]
+}
+
+/// Renders accessions as CSV text with a header row.
+///
+/// Missing optional fields become empty cells and subject lists are joined
+/// with semicolons. Columns whose field `redacted` returns true for are
+/// left out.
+pub fn accessions_to_csv(
+    items: &[AccessionsWithMetadataResponse],
+    redacted: impl Fn(&str) -> bool,
+) -> csv::Result<String> {
+    let kept: Vec<bool> = ACCESSION_CSV_HEADERS.iter().map(|h| !redacted(h)).collect();
+    let keep = |cells: Vec<String>| {
+        cells
+            .into_iter()
+            .zip(&kept)
+            .filter_map(|(cell, keep)| keep.then_some(cell))
+            .collect::<Vec<_>>()
+    };
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(keep(ACCESSION_CSV_HEADERS.map(String::from).to_vec()))?;
+    for item in items {
+        writer.write_record(keep(accession_csv_record(item).to_vec()))?;
+    }
+    let bytes = writer.into_inner().map_err(|e| e.into_error())?;
+    // Every cell comes from a Rust `String`, so the output is valid UTF-8.
+    Ok(String::from_utf8(bytes).unwrap_or_default())
+}
+
+/// Escapes text for use in XML element content.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Appends a `dc:<element>` line, tagged with `xml:lang` when `lang` is given.
+fn push_dc_element(xml: &mut String, element: &str, lang: Option<&str>, value: &str) {
+    let lang_attr = lang
+        .map(|l| format!(" xml:lang=\"{}\"", l))
+        .unwrap_or_default();
+    xml.push_str(&format!(
+        "  <dc:{element}{lang_attr}>{}</dc:{element}>\n",
+        escape_xml(value)
+    ));
+}
+
+/// Renders an accession as an OAI-PMH style Dublin Core XML record.
+///
+/// English and Arabic titles, descriptions and subjects are emitted with
+/// `xml:lang="en"` and `xml:lang="ar"` when present; the seed URL is the
+/// record's `dc:identifier`. Elements built from a field `redacted` returns
+/// true for are left out.
+pub fn accession_to_dublin_core(
+    item: &AccessionsWithMetadataResponse,
+    redacted: impl Fn(&str) -> bool,
+) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <oai_dc:dc xmlns:oai_dc=\"http://www.openarchives.org/OAI/2.0/oai_dc/\" \
+         xmlns:dc=\"http://purl.org/dc/elements/1.1/\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"http://www.openarchives.org/OAI/2.0/oai_dc/ \
+         http://www.openarchives.org/OAI/2.0/oai_dc.xsd\">\n",
+    );
+
+    // Redacted fields read as absent.
+    let shown = |field: &str| !redacted(field);
+    let languages = [
+        (
+            "en",
+            item.title_en.as_deref().filter(|_| shown("title_en")),
+            item.description_en
+                .as_deref()
+                .filter(|_| shown("description_en")),
+            item.subjects_en.as_ref().filter(|_| shown("subjects_en")),
+        ),
+        (
+            "ar",
+            item.title_ar.as_deref().filter(|_| shown("title_ar")),
+            item.description_ar
+                .as_deref()
+                .filter(|_| shown("description_ar")),
+            item.subjects_ar.as_ref().filter(|_| shown("subjects_ar")),
+        ),
+    ];
+    for (lang, title, _, _) in &languages {
+        if let Some(title) = title {
+            push_dc_element(&mut xml, "title", Some(lang), title);
+        }
+    }
+    for (lang, _, _, subjects) in &languages {
+        for subject in subjects.iter().copied().flatten() {
+            push_dc_element(&mut xml, "subject", Some(lang), subject);
+        }
+    }
+    for (lang, _, description, _) in &languages {
+        if let Some(description) = description {
+            push_dc_element(&mut xml, "description", Some(lang), description);
+        }
+    }
+    if shown("dublin_metadata_date") {
+        push_dc_element(&mut xml, "date", None, &item.dublin_metadata_date);
+    }
+    if shown("dublin_metadata_format") {
+        push_dc_element(
+            &mut xml,
+            "format",
+            None,
+            &variant_name(&item.dublin_metadata_format),
+        );
+    }
+    if shown("seed_url") {
+        push_dc_element(&mut xml, "identifier", None, &item.seed_url);
+    }
+
+    xml.push_str("</oai_dc:dc>\n");
+    xml
+}
+
+/// A temporary directory that is removed, with its contents, when dropped.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Creates a uniquely named directory under the system temp directory.
+    pub fn create(prefix: &str) -> io::Result<Self> {
+        let path = std::env::temp_dir().join(format!("{}-{}", prefix, uuid::Uuid::new_v4()));
+        std::fs::create_dir(&path)?;
+        Ok(Self(path))
+    }
+
+    /// Path of the directory.
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Writes a collection export zip to `dest`: the JSON `manifest` as
+/// `BUNDLE_MANIFEST`, plus every file in `archive_dir` (if any) under
+/// `BUNDLE_ARCHIVE_DIR`. Archives are already compressed, so they are
+/// stored as is. Returns the number of archives written.
+pub fn write_collection_zip(
+    dest: &Path,
+    manifest: &str,
+    archive_dir: Option<&Path>,
+) -> zip::result::ZipResult<usize> {
+    let mut zip = zip::ZipWriter::new(File::create(dest)?);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip.start_file(BUNDLE_MANIFEST, deflated)?;
+    io::Write::write_all(&mut zip, manifest.as_bytes())?;
+
+    let mut archives = 0;
+    if let Some(dir) = archive_dir {
+        let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<io::Result<_>>()?;
+        entries.sort();
+        let stored = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Stored)
+            .large_file(true);
+        for path in entries {
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            zip.start_file(format!("{}/{}", BUNDLE_ARCHIVE_DIR, name), stored)?;
+            io::copy(&mut File::open(&path)?, &mut zip)?;
+            archives += 1;
+        }
+    }
+    zip.finish()?;
+    Ok(archives)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accession(id: i64) -> AccessionsWithMetadataResponse {
+        AccessionsWithMetadataResponse {
+            seed_url: "https://example.com/a,b".to_string(),
+            subjects_en: Some(vec!["Protests".to_string(), "Khartoum".to_string()]),
+            subjects_en_ids: Some(vec![1, 2]),
+            ..AccessionsWithMetadataResponse::sample(id)
+        }
+    }
+
+    #[test]
+    fn test_accessions_to_csv() {
+        let csv = accessions_to_csv(&[accession(1)], |_| false).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(
+            lines[0],
+            "id,seed_url,crawl_status,crawl_timestamp,is_private,title_en,title_ar,subjects_en,subjects_ar,dublin_metadata_date"
+        );
+        assert_eq!(
+            lines[1],
+            "1,\"https://example.com/a,b\",Complete,2024-05-01T12:00:00,false,Example,,Protests;Khartoum,,2024-05-01T00:00:00"
+        );
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn test_accession_to_dublin_core() {
+        let mut item = accession(1);
+        item.title_ar = Some("مثال".to_string());
+        item.description_en = Some("Fish & <chips>".to_string());
+
+        let xml = accession_to_dublin_core(&item, |_| false);
+
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<oai_dc:dc "));
+        assert!(xml.contains("  <dc:title xml:lang=\"en\">Example</dc:title>\n"));
+        assert!(xml.contains("  <dc:title xml:lang=\"ar\">مثال</dc:title>\n"));
+        assert!(xml.contains("  <dc:subject xml:lang=\"en\">Khartoum</dc:subject>\n"));
+        assert!(xml.contains(
+            "  <dc:description xml:lang=\"en\">Fish &amp; &lt;chips&gt;</dc:description>\n"
+        ));
+        assert!(!xml.contains("<dc:description xml:lang=\"ar\">"));
+        assert!(xml.contains("  <dc:date>2024-05-01T00:00:00</dc:date>\n"));
+        assert!(xml.contains("  <dc:format>wacz</dc:format>\n"));
+        assert!(xml.contains("  <dc:identifier>https://example.com/a,b</dc:identifier>\n"));
+        assert!(xml.ends_with("</oai_dc:dc>\n"));
+    }
+
+    #[test]
+    fn test_exports_leave_out_redacted_fields() {
+        let redacted = |field: &str| field == "seed_url" || field == "title_en";
+
+        let csv = accessions_to_csv(&[accession(1)], redacted).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "id,crawl_status,crawl_timestamp,is_private,title_ar,subjects_en,subjects_ar,dublin_metadata_date"
+        );
+        assert_eq!(
+            lines[1],
+            "1,Complete,2024-05-01T12:00:00,false,,Protests;Khartoum,,2024-05-01T00:00:00"
+        );
+
+        let xml = accession_to_dublin_core(&accession(1), redacted);
+        assert!(!xml.contains("<dc:title"));
+        assert!(!xml.contains("<dc:identifier>"));
+        assert!(xml.contains("  <dc:subject xml:lang=\"en\">Khartoum</dc:subject>\n"));
+    }
+
+    #[test]
+    fn test_accessions_to_csv_empty_has_header_only() {
+        let csv = accessions_to_csv(&[], |_| false).unwrap();
+        assert_eq!(csv.lines().count(), 1);
+    }
+
+    #[test]
+    fn test_write_collection_zip_stores_manifest_and_archives() {
+        let archives = TempDir::create("sda-export-test-archives").unwrap();
+        std::fs::write(archives.path().join("5.wacz"), b"wacz bytes").unwrap();
+        std::fs::write(archives.path().join("6.warc"), b"warc bytes").unwrap();
+        let out = TempDir::create("sda-export-test-out").unwrap();
+        let dest = out.path().join("bundle.zip");
+
+        let count =
+            write_collection_zip(&dest, "{\"accessions\":[]}", Some(archives.path())).unwrap();
+        assert_eq!(count, 2);
+
+        let mut zip = zip::ZipArchive::new(File::open(&dest).unwrap()).unwrap();
+        let names: Vec<&str> = zip.file_names().collect();
+        assert_eq!(names.len(), 3);
+        let mut manifest = String::new();
+        io::Read::read_to_string(&mut zip.by_name(BUNDLE_MANIFEST).unwrap(), &mut manifest)
+            .unwrap();
+        assert_eq!(manifest, "{\"accessions\":[]}");
+        let mut archive = Vec::new();
+        io::Read::read_to_end(&mut zip.by_name("archives/5.wacz").unwrap(), &mut archive).unwrap();
+        assert_eq!(archive, b"wacz bytes");
+    }
+
+    #[test]
+    fn test_temp_dir_is_removed_on_drop() {
+        let dir = TempDir::create("sda-export-test-drop").unwrap();
+        let path = dir.path().to_path_buf();
+        std::fs::write(path.join("file"), b"x").unwrap();
+        drop(dir);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,95 @@
+//! Guessing whether accession metadata is written in Arabic or English.
+//!
+//! The archive only stores the two languages, which use different scripts,
+//! so counting Arabic and Latin letters is enough to tell them apart without
+//! a statistical language model. Text mixing both is assigned to the script
+//! with more letters; text with neither is left undetected.
+
+use crate::model::MetadataLanguage;
+
+/// Returns whether `c` is a letter of the Arabic script.
+pub fn is_arabic_letter(c: char) -> bool {
+    matches!(c, '\u{0600}'..='\u{06FF}' | '\u{0750}'..='\u{077F}' | '\u{08A0}'..='\u{08FF}')
+        && c.is_alphabetic()
+}
+
+/// Detects the language of `texts` from the script of their letters.
+///
+/// Returns `None` when they contain no Arabic or Latin letters, or as many
+/// of each.
+pub fn detect(texts: &[&str]) -> Option<MetadataLanguage> {
+    let (mut arabic, mut latin) = (0usize, 0usize);
+    for c in texts.iter().flat_map(|text| text.chars()) {
+        if is_arabic_letter(c) {
+            arabic += 1;
+        } else if c.is_alphabetic() && c.is_ascii() {
+            latin += 1;
+        }
+    }
+    match arabic.cmp(&latin) {
+        std::cmp::Ordering::Greater => Some(MetadataLanguage::Arabic),
+        std::cmp::Ordering::Less => Some(MetadataLanguage::English),
+        std::cmp::Ordering::Equal => None,
+    }
+}
+
+/// Returns `language` unless it is `None`, in which case the language of
+/// `texts` is detected and the guess logged. Stays `None` when nothing can
+/// be detected, so the API or local validation can report it.
+pub fn or_detect(language: MetadataLanguage, texts: &[&str]) -> MetadataLanguage {
+    if language != MetadataLanguage::None {
+        return language;
+    }
+    match detect(texts) {
+        Some(detected) => {
+            tracing::info!(
+                metadata_language = ?detected,
+                "metadata_language not set; detected from the title and description"
+            );
+            detected
+        }
+        None => {
+            tracing::warn!("metadata_language not set and could not be detected from the text");
+            MetadataLanguage::None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_arabic_and_english() {
+        assert_eq!(
+            detect(&["الحرب في دارفور", "صور من الخرطوم"]),
+            Some(MetadataLanguage::Arabic)
+        );
+        assert_eq!(
+            detect(&["War in Darfur", "Photos from Khartoum"]),
+            Some(MetadataLanguage::English)
+        );
+        // A Latin acronym in Arabic text does not flip the result.
+        assert_eq!(
+            detect(&["تقرير BBC عن الفيضانات في السودان"]),
+            Some(MetadataLanguage::Arabic)
+        );
+        assert_eq!(detect(&["2024-05-01", ""]), None);
+    }
+
+    #[test]
+    fn test_explicit_language_is_kept() {
+        assert_eq!(
+            or_detect(MetadataLanguage::English, &["الحرب في دارفور"]),
+            MetadataLanguage::English
+        );
+        assert_eq!(
+            or_detect(MetadataLanguage::None, &["الحرب في دارفور"]),
+            MetadataLanguage::Arabic
+        );
+        assert_eq!(
+            or_detect(MetadataLanguage::None, &["123"]),
+            MetadataLanguage::None
+        );
+    }
+}
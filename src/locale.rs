@@ -0,0 +1,379 @@
+//! Localization of user-facing text.
+//!
+//! Tool descriptions are compile-time attributes, so the English text lives
+//! on the `#[tool]` attributes in `server.rs` and this module supplies the
+//! translations that replace it at runtime in the tool list. Argument names,
+//! enum values and JSON keys are never translated, so the Arabic text keeps
+//! them in their original form.
+
+use std::borrow::Cow;
+
+use rmcp::ErrorData as McpError;
+use rmcp::model::ErrorCode;
+
+/// Language of tool descriptions and error messages shown to operators.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Locale {
+    /// English, the language of the `#[tool]` attributes.
+    #[default]
+    En,
+    /// Arabic.
+    Ar,
+}
+
+/// Arabic tool descriptions, keyed by tool name.
+const AR_TOOL_DESCRIPTIONS: &[(&str, &str)] = &[
+    (
+        "list_accessions",
+        "عرض قائمة المواد المؤرشفة العامة. لا تُعرض المواد الخاصة إلا عبر list_private_accessions؛ ويجب أن يكون is_private هنا false. اضبط count_only على true للحصول على {\"total\": N} فقط للمرشحات بدلاً من العناصر. استخدم fields (مثل [\"id\", \"title_en\", \"crawl_status\"]) لإرجاع هذه المفاتيح فقط لكل عنصر، أو اضبط verbosity على summary للحصول على id وtitle وseed_url وcrawl_status فقط.",
+    ),
+    (
+        "recent_accessions",
+        "عرض الجديد: المواد المؤرشفة التي زُحفت خلال آخر `since` (مثل \"24h\" أو \"7d\" أو \"2w\"؛ والافتراضي \"7d\")، الأحدث أولاً، في قائمة موجزة تضم id وtitle (الإنجليزي، أو العربي إن لم يوجد) وseed_url وcrawl_status وcrawl_timestamp. اضبط is_private على true للمواد الخاصة.",
+    ),
+    (
+        "search",
+        "البحث في المواد المؤرشفة العامة والمجموعات العامة والموضوعات عن query_term دفعة واحدة، مثلاً للعثور على كل ما يخص دارفور. يعيد لكل فئة العدد الإجمالي وأبرز النتائج (limit لكل فئة، والافتراضي 5 وبحد أقصى 20): المواد المؤرشفة بصيغة id وtitle وseed_url وcrawl_status وcrawl_timestamp، والمجموعات والموضوعات كاملة. اضبط lang على english أو arabic للبحث بلغة واحدة؛ وإن لم يُضبط يُبحث في الموضوعات بالعربية إذا احتوى query_term على حروف عربية، وإلا بالإنجليزية. الفئة التي يفشل البحث فيها تعرض خطأها دون إفشال الفئات الأخرى. استخدم أدوات القوائم لتصفح مزيد من النتائج.",
+    ),
+    (
+        "list_private_accessions",
+        "عرض قائمة المواد المؤرشفة الخاصة (is_private مفترض ويمكن حذفه). اضبط count_only على true للحصول على {\"total\": N} فقط للمرشحات بدلاً من العناصر. استخدم fields (مثل [\"id\", \"title_en\", \"crawl_status\"]) لإرجاع هذه المفاتيح فقط لكل عنصر، أو اضبط verbosity على summary للحصول على id وtitle وseed_url وcrawl_status فقط.",
+    ),
+    (
+        "open_accession_cursor",
+        "التنقل بين صفحات المواد المؤرشفة دون تتبع أرقام الصفحات: تأخذ مرشحات list_accessions نفسها (اضبط is_private على true لعرض المواد الخاصة بدلاً منها، وpage للبدء من غير الصفحة الأولى)، وتعيد الصفحة الأولى مع cursor_id وhas_more، وتحفظ المرشحات على الخادم. استدعِ next_accession_page بـ cursor_id لكل صفحة تالية؛ يُغلق المؤشر تلقائياً بعد الصفحة الأخيرة (ولا يُعاد cursor_id حينها)، ويمكن إغلاقه مبكراً بـ close_cursor. تنتهي صلاحية المؤشرات غير المستخدمة بعد 10 دقائق. لا يُدعم count_only هنا.",
+    ),
+    (
+        "next_accession_page",
+        "جلب الصفحة التالية من مؤشر مواد مؤرشفة فُتح بـ open_accession_cursor. يعيد الصفحة مع has_more، وcursor_id لاستخدامه للصفحة التي تليها ما دامت هناك صفحات أخرى. يشير كل cursor_id إلى صفحة واحدة، فإعادة الاستدعاء بـ cursor_id نفسه تعيد الصفحة نفسها.",
+    ),
+    (
+        "close_cursor",
+        "إغلاق مؤشر مواد مؤرشفة لم يعد مطلوباً لتحريره على الخادم. تُغلق المؤشرات تلقائياً بعد صفحتها الأخيرة وتنتهي صلاحيتها بعد 10 دقائق دون استخدام، لذا لا حاجة إليها إلا للتوقف مبكراً.",
+    ),
+    (
+        "server_info",
+        "عرض إصدار الخادم وإيداع git وعنوان SDA API الأساسي وإصدار بروتوكول MCP المتفق عليه مع هذا العميل وما إذا كان وضع التجربة (dry-run) مفعلاً. مفيد للتأكد من النسخة والإعدادات قيد التشغيل.",
+    ),
+    (
+        "describe_tool",
+        "عرض وصف أداة واحدة ومخطط JSON Schema لمعاملاتها، بالاسم، دون استدعائها. مفيد لبناء النماذج أو التحقق من المعاملات لدى العميل. استخدم list_tool_schemas للحصول على جميع الأدوات دفعة واحدة.",
+    ),
+    (
+        "list_tool_schemas",
+        "عرض جميع الأدوات مع وصف كل منها ومخطط JSON Schema لمعاملاتها، مرتبة بالاسم، دون استدعاء أي منها. المخططات هي نفسها المعلنة في tools/list.",
+    ),
+    (
+        "describe_enums",
+        "عرض القيم الصالحة لمعاملات وحقول التعداد (MetadataLanguage وDublinMetadataFormat وBrowserProfile وCrawlStatus وSortOrder وDublinMetadataRelationType وVerbosity وTimelineBucket وSubjectNameMode). راجعها قبل تمرير قيمة تعداد لست متأكداً منها.",
+    ),
+    (
+        "archive_stats",
+        "عرض ملخص للأرشيف: إجمالي المواد المؤرشفة، وأعداد المواد العامة والخاصة، وعدد ما يحتوي منها على بيانات وصفية إنجليزية أو عربية. يستخدم استعلامات عدّ خفيفة. اضبط include_crawl_status على true لعدّ المواد حسب crawl_status أيضاً؛ وهذا يمر على كل المواد وهو بطيء في الأرشيفات الكبيرة.",
+    ),
+    (
+        "list_used_subjects",
+        "عرض الموضوعات المستخدمة فعلاً في المواد المؤرشفة فقط، مع عدد المواد الموسومة بكل موضوع، الأكثر تكراراً أولاً. يحدد lang معرفات الموضوعات التي تُعدّ: english (الافتراضي، subjects_en_ids) أو arabic (subjects_ar_ids). اضبط is_private على true لعدّ المواد الخاصة. يمر على كل المواد، لذا فهو بطيء في الأرشيفات الكبيرة؛ استخدم list_subjects للحصول على المفردات الكاملة.",
+    ),
+    (
+        "get_crawl_diagnostics",
+        "شرح نتيجة الزحف لمادة مؤرشفة، مثل سبب انتهائها بحالة Error أو BadCrawl. لا تتيح واجهة SDA سجلات الزاحف، لذا تشرح هذه الأداة crawl_status، وللزحف الفاشل تفحص عنوان البذرة مباشرة من جديد (كما تفعل preview_crawl_target؛ اضبط check_target على false لتخطي ذلك) بحثاً عن الأسباب المحتملة: صفحة محذوفة، أو صفحة تتطلب تسجيل الدخول، أو موقع يحظر الزواحف أو يمنعها في robots.txt، أو عمليات إعادة توجيه، أو صفحة تحتاج browser_profile من نوع facebook. يعيد failed وexplanation وsuggestions (الأكثر تحديداً أولاً) وcrawl_id وjob_run_id للبحث في سجلات الزاحف نفسه، ونتيجة فحص الهدف. وينبّه أيضاً إلى الزحف الذي بقي معلقاً مدة طويلة على غير العادة. يعمل مع المواد العامة والخاصة.",
+    ),
+    (
+        "cancel_crawl",
+        "إلغاء زحف ما زال في حالة Pending، مثل زحف أُرسل بالخطأ. لا توفر SDA API نقطة نهاية للإلغاء، لذا تُحذف المادة المؤرشفة المعلقة بدلاً من ذلك؛ وقد تكتمل مهمة زحف جارية بالفعل لدى الخادم. المادة التي انتهى زحفها (Complete أو Error أو BadCrawl)، بما في ذلك التي ينتهي زحفها أثناء الإلغاء، تُترك دون تغيير ويُبلَّغ عنها مع cancelled = false وحالتها.",
+    ),
+    (
+        "register_accession_from_s3",
+        "تسجيل مادة مؤرشفة لملف WACZ سبق التقاطه ورفعه إلى حاوية الأرشيف، بدلاً من الزحف. s3_filename هو مفتاح الملف في الحاوية (مثل \"captures/2024/site.wacz\"، وليس عنوان URL)، وsource_url هو العنوان الأصلي للصفحة الملتقطة، ويُسجَّل عنواناً أولياً دون أن يُزحف إليه. حقول البيانات الوصفية كما في create_accession_crawl، وmetadata_format دائماً wacz. يعيد رد الـ API؛ وإذا رفض الـ API الملف فإن رسالة الخطأ تذكر s3_filename.",
+    ),
+    (
+        "preview_crawl_target",
+        "فحص عنوان URL قبل الزحف إليه. يطلب العنوان مباشرة (وليس عبر واجهة SDA، ودون مفتاح الواجهة)، متتبعاً حتى max_redirects من عمليات إعادة التوجيه (الافتراضي 10)، ويعرض العنوان النهائي ورمز الحالة ونوع المحتوى، وما إذا كان قد أُعيد توجيهه وإلى أين، وما إذا كان ملف robots.txt للموقع يمنعه لـ User-agent *. ويقترح أيضاً browser_profile عندما يحتاجه الموقع (مثل facebook). استخدمه لتقرر ما إذا كنت ستستدعي create_accession_crawl.",
+    ),
+    (
+        "estimate_crawl_size",
+        "تقدير حجم الزحف إلى عنوان URL قبل إرساله، لتخطيط مساحة التخزين. يطلب العنوان مباشرة (وليس عبر واجهة SDA، ودون مفتاح الواجهة): طلب HEAD لمعرفة content_length وcontent_type، ثم، لصفحات HTML، طلب GET سطحي يعدّ الموارد المختلفة التي تحمّلها الصفحة (الصور والبرامج النصية وأوراق الأنماط والوسائط) وروابطها إلى الموقع نفسه. يعيد estimated_wacz_bytes مع نطاق تقديري estimated_wacz_range من الأدنى إلى الأعلى، وestimated_pages (صفحة البذرة وحدها)، ودرجة ثقة confidence بين high وmedium وlow، وملاحظات notes عن طريقة التقدير؛ ويُشار إلى الموقع الذي يرفض HEAD، ويُذكر في error الموقع الذي تعذّر فحصه كلياً. هذا تقدير تقريبي فقط: لا تُحسب الموارد التي تحمّلها البرامج النصية. استخدمه مع preview_crawl_target.",
+    ),
+    (
+        "inspect_wacz",
+        "فحص ملف WACZ لمادة مؤرشفة دون تنزيله: يقرأ فقط datapackage.json وقائمة الصفحات باستخدام طلبات HTTP الجزئية (Range)، ويعيد عدد الصفحات الملتقطة ووقت أول وآخر التقاط والصفحة الرئيسية وبرنامج الزحف وحجم ملف WACZ والملفات التي يحتويها. يلجأ إلى التنزيل الكامل إذا لم يدعم الخادم الطلبات الجزئية (وتكون used_range_requests حينها false)، وتُرفض حينها الأرشيفات التي تتجاوز 512 ميغابايت. يعمل مع المواد العامة والخاصة؛ وتُرفض المواد بصيغة WARC.",
+    ),
+    (
+        "list_wacz_pages",
+        "عرض الصفحات الملتقطة في ملف WACZ لمادة مؤرشفة، مع id وعنوان URL والعنوان ووقت الالتقاط (ts) لكل صفحة، صفحات البذور أولاً ثم الصفحات التي بلغها الزحف بتتبع الروابط (وتكون seed حينها false). يقرأ قوائم الصفحات فقط باستخدام طلبات HTTP الجزئية (Range)، ويلجأ إلى التنزيل الكامل إذا لم يدعمها الخادم. مقسمة إلى صفحات بـ page (من 0) وper_page (الافتراضي 100)؛ ويعطي page_count العدد الكلي. مرر id إلى get_wacz_page لقراءة تلك الصفحة. يعمل مع المواد العامة والخاصة؛ وتُرفض المواد بصيغة WARC.",
+    ),
+    (
+        "get_wacz_page",
+        "جلب صفحة واحدة ملتقطة في ملف WACZ لمادة مؤرشفة بمعرفها من list_wacz_pages: نصها المقروء (بعد إزالة وسوم HTML والنصوص البرمجية والأنماط)، أو HTML الملتقط كما هو عند ضبط html على true، مع عنوان URL والعنوان ووقت الالتقاط. يُقتطع المحتوى عند max_chars حرفاً (الافتراضي 20000)؛ ويبين truncated وtotal_chars ما إذا كان هناك المزيد. يُنزَّل ملف WACZ لقراءة الصفحة، وتُرفض الأرشيفات التي تتجاوز 512 ميغابايت. يعمل مع المواد العامة والخاصة؛ وتُرفض المواد بصيغة WARC.",
+    ),
+    (
+        "health_check",
+        "التحقق مما إذا كانت واجهة SDA متاحة وتقبل مفتاح الواجهة، بإجراء طلب صغير موثق الآن. يعرض healthy ورمز حالة HTTP وزمن الاستجابة وأي خطأ، بالإضافة إلى نتيجة فحص بدء التشغيل إذا شُغّل الخادم مع --probe-on-start. استخدمه عندما تفشل الأدوات الأخرى بشكل غير متوقع.",
+    ),
+    (
+        "find_untranslated_accessions",
+        "البحث عن المواد التي تنقصها ترجمة: تلك التي لها بيانات وصفية بلغة واحدة فقط. اضبط missing على english للمواد ذات البيانات العربية فقط، أو arabic للمواد ذات البيانات الإنجليزية فقط؛ واحذفه لإيجاد النوعين. تضيّق query_term وdate_from وdate_to الاختيارية المواد المفحوصة، وتفحص is_private المواد الخاصة بدلاً من العامة. يمر على كل المواد المطابقة، لذا فإن المرشحات الواسعة بطيئة في الأرشيفات الكبيرة. يعيد معرّف كل مادة واللغة الناقصة وعنوانها باللغة الموجودة وعنوان البذرة، مع عدد كل نوع.",
+    ),
+    (
+        "retry_failed_crawls",
+        "إعادة محاولة الزحف الفاشل دفعة واحدة: يبحث عن كل مادة حالة زحفها Error أو BadCrawl تطابق المرشحات (date_from وdate_to على تاريخ الزحف، وmetadata_subjects، وis_private لإعادة محاولة المواد الخاصة بدلاً من العامة) ويعيد الزحف إلى عنوان البذرة لكل منها كمادة جديدة بالبيانات الوصفية نفسها، مثل recrawl_accession، بضع مواد في كل مرة. يُطبَّق browser_profile (مثل facebook) على كل إعادة زحف يقع عنوان بذرتها على الموقع المخصص له، ويُستبعد من البقية مع تحذير؛ ويُنبَّه أيضاً إلى المواد الواقعة على موقع يحتاج ملف تعريف لم يُعطَ. اضبط dry_run على true لعرض المواد التي ستُعاد محاولتها فقط. يعيد نتيجة لكل مادة فاشلة بحالتها (would_retry أو retried أو failed) ومعرّف المادة الجديدة عند معرفته وأي تحذير بشأن browser_profile وأي خطأ؛ ولا يوقف فشل إحداها البقية. تبقى المواد الفاشلة في مكانها، لذا فإن استدعاءه مرة أخرى يعيد محاولتها مجدداً.",
+    ),
+    (
+        "check_time_sync",
+        "مقارنة ساعة هذا الخادم بساعة واجهة SDA، المقروءة من ترويسة Date لطلب صغير. يعرض الفرق skew_seconds (موجب عندما تكون ساعة الواجهة متقدمة) وزمن الطلب، ويحذّر إذا تجاوز الفرق max_skew_seconds (الافتراضي 30). دقة الترويسة ثانية واحدة. استخدمه عندما لا تعيد مرشحات التاريخ مثل date_from=اليوم ما هو متوقع.",
+    ),
+    (
+        "subject_tree",
+        "تصفح مفردات الموضوعات كاملة في مجموعات بدلاً من التنقل بين صفحات list_subjects. يحدد lang المفردات: english (الافتراضي) أو arabic. قيمة group_by هي first_letter (الافتراضي؛ تُتخطى \"ال\" في البداية) أو first_word. اضبط include_counts على true للحصول أيضاً على عدد المواد الموسومة بكل موضوع وكل مجموعة (اضبط is_private على true لعدّ المواد الخاصة)؛ يمر ذلك على كل المواد، لذا فهو بطيء في الأرشيفات الكبيرة.",
+    ),
+    ("get_accession", "جلب مادة مؤرشفة واحدة"),
+    (
+        "export_accession_dublin_core",
+        "تصدير مادة مؤرشفة واحدة كسجل Dublin Core بصيغة XML (dc:title وdc:subject وdc:description وdc:date وdc:format وdc:identifier = عنوان URL الأصلي). توسم القيم الإنجليزية والعربية بـ xml:lang.",
+    ),
+    (
+        "get_accession_content",
+        "جلب النص المقروء للصفحة الرئيسية الملتقطة في مادة مؤرشفة، مستخرجاً من أرشيف WACZ/WARC (بعد إزالة وسوم HTML والسكربتات والأنماط)، مع عنوان الصفحة وعنوان URL. يقتصر النص على max_chars حرفاً (20000 افتراضياً)؛ ويبين truncated وtotal_chars ما إذا كان هناك المزيد. تُرفض الأرشيفات التي تتجاوز 512 ميغابايت. اضبط is_private على true للمواد الخاصة.",
+    ),
+    (
+        "download_wacz",
+        "تنزيل ملف الأرشيف لمادة مؤرشفة (WACZ، أو WARC للمواد المخزنة بصيغة WARC) إلى dest_path على جهاز الخادم. اضبط is_private على true للمواد الخاصة. يعيد عدد البايتات وبصمة SHA-256 للملف لإثبات المصدر، ويفشل إذا كان التنزيل أقصر أو أطول مما أعلنه الخادم.",
+    ),
+    (
+        "get_accession_by_url",
+        "البحث عن المواد المؤرشفة بعنوان URL الأصلي المطابق تماماً. يعيد كل النتائج المطابقة (إعادات الأرشفة لنفس العنوان)، الأحدث crawl_timestamp أولاً. اضبط is_private على true للبحث في المواد الخاصة.",
+    ),
+    (
+        "get_accessions",
+        "جلب عدة مواد مؤرشفة بمعرفاتها في استدعاء واحد. يعيد المواد التي عُثر عليها مع خريطة errors مفهرسة بالمعرف لأي منها فشل.",
+    ),
+    (
+        "diff_accessions",
+        "مقارنة مادتين مؤرشفتين حقلاً بحقل، مثل الالتقاط الأصلي (old_id) وإعادة زحفه (new_id). يعيد كل حقل من البيانات الوصفية يختلف بينهما (العناوين والأوصاف والموضوعات والخصوصية وcrawl_status والطوابع الزمنية وغيرها) مع قيمته القديمة والجديدة، والعناصر المضافة إلى الحقول القائمة أو المحذوفة منها، والموضوعات الإنجليزية والعربية المضافة أو المحذوفة. يعمل مع المواد العامة والخاصة.",
+    ),
+    (
+        "export_accessions_csv",
+        "تصدير كل المواد المؤرشفة المطابقة لمرشحات list_accessions بصيغة CSV (id وseed_url وcrawl_status وcrawl_timestamp وis_private وtitle_en وtitle_ar وsubjects_en وsubjects_ar وdublin_metadata_date، مع فصل الموضوعات بفواصل منقوطة). اضبط dest_path لكتابة ملف على جهاز الخادم، أو اتركه فارغاً لإرجاع نص CSV.",
+    ),
+    (
+        "export_accessions_ndjson",
+        "تصدير كل المواد المؤرشفة المطابقة لمرشحات list_accessions إلى ملف JSON مفصول بأسطر (NDJSON) على جهاز الخادم، كائن مادة كامل في كل سطر، يُكتب أثناء جلب الصفحات بحيث يتسع حتى للأرشيف كله. استخدمها بدلاً من أدوات القوائم أو CSV حين تكون النتيجة أكبر من أن تُعاد مباشرة؛ ويعمل الملف مع jq وغيرها من الأدوات التي تعالج الأسطر. dest_path مطلوب. يعيد المسار وعدد المواد المكتوبة.",
+    ),
+    ("get_private_accession", "جلب مادة مؤرشفة خاصة واحدة"),
+    (
+        "update_accession",
+        "تحديث مادة مؤرشفة. ملاحظة: يجب أن تطابق contributor_role_ids قائمة contributor_ids واحداً لواحد (الطول نفسه). **مهم:** يحدد الحقل metadata_language لغة العنوان والوصف اللذين يُحدَّثان - عند ضبطه على english أدخل نصاً إنجليزياً في metadata_title/metadata_description، وعند ضبطه على arabic أدخل نصاً عربياً في هذين الحقلين. إذا حُذف metadata_language تُكتشف اللغة من حروف العنوان والوصف. يمكن تحديد الموضوعات بأسمائها في metadata_subject_names (بلغة البيانات الوصفية) إلى جانب معرفاتها؛ تُحوَّل الأسماء إلى موضوعات موجودة، أو تُنشأ عند ضبط subject_name_mode على find_or_create، ويفشل الاستدعاء عند وجود اسم غير معروف أو ملتبس.",
+    ),
+    (
+        "patch_accession",
+        "تغيير حقول محددة في مادة مؤرشفة دون إعادة إرسال البقية (مثل is_private فقط). تحتفظ الحقول المحذوفة بقيمها الحالية. يحدد metadata_language لغة العنوان والوصف والموضوعات والمساهمين والمنشئ والموقع التي تُعدَّل؛ اتركه none لاستخدام الإنجليزية إن وُجدت وإلا العربية. حقول القوائم تستبدل القائمة كاملة.",
+    ),
+    (
+        "set_accessions_privacy",
+        "جعل عدة مواد مؤرشفة خاصة (is_private = true) أو عامة (is_private = false) في استدعاء واحد، مع الإبقاء على كل البيانات الوصفية الأخرى. تُتخطى المواد التي لها الخصوصية المطلوبة أصلاً. يعيد نتيجة لكل معرف (updated أو unchanged أو failed مع الخطأ) والمجاميع.",
+    ),
+    (
+        "bulk_privacy_change",
+        "نقل عدة مواد مؤرشفة بين العامة (is_private = false) والخاصة (is_private = true)، مثل set_accessions_privacy، مع إضافة قيد تدقيق يسجل الوقت والفاعل (actor الاختياري) وخصوصية كل معرف قبل التغيير وبعده. يتطلب ملف التدقيق في الخادم. يعيد عدد المواد التي تغيرت والمتخطاة (لها الخصوصية المطلوبة أصلاً) والفاشلة، وقيد التدقيق المكتوب. يُكتب القيد حتى عند إلغاء الاستدعاء، مع تعليم المواد التي لم تصل نتيجتها بـ cancelled.",
+    ),
+    (
+        "continue_result",
+        "جلب الجزء التالي من نتيجة أداة كانت أكبر من أن تُعاد دفعة واحدة. عند قطع نتيجة يليها جزؤها الأول كائن JSON فيه chunk وchunks وtotal_bytes وcontinuation_token؛ مرّر الرمز هنا للحصول على الجزء التالي، ويليه كائن من النوع نفسه، ثم اجمع الأجزاء بالترتيب لإعادة بناء النتيجة. لا يحتوي الجزء الأخير على continuation_token. تنتهي صلاحية الرموز بعد 10 دقائق دون استخدام؛ وجلب الرمز نفسه مرتين يعيد الجزء نفسه.",
+    ),
+    (
+        "subject_cooccurrence",
+        "معرفة الموضوعات التي تظهر معاً على المواد المؤرشفة نفسها، للتحليل الموضوعي. يعيد أكثر top_n زوجاً من الموضوعات (20 افتراضياً) حسب عدد المواد الموسومة بكليهما، مع عدد مواد كل موضوع، وعدد الأزواج المختلفة. يحدد lang معرفات الموضوعات التي تُقرن: english (افتراضياً، subjects_en_ids) أو arabic (subjects_ar_ids). يمكن تضييق المواد بـ query_term ونطاق تاريخ الزحف (date_from وdate_to)؛ واضبط is_private على true لتحليل المواد الخاصة. يتصفح كل المواد المطابقة، لذا يكون بطيئاً في الأرشيفات الكبيرة دون مرشحات.",
+    ),
+    (
+        "recrawl_accession",
+        "إعادة أرشفة مادة موجودة: يعيد زحف عنوان URL الأصلي كمادة جديدة، مع نسخ العنوان والوصف والزمن والموضوعات والمساهمين والمنشئ والموقع والخصوصية واللغة والصيغة من المادة الأصلية. يمكن تمرير أي من حقول بيانات patch_accession لاستبدال القيمة المنسوخة. اضبط browser_profile (مثل facebook) لإعادة زحف صفحة فشل زحفها الأول دونه؛ لا يُطبَّق ملف تعريف مخصص لموقع آخر، ويُعاد تحذير عند استبعاده أو عندما يحتاج الموقع ملف تعريف لم يُعطَ. يعيد استجابة عملية الزحف الجديدة.",
+    ),
+    (
+        "accessions_timeline",
+        "عدّ المواد المؤرشفة حسب تاريخ الزحف لتقارير النشاط: مدرج تكراري بعدد لكل يوم أو أسبوع (يبدأ الاثنين) أو شهر (bucket، الافتراضي month) بين date_from وdate_to (الافتراضي اليوم). تقبل التواريخ الصيغ YYYY-MM-DD وYYYY-MM وYYYY أو صيغاً مثل \"last month\" و\"6 months ago\". تُسرد كل الفترات في النطاق من الأقدم إلى الأحدث، بما فيها الفارغة، مع المجموع. اضبط is_private على true لعدّ المواد الخاصة. النطاقات الطويلة تتطلب المرور على عدد كبير من المواد، لذا يُفضَّل استخدام الفترات الشهرية لتقارير السنوات المتعددة.",
+    ),
+    (
+        "bookmark_accession",
+        "وضع إشارة مرجعية على مادة مؤرشفة للعودة إليها لاحقاً، مع ملاحظة اختيارية. تُحفظ الإشارات المرجعية في ملف محلي وتبقى بعد إعادة التشغيل، ولا تُخزَّن في الأرشيف. يجب أن تكون المادة موجودة (عامة أو خاصة). وضع الإشارة مرة أخرى يستبدل الملاحظة. يتطلب تشغيل الخادم مع --bookmarks-file.",
+    ),
+    (
+        "list_bookmarks",
+        "عرض المواد المؤرشفة ذات الإشارات المرجعية مع ملاحظاتها، مرتبة حسب المعرف. اضبط hydrate على true لجلب العنوان الحالي لكل مادة وعنوان URL الأصلي وحالة الزحف أيضاً؛ تُعرض المادة التي تعذر جلبها مع رسالة خطأ. يتطلب تشغيل الخادم مع --bookmarks-file.",
+    ),
+    (
+        "remove_bookmark",
+        "إزالة الإشارة المرجعية عن مادة مؤرشفة. يُبلَّغ removed = false إذا لم تكن عليها إشارة. لا تتأثر المادة نفسها. يتطلب تشغيل الخادم مع --bookmarks-file.",
+    ),
+    (
+        "list_profiles",
+        "عرض نسخ واجهة SDA (الملفات الشخصية) المعرّفة في ملف --profiles-file للخادم، مثل staging وproduction، والنسخة التي تستخدمها استدعاءات الأدوات حالياً. يعرض عنوان URL الأساسي لكل ملف شخصي وإعداداته الافتراضية ومصدر مفتاح الواجهة، ولا يعرض المفتاح نفسه أبداً. بدّل بين الملفات الشخصية باستخدام use_profile.",
+    ),
+    (
+        "use_profile",
+        "تبديل نسخة واجهة SDA (ملف شخصي من ملف --profiles-file للخادم، مثل staging أو production) التي تستخدمها كل استدعاءات الأدوات اللاحقة، دون إعادة تشغيل الخادم. تُطبَّق الإعدادات الافتراضية dry_run وdefault_private للملف الشخصي من حينها. يعيد الملفات الشخصية والملف النشط الجديد، كما يفعل list_profiles.",
+    ),
+    (
+        "create_accession_crawl",
+        "إنشاء مادة مؤرشفة جديدة (زحف). ملاحظة: يجب أن يكون metadata_time بصيغة ISO 8601 دون منطقة زمنية (مثل '2026-02-01T00:00:00' وليس '2026-02-01T00:00:00Z'). يجب أن تطابق معرفات أدوار المساهمين معرفات المساهمين واحداً لواحد (الطول نفسه). **مهم:** يحدد الحقل metadata_language لغة العنوان والوصف اللذين يُنشآن - عند ضبطه على english أدخل نصاً إنجليزياً، وعند ضبطه على arabic أدخل نصاً عربياً. إذا حُذف metadata_language تُكتشف اللغة من حروف العنوان والوصف. يمكن تحديد الموضوعات بأسمائها في metadata_subject_names (بلغة البيانات الوصفية) إلى جانب معرفاتها في metadata_subjects؛ تُحوَّل الأسماء إلى موضوعات موجودة، أو تُنشأ عند ضبط subject_name_mode على find_or_create، ويفشل الاستدعاء عند وجود اسم غير معروف أو ملتبس. تُحذف معرفات metadata_subjects المكررة وغير الموجبة. قبل الزحف تُفحص جميع الحقول (عنوان URL والعنوان وmetadata_time والصيغة وأدوار المساهمين ووجود كل موضوع)؛ وإذا كان أي منها غير صالح يفشل الاستدعاء مع سرد جميع المشكلات دفعة واحدة كأزواج {field, message} في المصفوفة problems ضمن بيانات الخطأ. إذا كان العنوان نفسه قد أُرشف مؤخراً، أو رفضت الواجهة الزحف لأنه مكرر (409)، تُعاد المادة الموجودة مع already_existed = true بدلاً من الزحف مجدداً؛ اضبط force على true لتخطي التحقق من الزحف الحديث، أو dedupe على false للحصول على خطأ بدلاً من ذلك. تحتوي الاستجابة دائماً على already_existed، وعلى accession_id عند معرفته.",
+    ),
+    (
+        "validate_crawl_request",
+        "فحص طلب create_accession_crawl دون إرساله. يأخذ المعاملات نفسها ويبلغ عن مشكلات عنوان URL وmetadata_language وmetadata_title وصيغة metadata_time وتطابق المساهمين مع أدوارهم وbrowser_profile، وعن أي معرفات في metadata_subjects غير موجودة. يعيد {\"valid\": true, \"problems\": []} عندما يكون الطلب جاهزاً للإرسال.",
+    ),
+    (
+        "list_subjects",
+        "عرض قائمة الموضوعات. استخدم المعامل lang بالقيمة 'english' أو 'arabic' للحصول على الموضوعات بتلك اللغة. استخدم query_term للبحث عن الموضوعات بالاسم - تحقق دائماً بهذه الطريقة من وجود الموضوع قبل استدعاء create_subject. اضبط count_only على true للحصول على {\"total\": N} فقط بدلاً من العناصر.",
+    ),
+    ("get_subject", "جلب موضوع واحد"),
+    ("create_subject", "إنشاء موضوع"),
+    (
+        "create_subjects",
+        "إنشاء عدة موضوعات دفعة واحدة، مثلاً عند تأسيس أرشيف جديد. الأسماء المكررة في الدفعة (نفس lang مع تجاهل حالة الأحرف) تُنشأ مرة واحدة. اضبط skip_existing على true للبحث عن كل موضوع أولاً وتخطي الموجود منها. يعيد نتيجة لكل موضوع تحتوي created_id أو existing_id (عند التخطي) أو error.",
+    ),
+    (
+        "delete_subject",
+        "حذف موضوع. يفشل مع ذكر عدد المواد المتأثرة إذا كانت أي مادة عامة أو خاصة لا تزال تستخدم الموضوع؛ اضبط force على true للحذف على أي حال (وهذا يتخطى التحقق أيضاً).",
+    ),
+    ("update_subject", "تحديث موضوع"),
+    (
+        "list_collections",
+        "عرض قائمة المجموعات العامة. اضبط count_only على true للحصول على {\"total\": N} فقط للمرشحات بدلاً من العناصر.",
+    ),
+    (
+        "list_private_collections",
+        "عرض قائمة المجموعات الخاصة (is_private افتراضيه true ويمكن حذفه؛ اضبطه على false لعرض المجموعات العامة بدلاً منها). تعيد الواجهة أحد النوعين فقط وليس كليهما، وتبيّن النتيجة أيهما: يكرر is_private قيمة المرشح ويصف scope الشريحة المعادة. اضبط count_only على true للحصول على العدد الإجمالي فقط للمرشحات بدلاً من العناصر.",
+    ),
+    ("get_collection", "جلب مجموعة واحدة"),
+    ("create_collection", "إنشاء مجموعة"),
+    ("update_collection", "تحديث مجموعة"),
+    (
+        "find_or_create_subject",
+        "جلب معرّف الموضوع الذي يحمل هذا الاسم (دون تمييز حالة الأحرف) في lang، وإنشاؤه إن لم يكن موجوداً. يعيد {\"id\": N, \"created\": true|false}. فضّل هذه الأداة على create_subject لتجنب تكرار الموضوعات.",
+    ),
+    (
+        "patch_collection",
+        "تغيير حقول محددة من مجموعة دون إعادة إرسال البقية (مثل is_private فقط). تحتفظ الحقول المحذوفة بقيمها الحالية، ومنها subject_ids؛ وإذا أُعطيت subject_ids فإنها تستبدل القائمة كلها. يعيد المجموعة المحدثة.",
+    ),
+    (
+        "add_collection_subject",
+        "إضافة موضوع إلى مجموعة مع الإبقاء على موضوعاتها الحالية. يعيد المجموعة المحدثة.",
+    ),
+    (
+        "remove_collection_subject",
+        "إزالة موضوع من مجموعة مع الإبقاء على موضوعاتها الأخرى. يعيد المجموعة المحدثة.",
+    ),
+    (
+        "list_collection_accessions",
+        "عرض المواد المؤرشفة الأعضاء في مجموعة. اضبط verbosity على summary لإرجاع id وtitle وseed_url وcrawl_status فقط لكل عنصر.",
+    ),
+    (
+        "get_accession_collections",
+        "عرض المجموعات، العامة والخاصة، التي تنتمي إليها مادة مؤرشفة. لا تعرض واجهة SDA العضوية إلا من جهة المجموعة، لذا تفحص هذه الأداة أعضاء كل مجموعة، ما يتطلب طلباً واحداً لكل صفحة من كل مجموعة؛ ويُعاد استخدام العضويات التي عُثر عليها لبضع دقائق (انظر cached)، فيكون البحث عن عدة مواد متتالية سريعاً. اضبط refresh على true لإعادة الفحص. يعيد المجموعات مع collections_scanned.",
+    ),
+    (
+        "export_collection",
+        "تصدير مجموعة كعنصر واحد: بياناتها الوصفية وموضوعاتها والبيانات الوصفية وwacz_url لكل مادة مؤرشفة فيها، في بيان JSON واحد. اضبط include_wacz على true لتنزيل أرشيف WACZ/WARC لكل مادة أيضاً؛ ويكون التصدير حينها ملف zip يضم collection.json ومجلد archives/. اضبط dest_path لكتابة الملف على جهاز الخادم (المسار المنتهي بـ .zip يعطي ملف zip)؛ أو اتركه فارغاً للحصول على JSON مباشرة إذا كان صغيراً، وإلا يُعاد مسار ملف مؤقت. تُدرج المواد التي تعذر جلبها تحت errors.",
+    ),
+    (
+        "add_to_collection",
+        "إضافة مادة مؤرشفة إلى مجموعة. ينجح مع رسالة توضيحية إذا كانت المادة عضواً فيها بالفعل.",
+    ),
+    (
+        "remove_from_collection",
+        "إزالة مادة مؤرشفة من مجموعة. ينجح مع رسالة توضيحية إذا لم تكن المادة عضواً فيها.",
+    ),
+    ("list_contributors", "عرض قائمة المساهمين"),
+    ("get_contributor", "جلب مساهم"),
+    ("create_contributor", "إنشاء مساهم"),
+    ("update_contributor", "تحديث مساهم"),
+    ("delete_contributor", "حذف مساهم"),
+    ("list_contributor_roles", "عرض قائمة أدوار المساهمين"),
+    ("get_contributor_role", "جلب دور مساهم"),
+    ("create_contributor_role", "إنشاء دور مساهم"),
+    ("update_contributor_role", "تحديث دور مساهم"),
+    ("delete_contributor_role", "حذف دور مساهم"),
+    ("list_creators", "عرض قائمة المنشئين"),
+    ("get_creator", "جلب منشئ"),
+    ("create_creator", "إنشاء منشئ"),
+    ("update_creator", "تحديث منشئ"),
+    ("delete_creator", "حذف منشئ"),
+    ("list_locations", "عرض قائمة المواقع"),
+    ("get_location", "جلب موقع"),
+    ("create_location", "إنشاء موقع"),
+    ("update_location", "تحديث موقع"),
+    ("delete_location", "حذف موقع"),
+    ("list_relations", "عرض قائمة العلاقات لمادة مؤرشفة"),
+    ("get_relation", "جلب علاقة"),
+    ("create_relation", "إنشاء علاقة"),
+    ("delete_relation", "حذف علاقة"),
+];
+
+impl Locale {
+    /// Returns the description of `tool` in this locale, or `None` to keep
+    /// the English description from the `#[tool]` attribute.
+    pub fn tool_description(self, tool: &str) -> Option<&'static str> {
+        match self {
+            Locale::En => None,
+            Locale::Ar => AR_TOOL_DESCRIPTIONS
+                .iter()
+                .find(|(name, _)| *name == tool)
+                .map(|(_, description)| *description),
+        }
+    }
+
+    /// Returns the server instructions sent during initialization.
+    pub fn instructions(self) -> &'static str {
+        match self {
+            Locale::En => {
+                "This server provides tools to interact with the Sudan Digital Archive API."
+            }
+            Locale::Ar => {
+                "يوفر هذا الخادم أدوات للتعامل مع واجهة برمجة تطبيقات أرشيف السودان الرقمي."
+            }
+        }
+    }
+
+    /// Prefixes an error message with a translated summary of its kind.
+    ///
+    /// The original English message is kept after the summary because it
+    /// carries API details (status codes, response bodies) that are not
+    /// translated. The error code and data are unchanged.
+    pub fn localize_error(self, mut error: McpError) -> McpError {
+        if self == Locale::En {
+            return error;
+        }
+        let summary = match error.code {
+            ErrorCode::INVALID_PARAMS => "معاملات غير صالحة",
+            ErrorCode::INVALID_REQUEST => "طلب غير صالح",
+            ErrorCode::RESOURCE_NOT_FOUND => "المورد غير موجود",
+            ErrorCode::METHOD_NOT_FOUND => "الطريقة غير موجودة",
+            _ => "خطأ داخلي",
+        };
+        error.message = Cow::Owned(format!("{}: {}", summary, error.message));
+        error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_english_keeps_attribute_text() {
+        assert_eq!(Locale::En.tool_description("get_accession"), None);
+        let error = McpError::invalid_params("Bad page", None);
+        assert_eq!(Locale::En.localize_error(error).message, "Bad page");
+    }
+
+    #[test]
+    fn test_arabic_error_keeps_original_message() {
+        let error = McpError::resource_not_found("Failed to get accession 7: HTTP 404", None);
+        let localized = Locale::Ar.localize_error(error);
+        assert_eq!(localized.code, ErrorCode::RESOURCE_NOT_FOUND);
+        assert_eq!(
+            localized.message,
+            "المورد غير موجود: Failed to get accession 7: HTTP 404"
+        );
+    }
+}
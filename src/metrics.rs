@@ -0,0 +1,222 @@
+//! Request timing and size metrics for SDA API calls.
+//!
+//! Every request sent by `SdaClient` is reported as a structured `tracing`
+//! event on the `sda_mcp_server::metrics` target with the fields `method`,
+//! `endpoint`, `status`, `duration_ms`, `bytes` and `api_request_id`, so
+//! operators can follow API performance from the logs (filter with e.g.
+//! `RUST_LOG=sda_mcp_server::metrics=info`).
+//!
+//! With the `metrics` cargo feature, tool calls and upstream requests are
+//! also counted through the `metrics` facade and can be served in Prometheus
+//! format with `prometheus::install`. Without an installed exporter the
+//! counters are no-ops.
+
+use std::time::Duration;
+
+use reqwest::{Method, Url};
+
+/// Target of the tracing events emitted by this module.
+const TARGET: &str = "sda_mcp_server::metrics";
+
+/// Label of every request outside the API, such as archive downloads.
+const ARCHIVE_LABEL: &str = "archive";
+
+/// Returns a low-cardinality label for a request URL.
+///
+/// The query string is dropped and numeric path segments are replaced by
+/// `{id}`, so `/api/v1/accessions/42` and `/api/v1/accessions/7` share the
+/// label `/api/v1/accessions/{id}`. Any base path in front of `/api/` is
+/// removed as well. URLs outside the API, whose paths name individual
+/// archive files, all share the label `archive`.
+pub fn endpoint_label(url: &Url) -> String {
+    let path = url.path();
+    let Some(start) = path.find("/api/") else {
+        return ARCHIVE_LABEL.to_string();
+    };
+    path[start..]
+        .split('/')
+        .map(|segment| {
+            if !segment.is_empty() && segment.bytes().all(|b| b.is_ascii_digit()) {
+                "{id}"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Records a request that received an HTTP response.
+///
+/// `bytes` is the response `Content-Length`, when the server sent one, and
+/// `api_request_id` the `X-Request-Id` the API echoed back, if any.
+pub fn record_response(
+    method: &Method,
+    endpoint: &str,
+    status: u16,
+    duration: Duration,
+    bytes: Option<u64>,
+    api_request_id: Option<&str>,
+) {
+    let duration_ms = duration.as_millis() as u64;
+    tracing::info!(
+        target: TARGET,
+        method = %method,
+        endpoint,
+        status,
+        duration_ms,
+        bytes,
+        api_request_id,
+        "SDA API request completed"
+    );
+    #[cfg(feature = "metrics")]
+    prometheus::record_request(method, endpoint, Some(status), duration);
+}
+
+/// Records a request that failed before any response was received.
+pub fn record_failure(
+    method: &Method,
+    endpoint: &str,
+    duration: Duration,
+    error: &dyn std::fmt::Display,
+) {
+    let duration_ms = duration.as_millis() as u64;
+    tracing::info!(
+        target: TARGET,
+        method = %method,
+        endpoint,
+        duration_ms,
+        error = %error,
+        "SDA API request failed"
+    );
+    #[cfg(feature = "metrics")]
+    prometheus::record_request(method, endpoint, None, duration);
+}
+
+/// Records the outcome of one MCP tool call.
+///
+/// A call counts as an error when it returns an MCP error or a result
+/// flagged with `is_error`.
+pub fn record_tool_call(tool: &str, is_error: bool) {
+    #[cfg(feature = "metrics")]
+    prometheus::record_tool_call(tool, is_error);
+    #[cfg(not(feature = "metrics"))]
+    let _ = (tool, is_error);
+}
+
+/// Prometheus counters and histograms, enabled by the `metrics` feature.
+///
+/// Metric names:
+/// - `sda_tool_calls_total{tool}` and `sda_tool_errors_total{tool}`
+/// - `sda_api_requests_total{method, endpoint, status}`; `status` is
+///   `error` when no response was received
+/// - `sda_api_request_duration_seconds{method, endpoint}` (histogram)
+#[cfg(feature = "metrics")]
+pub mod prometheus {
+    use std::net::SocketAddr;
+    use std::time::Duration;
+
+    use metrics_exporter_prometheus::{BuildError, Matcher, PrometheusBuilder};
+    use reqwest::Method;
+
+    /// Histogram buckets for upstream request durations, in seconds.
+    const DURATION_BUCKETS: [f64; 10] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+    /// Starts the Prometheus exporter, serving `/metrics` on `addr`.
+    ///
+    /// Must be called from within the Tokio runtime.
+    pub fn install(addr: SocketAddr) -> Result<(), BuildError> {
+        PrometheusBuilder::new()
+            .with_http_listener(addr)
+            .set_buckets_for_metric(
+                Matcher::Full("sda_api_request_duration_seconds".to_string()),
+                &DURATION_BUCKETS,
+            )?
+            .install()
+    }
+
+    /// Counts one upstream request and records its duration.
+    pub(super) fn record_request(
+        method: &Method,
+        endpoint: &str,
+        status: Option<u16>,
+        duration: Duration,
+    ) {
+        let status = status.map_or_else(|| "error".to_string(), |s| s.to_string());
+        ::metrics::counter!(
+            "sda_api_requests_total",
+            "method" => method.to_string(),
+            "endpoint" => endpoint.to_string(),
+            "status" => status
+        )
+        .increment(1);
+        ::metrics::histogram!(
+            "sda_api_request_duration_seconds",
+            "method" => method.to_string(),
+            "endpoint" => endpoint.to_string()
+        )
+        .record(duration.as_secs_f64());
+    }
+
+    /// Counts one tool call and, if it failed, one tool error.
+    pub(super) fn record_tool_call(tool: &str, is_error: bool) {
+        ::metrics::counter!("sda_tool_calls_total", "tool" => tool.to_string()).increment(1);
+        if is_error {
+            ::metrics::counter!("sda_tool_errors_total", "tool" => tool.to_string()).increment(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_label_replaces_ids_and_strips_query() {
+        let url = Url::parse("https://api.example.com/sda-api/api/v1/accessions/42?lang=english")
+            .unwrap();
+        assert_eq!(endpoint_label(&url), "/api/v1/accessions/{id}");
+
+        let url = Url::parse("https://api.example.com/api/v1/collections/3/accessions/17").unwrap();
+        assert_eq!(
+            endpoint_label(&url),
+            "/api/v1/collections/{id}/accessions/{id}"
+        );
+    }
+
+    #[test]
+    fn test_endpoint_label_collapses_paths_outside_the_api() {
+        for url in [
+            "https://bucket.s3.amazonaws.com/wacz/file.wacz?sig=1",
+            "https://bucket.s3.amazonaws.com/wacz/other.wacz",
+        ] {
+            assert_eq!(endpoint_label(&Url::parse(url).unwrap()), "archive");
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_prometheus_output_counts_tools_and_requests() {
+        let recorder = metrics_exporter_prometheus::PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        ::metrics::with_local_recorder(&recorder, || {
+            record_tool_call("get_accession", false);
+            record_tool_call("get_accession", true);
+            record_response(
+                &Method::GET,
+                "/api/v1/accessions/{id}",
+                404,
+                Duration::from_millis(20),
+                None,
+                None,
+            );
+        });
+
+        let output = handle.render();
+        assert!(output.contains("sda_tool_calls_total{tool=\"get_accession\"} 2"));
+        assert!(output.contains("sda_tool_errors_total{tool=\"get_accession\"} 1"));
+        assert!(output.contains(
+            "sda_api_requests_total{method=\"GET\",endpoint=\"/api/v1/accessions/{id}\",status=\"404\"} 1"
+        ));
+    }
+}
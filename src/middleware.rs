@@ -0,0 +1,569 @@
+//! Layers run around every request `SdaClient` sends.
+//!
+//! The client sends requests through a `reqwest_middleware` stack, so
+//! cross-cutting concerns are written once as `Middleware` layers instead of
+//! being repeated at each call site. Each layer wraps the ones after it, in
+//! this order:
+//!
+//! 1. `RetryRateLimited` re-sends a request the API answered with 429 Too
+//!    Many Requests, unless the request carries the `NoRetry` extension.
+//! 2. `ApiKey`, `ExtraHeaders` and `RequestId` add headers.
+//! 3. `Limits` applies the default timeout and waits for a concurrency
+//!    permit and the rate limiter.
+//! 4. Layers added with `SdaClientBuilder::middleware`, such as `RequestLog`.
+//! 5. `Capture` (with `--debug-capture-dir`) and `Metrics` record what is
+//!    actually sent; `Metrics` also leaves the attempt's `Attempt` timing in
+//!    the request's extensions.
+//!
+//! Everything after `RetryRateLimited` runs once per attempt, so retries are
+//! counted against the limits and recorded like any other request.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use governor::DefaultDirectRateLimiter;
+use http::Extensions;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, InvalidHeaderValue, RETRY_AFTER};
+use reqwest::{Request, Response, StatusCode, Url};
+use reqwest_middleware::{Error, Next};
+use tokio::sync::Semaphore;
+
+use crate::capture::DebugCapture;
+use crate::error::SdaError;
+use crate::metrics;
+use crate::request_id;
+use crate::schema_check;
+
+pub use reqwest_middleware::Middleware;
+
+/// Header carrying the API key.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Base delay for exponential backoff when the API gives no `Retry-After` hint.
+const BACKOFF_BASE: Duration = Duration::from_millis(500);
+
+/// Request extension that makes `RetryRateLimited` send the request once,
+/// returning a 429 response as-is, e.g. for a health check that should
+/// report rate limiting instead of waiting it out.
+#[derive(Debug, Clone, Copy)]
+pub struct NoRetry;
+
+/// When the last attempt at a request went out and how long it took to
+/// answer, left in the request's extensions by `Metrics`. Waits for the
+/// concurrency and rate limits and for retries are not included.
+#[derive(Debug, Clone, Copy)]
+pub struct Attempt {
+    /// When the attempt was handed to the HTTP client.
+    pub sent: DateTime<Utc>,
+    /// Time until the response headers arrived or the attempt failed.
+    pub round_trip: Duration,
+}
+
+/// Retries requests the API answers with 429 Too Many Requests.
+///
+/// The wait before each retry honours the `Retry-After` header (seconds or
+/// HTTP-date) capped at `max_retry_wait`, falling back to exponential backoff
+/// when the header is absent. The final response is returned as-is so that
+/// the caller can report a persistent 429 with its body. Requests with
+/// streaming bodies cannot be cloned and are sent once.
+pub struct RetryRateLimited {
+    /// Number of times a rate-limited request is retried.
+    pub max_retries: u32,
+    /// Upper bound on the wait before each retry.
+    pub max_retry_wait: Duration,
+}
+
+#[async_trait]
+impl Middleware for RetryRateLimited {
+    async fn handle(
+        &self,
+        request: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        if extensions.get::<NoRetry>().is_some() {
+            return next.run(request, extensions).await;
+        }
+        let mut attempt = 0;
+        loop {
+            let Some(duplicate) = request.try_clone() else {
+                return next.run(request, extensions).await;
+            };
+            // Later layers hold the concurrency permit only until the
+            // response headers arrive, so sleeping retries don't block others.
+            let response = next.clone().run(duplicate, extensions).await?;
+            if response.status() != StatusCode::TOO_MANY_REQUESTS || attempt >= self.max_retries {
+                return Ok(response);
+            }
+
+            let delay = parse_retry_after(response.headers(), Utc::now())
+                .unwrap_or_else(|| backoff_delay(attempt))
+                .min(self.max_retry_wait);
+            let header = |name: &str| {
+                response
+                    .headers()
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or("-")
+                    .to_string()
+            };
+            tracing::warn!(
+                method = %request.method(),
+                url = %request.url(),
+                attempt = attempt + 1,
+                max_retries = self.max_retries,
+                delay_ms = delay.as_millis() as u64,
+                retry_after = %header("retry-after"),
+                ratelimit_limit = %header("x-ratelimit-limit"),
+                ratelimit_remaining = %header("x-ratelimit-remaining"),
+                ratelimit_reset = %header("x-ratelimit-reset"),
+                "Rate limited by SDA API, retrying"
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// Parses a `Retry-After` header given either as delay-seconds or an HTTP-date.
+///
+/// Returns `None` when the header is missing or malformed. Dates in the past
+/// yield a zero delay.
+fn parse_retry_after(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// Computes the exponential backoff delay for the given zero-based retry attempt.
+fn backoff_delay(attempt: u32) -> Duration {
+    BACKOFF_BASE.saturating_mul(2u32.saturating_pow(attempt))
+}
+
+/// Returns whether `url` is on the API at `base_url`, as opposed to another
+/// host such as WACZ storage.
+fn is_api_url(base_url: &str, url: &Url) -> bool {
+    url.as_str()
+        .strip_prefix(base_url)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '?']))
+}
+
+/// Sends the API key on every API request.
+pub struct ApiKey {
+    /// Base URL of the API, without a trailing slash.
+    base_url: String,
+    /// The key, marked sensitive so it is never logged.
+    key: HeaderValue,
+}
+
+impl ApiKey {
+    /// Creates the layer for `key` on the API at `base_url`, failing if the
+    /// key cannot be sent as a header value.
+    pub fn new(base_url: &str, key: &str) -> Result<Self, InvalidHeaderValue> {
+        let mut key = HeaderValue::from_str(key)?;
+        key.set_sensitive(true);
+        Ok(Self {
+            base_url: base_url.to_string(),
+            key,
+        })
+    }
+
+    /// The header name and value sent with API requests.
+    pub fn header(&self) -> (&'static str, &HeaderValue) {
+        (API_KEY_HEADER, &self.key)
+    }
+}
+
+#[async_trait]
+impl Middleware for ApiKey {
+    async fn handle(
+        &self,
+        mut request: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        if is_api_url(&self.base_url, request.url()) {
+            request
+                .headers_mut()
+                .insert(API_KEY_HEADER, self.key.clone());
+        }
+        next.run(request, extensions).await
+    }
+}
+
+/// Adds operator-configured headers (`--header`) to API requests.
+///
+/// Headers a request already carries take precedence, so this layer runs
+/// after any that set headers of their own.
+pub struct ExtraHeaders {
+    /// Base URL of the API, without a trailing slash.
+    base_url: String,
+    /// Headers to add; a repeated name sends every value. Values are marked
+    /// sensitive, as they often carry gateway tokens.
+    headers: HeaderMap,
+}
+
+impl ExtraHeaders {
+    /// Creates the layer adding `headers` to requests for the API at `base_url`.
+    pub fn new(base_url: &str, mut headers: HeaderMap) -> Self {
+        for value in headers.values_mut() {
+            value.set_sensitive(true);
+        }
+        Self {
+            base_url: base_url.to_string(),
+            headers,
+        }
+    }
+
+    /// Adds the headers `request` does not already carry.
+    fn apply(&self, request: &mut Request) {
+        if !is_api_url(&self.base_url, request.url()) {
+            return;
+        }
+        let headers = request.headers_mut();
+        for name in self.headers.keys() {
+            if !headers.contains_key(name) {
+                for value in self.headers.get_all(name) {
+                    headers.append(name.clone(), value.clone());
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Middleware for ExtraHeaders {
+    async fn handle(
+        &self,
+        mut request: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        self.apply(&mut request);
+        next.run(request, extensions).await
+    }
+}
+
+/// Sends the current tool call's correlation ID as `X-Request-Id`.
+pub struct RequestId;
+
+#[async_trait]
+impl Middleware for RequestId {
+    async fn handle(
+        &self,
+        mut request: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        if let Some(id) = request_id::current().and_then(|id| HeaderValue::from_str(&id).ok()) {
+            request
+                .headers_mut()
+                .insert(HeaderName::from_static(request_id::HEADER), id);
+        }
+        next.run(request, extensions).await
+    }
+}
+
+/// Gives requests that set no timeout of their own the ordinary one, and
+/// holds each attempt until it may go out.
+///
+/// An attempt first takes one of the concurrency permits, held until its
+/// response headers arrive. When a rate limit is set, it then waits for its
+/// turn, for at most the request's timeout, failing with
+/// `SdaError::RateLimited` if the turn does not come.
+pub struct Limits {
+    /// Timeout of each request that does not set its own.
+    pub timeout: Duration,
+    /// Limits how many requests are in flight at once.
+    pub concurrency: Arc<Semaphore>,
+    /// Limits how many requests are started per second.
+    pub rate_limiter: Option<Arc<DefaultDirectRateLimiter>>,
+}
+
+#[async_trait]
+impl Middleware for Limits {
+    async fn handle(
+        &self,
+        mut request: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let timeout = *request.timeout_mut().get_or_insert(self.timeout);
+        let _permit = self.concurrency.acquire().await;
+        if let Some(rate_limiter) = &self.rate_limiter {
+            tokio::time::timeout(timeout, rate_limiter.until_ready())
+                .await
+                .map_err(|_| {
+                    Error::middleware(SdaError::RateLimited {
+                        context: "Waiting for the rate limiter".to_string(),
+                        waited: timeout,
+                    })
+                })?;
+        }
+        next.run(request, extensions).await
+    }
+}
+
+/// Records every exchange to a file in a `DebugCapture` directory.
+pub struct Capture {
+    /// Base URL of the API, without a trailing slash.
+    base_url: String,
+    /// Where the exchanges are written.
+    capture: Arc<DebugCapture>,
+}
+
+impl Capture {
+    /// Creates the layer recording to `capture`, hiding the query of URLs
+    /// outside the API at `base_url`.
+    pub fn new(base_url: &str, capture: Arc<DebugCapture>) -> Self {
+        Self {
+            base_url: base_url.to_string(),
+            capture,
+        }
+    }
+}
+
+#[async_trait]
+impl Middleware for Capture {
+    async fn handle(
+        &self,
+        request: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let is_api = is_api_url(&self.base_url, request.url());
+        let exchange = self.capture.start(&request, is_api);
+        match next.run(request, extensions).await {
+            Ok(response) => Ok(exchange.wrap(response)),
+            Err(e) => {
+                exchange.fail(&e);
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Records the timing and size of every request with `metrics`, and leaves
+/// the timing in the request's extensions as an `Attempt`.
+pub struct Metrics;
+
+#[async_trait]
+impl Middleware for Metrics {
+    async fn handle(
+        &self,
+        request: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let method = request.method().clone();
+        let endpoint = metrics::endpoint_label(request.url());
+        let sent = Utc::now();
+        let started = Instant::now();
+        let result = next.run(request, extensions).await;
+        let elapsed = started.elapsed();
+        extensions.insert(Attempt {
+            sent,
+            round_trip: elapsed,
+        });
+        match &result {
+            Ok(response) => metrics::record_response(
+                &method,
+                &endpoint,
+                response.status().as_u16(),
+                elapsed,
+                response.content_length(),
+                response
+                    .headers()
+                    .get(request_id::HEADER)
+                    .and_then(|v| v.to_str().ok()),
+            ),
+            Err(e) => metrics::record_failure(&method, &endpoint, elapsed, e),
+        }
+        result
+    }
+}
+
+/// Logs each request with its body and each response status, for
+/// debugging an integration (`--log-requests`). Sensitive headers such as
+/// the API key are never logged.
+pub struct RequestLog;
+
+/// Target of the tracing events emitted by `RequestLog`.
+const REQUEST_LOG_TARGET: &str = "sda_mcp_server::requests";
+
+#[async_trait]
+impl Middleware for RequestLog {
+    async fn handle(
+        &self,
+        request: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let method = request.method().clone();
+        let url = request.url().clone();
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(schema_check::body_snippet);
+        let headers: Vec<String> = request
+            .headers()
+            .iter()
+            .filter(|(_, value)| !value.is_sensitive())
+            .map(|(name, value)| format!("{}: {}", name, value.to_str().unwrap_or("<binary>")))
+            .collect();
+        tracing::info!(
+            target: REQUEST_LOG_TARGET,
+            method = %method,
+            url = %url,
+            headers = ?headers,
+            body = body.as_deref().unwrap_or("-"),
+            "Sending SDA API request"
+        );
+
+        let started = Instant::now();
+        let result = next.run(request, extensions).await;
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        match &result {
+            Ok(response) => tracing::info!(
+                target: REQUEST_LOG_TARGET,
+                method = %method,
+                url = %url,
+                status = response.status().as_u16(),
+                elapsed_ms,
+                "Received SDA API response"
+            ),
+            Err(e) => tracing::info!(
+                target: REQUEST_LOG_TARGET,
+                method = %method,
+                url = %url,
+                elapsed_ms,
+                error = %e,
+                "SDA API request failed"
+            ),
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Ends a stack without sending anything, keeping the headers of each
+    /// request it receives.
+    #[derive(Clone, Default)]
+    struct Sink(Arc<Mutex<Vec<HeaderMap>>>);
+
+    #[async_trait]
+    impl Middleware for Sink {
+        async fn handle(
+            &self,
+            request: Request,
+            _extensions: &mut Extensions,
+            _next: Next<'_>,
+        ) -> reqwest_middleware::Result<Response> {
+            self.0.lock().unwrap().push(request.headers().clone());
+            Ok(Response::from(http::Response::new(reqwest::Body::from(""))))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_api_key_and_extra_headers_only_go_to_the_api() {
+        let mut extra = HeaderMap::new();
+        extra.insert("x-gateway", HeaderValue::from_static("mirror"));
+        extra.insert(API_KEY_HEADER, HeaderValue::from_static("not-the-key"));
+        let sink = Sink::default();
+        let client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new())
+            .with(ApiKey::new("https://api.test/sda-api", "secret").unwrap())
+            .with(ExtraHeaders::new("https://api.test/sda-api", extra))
+            .with(sink.clone())
+            .build();
+
+        client
+            .get("https://api.test/sda-api/api/v1/subjects")
+            .send()
+            .await
+            .unwrap();
+        let api = sink.0.lock().unwrap().pop().unwrap();
+        assert_eq!(api[API_KEY_HEADER], "secret");
+        assert!(api[API_KEY_HEADER].is_sensitive());
+        assert_eq!(api["x-gateway"], "mirror");
+        assert!(api["x-gateway"].is_sensitive());
+
+        for other in [
+            "https://files.test/5.wacz",
+            "https://api.test/sda-api-other/api/v1/subjects",
+        ] {
+            client.get(other).send().await.unwrap();
+            let storage = sink.0.lock().unwrap().pop().unwrap();
+            assert!(storage.is_empty(), "{}", other);
+        }
+    }
+
+    #[test]
+    fn test_api_key_must_be_a_header_value() {
+        assert!(ApiKey::new("https://api.test", "line\nbreak").is_err());
+    }
+
+    #[test]
+    fn test_parse_retry_after_seconds() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+
+        let delay = parse_retry_after(&headers, Utc::now());
+        assert_eq!(delay, Some(Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn test_parse_retry_after_http_date() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:30 GMT"),
+        );
+        let now = DateTime::parse_from_rfc3339("2015-10-21T07:28:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let delay = parse_retry_after(&headers, now);
+        assert_eq!(delay, Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_parse_retry_after_past_date_is_zero() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+
+        let delay = parse_retry_after(&headers, Utc::now());
+        assert_eq!(delay, Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_parse_retry_after_missing_or_invalid() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers, Utc::now()), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+        assert_eq!(parse_retry_after(&headers, Utc::now()), None);
+    }
+
+    #[test]
+    fn test_backoff_delay_doubles() {
+        assert_eq!(backoff_delay(0), Duration::from_millis(500));
+        assert_eq!(backoff_delay(1), Duration::from_millis(1000));
+        assert_eq!(backoff_delay(3), Duration::from_millis(4000));
+    }
+}
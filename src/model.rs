@@ -3,6 +3,8 @@
 //! This module defines the structs and enums used for communication
 //! between the MCP server and the SDA API.
 
+use std::collections::BTreeMap;
+
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -20,28 +22,104 @@ pub enum MetadataLanguage {
 }
 
 /// Supported metadata formats.
-#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq)]
 pub enum DublinMetadataFormat {
     /// Web ARChive (Zipped) format.
     #[serde(rename = "wacz")]
     Wacz,
+    /// Raw Web ARChive format.
+    #[serde(rename = "warc")]
+    Warc,
 }
 
 /// Supported browser profiles for hard to archive sites.
-#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum BrowserProfile {
     /// Profile for crawling Facebook.
     Facebook,
 }
 
+/// Browser profile chosen in tool arguments, where leaving it out means none.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BrowserProfileChoice {
+    /// Crawl without a browser profile.
+    #[default]
+    None,
+    /// Profile for crawling Facebook.
+    Facebook,
+}
+
+impl BrowserProfileChoice {
+    /// Returns the profile to send to the API, if any.
+    pub fn profile(self) -> Option<BrowserProfile> {
+        match self {
+            Self::None => None,
+            Self::Facebook => Some(BrowserProfile::Facebook),
+        }
+    }
+}
+
+/// Sort direction for list queries.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    /// Ascending order.
+    #[default]
+    Asc,
+    /// Descending order.
+    Desc,
+}
+
+/// Width of the buckets counted by `accessions_timeline`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineBucket {
+    /// One bucket per calendar day.
+    Day,
+    /// One bucket per ISO week, starting on Monday.
+    Week,
+    /// One bucket per calendar month.
+    #[default]
+    Month,
+}
+
+/// How `metadata_subject_names` are turned into subject IDs.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SubjectNameMode {
+    /// Use existing subjects only; a name with no match is an error.
+    #[default]
+    Lookup,
+    /// Create a subject for each name with no match.
+    FindOrCreate,
+}
+
+/// How much of each accession the list tools return.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Verbosity {
+    /// Only `id`, `title`, `seed_url` and `crawl_status`.
+    Summary,
+    /// Every field.
+    #[default]
+    Full,
+}
+
+/// Accession fields kept by `Verbosity::Summary`, besides the combined `title`.
+pub const SUMMARY_ACCESSION_FIELDS: &[&str] = &["id", "seed_url", "crawl_status"];
+
+/// Accession fields the API accepts in the `sort_by` query parameter.
+pub const SORTABLE_ACCESSION_FIELDS: &[&str] = &["crawl_timestamp", "dublin_metadata_date", "id"];
+
 /// Default value for pagination fields.
 fn default_pagination() -> i64 {
     -1
 }
 
 /// Default value for collection ID filter.
-fn default_collection_id() -> i32 {
+fn default_collection_id() -> i64 {
     -1
 }
 
@@ -50,21 +128,59 @@ fn default_id() -> i64 {
     -1
 }
 
+/// Default value for the redirect limit of `preview_crawl_target`.
+fn default_max_redirects() -> u32 {
+    10
+}
+
+/// Default number of matches `search` returns per category.
+fn default_search_limit() -> i64 {
+    5
+}
+
+/// Default look-back window of `recent_accessions`.
+fn default_since() -> String {
+    "7d".to_string()
+}
+
+/// Default number of pairs `subject_cooccurrence` returns.
+fn default_top_pairs() -> usize {
+    20
+}
+
+/// Default value for flags that are on unless disabled.
+fn default_true() -> bool {
+    true
+}
+
 /// Arguments for creating a new accession (crawl).
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct CreateAccessionCrawlArgs {
     /// The URL to crawl.
     pub url: String,
     /// Language of the metadata. Use "english" for English text, "arabic" for Arabic text.
+    /// When omitted, it is detected from the title and description.
+    #[serde(default)]
     pub metadata_language: MetadataLanguage,
     /// Title of the accession. Provide English text if metadata_language is "english", Arabic text if "arabic".
     pub metadata_title: String,
     /// Time period related to the accession (ISO 8601, e.g. "2026-02-01T00:00:00" - do NOT include the "Z" suffix).
     pub metadata_time: String,
     /// List of subject IDs.
-    pub metadata_subjects: Vec<i32>,
-    /// Whether the accession is private.
-    pub is_private: bool,
+    #[serde(default)]
+    pub metadata_subjects: Vec<i64>,
+    /// Subject names in the metadata language, resolved to IDs and added to metadata_subjects.
+    #[serde(default)]
+    pub metadata_subject_names: Vec<String>,
+    /// How metadata_subject_names are resolved: "lookup" (existing subjects only, the
+    /// default) or "find_or_create" (create subjects that do not exist yet).
+    #[serde(default)]
+    pub subject_name_mode: SubjectNameMode,
+    /// Whether the accession is private. When omitted, the server's default applies
+    /// (public unless it runs with `--default-private`).
+    #[serde(default)]
+    #[schemars(with = "bool")]
+    pub is_private: Option<bool>,
     /// Format of the metadata.
     pub metadata_format: DublinMetadataFormat,
     /// Optional browser profile for specific sites.
@@ -78,10 +194,10 @@ pub struct CreateAccessionCrawlArgs {
     pub s3_filename: Option<String>,
     /// List of contributor IDs.
     #[serde(default)]
-    pub metadata_contributor_ids: Vec<i32>,
+    pub metadata_contributor_ids: Vec<i64>,
     /// List of contributor role IDs - must be 1:1 with contributors (same length).
     #[serde(default)]
-    pub metadata_contributor_role_ids: Vec<Option<i32>>,
+    pub metadata_contributor_role_ids: Vec<Option<i64>>,
     /// Creator ID.
     #[serde(default = "default_id")]
     pub metadata_creator_id: i64,
@@ -91,10 +207,235 @@ pub struct CreateAccessionCrawlArgs {
     /// Whether to send email notification after crawl completes.
     #[serde(default)]
     pub send_email_notification: bool,
+    /// Unique key for this crawl request, e.g. a UUID. Retries that reuse the key are not
+    /// crawled twice by the API. Leave empty to send none.
+    #[serde(default)]
+    pub idempotency_key: String,
+    /// Crawl even if an accession of the same URL was created recently. By default the
+    /// recent accession is returned instead of starting a duplicate crawl.
+    #[serde(default)]
+    pub force: bool,
+    /// Return the existing accession, with already_existed true, instead of an error when
+    /// the URL is already archived: either found by the recent-crawl check or rejected by
+    /// the API with 409 Conflict. Set to false to skip the recent-crawl check and report
+    /// a 409 as an error.
+    #[serde(default = "default_true")]
+    pub dedupe: bool,
+}
+
+/// Arguments for registering an accession backed by an already-uploaded WACZ.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RegisterAccessionFromS3Args {
+    /// Object key of the uploaded WACZ in the archive's bucket, e.g. "captures/2024/site.wacz".
+    pub s3_filename: String,
+    /// Original URL of the captured page, recorded as the accession's seed URL. It is not crawled.
+    pub source_url: String,
+    /// Language of the metadata. Use "english" for English text, "arabic" for Arabic text.
+    /// When omitted, it is detected from the title and description.
+    #[serde(default)]
+    pub metadata_language: MetadataLanguage,
+    /// Title of the accession. Provide English text if metadata_language is "english", Arabic text if "arabic".
+    pub metadata_title: String,
+    /// Time period related to the accession (ISO 8601, e.g. "2026-02-01T00:00:00" - do NOT include the "Z" suffix).
+    pub metadata_time: String,
+    /// List of subject IDs.
+    pub metadata_subjects: Vec<i64>,
+    /// Whether the accession is private. When omitted, the server's default applies
+    /// (public unless it runs with `--default-private`).
+    #[serde(default)]
+    #[schemars(with = "bool")]
+    pub is_private: Option<bool>,
+    /// Description of the accession. Provide English text if metadata_language is "english", Arabic text if "arabic".
+    #[serde(default)]
+    pub metadata_description: Option<String>,
+    /// List of contributor IDs.
+    #[serde(default)]
+    pub metadata_contributor_ids: Vec<i64>,
+    /// List of contributor role IDs - must be 1:1 with contributors (same length).
+    #[serde(default)]
+    pub metadata_contributor_role_ids: Vec<Option<i64>>,
+    /// Creator ID.
+    #[serde(default = "default_id")]
+    pub metadata_creator_id: i64,
+    /// Location ID.
+    #[serde(default = "default_id")]
+    pub metadata_location_id: i64,
+    /// Unique key for this request, e.g. a UUID. Retries that reuse the key are not
+    /// registered twice by the API. Leave empty to send none.
+    #[serde(default)]
+    pub idempotency_key: String,
 }
 
-/// Arguments for listing accessions.
+/// Arguments for checking a URL before crawling it.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PreviewCrawlTargetArgs {
+    /// The URL that would be crawled.
+    pub url: String,
+    /// Maximum number of redirects to follow. Defaults to 10.
+    #[serde(default = "default_max_redirects")]
+    pub max_redirects: u32,
+}
+
+/// One redirect followed while previewing a crawl target.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct RedirectHop {
+    /// The URL that answered with a redirect.
+    pub url: String,
+    /// The redirect status code, e.g. 301.
+    pub status: u16,
+}
+
+/// What the target site's robots.txt says about a URL.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct RobotsTxtCheck {
+    /// URL of the robots.txt file.
+    pub url: String,
+    /// Whether a robots.txt file was found.
+    pub found: bool,
+    /// Whether crawlers matching `User-agent: *` may fetch the URL. Missing when robots.txt
+    /// could not be fetched.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed: Option<bool>,
+    /// The Allow or Disallow rule that decided the verdict, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matched_rule: Option<String>,
+}
+
+/// Result of the `preview_crawl_target` tool.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CrawlTargetPreview {
+    /// The URL that was checked.
+    pub url: String,
+    /// The URL reached after following redirects.
+    pub final_url: String,
+    /// HTTP status of the final response. Missing when the target could not be reached.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Whether the final response was a 2xx success.
+    pub reachable: bool,
+    /// Content-Type of the final response.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// Whether any redirect was followed.
+    pub redirected: bool,
+    /// Redirects followed, in order.
+    pub redirects: Vec<RedirectHop>,
+    /// Whether the redirect limit stopped the chain before a final response.
+    pub redirect_limit_reached: bool,
+    /// Why the target could not be reached, if it could not.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// What robots.txt says about the final URL.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub robots_txt: Option<RobotsTxtCheck>,
+    /// Browser profile the crawl likely needs, e.g. facebook for Facebook pages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggested_browser_profile: Option<BrowserProfile>,
+}
+
+/// Arguments for estimating the size of a crawl.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EstimateCrawlSizeArgs {
+    /// The URL a crawl would archive.
+    pub url: String,
+}
+
+/// How much an estimate can be relied on.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EstimateConfidence {
+    /// The target's size is known from its Content-Length.
+    High,
+    /// Derived from the page and an average size per linked resource.
+    Medium,
+    /// The page could not be read, or is built by scripts the probe does not run.
+    Low,
+}
+
+/// Result of the `estimate_crawl_size` tool.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CrawlSizeEstimate {
+    /// The URL that was probed.
+    pub url: String,
+    /// The URL reached after following redirects.
+    pub final_url: String,
+    /// HTTP status of the probe. Missing when the target could not be reached.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Content-Type of the target.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// Size of the target in bytes, from its Content-Length or the page read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_length: Option<u64>,
+    /// Whether the site answered a HEAD request; unset when it could not be reached at all.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head_supported: Option<bool>,
+    /// Distinct images, scripts, stylesheets, frames and media the page links to.
+    pub linked_resources: usize,
+    /// Distinct links to other pages on the same site.
+    pub same_site_links: usize,
+    /// Pages a crawl of this URL captures: the seed page itself.
+    pub estimated_pages: usize,
+    /// Rough size of the WACZ file in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_wacz_bytes: Option<u64>,
+    /// Plausible range of the WACZ size, `[low, high]` in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_wacz_range: Option<[u64; 2]>,
+    /// How much the estimate can be relied on.
+    pub confidence: EstimateConfidence,
+    /// What the estimate is based on and what it leaves out.
+    pub notes: Vec<String>,
+    /// Browser profile the crawl likely needs, e.g. facebook for Facebook pages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggested_browser_profile: Option<BrowserProfile>,
+    /// Why the target could not be probed, if it could not.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Arguments for diagnosing an accession's crawl.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GetCrawlDiagnosticsArgs {
+    /// The accession ID. Private accessions are found too.
+    pub id: i64,
+    /// For failed crawls, also request the seed URL directly, as preview_crawl_target does,
+    /// to look for the cause. Set to false to skip contacting the site.
+    #[serde(default = "default_true")]
+    pub check_target: bool,
+}
+
+/// Explanation of an accession's crawl outcome.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CrawlDiagnostics {
+    /// The ID of the accession.
+    pub accession_id: i64,
+    /// The URL that was crawled.
+    pub seed_url: String,
+    /// Status of the crawl.
+    pub crawl_status: CrawlStatus,
+    /// Timestamp when the crawl occurred.
+    pub crawl_timestamp: String,
+    /// Crawl identifier, for looking the crawl up in the crawler's own logs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crawl_id: Option<String>,
+    /// Job run identifier, for looking the crawl up in the crawler's own logs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_run_id: Option<String>,
+    /// Whether the crawl ended in Error or BadCrawl.
+    pub failed: bool,
+    /// What the status means.
+    pub explanation: String,
+    /// Likely causes found and what to try next, most specific first.
+    pub suggestions: Vec<String>,
+    /// Result of checking the seed URL now, for failed crawls with check_target set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<CrawlTargetPreview>,
+}
+
+/// Arguments for listing accessions.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ListAccessionsArgs {
     /// Page number for pagination.
@@ -106,48 +447,77 @@ pub struct ListAccessionsArgs {
     /// Language filter for metadata.
     #[serde(default)]
     pub lang: MetadataLanguage,
+    /// Return accessions with metadata in either language, ignoring `lang`, and keep both the
+    /// English and Arabic variant of every field. Each item also gets a combined `title`
+    /// (English, falling back to Arabic).
+    #[serde(default, alias = "include_both_languages")]
+    pub include_both_languages: bool,
     /// Filter by specific metadata subject IDs.
-    #[serde(default)]
-    pub metadata_subjects: Vec<i32>,
+    #[serde(default, alias = "metadata_subjects")]
+    pub metadata_subjects: Vec<i64>,
     /// Whether the subject filter should be inclusive.
-    #[serde(default)]
+    #[serde(default, alias = "metadata_subjects_inclusive_filter")]
     pub metadata_subjects_inclusive_filter: bool,
     /// Filter by specific metadata location IDs.
-    #[serde(default)]
-    pub metadata_locations: Vec<i32>,
+    #[serde(default, alias = "metadata_locations")]
+    pub metadata_locations: Vec<i64>,
     /// Filter by specific metadata creator IDs.
-    #[serde(default)]
-    pub metadata_creators: Vec<i32>,
+    #[serde(default, alias = "metadata_creators")]
+    pub metadata_creators: Vec<i64>,
     /// Filter by specific metadata contributor IDs.
-    #[serde(default)]
-    pub metadata_contributors: Vec<i32>,
+    #[serde(default, alias = "metadata_contributors")]
+    pub metadata_contributors: Vec<i64>,
     /// Whether the contributor filter should be inclusive.
-    #[serde(default)]
+    #[serde(default, alias = "metadata_contributors_inclusive_filter")]
     pub metadata_contributors_inclusive_filter: bool,
     /// Filter by specific metadata contributor role IDs.
-    #[serde(default)]
-    pub metadata_contributor_roles: Vec<i32>,
+    #[serde(default, alias = "metadata_contributor_roles")]
+    pub metadata_contributor_roles: Vec<i64>,
     /// Whether the contributor role filter should be inclusive.
-    #[serde(default)]
+    #[serde(default, alias = "metadata_contributor_roles_inclusive_filter")]
     pub metadata_contributor_roles_inclusive_filter: bool,
     /// General query term to search for.
-    #[serde(default)]
+    #[serde(default, alias = "query_term")]
     pub query_term: String,
     /// Filter by URL.
-    #[serde(default)]
+    #[serde(default, alias = "url_filter")]
     pub url_filter: String,
-    /// Start date filter.
-    #[serde(default)]
+    /// Start date filter: YYYY-MM-DD, YYYY-MM, YYYY, or relative forms such as "today", "last month" or "7 days ago". Periods start on their first day.
+    #[serde(default, alias = "date_from")]
     pub date_from: String,
-    /// End date filter.
-    #[serde(default)]
+    /// End date filter, in the same forms as date_from. Periods end on their last day, so "2024" includes all of 2024.
+    #[serde(default, alias = "date_to")]
     pub date_to: String,
     /// Filter by location string.
     #[serde(default)]
     pub location: String,
-    /// Whether to include private accessions.
-    #[serde(default)]
+    /// List private instead of public accessions. list_accessions rejects true (use
+    /// list_private_accessions, which always lists private accessions and ignores this field);
+    /// export_accessions_csv exports private accessions when it is true.
+    #[serde(default, alias = "is_private")]
     pub is_private: bool,
+    /// Field to sort by: one of "crawl_timestamp", "dublin_metadata_date" or "id".
+    /// Leave empty to use the API's default ordering.
+    #[serde(default, alias = "sort_by")]
+    pub sort_by: String,
+    /// Sort direction, only applied when `sort_by` is set.
+    #[serde(default, alias = "sort_order")]
+    pub sort_order: SortOrder,
+    /// Return only the total number of matching items instead of the items themselves.
+    #[serde(default, alias = "count_only")]
+    pub count_only: bool,
+    /// Accession fields to include in each returned item, e.g. ["id", "title_en", "crawl_status"].
+    /// Leave empty to return every field.
+    #[serde(default)]
+    pub fields: Vec<String>,
+    /// "full" (the default) returns every field; "summary" returns only id, title (English,
+    /// falling back to Arabic), seed_url and crawl_status. Cannot be combined with fields.
+    #[serde(default)]
+    pub verbosity: Verbosity,
+    /// Skip the server's default lang and metadata_subjects filters (--default-lang,
+    /// --default-subjects) for this call, so unset filters match every language and subject.
+    #[serde(default, alias = "ignore_defaults")]
+    pub ignore_defaults: bool,
 }
 
 impl Default for ListAccessionsArgs {
@@ -156,6 +526,7 @@ impl Default for ListAccessionsArgs {
             page: -1,
             per_page: -1,
             lang: MetadataLanguage::default(),
+            include_both_languages: false,
             metadata_subjects: Vec::new(),
             metadata_subjects_inclusive_filter: false,
             metadata_locations: Vec::new(),
@@ -170,6 +541,12 @@ impl Default for ListAccessionsArgs {
             date_to: String::new(),
             location: String::new(),
             is_private: false,
+            sort_by: String::new(),
+            sort_order: SortOrder::default(),
+            count_only: false,
+            fields: Vec::new(),
+            verbosity: Verbosity::default(),
+            ignore_defaults: false,
         }
     }
 }
@@ -187,47 +564,322 @@ pub struct ListSubjectsArgs {
     /// Language for subjects - REQUIRED field.
     pub lang: MetadataLanguage,
     /// Filter subjects by collection ID.
-    #[serde(default = "default_collection_id")]
-    pub in_collection_id: i32,
+    #[serde(default = "default_collection_id", alias = "in_collection_id")]
+    pub in_collection_id: i64,
+    /// Search term to filter subjects by name. Leave empty to list all subjects.
+    #[serde(default, alias = "query_term")]
+    pub query_term: String,
+    /// Return only the total number of matching items instead of the items themselves.
+    #[serde(default, alias = "count_only")]
+    pub count_only: bool,
 }
 
 /// Simple arguments containing only an ID.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct IdArgs {
     /// The unique identifier.
-    pub id: i32,
+    pub id: i64,
+}
+
+/// Arguments for exporting accessions as CSV.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ExportAccessionsCsvArgs {
+    /// Filters selecting the accessions to export. Every matching page is
+    /// fetched; `count_only` and `fields` are ignored.
+    #[serde(flatten)]
+    pub filters: ListAccessionsArgs,
+    /// File to write the CSV to. Leave empty to return the CSV text inline.
+    #[serde(default)]
+    pub dest_path: String,
+}
+
+/// Arguments for exporting accessions as newline-delimited JSON.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ExportAccessionsNdjsonArgs {
+    /// Filters selecting the accessions to export. Every matching page is
+    /// fetched; `count_only` and `fields` are ignored.
+    #[serde(flatten)]
+    pub filters: ListAccessionsArgs,
+    /// File on the server host to write to, one accession JSON object per line.
+    pub dest_path: String,
+}
+
+/// Result of an NDJSON export.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct NdjsonExport {
+    /// File the accessions were written to.
+    pub path: String,
+    /// Number of accessions written.
+    pub items: u64,
+}
+
+/// Arguments for looking up accessions by their captured URL.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GetAccessionByUrlArgs {
+    /// The exact seed URL that was crawled.
+    pub url: String,
+    /// Set to true to search private accessions instead of public ones.
+    #[serde(default)]
+    pub is_private: bool,
+}
+
+/// Arguments for downloading an accession's WACZ file.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DownloadWaczArgs {
+    /// The ID of the accession to download.
+    pub id: i64,
+    /// File to write the archive (WACZ or WARC) to on the server host.
+    pub dest_path: String,
+    /// Set to true for private accessions.
+    #[serde(default)]
+    pub is_private: bool,
+}
+
+/// Arguments for reading the text of an accession's archived page.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GetAccessionContentArgs {
+    /// The ID of the accession.
+    pub id: i64,
+    /// Set to true for private accessions.
+    #[serde(default)]
+    pub is_private: bool,
+    /// Maximum number of characters of text to return. Use -1 for the default (20000).
+    #[serde(default = "default_pagination")]
+    pub max_chars: i64,
+}
+
+/// Arguments for fetching several accessions in one call.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GetAccessionsArgs {
+    /// The accession IDs to fetch.
+    pub ids: Vec<i64>,
+}
+
+/// Arguments for setting the privacy of several accessions.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SetAccessionsPrivacyArgs {
+    /// The accession IDs to update.
+    pub ids: Vec<i64>,
+    /// The privacy to set: true makes the accessions private, false makes them public.
+    pub is_private: bool,
+}
+
+/// Arguments for an audited bulk privacy change.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct BulkPrivacyChangeArgs {
+    /// The accession IDs to move.
+    pub ids: Vec<i64>,
+    /// The privacy to set: true makes the accessions private, false makes them public.
+    pub is_private: bool,
+    /// Who is making the change, e.g. a moderator's name or email, recorded in the audit log.
+    /// Leave empty to record no actor.
+    #[serde(default)]
+    pub actor: String,
 }
 
 /// Arguments for updating an accession.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct UpdateAccessionArgs {
     /// The ID of the accession to update.
-    pub id: i32,
+    pub id: i64,
     /// Privacy status.
     pub is_private: bool,
     /// Description of the accession. Provide English text if metadata_language is "english", Arabic text if "arabic".
     #[serde(default)]
     pub metadata_description: String,
     /// Language of the metadata. Use "english" for English text, "arabic" for Arabic text.
+    /// When omitted, it is detected from the title and description.
+    #[serde(default)]
     pub metadata_language: MetadataLanguage,
     /// List of subject IDs.
-    pub metadata_subjects: Vec<i32>,
+    #[serde(default)]
+    pub metadata_subjects: Vec<i64>,
+    /// Subject names in the metadata language, resolved to IDs and added to metadata_subjects.
+    #[serde(default)]
+    pub metadata_subject_names: Vec<String>,
+    /// How metadata_subject_names are resolved: "lookup" (existing subjects only, the
+    /// default) or "find_or_create" (create subjects that do not exist yet).
+    #[serde(default)]
+    pub subject_name_mode: SubjectNameMode,
     /// Time period related to the accession.
     pub metadata_time: String,
     /// Title of the accession. Provide English text if metadata_language is "english", Arabic text if "arabic".
     pub metadata_title: String,
     /// List of contributor IDs.
     #[serde(default)]
-    pub metadata_contributor_ids: Vec<i32>,
+    pub metadata_contributor_ids: Vec<i64>,
     /// List of contributor role IDs - must be 1:1 with contributors (same length).
     #[serde(default)]
-    pub metadata_contributor_role_ids: Vec<Option<i32>>,
+    pub metadata_contributor_role_ids: Vec<Option<i64>>,
     /// Creator ID.
     #[serde(default = "default_id")]
     pub metadata_creator_id: i64,
     /// Location ID.
     #[serde(default = "default_id")]
     pub metadata_location_id: i64,
+    /// The accession's crawl_timestamp as you last read it. When set, the update is refused
+    /// with a conflict if the accession's current crawl_timestamp differs. Leave empty to skip.
+    #[serde(default)]
+    pub expected_crawl_timestamp: String,
+    /// The etag returned by get_accession. When set, it is sent as If-Match, so the update
+    /// is refused with a conflict if the accession changed since that read. Leave empty to skip.
+    #[serde(default)]
+    pub expected_etag: String,
+}
+
+/// Fields to change on an existing accession; `None` keeps the current value.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Default, Clone)]
+pub struct UpdateAccessionPatch {
+    /// Language whose title, description, subjects, contributors, creator and
+    /// location are patched. Leave as "none" to detect it from a patched title or
+    /// description, or else use the accession's English metadata if it has any,
+    /// otherwise its Arabic metadata.
+    #[serde(default)]
+    pub metadata_language: MetadataLanguage,
+    /// New privacy status.
+    #[serde(default)]
+    pub is_private: Option<bool>,
+    /// New title, in the patched language.
+    #[serde(default)]
+    pub metadata_title: Option<String>,
+    /// New description, in the patched language.
+    #[serde(default)]
+    pub metadata_description: Option<String>,
+    /// New time period, in ISO 8601 format without timezone.
+    #[serde(default)]
+    pub metadata_time: Option<String>,
+    /// New list of subject IDs, replacing the current list.
+    #[serde(default)]
+    pub metadata_subjects: Option<Vec<i64>>,
+    /// New list of contributor IDs, replacing the current list.
+    #[serde(default)]
+    pub metadata_contributor_ids: Option<Vec<i64>>,
+    /// New contributor role IDs - must be 1:1 with the contributor IDs.
+    #[serde(default)]
+    pub metadata_contributor_role_ids: Option<Vec<Option<i64>>>,
+    /// New creator ID.
+    #[serde(default)]
+    pub metadata_creator_id: Option<i64>,
+    /// New location ID.
+    #[serde(default)]
+    pub metadata_location_id: Option<i64>,
+}
+
+/// Arguments for partially updating an accession.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PatchAccessionArgs {
+    /// The ID of the accession to patch.
+    pub id: i64,
+    /// Fields to change; omitted fields keep their current values.
+    #[serde(flatten)]
+    pub patch: UpdateAccessionPatch,
+    /// The accession's crawl_timestamp as you last read it. When set, the patch is refused
+    /// with a conflict if the accession's current crawl_timestamp differs. Leave empty to skip.
+    #[serde(default)]
+    pub expected_crawl_timestamp: String,
+    /// The etag returned by get_accession. When set, it is sent as If-Match, so the patch
+    /// is refused with a conflict if the accession changed since that read. Leave empty to skip.
+    #[serde(default)]
+    pub expected_etag: String,
+}
+
+/// Arguments for re-crawling an existing accession.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RecrawlAccessionArgs {
+    /// The ID of the accession whose seed URL is crawled again.
+    pub id: i64,
+    /// Metadata overrides for the new accession; omitted fields are copied from the original.
+    #[serde(flatten)]
+    pub overrides: UpdateAccessionPatch,
+    /// Optional browser profile for specific sites, e.g. facebook when the first crawl failed
+    /// without it. Not applied when the seed URL is on another site.
+    #[serde(default)]
+    pub browser_profile: Option<BrowserProfile>,
+    /// Whether to send email notification after crawl completes.
+    #[serde(default)]
+    pub send_email_notification: bool,
+}
+
+/// Arguments for re-crawling every failed accession matching some filters.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RetryFailedCrawlsArgs {
+    /// Only retry accessions crawled on or after this date: YYYY-MM-DD, YYYY-MM, YYYY, or
+    /// relative forms such as "7 days ago".
+    #[serde(default)]
+    pub date_from: String,
+    /// Only retry accessions crawled on or before this date, in the same forms as date_from.
+    #[serde(default)]
+    pub date_to: String,
+    /// Only retry accessions tagged with these subject IDs.
+    #[serde(default)]
+    pub metadata_subjects: Vec<i64>,
+    /// Retry private accessions instead of public ones.
+    #[serde(default)]
+    pub is_private: bool,
+    /// Browser profile for the re-crawls, e.g. facebook when the failures were Facebook pages.
+    /// Only applied to seed URLs on the site the profile is meant for. Defaults to none.
+    #[serde(default)]
+    pub browser_profile: BrowserProfileChoice,
+    /// Whether to send an email notification after each crawl completes.
+    #[serde(default)]
+    pub send_email_notification: bool,
+    /// List the accessions that would be retried without starting any crawl.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Outcome of retrying one failed crawl.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryCrawlStatus {
+    /// Dry run: the accession would be re-crawled.
+    WouldRetry,
+    /// A new crawl was started.
+    Retried,
+    /// The crawl could not be started.
+    Failed,
+    /// The call was cancelled before this crawl's result arrived, so it may
+    /// or may not have started.
+    Cancelled,
+}
+
+/// Result for one failed accession in `retry_failed_crawls`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RetryCrawlResult {
+    /// ID of the failed accession.
+    pub id: i64,
+    /// The seed URL crawled again.
+    pub seed_url: String,
+    /// Status of the failed crawl: Error or BadCrawl.
+    pub crawl_status: CrawlStatus,
+    /// What happened to the accession.
+    pub status: RetryCrawlStatus,
+    /// ID of the new accession, when the API's response includes it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_accession_id: Option<i64>,
+    /// Why browser_profile was left out, or that the site needs one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    /// Error message, when the crawl could not be started.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Response of `retry_failed_crawls`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RetryFailedCrawlsResponse {
+    /// Whether no crawl was started because of `dry_run` or `--dry-run`.
+    pub dry_run: bool,
+    /// Number of accessions matching the filters.
+    pub accessions_scanned: usize,
+    /// Number of re-crawls started.
+    pub retried: usize,
+    /// Number of failed accessions that could not be re-crawled.
+    pub failed: usize,
+    /// Whether the call was cancelled before every re-crawl was started.
+    pub cancelled: bool,
+    /// One result per failed accession, by ID.
+    pub results: Vec<RetryCrawlResult>,
 }
 
 /// Arguments for creating a metadata subject.
@@ -239,20 +891,33 @@ pub struct CreateSubjectArgs {
     pub metadata_subject: String,
 }
 
+/// Arguments for creating several metadata subjects in one call.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CreateSubjectsArgs {
+    /// The subjects to create.
+    pub subjects: Vec<CreateSubjectRequest>,
+    /// Skip subjects whose name already exists in the same language instead of creating duplicates.
+    #[serde(default)]
+    pub skip_existing: bool,
+}
+
 /// Arguments for deleting a metadata subject.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct DeleteSubjectArgs {
     /// The ID of the subject to delete.
-    pub id: i32,
+    pub id: i64,
     /// The language of the subject.
     pub lang: MetadataLanguage,
+    /// Delete even if accessions still reference the subject. Also skips the reference check.
+    #[serde(default)]
+    pub force: bool,
 }
 
 /// Arguments for updating a metadata subject.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct UpdateSubjectArgs {
     /// The ID of the subject to update.
-    pub id: i32,
+    pub id: i64,
     /// Language of the subject.
     pub lang: MetadataLanguage,
     /// The subject name/term.
@@ -260,7 +925,7 @@ pub struct UpdateSubjectArgs {
 }
 
 /// Request body for updating an accession.
-#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct UpdateAccessionRequest {
     /// Privacy status.
     pub is_private: bool,
@@ -270,17 +935,17 @@ pub struct UpdateAccessionRequest {
     /// Language of the metadata. Use "english" for English text, "arabic" for Arabic text.
     pub metadata_language: MetadataLanguage,
     /// List of subject IDs.
-    pub metadata_subjects: Vec<i32>,
+    pub metadata_subjects: Vec<i64>,
     /// Time period related to the accession.
     pub metadata_time: String,
     /// Title of the accession.
     pub metadata_title: String,
     /// List of contributor IDs.
     #[serde(default)]
-    pub metadata_contributor_ids: Vec<i32>,
+    pub metadata_contributor_ids: Vec<i64>,
     /// List of contributor role IDs - must be 1:1 with contributors (same length).
     #[serde(default)]
-    pub metadata_contributor_role_ids: Vec<Option<i32>>,
+    pub metadata_contributor_role_ids: Vec<Option<i64>>,
     /// Creator ID.
     pub metadata_creator_id: Option<i64>,
     /// Location ID.
@@ -288,7 +953,7 @@ pub struct UpdateAccessionRequest {
 }
 
 /// Request body for creating a metadata subject.
-#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct CreateSubjectRequest {
     /// Language of the subject.
     pub lang: MetadataLanguage,
@@ -324,7 +989,7 @@ pub struct CreateAccessionCrawlRequest {
     /// Time period related to the accession (ISO 8601).
     pub metadata_time: String,
     /// List of subject IDs.
-    pub metadata_subjects: Vec<i32>,
+    pub metadata_subjects: Vec<i64>,
     /// Whether the accession is private.
     pub is_private: bool,
     /// Format of the metadata.
@@ -340,10 +1005,10 @@ pub struct CreateAccessionCrawlRequest {
     pub s3_filename: Option<String>,
     /// List of contributor IDs.
     #[serde(default)]
-    pub metadata_contributor_ids: Vec<i32>,
+    pub metadata_contributor_ids: Vec<i64>,
     /// List of contributor role IDs - must be 1:1 with contributors (same length).
     #[serde(default)]
-    pub metadata_contributor_role_ids: Vec<Option<i32>>,
+    pub metadata_contributor_role_ids: Vec<Option<i64>>,
     /// Creator ID.
     pub metadata_creator_id: Option<i64>,
     /// Location ID.
@@ -351,6 +1016,175 @@ pub struct CreateAccessionCrawlRequest {
     /// Whether to send email notification after crawl completes.
     #[serde(default)]
     pub send_email_notification: bool,
+    /// Sent as the `Idempotency-Key` header rather than in the body.
+    #[serde(skip)]
+    #[schemars(skip)]
+    pub idempotency_key: Option<String>,
+}
+
+/// Response of `create_accession_crawl`, whether or not a crawl was started.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CreateCrawlResponse {
+    /// True when no crawl was started because the URL was already archived.
+    pub already_existed: bool,
+    /// ID of the existing accession, or of the new one when the API's response includes it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accession_id: Option<i64>,
+    /// When the existing accession was crawled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crawl_timestamp: Option<String>,
+    /// Crawl status of the existing accession.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crawl_status: Option<CrawlStatus>,
+    /// The API's response to a new crawl: JSON when it parses as JSON, a string otherwise.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_response: Option<serde_json::Value>,
+    /// Explanation of what happened.
+    pub message: String,
+}
+
+/// Outcome of `cancel_crawl`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CancelCrawlResponse {
+    /// ID of the accession.
+    pub accession_id: i64,
+    /// True when the pending accession was deleted.
+    pub cancelled: bool,
+    /// Crawl status of the accession when it was last read.
+    pub crawl_status: CrawlStatus,
+    /// Explanation of what happened.
+    pub message: String,
+}
+
+/// An accession flagged to revisit, stored in the `--bookmarks-file`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Bookmark {
+    /// ID of the bookmarked accession.
+    pub accession_id: i64,
+    /// Why the accession was bookmarked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// When the bookmark was first added (UTC, RFC 3339).
+    pub created_at: String,
+}
+
+/// Arguments for bookmarking an accession.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct BookmarkAccessionArgs {
+    /// ID of the accession to bookmark.
+    pub id: i64,
+    /// Optional note on why it is worth revisiting. Bookmarking an accession again replaces
+    /// its note; leave empty for none.
+    #[serde(default)]
+    pub note: String,
+}
+
+/// Outcome of `bookmark_accession`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct BookmarkAccessionResponse {
+    /// The stored bookmark.
+    pub bookmark: Bookmark,
+    /// False when the accession was already bookmarked and only its note changed.
+    pub created: bool,
+}
+
+/// Arguments for listing bookmarks.
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ListBookmarksArgs {
+    /// Fetch each bookmarked accession's current title, URL and crawl status from the API.
+    #[serde(default)]
+    pub hydrate: bool,
+}
+
+/// A bookmark, with the accession's current details when hydrated.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct BookmarkEntry {
+    /// The stored bookmark.
+    #[serde(flatten)]
+    pub bookmark: Bookmark,
+    /// The accession as it is now, when `hydrate` was set and the fetch succeeded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accession: Option<RecentAccession>,
+    /// Why the accession could not be fetched, e.g. because it was deleted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Response of `list_bookmarks`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListBookmarksResponse {
+    /// Bookmarks ordered by accession ID.
+    pub items: Vec<BookmarkEntry>,
+}
+
+/// Outcome of `remove_bookmark`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RemoveBookmarkResponse {
+    /// ID of the accession.
+    pub accession_id: i64,
+    /// False when the accession was not bookmarked.
+    pub removed: bool,
+}
+
+/// Arguments for fetching the next chunk of a truncated tool result.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ContinueResultArgs {
+    /// The continuation_token returned with the previous chunk.
+    pub continuation_token: String,
+}
+
+/// Arguments naming an open accession cursor.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CursorArgs {
+    /// The cursor_id returned with the previous page.
+    pub cursor_id: String,
+}
+
+/// One page of accessions read through a cursor.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AccessionCursorPage {
+    /// Cursor to pass to next_accession_page for the following page; absent after the last
+    /// page, when the cursor is closed. Each cursor_id names one page, so passing it again
+    /// returns the same page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor_id: Option<String>,
+    /// Whether more pages follow.
+    pub has_more: bool,
+    /// The page: items, num_pages, page and per_page, as list_accessions returns them.
+    #[serde(flatten)]
+    pub listing: serde_json::Value,
+}
+
+/// Where a chunk of a truncated tool result sits, returned after the chunk.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct ResultContinuation {
+    /// Position of this chunk, starting at 1.
+    pub chunk: usize,
+    /// Number of chunks in the whole result.
+    pub chunks: usize,
+    /// Length in bytes of the whole result.
+    pub total_bytes: usize,
+    /// Token to pass to continue_result for the next chunk; absent on the last chunk.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub continuation_token: Option<String>,
+}
+
+/// A single problem found while validating a request.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct ValidationProblem {
+    /// Name of the offending field.
+    pub field: String,
+    /// What is wrong and how to fix it.
+    pub message: String,
+}
+
+/// Result of validating a crawl request without submitting it.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CrawlValidationReport {
+    /// Whether the request passed every check.
+    pub valid: bool,
+    /// Problems found; empty when `valid` is true.
+    pub problems: Vec<ValidationProblem>,
 }
 
 /// Status of a web crawl.
@@ -370,7 +1204,7 @@ pub enum CrawlStatus {
 #[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct AccessionsWithMetadataResponse {
     /// Unique identifier.
-    pub id: i32,
+    pub id: i64,
     /// Whether the accession is private.
     pub is_private: bool,
     /// Status of the crawl.
@@ -396,23 +1230,23 @@ pub struct AccessionsWithMetadataResponse {
     /// English location.
     pub location_en: Option<String>,
     /// English location ID.
-    pub location_en_id: Option<i32>,
+    pub location_en_id: Option<i64>,
     /// Creator ID (English).
-    pub creator_en_id: Option<i32>,
+    pub creator_en_id: Option<i64>,
     /// Creator name (English).
     pub creator_en: Option<String>,
     /// List of subjects in English.
     pub subjects_en: Option<Vec<String>>,
     /// List of English subject IDs.
-    pub subjects_en_ids: Option<Vec<i32>>,
+    pub subjects_en_ids: Option<Vec<i64>>,
     /// List of contributors (English).
     pub contributors_en: Option<Vec<String>>,
     /// List of English contributor IDs.
-    pub contributor_en_ids: Option<Vec<i32>>,
+    pub contributor_en_ids: Option<Vec<i64>>,
     /// List of contributor roles (English).
     pub contributor_roles_en: Option<Vec<String>>,
     /// List of English contributor role IDs.
-    pub contributor_role_en_ids: Option<Vec<i32>>,
+    pub contributor_role_en_ids: Option<Vec<i64>>,
     /// Relations (English).
     pub relations_en: Option<serde_json::Value>,
     /// Arabic title.
@@ -422,23 +1256,23 @@ pub struct AccessionsWithMetadataResponse {
     /// Arabic location.
     pub location_ar: Option<String>,
     /// Arabic location ID.
-    pub location_ar_id: Option<i32>,
+    pub location_ar_id: Option<i64>,
     /// Creator ID (Arabic).
-    pub creator_ar_id: Option<i32>,
+    pub creator_ar_id: Option<i64>,
     /// Creator name (Arabic).
     pub creator_ar: Option<String>,
     /// List of subjects in Arabic.
     pub subjects_ar: Option<Vec<String>>,
     /// List of Arabic subject IDs.
-    pub subjects_ar_ids: Option<Vec<i32>>,
+    pub subjects_ar_ids: Option<Vec<i64>>,
     /// List of contributors (Arabic).
     pub contributors_ar: Option<Vec<String>>,
     /// List of Arabic contributor IDs.
-    pub contributor_ar_ids: Option<Vec<i32>>,
+    pub contributor_ar_ids: Option<Vec<i64>>,
     /// List of contributor roles (Arabic).
     pub contributor_roles_ar: Option<Vec<String>>,
     /// List of Arabic contributor role IDs.
-    pub contributor_role_ar_ids: Option<Vec<i32>>,
+    pub contributor_role_ar_ids: Option<Vec<i64>>,
     /// Relations (Arabic).
     pub relations_ar: Option<serde_json::Value>,
     /// Whether English metadata exists.
@@ -447,11 +1281,149 @@ pub struct AccessionsWithMetadataResponse {
     pub has_arabic_metadata: bool,
 }
 
-/// Response containing a list of accessions.
+#[cfg(test)]
+impl AccessionsWithMetadataResponse {
+    /// A complete public accession with an English title and no other
+    /// metadata, for tests to adjust with struct update syntax.
+    pub fn sample(id: i64) -> Self {
+        Self {
+            id,
+            is_private: false,
+            crawl_status: CrawlStatus::Complete,
+            crawl_timestamp: "2024-05-01T12:00:00".to_string(),
+            crawl_id: None,
+            org_id: None,
+            job_run_id: None,
+            seed_url: "https://example.com".to_string(),
+            dublin_metadata_date: "2024-05-01T00:00:00".to_string(),
+            dublin_metadata_format: DublinMetadataFormat::Wacz,
+            title_en: Some("Example".to_string()),
+            description_en: None,
+            location_en: None,
+            location_en_id: None,
+            creator_en_id: None,
+            creator_en: None,
+            subjects_en: None,
+            subjects_en_ids: None,
+            contributors_en: None,
+            contributor_en_ids: None,
+            contributor_roles_en: None,
+            contributor_role_en_ids: None,
+            relations_en: None,
+            title_ar: None,
+            description_ar: None,
+            location_ar: None,
+            location_ar_id: None,
+            creator_ar_id: None,
+            creator_ar: None,
+            subjects_ar: None,
+            subjects_ar_ids: None,
+            contributors_ar: None,
+            contributor_ar_ids: None,
+            contributor_roles_ar: None,
+            contributor_role_ar_ids: None,
+            relations_ar: None,
+            has_english_metadata: true,
+            has_arabic_metadata: false,
+        }
+    }
+}
+
+/// Response containing a list of accessions.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListAccessionsResponse {
+    /// List of accessions.
+    pub items: Vec<AccessionsWithMetadataResponse>,
+    /// Total number of pages.
+    pub num_pages: i64,
+    /// Current page number.
+    pub page: i64,
+    /// Items per page.
+    pub per_page: i64,
+}
+
+/// Arguments for listing recently crawled accessions.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RecentAccessionsArgs {
+    /// How far back to look: a number followed by h (hours), d (days) or w (weeks), e.g. "24h",
+    /// "7d" or "2w". Defaults to "7d".
+    #[serde(default = "default_since")]
+    pub since: String,
+    /// List private instead of public accessions.
+    #[serde(default)]
+    pub is_private: bool,
+    /// Page number for pagination.
+    #[serde(default = "default_pagination")]
+    pub page: i64,
+    /// Number of items per page.
+    #[serde(default = "default_pagination")]
+    pub per_page: i64,
+}
+
+/// Arguments for counting accessions per period of crawl date.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AccessionsTimelineArgs {
+    /// Start of the range, by crawl date: YYYY-MM-DD, YYYY-MM, YYYY, or relative forms such as
+    /// "last month" or "6 months ago".
+    pub date_from: String,
+    /// End of the range, in the same forms as date_from; periods end on their last day.
+    /// Defaults to today.
+    #[serde(default)]
+    pub date_to: String,
+    /// Bucket width: day, week (starting Monday) or month. Defaults to month.
+    #[serde(default)]
+    pub bucket: TimelineBucket,
+    /// Count private instead of public accessions.
+    #[serde(default)]
+    pub is_private: bool,
+}
+
+/// Number of accessions crawled in one bucket of a timeline.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct TimelineCount {
+    /// First day of the bucket (YYYY-MM-DD).
+    pub start: String,
+    /// Accessions crawled from `start` until the next bucket.
+    pub count: u64,
+}
+
+/// Histogram of accessions by crawl date.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
-pub struct ListAccessionsResponse {
-    /// List of accessions.
-    pub items: Vec<AccessionsWithMetadataResponse>,
+pub struct AccessionsTimelineResponse {
+    /// First day counted (YYYY-MM-DD).
+    pub date_from: String,
+    /// Last day counted (YYYY-MM-DD).
+    pub date_to: String,
+    /// Bucket width.
+    pub bucket: TimelineBucket,
+    /// Accessions counted across all buckets.
+    pub total: u64,
+    /// One entry per bucket in the range, oldest first, including empty ones.
+    pub buckets: Vec<TimelineCount>,
+}
+
+/// An accession in brief, as listed by `recent_accessions` and `search`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RecentAccession {
+    /// Unique identifier.
+    pub id: i64,
+    /// English title, falling back to the Arabic title.
+    pub title: Option<String>,
+    /// The URL that was crawled.
+    pub seed_url: String,
+    /// Status of the crawl.
+    pub crawl_status: CrawlStatus,
+    /// Timestamp when the crawl occurred.
+    pub crawl_timestamp: String,
+}
+
+/// Accessions crawled since a point in time, newest first.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RecentAccessionsResponse {
+    /// Start of the window (UTC, RFC 3339); only accessions crawled at or after it are listed.
+    pub since: String,
+    /// Accessions crawled since then, newest first.
+    pub items: Vec<RecentAccession>,
     /// Total number of pages.
     pub num_pages: i64,
     /// Current page number.
@@ -460,6 +1432,453 @@ pub struct ListAccessionsResponse {
     pub per_page: i64,
 }
 
+impl From<AccessionsWithMetadataResponse> for RecentAccession {
+    fn from(item: AccessionsWithMetadataResponse) -> Self {
+        Self {
+            id: item.id,
+            title: [item.title_en, item.title_ar]
+                .into_iter()
+                .flatten()
+                .find(|t| !t.is_empty()),
+            seed_url: item.seed_url,
+            crawl_status: item.crawl_status,
+            crawl_timestamp: item.crawl_timestamp,
+        }
+    }
+}
+
+/// Arguments for searching accessions, collections and subjects at once.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SearchArgs {
+    /// Text to search for, e.g. "Darfur".
+    pub query_term: String,
+    /// Language to search in. When unset, accessions and collections in either language are
+    /// searched, and subjects in Arabic if the query contains Arabic letters, otherwise English.
+    #[serde(default)]
+    pub lang: MetadataLanguage,
+    /// Most matches to return per category, from 1 to 20. Defaults to 5.
+    #[serde(default = "default_search_limit")]
+    pub limit: i64,
+}
+
+/// Top matches of one category of a `search`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SearchMatches<T> {
+    /// Total number of matches, of which at most `limit` are listed.
+    pub total: i64,
+    /// Top matches, in the API's order.
+    pub items: Vec<T>,
+    /// Why this category could not be searched; the others are still reported.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Public accessions, collections and subjects matching a query.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SearchResponse {
+    /// The query searched for.
+    pub query_term: String,
+    /// Matching public accessions.
+    pub accessions: SearchMatches<RecentAccession>,
+    /// Matching public collections.
+    pub collections: SearchMatches<CollectionResponse>,
+    /// Matching subjects, in the language searched.
+    pub subjects: SearchMatches<DublinMetadataSubjectResponse>,
+}
+
+/// Response for list tools called with `count_only`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CountResponse {
+    /// Total number of items matching the filters.
+    pub total: i64,
+}
+
+/// Arguments for the archive statistics summary.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ArchiveStatsArgs {
+    /// Also break accessions down by crawl status. The API cannot filter by status, so this
+    /// pages through every accession and is much slower on large archives.
+    #[serde(default)]
+    pub include_crawl_status: bool,
+}
+
+/// Arguments for tallying the subjects used by accessions.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListUsedSubjectsArgs {
+    /// Which subject IDs to aggregate: "english" (subjects_en_ids, the default) or "arabic" (subjects_ar_ids).
+    #[serde(default)]
+    pub lang: MetadataLanguage,
+    /// Tally private accessions instead of public ones.
+    #[serde(default)]
+    pub is_private: bool,
+}
+
+/// A subject together with the number of accessions tagged with it.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+pub struct UsedSubject {
+    /// Subject ID.
+    pub id: i64,
+    /// Subject name, as reported on the accessions.
+    pub subject: Option<String>,
+    /// Number of accessions tagged with the subject.
+    pub count: i64,
+}
+
+/// Response of the `list_used_subjects` tool.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListUsedSubjectsResponse {
+    /// Number of accessions scanned.
+    pub accessions_scanned: i64,
+    /// Subjects in use, most frequent first.
+    pub items: Vec<UsedSubject>,
+}
+
+/// Arguments for counting which subjects appear together on accessions.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SubjectCooccurrenceArgs {
+    /// Which subject IDs to pair: "english" (subjects_en_ids, the default) or "arabic" (subjects_ar_ids).
+    #[serde(default)]
+    pub lang: MetadataLanguage,
+    /// Only count accessions matching this search term.
+    #[serde(default)]
+    pub query_term: String,
+    /// Only count accessions crawled on or after this date: YYYY-MM-DD, YYYY-MM, YYYY, or relative
+    /// forms such as "last month".
+    #[serde(default)]
+    pub date_from: String,
+    /// Only count accessions crawled on or before this date, in the same forms as date_from.
+    #[serde(default)]
+    pub date_to: String,
+    /// Count private accessions instead of public ones.
+    #[serde(default)]
+    pub is_private: bool,
+    /// Number of pairs to return, most frequent first. Defaults to 20.
+    #[serde(default = "default_top_pairs")]
+    pub top_n: usize,
+}
+
+/// Two subjects and the number of accessions tagged with both.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct SubjectPair {
+    /// The subject with the lower ID.
+    pub first: UsedSubject,
+    /// The subject with the higher ID.
+    pub second: UsedSubject,
+    /// Number of accessions tagged with both subjects.
+    pub count: i64,
+}
+
+/// Response of the `subject_cooccurrence` tool.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SubjectCooccurrenceResponse {
+    /// Number of accessions scanned.
+    pub accessions_scanned: i64,
+    /// Number of distinct subject pairs found on at least one accession.
+    pub distinct_pairs: usize,
+    /// The most frequent pairs, most frequent first. Each subject's count is the number of
+    /// scanned accessions tagged with it.
+    pub pairs: Vec<SubjectPair>,
+}
+
+/// Arguments for finding accessions with metadata in only one language.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FindUntranslatedAccessionsArgs {
+    /// Which translation is missing: "english" for accessions with only Arabic metadata,
+    /// "arabic" for those with only English metadata. Omit to find both.
+    #[serde(default)]
+    pub missing: MetadataLanguage,
+    /// Only check accessions matching this search term.
+    #[serde(default)]
+    pub query_term: String,
+    /// Only check accessions crawled on or after this date: YYYY-MM-DD, YYYY-MM, YYYY, or
+    /// relative forms such as "last month".
+    #[serde(default)]
+    pub date_from: String,
+    /// Only check accessions crawled on or before this date, in the same forms as date_from.
+    #[serde(default)]
+    pub date_to: String,
+    /// Check private accessions instead of public ones.
+    #[serde(default)]
+    pub is_private: bool,
+}
+
+/// An accession whose metadata exists in one language only.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct UntranslatedAccession {
+    /// Accession ID.
+    pub id: i64,
+    /// The language the metadata is missing in.
+    pub missing: MetadataLanguage,
+    /// Title in the language the metadata exists in.
+    pub title: Option<String>,
+    /// The URL that was crawled.
+    pub seed_url: String,
+}
+
+/// Response of `find_untranslated_accessions`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FindUntranslatedAccessionsResponse {
+    /// Number of accessions scanned.
+    pub accessions_scanned: usize,
+    /// Number of accessions with Arabic metadata only.
+    pub missing_english: usize,
+    /// Number of accessions with English metadata only.
+    pub missing_arabic: usize,
+    /// The accessions missing the requested translation, by ID.
+    pub accessions: Vec<UntranslatedAccession>,
+}
+
+/// How `subject_tree` groups subjects.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SubjectGrouping {
+    /// By the first letter of the name, case-insensitively.
+    #[default]
+    FirstLetter,
+    /// By the first word of the name, case-insensitively.
+    FirstWord,
+}
+
+/// Arguments for browsing the subject vocabulary as groups.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SubjectTreeArgs {
+    /// Vocabulary to group: "english" (the default) or "arabic".
+    #[serde(default)]
+    pub lang: MetadataLanguage,
+    /// How to group subjects: "first_letter" (the default) or "first_word".
+    #[serde(default)]
+    pub group_by: SubjectGrouping,
+    /// Also count the accessions tagged with each subject and group. Pages through every
+    /// accession, so it is much slower on large archives.
+    #[serde(default)]
+    pub include_counts: bool,
+    /// Count private accessions instead of public ones. Only used with include_counts.
+    #[serde(default)]
+    pub is_private: bool,
+}
+
+/// A subject within a `subject_tree` group.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct SubjectTreeEntry {
+    /// Subject ID.
+    pub id: i64,
+    /// Subject name.
+    pub subject: String,
+    /// Number of accessions tagged with the subject, when counts were requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accession_count: Option<i64>,
+}
+
+/// One group of subjects in a `subject_tree` response.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct SubjectTreeGroup {
+    /// The group key: an upper-cased first letter, a lower-cased first word, or "#" for
+    /// names that do not start with a letter or digit.
+    pub key: String,
+    /// Number of subjects in the group.
+    pub subject_count: usize,
+    /// Number of distinct accessions tagged with any subject in the group, when counts
+    /// were requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accession_count: Option<i64>,
+    /// Subjects in the group, sorted by name.
+    pub subjects: Vec<SubjectTreeEntry>,
+}
+
+/// Response of the `subject_tree` tool.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SubjectTreeResponse {
+    /// How the subjects were grouped.
+    pub group_by: SubjectGrouping,
+    /// Number of subjects in the vocabulary.
+    pub total_subjects: usize,
+    /// Number of accessions scanned for counts, when counts were requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accessions_scanned: Option<i64>,
+    /// Groups, sorted by key.
+    pub groups: Vec<SubjectTreeGroup>,
+}
+
+/// Accession counts for one visibility (public or private).
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
+pub struct AccessionCounts {
+    /// Number of accessions.
+    pub total: i64,
+    /// Number of accessions with English metadata.
+    pub with_english_metadata: i64,
+    /// Number of accessions with Arabic metadata.
+    pub with_arabic_metadata: i64,
+}
+
+/// Dashboard-style summary returned by the `archive_stats` tool.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ArchiveStatsResponse {
+    /// Number of public and private accessions together.
+    pub total: i64,
+    /// Counts for public accessions.
+    pub public: AccessionCounts,
+    /// Counts for private accessions.
+    pub private: AccessionCounts,
+    /// Number of accessions per crawl status, when requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crawl_status: Option<BTreeMap<String, i64>>,
+}
+
+/// Arguments for describing a single tool.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DescribeToolArgs {
+    /// Name of the tool, e.g. `create_accession_crawl`.
+    pub name: String,
+}
+
+/// A tool's description and the JSON Schema of its arguments.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ToolSchema {
+    /// Name of the tool.
+    pub name: String,
+    /// Description of the tool, in the server's locale.
+    pub description: String,
+    /// JSON Schema the tool's arguments must satisfy.
+    pub input_schema: serde_json::Value,
+}
+
+/// Build and configuration details reported by the `server_info` tool.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ServerInfoResponse {
+    /// Crate version of the running server.
+    pub version: String,
+    /// Git commit the binary was built from, or "unknown".
+    pub git_commit: String,
+    /// SDA API base URL, with any embedded credentials removed.
+    pub base_url: String,
+    /// MCP protocol version advertised by the server.
+    pub protocol_version: String,
+    /// Whether mutating tools are running in dry-run mode.
+    pub dry_run: bool,
+}
+
+/// Result of a single SDA API health probe.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HealthReport {
+    /// Whether the API answered the probe with a success status.
+    pub healthy: bool,
+    /// HTTP status of the probe. Missing when the API could not be reached.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// How long the probe took, in milliseconds.
+    pub latency_ms: u64,
+    /// Why the probe failed, if it did.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// When the probe was made (RFC 3339, UTC).
+    pub checked_at: String,
+}
+
+/// Default largest clock skew `check_time_sync` accepts, in seconds.
+fn default_max_skew_seconds() -> u64 {
+    30
+}
+
+/// Arguments for comparing the server's clock with the API's.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CheckTimeSyncArgs {
+    /// Largest difference between the clocks, in seconds, before a warning is given.
+    /// Defaults to 30.
+    #[serde(default = "default_max_skew_seconds")]
+    pub max_skew_seconds: u64,
+}
+
+/// Difference between this server's clock and the SDA API's.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TimeSyncResponse {
+    /// This server's time halfway through the request (RFC 3339, UTC).
+    pub local_time: String,
+    /// The time in the API's `Date` response header (RFC 3339, UTC).
+    pub api_time: String,
+    /// API time minus local time, in whole seconds: positive when the API's clock is ahead.
+    /// The `Date` header has one-second resolution, so ±1 is noise.
+    pub skew_seconds: i64,
+    /// How long the request took, in milliseconds.
+    pub round_trip_ms: u64,
+    /// The threshold the skew was compared with.
+    pub max_skew_seconds: u64,
+    /// Whether the skew is within the threshold.
+    pub in_sync: bool,
+    /// What the skew means for date filters, when it exceeds the threshold.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+/// Response of the `health_check` tool.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct HealthCheckResponse {
+    /// SDA API base URL, with any embedded credentials removed.
+    pub base_url: String,
+    /// Result of probing the API now.
+    pub current: HealthReport,
+    /// Result of the probe made at startup with `--probe-on-start`, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub startup: Option<HealthReport>,
+}
+
+/// One profile from the `--profiles-file`, as listed by `list_profiles`.
+///
+/// API keys are never included; only where the key comes from.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ProfileSummary {
+    /// Profile name, as passed to `--profile`.
+    pub name: String,
+    /// Whether this server is using the profile.
+    pub active: bool,
+    /// SDA API base URL, with any embedded credentials removed.
+    pub base_url: String,
+    /// File the API key is read from, if the profile names one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_file: Option<String>,
+    /// Whether the API key is written in the profiles file itself.
+    pub has_inline_api_key: bool,
+    /// Whether new accessions default to private.
+    pub default_private: bool,
+    /// Whether mutating tools run in dry-run mode.
+    pub dry_run: bool,
+}
+
+/// Arguments for switching the active profile.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct UseProfileArgs {
+    /// Name of the profile to use, as listed by list_profiles.
+    pub name: String,
+}
+
+/// Response of the `list_profiles` and `use_profile` tools.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListProfilesResponse {
+    /// Name of the active profile; absent without `--profiles-file`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<String>,
+    /// SDA API base URL this server is using, with any embedded credentials
+    /// removed.
+    pub base_url: String,
+    /// Every profile in the profiles file, in name order.
+    pub profiles: Vec<ProfileSummary>,
+}
+
+/// Response for mutating tools called while the server runs with `--dry-run`.
+///
+/// Describes the request that would have been sent to the API.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DryRunResponse {
+    /// Always `true`; marks the result as a simulation.
+    pub dry_run: bool,
+    /// HTTP method of the skipped request.
+    pub method: String,
+    /// Full URL of the skipped request.
+    pub url: String,
+    /// JSON body of the skipped request, if it has one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<serde_json::Value>,
+}
+
 /// Response containing a single accession and its download URL.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct GetOneAccessionResponse {
@@ -467,13 +1886,450 @@ pub struct GetOneAccessionResponse {
     pub accession: AccessionsWithMetadataResponse,
     /// URL to download the WACZ file.
     pub wacz_url: String,
+    /// URL to download the raw WARC file, for accessions stored as WARC.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warc_url: Option<String>,
+    /// The `ETag` the API sent with this accession, if any. Pass it back as
+    /// `expected_etag` on an update to have the write refused if the
+    /// accession changed since this read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+}
+
+impl GetOneAccessionResponse {
+    /// Returns the download URL matching the accession's `dublin_metadata_format`.
+    ///
+    /// WARC accessions use `warc_url` when the API reports one; otherwise the
+    /// archive is served through `wacz_url`, whatever its format.
+    pub fn archive_url(&self) -> &str {
+        match self.accession.dublin_metadata_format {
+            DublinMetadataFormat::Warc => self.warc_url.as_deref().unwrap_or(&self.wacz_url),
+            DublinMetadataFormat::Wacz => &self.wacz_url,
+        }
+    }
+}
+
+/// Arguments for comparing two accessions.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DiffAccessionsArgs {
+    /// ID of the earlier accession, e.g. the original capture. Private accessions are found too.
+    pub old_id: i64,
+    /// ID of the later accession, e.g. the re-crawl. Private accessions are found too.
+    pub new_id: i64,
+}
+
+/// A metadata field whose value differs between two accessions.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct FieldChange {
+    /// Field name; nested fields are joined with dots, e.g. `relations_en.url`.
+    pub field: String,
+    /// Value in the old accession, `null` when missing.
+    pub old: serde_json::Value,
+    /// Value in the new accession, `null` when missing.
+    pub new: serde_json::Value,
+    /// For list fields, items only in the new accession.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<serde_json::Value>,
+    /// For list fields, items only in the old accession.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<serde_json::Value>,
+}
+
+/// Field-by-field comparison of two accessions' metadata.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AccessionDiffResponse {
+    /// ID of the earlier accession.
+    pub old_id: i64,
+    /// ID of the later accession.
+    pub new_id: i64,
+    /// Whether both accessions archive the same URL.
+    pub same_seed_url: bool,
+    /// Whether the metadata is identical apart from the IDs.
+    pub identical: bool,
+    /// English and Arabic subjects only on the new accession.
+    pub subjects_added: Vec<String>,
+    /// English and Arabic subjects only on the old accession.
+    pub subjects_removed: Vec<String>,
+    /// Every field that differs, sorted by field name.
+    pub changes: Vec<FieldChange>,
+}
+
+/// Arguments for inspecting an accession's WACZ file.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct InspectWaczArgs {
+    /// The accession ID. Private accessions are found too.
+    pub id: i64,
+}
+
+/// A file listed in a WACZ's `datapackage.json`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct WaczResource {
+    /// Path inside the WACZ.
+    pub path: String,
+    /// Size in bytes, when listed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+}
+
+/// Capture metadata read from an accession's WACZ file.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WaczInspection {
+    /// The ID of the accession.
+    pub accession_id: i64,
+    /// Size of the WACZ file, in bytes.
+    pub total_bytes: u64,
+    /// Bytes downloaded to inspect it.
+    pub bytes_fetched: u64,
+    /// Whether only parts of the file were fetched with HTTP range requests. False when the
+    /// server does not support them and the whole file was downloaded.
+    pub used_range_requests: bool,
+    /// WACZ format version.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wacz_version: Option<String>,
+    /// Package title.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Software that wrote the WACZ, e.g. the crawler version.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub software: Option<String>,
+    /// When the WACZ was created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    /// URL of the main captured page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub main_page_url: Option<String>,
+    /// Capture time of the main page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub main_page_date: Option<String>,
+    /// Number of captured pages. Missing when the page list is absent or too large to fetch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_count: Option<u64>,
+    /// Earliest page capture time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_capture: Option<String>,
+    /// Latest page capture time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_capture: Option<String>,
+    /// Files listed in the WACZ's datapackage.json.
+    pub resources: Vec<WaczResource>,
+}
+
+/// Readable content of an accession's main archived page.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AccessionContentResponse {
+    /// The ID of the accession.
+    pub accession_id: i64,
+    /// URL of the captured page.
+    pub url: String,
+    /// Page title, if one was found.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Content type of the captured response.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// Extracted text of the page.
+    pub text: String,
+    /// Length of the full extracted text, in characters.
+    pub total_chars: usize,
+    /// Whether `text` was truncated to the requested `max_chars`.
+    pub truncated: bool,
+}
+
+/// Arguments for listing the pages captured in an accession's WACZ file.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListWaczPagesArgs {
+    /// The accession ID. Private accessions are found too.
+    pub id: i64,
+    /// Page of the list to return, from 0. Use -1 for the first.
+    #[serde(default = "default_pagination")]
+    pub page: i64,
+    /// Captured pages per page of the list. Use -1 for the default (100).
+    #[serde(default = "default_pagination")]
+    pub per_page: i64,
+}
+
+/// A page captured in a WACZ file.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct WaczPage {
+    /// ID to pass to get_wacz_page.
+    pub id: String,
+    /// URL of the page.
+    pub url: String,
+    /// Page title, when listed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Capture time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ts: Option<String>,
+    /// Whether the page is a crawl seed, as opposed to a page reached by following links.
+    pub seed: bool,
+}
+
+/// Pages captured in an accession's WACZ file.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WaczPagesResponse {
+    /// The ID of the accession.
+    pub accession_id: i64,
+    /// Number of captured pages in the WACZ.
+    pub page_count: usize,
+    /// The captured pages on this page of the list, seeds first.
+    pub items: Vec<WaczPage>,
+    /// This page of the list, from 0.
+    pub page: i64,
+    /// Captured pages per page of the list.
+    pub per_page: i64,
+    /// Number of pages in the list.
+    pub num_pages: i64,
+    /// Whether only the page lists were fetched with HTTP range requests. False when the
+    /// server does not support them and the whole file was downloaded.
+    pub used_range_requests: bool,
+}
+
+/// Arguments for reading one page captured in an accession's WACZ file.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GetWaczPageArgs {
+    /// The accession ID. Private accessions are found too.
+    pub id: i64,
+    /// The page's id, as returned by list_wacz_pages.
+    pub page_id: String,
+    /// Return the captured HTML (or other body) as is instead of its extracted text.
+    #[serde(default)]
+    pub html: bool,
+    /// Maximum number of characters to return. Use -1 for the default (20000).
+    #[serde(default = "default_pagination")]
+    pub max_chars: i64,
+}
+
+/// Content of one page captured in an accession's WACZ file.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WaczPageContent {
+    /// The ID of the accession.
+    pub accession_id: i64,
+    /// The page's id.
+    pub page_id: String,
+    /// URL of the captured page.
+    pub url: String,
+    /// Page title, if one was found.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Capture time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ts: Option<String>,
+    /// Content type of the captured response.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// Whether `content` is the captured body as is rather than extracted text.
+    pub html: bool,
+    /// Extracted text of the page, or its captured body with `html`.
+    pub content: String,
+    /// Length of the full content, in characters.
+    pub total_chars: usize,
+    /// Whether `content` was truncated to the requested `max_chars`.
+    pub truncated: bool,
+}
+
+/// Result of downloading an accession's archive file.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WaczDownloadResponse {
+    /// The ID of the downloaded accession.
+    pub accession_id: i64,
+    /// Path the archive was written to.
+    pub path: String,
+    /// Format of the downloaded archive.
+    pub format: DublinMetadataFormat,
+    /// Number of bytes written.
+    pub bytes: u64,
+    /// Hex-encoded SHA-256 of the downloaded bytes, for provenance records.
+    pub sha256: String,
+    /// Whether the byte count was checked against the server's `Content-Length`.
+    pub length_verified: bool,
+}
+
+/// Response for a batch accession fetch.
+///
+/// Failures for individual IDs are reported in `errors` rather than failing
+/// the whole batch.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GetAccessionsResponse {
+    /// Accessions that were fetched successfully, in request order.
+    pub items: Vec<GetOneAccessionResponse>,
+    /// Error messages keyed by the accession ID that failed.
+    pub errors: BTreeMap<i64, String>,
+}
+
+/// Outcome of one subject in a bulk create.
+///
+/// Exactly one of `created_id`, `existing_id` and `error` is set.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CreateSubjectResult {
+    /// The subject as requested.
+    pub request: CreateSubjectRequest,
+    /// ID of the newly created subject.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_id: Option<i64>,
+    /// ID of the subject that already existed, when it was skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub existing_id: Option<i64>,
+    /// Why the subject could not be created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Response of a bulk subject create.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CreateSubjectsResponse {
+    /// One result per distinct requested subject, in request order.
+    pub results: Vec<CreateSubjectResult>,
+}
+
+/// Outcome of setting one accession's privacy.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PrivacyChangeStatus {
+    /// The privacy flag was changed.
+    Updated,
+    /// The accession already had the requested privacy.
+    Unchanged,
+    /// The accession could not be read or updated.
+    Failed,
+    /// The call was cancelled before this accession's result arrived, so
+    /// its privacy may or may not have changed.
+    Cancelled,
+}
+
+/// Result for one accession in a bulk privacy update.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AccessionPrivacyResult {
+    /// Accession ID.
+    pub id: i64,
+    /// What happened to the accession.
+    pub status: PrivacyChangeStatus,
+    /// Privacy before the update, when the accession could be read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub was_private: Option<bool>,
+    /// Error message, when the update failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Response of a bulk privacy update.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SetAccessionsPrivacyResponse {
+    /// The privacy that was requested.
+    pub is_private: bool,
+    /// Number of accessions changed.
+    pub updated: usize,
+    /// Number of accessions already at the requested privacy.
+    pub unchanged: usize,
+    /// Number of accessions that could not be updated.
+    pub failed: usize,
+    /// One result per distinct ID, in request order.
+    pub results: Vec<AccessionPrivacyResult>,
+}
+
+impl SetAccessionsPrivacyResponse {
+    /// Builds the response from each distinct ID's result.
+    pub fn new(is_private: bool, results: Vec<AccessionPrivacyResult>) -> Self {
+        let count =
+            |status: PrivacyChangeStatus| results.iter().filter(|r| r.status == status).count();
+        Self {
+            is_private,
+            updated: count(PrivacyChangeStatus::Updated),
+            unchanged: count(PrivacyChangeStatus::Unchanged),
+            failed: count(PrivacyChangeStatus::Failed),
+            results,
+        }
+    }
+}
+
+/// Before and after privacy of one accession in an audit entry.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct PrivacyAuditChange {
+    /// Accession ID.
+    pub id: i64,
+    /// What happened to the accession.
+    pub status: PrivacyChangeStatus,
+    /// Privacy before the change; null if the accession could not be read
+    /// or the call was cancelled first.
+    pub before: Option<bool>,
+    /// Privacy after the change; null if the accession could not be read
+    /// or the call was cancelled first.
+    pub after: Option<bool>,
+    /// Error message, when the update failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// One line of the audit file, written per `bulk_privacy_change` call.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PrivacyAuditEntry {
+    /// When the change finished, in RFC 3339 format (UTC).
+    pub timestamp: String,
+    /// The audited action, `bulk_privacy_change`.
+    pub action: String,
+    /// Who made the change, as given by the caller.
+    pub actor: Option<String>,
+    /// The privacy that was requested.
+    pub is_private: bool,
+    /// Every distinct ID requested, in request order.
+    pub changes: Vec<PrivacyAuditChange>,
+    /// Whether the call was cancelled before every result arrived; the IDs
+    /// still pending then have status `cancelled`.
+    pub cancelled: bool,
+}
+
+/// Response of an audited bulk privacy change.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct BulkPrivacyChangeResponse {
+    /// The privacy that was requested.
+    pub is_private: bool,
+    /// Number of accessions changed.
+    pub changed: usize,
+    /// Number of accessions skipped because they already had the requested privacy.
+    pub skipped: usize,
+    /// Number of accessions that could not be updated.
+    pub failed: usize,
+    /// Whether the call was cancelled before every result arrived.
+    pub cancelled: bool,
+    /// The audit file the entry was appended to.
+    pub audit_file: String,
+    /// The audit entry that was written.
+    pub entry: PrivacyAuditEntry,
+}
+
+impl PrivacyAuditChange {
+    /// Builds the audit record of one bulk privacy result.
+    pub fn from_result(result: &AccessionPrivacyResult, is_private: bool) -> Self {
+        let after = match result.status {
+            PrivacyChangeStatus::Updated => Some(is_private),
+            PrivacyChangeStatus::Unchanged
+            | PrivacyChangeStatus::Failed
+            | PrivacyChangeStatus::Cancelled => result.was_private,
+        };
+        Self {
+            id: result.id,
+            status: result.status,
+            before: result.was_private,
+            after,
+            error: result.error.clone(),
+        }
+    }
+}
+
+/// Result of looking up a subject by name, creating it if absent.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FindOrCreateSubjectResponse {
+    /// ID of the matching or newly created subject.
+    pub id: i64,
+    /// Whether the subject was created by this call.
+    pub created: bool,
 }
 
 /// Represents a single metadata subject.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct DublinMetadataSubjectResponse {
     /// Unique identifier.
-    pub id: i32,
+    pub id: i64,
     /// The subject name.
     pub subject: String,
 }
@@ -504,6 +2360,21 @@ pub struct ListCollectionsArgs {
     /// Language filter.
     #[serde(default)]
     pub lang: MetadataLanguage,
+    /// Filter by specific metadata subject IDs.
+    #[serde(default, alias = "metadata_subjects")]
+    pub metadata_subjects: Vec<i64>,
+    /// General query term to search for.
+    #[serde(default, alias = "query_term")]
+    pub query_term: String,
+    /// Start date filter: YYYY-MM-DD, YYYY-MM, YYYY, or relative forms such as "today", "last month" or "7 days ago". Periods start on their first day.
+    #[serde(default, alias = "date_from")]
+    pub date_from: String,
+    /// End date filter, in the same forms as date_from. Periods end on their last day, so "2024" includes all of 2024.
+    #[serde(default, alias = "date_to")]
+    pub date_to: String,
+    /// Return only the total number of matching items instead of the items themselves.
+    #[serde(default, alias = "count_only")]
+    pub count_only: bool,
 }
 
 impl Default for ListCollectionsArgs {
@@ -512,6 +2383,11 @@ impl Default for ListCollectionsArgs {
             page: -1,
             per_page: -1,
             lang: MetadataLanguage::default(),
+            metadata_subjects: Vec::new(),
+            query_term: String::new(),
+            date_from: String::new(),
+            date_to: String::new(),
+            count_only: false,
         }
     }
 }
@@ -529,9 +2405,25 @@ pub struct ListPrivateCollectionsArgs {
     /// Language filter.
     #[serde(default)]
     pub lang: MetadataLanguage,
-    /// Filter by private status.
-    #[serde(default)]
+    /// Filter by specific metadata subject IDs.
+    #[serde(default, alias = "metadata_subjects")]
+    pub metadata_subjects: Vec<i64>,
+    /// General query term to search for.
+    #[serde(default, alias = "query_term")]
+    pub query_term: String,
+    /// Start date filter: YYYY-MM-DD, YYYY-MM, YYYY, or relative forms such as "today", "last month" or "7 days ago". Periods start on their first day.
+    #[serde(default, alias = "date_from")]
+    pub date_from: String,
+    /// End date filter, in the same forms as date_from. Periods end on their last day, so "2024" includes all of 2024.
+    #[serde(default, alias = "date_to")]
+    pub date_to: String,
+    /// List private collections (the default). Set to false to list public collections instead;
+    /// the API filters on this value, so it never returns both at once.
+    #[serde(default = "default_true", alias = "is_private")]
     pub is_private: bool,
+    /// Return only the total number of matching items instead of the items themselves.
+    #[serde(default, alias = "count_only")]
+    pub count_only: bool,
 }
 
 impl Default for ListPrivateCollectionsArgs {
@@ -540,7 +2432,12 @@ impl Default for ListPrivateCollectionsArgs {
             page: -1,
             per_page: -1,
             lang: MetadataLanguage::default(),
+            metadata_subjects: Vec::new(),
+            query_term: String::new(),
+            date_from: String::new(),
+            date_to: String::new(),
             is_private: true,
+            count_only: false,
         }
     }
 }
@@ -549,7 +2446,7 @@ impl Default for ListPrivateCollectionsArgs {
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct GetCollectionArgs {
     /// The collection ID.
-    pub id: i32,
+    pub id: i64,
     /// Language for the collection.
     #[serde(default)]
     pub lang: MetadataLanguage,
@@ -559,7 +2456,7 @@ pub struct GetCollectionArgs {
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct GetSubjectArgs {
     /// The subject ID.
-    pub id: i32,
+    pub id: i64,
     /// Language for the subject.
     #[serde(default)]
     pub lang: MetadataLanguage,
@@ -575,7 +2472,7 @@ pub struct CreateCollectionArgs {
     /// Whether the collection is private.
     pub is_private: bool,
     /// List of subject IDs.
-    pub subject_ids: Vec<i32>,
+    pub subject_ids: Vec<i64>,
     /// Description of the collection.
     #[serde(default)]
     pub description: String,
@@ -585,7 +2482,7 @@ pub struct CreateCollectionArgs {
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct UpdateCollectionArgs {
     /// The ID of the collection to update.
-    pub id: i32,
+    pub id: i64,
     /// Language of the collection.
     pub lang: MetadataLanguage,
     /// Title of the collection.
@@ -593,12 +2490,179 @@ pub struct UpdateCollectionArgs {
     /// Whether the collection is private.
     pub is_private: bool,
     /// List of subject IDs.
-    pub subject_ids: Vec<i32>,
+    pub subject_ids: Vec<i64>,
     /// Description of the collection.
     #[serde(default)]
     pub description: String,
 }
 
+/// Fields to change on an existing collection; `None` keeps the current value.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Default, Clone)]
+pub struct UpdateCollectionPatch {
+    /// New title.
+    #[serde(default)]
+    pub title: Option<String>,
+    /// New privacy status.
+    #[serde(default)]
+    pub is_private: Option<bool>,
+    /// New list of subject IDs, replacing the current list.
+    #[serde(default)]
+    pub subject_ids: Option<Vec<i64>>,
+    /// New description.
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Arguments for partially updating a collection.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PatchCollectionArgs {
+    /// The ID of the collection to patch.
+    pub id: i64,
+    /// Language of the collection.
+    pub lang: MetadataLanguage,
+    /// Fields to change; omitted fields keep their current values.
+    #[serde(flatten)]
+    pub patch: UpdateCollectionPatch,
+}
+
+/// Arguments for adding a subject to, or removing it from, a collection.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CollectionSubjectArgs {
+    /// The collection ID.
+    pub collection_id: i64,
+    /// The subject ID to add or remove.
+    pub subject_id: i64,
+    /// Language of the collection.
+    pub lang: MetadataLanguage,
+}
+
+/// Arguments for adding an accession to, or removing it from, a collection.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CollectionMembershipArgs {
+    /// The collection ID.
+    pub collection_id: i64,
+    /// The accession ID.
+    pub accession_id: i64,
+}
+
+/// Arguments for listing the accessions in a collection.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListCollectionAccessionsArgs {
+    /// The collection ID.
+    pub collection_id: i64,
+    /// Page number for pagination.
+    #[serde(default = "default_pagination")]
+    pub page: i64,
+    /// Number of items per page.
+    #[serde(default = "default_pagination")]
+    pub per_page: i64,
+    /// Language filter for metadata.
+    #[serde(default)]
+    pub lang: MetadataLanguage,
+    /// "full" (the default) returns every field; "summary" returns only id, title (English,
+    /// falling back to Arabic), seed_url and crawl_status.
+    #[serde(default)]
+    pub verbosity: Verbosity,
+}
+
+/// Arguments for listing the collections an accession belongs to.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GetAccessionCollectionsArgs {
+    /// The accession ID.
+    pub id: i64,
+    /// Language of the collection titles and descriptions.
+    #[serde(default)]
+    pub lang: MetadataLanguage,
+    /// Scan every collection again instead of using memberships found in the last few minutes.
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+/// Response of `get_accession_collections`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AccessionCollectionsResponse {
+    /// The accession looked up.
+    pub accession_id: i64,
+    /// Collections, public and private, the accession is a member of.
+    pub collections: Vec<CollectionResponse>,
+    /// Number of collections whose members were checked.
+    pub collections_scanned: usize,
+    /// Whether the memberships came from a recent scan instead of a new one.
+    pub cached: bool,
+}
+
+/// Arguments for exporting a collection with its accessions.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ExportCollectionArgs {
+    /// The collection ID.
+    pub collection_id: i64,
+    /// Language of the collection and accession metadata to fetch.
+    #[serde(default)]
+    pub lang: MetadataLanguage,
+    /// Also download each accession's WACZ/WARC archive into the export, which is then always a zip.
+    #[serde(default)]
+    pub include_wacz: bool,
+    /// Path on the server host to write the export to. A path ending in ".zip" (or include_wacz)
+    /// produces a zip with a collection.json manifest; any other path gets the JSON itself. Leave
+    /// empty to get small exports inline; large ones are written to a temporary file.
+    #[serde(default)]
+    pub dest_path: String,
+}
+
+/// A collection subject as listed in an export.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CollectionExportSubject {
+    /// Subject ID.
+    pub id: i64,
+    /// Subject name, when it could be looked up.
+    pub subject: Option<String>,
+}
+
+/// One accession in a collection export.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ExportedAccession {
+    /// Accession metadata.
+    pub accession: AccessionsWithMetadataResponse,
+    /// Pre-signed URL of the accession's WACZ file.
+    pub wacz_url: String,
+    /// Path of the archive inside the export zip, when it was downloaded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive_file: Option<String>,
+    /// Hex-encoded SHA-256 of the downloaded archive.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+/// Manifest of a collection export.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CollectionExport {
+    /// When the export was made (RFC 3339, UTC).
+    pub exported_at: String,
+    /// Collection metadata.
+    pub collection: CollectionResponse,
+    /// The collection's subjects.
+    pub subjects: Vec<CollectionExportSubject>,
+    /// Member accessions, in collection order.
+    pub accessions: Vec<ExportedAccession>,
+    /// Accessions whose details or archive could not be fetched, keyed by ID.
+    pub errors: BTreeMap<i64, String>,
+}
+
+/// Summary returned when a collection export is written to a file.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CollectionExportFile {
+    /// Path of the written file.
+    pub path: String,
+    /// "json" or "zip".
+    pub format: String,
+    /// Number of accessions exported.
+    pub accessions: usize,
+    /// Number of WACZ/WARC archives included.
+    pub archives: usize,
+    /// Accessions whose details or archive could not be fetched, keyed by ID.
+    pub errors: BTreeMap<i64, String>,
+}
+
 /// Request body for creating a collection.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct CreateCollectionRequest {
@@ -609,7 +2673,7 @@ pub struct CreateCollectionRequest {
     /// Whether the collection is private.
     pub is_private: bool,
     /// List of subject IDs.
-    pub subject_ids: Vec<i32>,
+    pub subject_ids: Vec<i64>,
     /// Description of the collection.
     #[serde(default)]
     pub description: String,
@@ -625,7 +2689,7 @@ pub struct UpdateCollectionRequest {
     /// Whether the collection is private.
     pub is_private: bool,
     /// List of subject IDs.
-    pub subject_ids: Vec<i32>,
+    pub subject_ids: Vec<i64>,
     /// Description of the collection.
     #[serde(default)]
     pub description: String,
@@ -635,13 +2699,16 @@ pub struct UpdateCollectionRequest {
 #[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct CollectionResponse {
     /// Unique identifier.
-    pub id: i32,
+    pub id: i64,
     /// Title of the collection.
     pub title: String,
     /// Whether the collection is private.
     pub is_private: bool,
     /// Description of the collection.
     pub description: Option<String>,
+    /// IDs of the collection's subjects, when the API reports them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject_ids: Option<Vec<i64>>,
 }
 
 /// Response containing a list of collections.
@@ -657,6 +2724,35 @@ pub struct ListCollectionsResponse {
     pub per_page: i64,
 }
 
+/// Response of `list_private_collections`, stating which collections the
+/// `is_private` filter selected.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PrivateCollectionsResponse {
+    /// The `is_private` value sent to the API.
+    pub is_private: bool,
+    /// Plain-language description of the slice returned.
+    pub scope: String,
+    /// Number of matching collections, when `count_only` was set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
+    /// The page of collections, unless `count_only` was set.
+    #[serde(flatten)]
+    pub listing: Option<ListCollectionsResponse>,
+}
+
+impl PrivateCollectionsResponse {
+    /// Describes the collections selected by `is_private`.
+    pub fn scope(is_private: bool) -> String {
+        if is_private {
+            "Private collections only (is_private=true); public collections are not included."
+                .to_string()
+        } else {
+            "Public collections only (is_private=false); private collections are not included."
+                .to_string()
+        }
+    }
+}
+
 /// Relation types for accession relations.
 #[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "snake_case")]
@@ -681,14 +2777,14 @@ pub struct ListContributorsArgs {
     pub per_page: i64,
     #[serde(default)]
     pub lang: MetadataLanguage,
-    #[serde(default)]
+    #[serde(default, alias = "query_term")]
     pub query_term: String,
 }
 
 /// Arguments for getting a single contributor.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct GetContributorArgs {
-    pub id: i32,
+    pub id: i64,
     #[serde(default)]
     pub lang: MetadataLanguage,
 }
@@ -703,7 +2799,7 @@ pub struct CreateContributorArgs {
 /// Arguments for updating a contributor.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct UpdateContributorArgs {
-    pub id: i32,
+    pub id: i64,
     pub lang: MetadataLanguage,
     pub contributor: String,
 }
@@ -711,7 +2807,7 @@ pub struct UpdateContributorArgs {
 /// Arguments for deleting a contributor.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct DeleteContributorArgs {
-    pub id: i32,
+    pub id: i64,
     pub lang: MetadataLanguage,
 }
 
@@ -738,7 +2834,7 @@ pub struct DeleteContributorRequest {
 /// Response containing a single contributor.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ContributorResponse {
-    pub id: i32,
+    pub id: i64,
     pub contributor: String,
 }
 
@@ -761,14 +2857,14 @@ pub struct ListContributorRolesArgs {
     pub per_page: i64,
     #[serde(default)]
     pub lang: MetadataLanguage,
-    #[serde(default)]
+    #[serde(default, alias = "query_term")]
     pub query_term: String,
 }
 
 /// Arguments for getting a single contributor role.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct GetContributorRoleArgs {
-    pub id: i32,
+    pub id: i64,
     #[serde(default)]
     pub lang: MetadataLanguage,
 }
@@ -783,7 +2879,7 @@ pub struct CreateContributorRoleArgs {
 /// Arguments for updating a contributor role.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct UpdateContributorRoleArgs {
-    pub id: i32,
+    pub id: i64,
     pub lang: MetadataLanguage,
     pub role: String,
 }
@@ -791,7 +2887,7 @@ pub struct UpdateContributorRoleArgs {
 /// Arguments for deleting a contributor role.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct DeleteContributorRoleArgs {
-    pub id: i32,
+    pub id: i64,
     pub lang: MetadataLanguage,
 }
 
@@ -818,7 +2914,7 @@ pub struct DeleteContributorRoleRequest {
 /// Response containing a single contributor role.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ContributorRoleResponse {
-    pub id: i32,
+    pub id: i64,
     pub role: String,
 }
 
@@ -841,14 +2937,14 @@ pub struct ListCreatorsArgs {
     pub per_page: i64,
     #[serde(default)]
     pub lang: MetadataLanguage,
-    #[serde(default)]
+    #[serde(default, alias = "query_term")]
     pub query_term: String,
 }
 
 /// Arguments for getting a single creator.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct GetCreatorArgs {
-    pub id: i32,
+    pub id: i64,
     #[serde(default)]
     pub lang: MetadataLanguage,
 }
@@ -863,7 +2959,7 @@ pub struct CreateCreatorArgs {
 /// Arguments for updating a creator.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct UpdateCreatorArgs {
-    pub id: i32,
+    pub id: i64,
     pub lang: MetadataLanguage,
     pub creator: String,
 }
@@ -871,7 +2967,7 @@ pub struct UpdateCreatorArgs {
 /// Arguments for deleting a creator.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct DeleteCreatorArgs {
-    pub id: i32,
+    pub id: i64,
     pub lang: MetadataLanguage,
 }
 
@@ -898,7 +2994,7 @@ pub struct DeleteCreatorRequest {
 /// Response containing a single creator.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct CreatorResponse {
-    pub id: i32,
+    pub id: i64,
     pub creator: String,
 }
 
@@ -921,14 +3017,14 @@ pub struct ListLocationsArgs {
     pub per_page: i64,
     #[serde(default)]
     pub lang: MetadataLanguage,
-    #[serde(default)]
+    #[serde(default, alias = "query_term")]
     pub query_term: String,
 }
 
 /// Arguments for getting a single location.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct GetLocationArgs {
-    pub id: i32,
+    pub id: i64,
     #[serde(default)]
     pub lang: MetadataLanguage,
 }
@@ -943,7 +3039,7 @@ pub struct CreateLocationArgs {
 /// Arguments for updating a location.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct UpdateLocationArgs {
-    pub id: i32,
+    pub id: i64,
     pub lang: MetadataLanguage,
     pub location: String,
 }
@@ -951,7 +3047,7 @@ pub struct UpdateLocationArgs {
 /// Arguments for deleting a location.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct DeleteLocationArgs {
-    pub id: i32,
+    pub id: i64,
     pub lang: MetadataLanguage,
 }
 
@@ -978,7 +3074,7 @@ pub struct DeleteLocationRequest {
 /// Response containing a single location.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct LocationResponse {
-    pub id: i32,
+    pub id: i64,
     pub location: String,
 }
 
@@ -995,7 +3091,8 @@ pub struct ListLocationsResponse {
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ListRelationsArgs {
-    pub accession_id: i32,
+    #[serde(alias = "accession_id")]
+    pub accession_id: i64,
     #[serde(default)]
     pub lang: MetadataLanguage,
 }
@@ -1003,8 +3100,8 @@ pub struct ListRelationsArgs {
 /// Arguments for getting a single relation.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct GetRelationArgs {
-    pub accession_id: i32,
-    pub relation_id: i32,
+    pub accession_id: i64,
+    pub relation_id: i64,
     #[serde(default)]
     pub lang: MetadataLanguage,
 }
@@ -1012,8 +3109,8 @@ pub struct GetRelationArgs {
 /// Arguments for creating a relation.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct CreateRelationArgs {
-    pub accession_id: i32,
-    pub related_accession_id: i32,
+    pub accession_id: i64,
+    pub related_accession_id: i64,
     pub relation_type: DublinMetadataRelationType,
     #[serde(default)]
     pub lang: MetadataLanguage,
@@ -1022,8 +3119,8 @@ pub struct CreateRelationArgs {
 /// Arguments for deleting a relation.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct DeleteRelationArgs {
-    pub accession_id: i32,
-    pub relation_id: i32,
+    pub accession_id: i64,
+    pub relation_id: i64,
     #[serde(default)]
     pub lang: MetadataLanguage,
 }
@@ -1031,15 +3128,15 @@ pub struct DeleteRelationArgs {
 /// Request body for creating a relation.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct CreateRelationRequest {
-    pub related_accession_id: i32,
+    pub related_accession_id: i64,
     pub relation_type: DublinMetadataRelationType,
 }
 
 /// Response containing a single relation.
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct RelationResponse {
-    pub id: i32,
-    pub related_accession_id: i32,
+    pub id: i64,
+    pub related_accession_id: i64,
     pub relation_type: String,
 }
 
@@ -1048,3 +3145,137 @@ pub struct RelationResponse {
 pub struct ListRelationsResponse {
     pub items: Vec<RelationResponse>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_list_args_accept_snake_case_and_camel_case() {
+        let snake: ListAccessionsArgs = serde_json::from_value(json!({
+            "per_page": 5,
+            "query_term": "Khartoum",
+            "url_filter": "example.com",
+            "date_from": "2024",
+            "date_to": "2024-05",
+            "metadata_subjects": [1, 2],
+            "metadata_subjects_inclusive_filter": true,
+            "metadata_contributor_roles": [3],
+            "is_private": true,
+            "sort_by": "id",
+        }))
+        .unwrap();
+        let camel: ListAccessionsArgs = serde_json::from_value(json!({
+            "perPage": 5,
+            "queryTerm": "Khartoum",
+            "urlFilter": "example.com",
+            "dateFrom": "2024",
+            "dateTo": "2024-05",
+            "metadataSubjects": [1, 2],
+            "metadataSubjectsInclusiveFilter": true,
+            "metadataContributorRoles": [3],
+            "isPrivate": true,
+            "sortBy": "id",
+        }))
+        .unwrap();
+        for args in [snake, camel] {
+            assert_eq!(args.per_page, 5);
+            assert_eq!(args.query_term, "Khartoum");
+            assert_eq!(args.url_filter, "example.com");
+            assert_eq!(
+                (args.date_from.as_str(), args.date_to.as_str()),
+                ("2024", "2024-05")
+            );
+            assert_eq!(args.metadata_subjects, [1, 2]);
+            assert!(args.metadata_subjects_inclusive_filter);
+            assert_eq!(args.metadata_contributor_roles, [3]);
+            assert!(args.is_private);
+            assert_eq!(args.sort_by, "id");
+        }
+
+        let subjects: ListSubjectsArgs =
+            serde_json::from_value(json!({ "in_collection_id": 4, "lang": "english" })).unwrap();
+        assert_eq!(subjects.in_collection_id, 4);
+        let collections: ListPrivateCollectionsArgs =
+            serde_json::from_value(json!({ "is_private": true, "lang": "arabic" })).unwrap();
+        assert!(collections.is_private);
+        let relations: ListRelationsArgs =
+            serde_json::from_value(json!({ "accession_id": 7 })).unwrap();
+        assert_eq!(relations.accession_id, 7);
+        let contributors: ListContributorsArgs =
+            serde_json::from_value(json!({ "query_term": "Ali" })).unwrap();
+        assert_eq!(contributors.query_term, "Ali");
+    }
+
+    #[test]
+    fn test_private_collections_listed_unless_is_private_is_false() {
+        let omitted: ListPrivateCollectionsArgs = serde_json::from_value(json!({})).unwrap();
+        assert!(omitted.is_private);
+        assert_eq!(
+            omitted.is_private,
+            ListPrivateCollectionsArgs::default().is_private
+        );
+        let public: ListPrivateCollectionsArgs =
+            serde_json::from_value(json!({ "isPrivate": false })).unwrap();
+        assert!(!public.is_private);
+    }
+
+    #[test]
+    fn test_private_collections_response_states_scope() {
+        let count = PrivateCollectionsResponse {
+            is_private: false,
+            scope: PrivateCollectionsResponse::scope(false),
+            total: Some(4),
+            listing: None,
+        };
+        let value = serde_json::to_value(&count).unwrap();
+        assert_eq!(value["is_private"], false);
+        assert!(value["scope"].as_str().unwrap().starts_with("Public"));
+        assert_eq!(value["total"], 4);
+        assert!(value.get("items").is_none());
+
+        let page = PrivateCollectionsResponse {
+            is_private: true,
+            scope: PrivateCollectionsResponse::scope(true),
+            total: None,
+            listing: Some(ListCollectionsResponse {
+                items: Vec::new(),
+                num_pages: 1,
+                page: 0,
+                per_page: 20,
+            }),
+        };
+        let value = serde_json::to_value(&page).unwrap();
+        assert_eq!(value["items"], json!([]));
+        assert_eq!(value["num_pages"], 1);
+        assert!(value.get("total").is_none());
+    }
+
+    #[test]
+    fn test_privacy_audit_change_reports_before_and_after() {
+        let result = |status, was_private| AccessionPrivacyResult {
+            id: 1,
+            status,
+            was_private,
+            error: None,
+        };
+        let change = |status, was_private| {
+            let audited = PrivacyAuditChange::from_result(&result(status, was_private), true);
+            (audited.before, audited.after)
+        };
+        assert_eq!(
+            change(PrivacyChangeStatus::Updated, Some(false)),
+            (Some(false), Some(true))
+        );
+        assert_eq!(
+            change(PrivacyChangeStatus::Unchanged, Some(true)),
+            (Some(true), Some(true))
+        );
+        assert_eq!(
+            change(PrivacyChangeStatus::Failed, Some(false)),
+            (Some(false), Some(false))
+        );
+        assert_eq!(change(PrivacyChangeStatus::Failed, None), (None, None));
+    }
+}
@@ -0,0 +1,882 @@
+//! Checks of a crawl target before a crawl is started.
+//!
+//! `preview_crawl_target` requests the page an accession would archive
+//! directly, not through the SDA API, following redirects one at a time so
+//! the chain can be reported. It then reads the final site's robots.txt and
+//! evaluates it for `User-agent: *`. Every URL in the chain is checked
+//! against the server's `CrawlPolicy` before it is requested, so a preview
+//! cannot reach a host the server would refuse to crawl, such as an
+//! internal address. `estimate_crawl_size` probes the same
+//! page for its size and the resources it links to, under the same policy. A plain HTTP client is
+//! used: the SDA API key, and the proxy and certificate settings meant for
+//! the API, are never applied to the target.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE, LOCATION};
+use reqwest::redirect::Policy;
+use reqwest::{Client, Url};
+
+use crate::crawl_policy::CrawlPolicy;
+use crate::error::{Result, SdaError};
+use crate::model::{
+    BrowserProfile, CrawlSizeEstimate, CrawlTargetPreview, EstimateConfidence, RedirectHop,
+    RobotsTxtCheck,
+};
+use crate::validation;
+
+/// Timeout for each request to the target site.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Largest robots.txt read, in bytes; the rest is ignored, as crawlers do.
+const MAX_ROBOTS_BYTES: usize = 512 * 1024;
+
+/// User agent sent to target sites.
+const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// Largest part of a page read to count its links, in bytes.
+const MAX_PAGE_BYTES: usize = 2 * 1024 * 1024;
+
+/// Redirects followed when estimating a crawl's size.
+const ESTIMATE_MAX_REDIRECTS: usize = 10;
+
+/// Assumed average size of a linked resource, WARC records included. Pages
+/// on the web average a few dozen kilobytes per request.
+const AVERAGE_RESOURCE_BYTES: u64 = 40 * 1024;
+
+/// Elements whose `src` the crawler fetches along with the page.
+const RESOURCE_TAGS: [&str; 8] = [
+    "img", "script", "iframe", "video", "audio", "source", "embed", "track",
+];
+
+/// Requests `url`, following up to `max_redirects` redirects, and checks the
+/// final URL against its site's robots.txt.
+///
+/// Fails only when `url` is not an absolute `http` or `https` URL or is
+/// refused by `policy`; an unreachable target, or a redirect to a host
+/// `policy` refuses, is reported in the preview's `error`.
+pub async fn preview_crawl_target(
+    url: &str,
+    max_redirects: u32,
+    policy: &CrawlPolicy,
+) -> Result<CrawlTargetPreview> {
+    let start = parse_target(url)?;
+    let client = Client::builder()
+        .user_agent(USER_AGENT)
+        .redirect(Policy::none())
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| SdaError::network("Failed to build HTTP client", e))?;
+
+    let mut preview = CrawlTargetPreview {
+        url: start.to_string(),
+        final_url: start.to_string(),
+        status: None,
+        reachable: false,
+        content_type: None,
+        redirected: false,
+        redirects: Vec::new(),
+        redirect_limit_reached: false,
+        error: None,
+        robots_txt: None,
+        suggested_browser_profile: None,
+    };
+    let mut current = start;
+    loop {
+        if let Err(message) = policy.check(current.as_str()) {
+            if preview.redirects.is_empty() {
+                return Err(SdaError::Validation(format!(
+                    "Crawl refused by domain policy: {}",
+                    message
+                )));
+            }
+            preview.error = Some(format!(
+                "The redirect to {} was not followed: {}",
+                current, message
+            ));
+            break;
+        }
+        let response = match client.get(current.clone()).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                preview.error = Some(format!("Failed to reach {}: {}", current, e));
+                break;
+            }
+        };
+        let status = response.status();
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| current.join(value).ok());
+        match location {
+            Some(next) if status.is_redirection() => {
+                if preview.redirects.len() as u32 >= max_redirects {
+                    preview.status = Some(status.as_u16());
+                    preview.redirect_limit_reached = true;
+                    break;
+                }
+                preview.redirects.push(RedirectHop {
+                    url: current.to_string(),
+                    status: status.as_u16(),
+                });
+                current = next;
+            }
+            _ => {
+                preview.status = Some(status.as_u16());
+                preview.reachable = status.is_success();
+                preview.content_type = response
+                    .headers()
+                    .get(CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string);
+                break;
+            }
+        }
+    }
+    preview.redirected = !preview.redirects.is_empty();
+    preview.final_url = current.to_string();
+    preview.suggested_browser_profile = suggest_browser_profile(&current);
+    if preview.error.is_none() {
+        preview.robots_txt = Some(check_robots_txt(&client, &current).await);
+    }
+    Ok(preview)
+}
+
+/// Estimates how large a crawl of `url` would be, from a HEAD request and,
+/// for HTML pages, the links in the page itself.
+///
+/// Fails only when `url` is not an absolute `http` or `https` URL or is
+/// refused by `policy`; a target that cannot be probed, including one that
+/// redirects to a host `policy` refuses, is reported in the estimate's
+/// `error`.
+pub async fn estimate_crawl_size(url: &str, policy: &CrawlPolicy) -> Result<CrawlSizeEstimate> {
+    let start = parse_target(url)?;
+    policy.check(start.as_str()).map_err(|message| {
+        SdaError::Validation(format!("Crawl refused by domain policy: {}", message))
+    })?;
+    let hop_policy = policy.clone();
+    let redirects = Policy::custom(move |attempt| {
+        if attempt.previous().len() >= ESTIMATE_MAX_REDIRECTS {
+            return attempt.error("too many redirects");
+        }
+        match hop_policy.check(attempt.url().as_str()) {
+            Ok(()) => attempt.follow(),
+            Err(message) => {
+                let error = format!(
+                    "the redirect to {} was not followed: {}",
+                    attempt.url(),
+                    message
+                );
+                attempt.error(error)
+            }
+        }
+    });
+    let client = Client::builder()
+        .user_agent(USER_AGENT)
+        .redirect(redirects)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| SdaError::network("Failed to build HTTP client", e))?;
+
+    let mut estimate = CrawlSizeEstimate {
+        url: start.to_string(),
+        final_url: start.to_string(),
+        status: None,
+        content_type: None,
+        content_length: None,
+        head_supported: None,
+        linked_resources: 0,
+        same_site_links: 0,
+        estimated_pages: 1,
+        estimated_wacz_bytes: None,
+        estimated_wacz_range: None,
+        confidence: EstimateConfidence::Low,
+        notes: Vec::new(),
+        suggested_browser_profile: None,
+        error: None,
+    };
+    match client.head(start.clone()).send().await {
+        Ok(response) if response.status().is_success() => {
+            estimate.head_supported = Some(true);
+            record_response(&mut estimate, &response);
+        }
+        Ok(response) => {
+            estimate.head_supported = Some(false);
+            estimate.notes.push(format!(
+                "The site answered HEAD with {}, so the page was fetched with GET instead.",
+                response.status()
+            ));
+        }
+        Err(e) => estimate.notes.push(format!(
+            "HEAD request failed ({}), so the page was fetched with GET instead.",
+            error_chain(&e)
+        )),
+    }
+
+    let is_html = |estimate: &CrawlSizeEstimate| {
+        estimate
+            .content_type
+            .as_deref()
+            .is_none_or(|t| t.to_lowercase().contains("html"))
+    };
+    // A non-HTML target of known size needs no download.
+    let mut page = None;
+    if estimate.head_supported != Some(true)
+        || is_html(&estimate)
+        || estimate.content_length.is_none()
+    {
+        match client.get(start.clone()).send().await {
+            Ok(mut response) => {
+                let content_length = estimate.content_length;
+                record_response(&mut estimate, &response);
+                estimate.content_length = estimate.content_length.or(content_length);
+                if !response.status().is_success() {
+                    estimate.error = Some(format!(
+                        "The page returned {}, so its size could not be estimated",
+                        response.status()
+                    ));
+                } else if is_html(&estimate) {
+                    let mut body = Vec::new();
+                    while let Ok(Some(chunk)) = response.chunk().await {
+                        body.extend_from_slice(&chunk);
+                        if body.len() >= MAX_PAGE_BYTES {
+                            body.truncate(MAX_PAGE_BYTES);
+                            estimate.notes.push(format!(
+                                "Only the first {} MiB of the page was read for links.",
+                                MAX_PAGE_BYTES / (1024 * 1024)
+                            ));
+                            break;
+                        }
+                    }
+                    page = Some(body);
+                }
+            }
+            Err(e) => {
+                estimate.error = Some(format!("Failed to reach {}: {}", start, error_chain(&e)));
+            }
+        }
+    }
+
+    let final_url = Url::parse(&estimate.final_url).unwrap_or(start);
+    estimate.suggested_browser_profile = suggest_browser_profile(&final_url);
+    if estimate.error.is_some() {
+        estimate.notes.push(
+            "The target could not be probed, so no size is estimated; the site may block automated requests that a crawler's browser gets through.".to_string(),
+        );
+        return Ok(estimate);
+    }
+    if let Some(page) = &page {
+        let (resources, links) = count_links(&String::from_utf8_lossy(page), &final_url);
+        estimate.linked_resources = resources;
+        estimate.same_site_links = links;
+        estimate.content_length = estimate.content_length.or(Some(page.len() as u64));
+    }
+    let (bytes, confidence) = size_estimate(
+        estimate.content_length,
+        page.is_some(),
+        estimate.linked_resources,
+    );
+    estimate.confidence = confidence;
+    estimate.estimated_wacz_bytes = bytes;
+    estimate.estimated_wacz_range = bytes.map(|b| wacz_range(b, confidence));
+    if page.is_some() {
+        estimate.notes.push(format!(
+            "Estimated from the page plus {} KiB for each linked resource; resources loaded by scripts are not counted.",
+            AVERAGE_RESOURCE_BYTES / 1024
+        ));
+        if estimate.same_site_links > 0 {
+            estimate.notes.push(format!(
+                "Only the seed page is counted; a crawl that also follows its {} same-site links would be much larger.",
+                estimate.same_site_links
+            ));
+        }
+    }
+    if estimate.suggested_browser_profile.is_some() {
+        estimate.confidence = EstimateConfidence::Low;
+        estimate.notes.push(
+            "This site builds its pages with scripts for logged-in browsers, so the probe sees little of what a crawl captures.".to_string(),
+        );
+    }
+    Ok(estimate)
+}
+
+/// Formats `error` with its causes, which for a refused redirect hold the
+/// reason.
+fn error_chain(error: &reqwest::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        message.push_str(&format!(": {}", cause));
+        source = cause.source();
+    }
+    message
+}
+
+/// Records the final URL, status, type and declared size of `response`.
+fn record_response(estimate: &mut CrawlSizeEstimate, response: &reqwest::Response) {
+    estimate.final_url = response.url().to_string();
+    estimate.status = Some(response.status().as_u16());
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    };
+    estimate.content_type = header(CONTENT_TYPE).map(str::to_string);
+    estimate.content_length = header(CONTENT_LENGTH).and_then(|len| len.parse().ok());
+}
+
+/// Estimates the WACZ size of a target of `content_length` bytes linking to
+/// `resources` resources. An HTML page is assumed to bring its resources
+/// along; any other file is archived as it is.
+fn size_estimate(
+    content_length: Option<u64>,
+    is_page: bool,
+    resources: usize,
+) -> (Option<u64>, EstimateConfidence) {
+    match (content_length, is_page) {
+        (Some(length), false) => (Some(length), EstimateConfidence::High),
+        (length, true) => (
+            Some(
+                length
+                    .unwrap_or(0)
+                    .saturating_add((resources as u64).saturating_mul(AVERAGE_RESOURCE_BYTES)),
+            ),
+            EstimateConfidence::Medium,
+        ),
+        (None, false) => (None, EstimateConfidence::Low),
+    }
+}
+
+/// Returns the low-high range around an estimate of `bytes`, narrow when
+/// the size is known and wide otherwise.
+fn wacz_range(bytes: u64, confidence: EstimateConfidence) -> [u64; 2] {
+    match confidence {
+        EstimateConfidence::High => [bytes, bytes.saturating_add(bytes / 4)],
+        _ => [bytes / 2, bytes.saturating_mul(3)],
+    }
+}
+
+/// Counts the distinct resources an HTML page loads, and its distinct links
+/// to other pages on the same host.
+///
+/// Tags are found by a plain scan rather than a full HTML parser, which is
+/// enough for an estimate.
+fn count_links(html: &str, base: &Url) -> (usize, usize) {
+    let mut resources = HashSet::new();
+    let mut pages = HashSet::new();
+    let lower = html.to_lowercase();
+    let mut rest = lower.as_str();
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let end = rest.find('>').unwrap_or(rest.len());
+        let tag = &rest[..end];
+        rest = &rest[end..];
+        let name_end = tag
+            .find(|c: char| c.is_whitespace() || c == '/')
+            .unwrap_or(tag.len());
+        let (name, attributes) = tag.split_at(name_end);
+        let resolve = |attr| attribute(attributes, attr).and_then(|v| base.join(&v).ok());
+        if RESOURCE_TAGS.contains(&name) {
+            if let Some(url) = resolve("src") {
+                resources.insert(url);
+            }
+        } else if name == "link" {
+            let rel = attribute(attributes, "rel").unwrap_or_default();
+            if ["stylesheet", "icon", "preload", "manifest"]
+                .iter()
+                .any(|kind| rel.split_whitespace().any(|r| r == *kind))
+                && let Some(url) = resolve("href")
+            {
+                resources.insert(url);
+            }
+        } else if name == "a"
+            && let Some(mut url) = resolve("href")
+        {
+            url.set_fragment(None);
+            if matches!(url.scheme(), "http" | "https")
+                && url.host_str() == base.host_str()
+                && url.as_str() != base.as_str()
+            {
+                pages.insert(url);
+            }
+        }
+    }
+    (resources.len(), pages.len())
+}
+
+/// Returns the value of attribute `name` in the attributes of a tag.
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let mut search = attributes;
+    loop {
+        let index = search.find(name)?;
+        let before = search[..index].chars().next_back();
+        let after = search[index + name.len()..].trim_start();
+        search = &search[index + name.len()..];
+        if !before.is_some_and(char::is_whitespace) {
+            continue;
+        }
+        let Some(value) = after.strip_prefix('=') else {
+            continue;
+        };
+        let value = value.trim_start();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or_default(),
+            _ => value.split_whitespace().next().unwrap_or_default(),
+        };
+        return Some(value.to_string()).filter(|v| !v.is_empty());
+    }
+}
+
+/// Parses a crawl target, which must be an absolute `http` or `https` URL.
+fn parse_target(url: &str) -> Result<Url> {
+    let trimmed = url.trim();
+    let parsed = Url::parse(trimmed)
+        .map_err(|e| SdaError::Validation(format!("Invalid URL '{}': {}", trimmed, e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(SdaError::Validation(format!(
+            "URL '{}' must use http or https",
+            trimmed
+        )));
+    }
+    Ok(parsed)
+}
+
+/// Returns the browser profile needed to archive `url`, if any.
+pub fn suggest_browser_profile(url: &Url) -> Option<BrowserProfile> {
+    validation::browser_profile_for_host(url.host_str()?)
+}
+
+/// Fetches the robots.txt of `url`'s site and evaluates it for `url`.
+///
+/// A 4xx response means there are no restrictions; a 5xx response or a
+/// failed request leaves `allowed` unset.
+async fn check_robots_txt(client: &Client, url: &Url) -> RobotsTxtCheck {
+    let mut robots_url = url.clone();
+    robots_url.set_path("/robots.txt");
+    robots_url.set_query(None);
+    robots_url.set_fragment(None);
+    let mut check = RobotsTxtCheck {
+        url: robots_url.to_string(),
+        found: false,
+        allowed: None,
+        matched_rule: None,
+    };
+    let Ok(mut response) = client.get(robots_url).send().await else {
+        return check;
+    };
+    let status = response.status();
+    if status.is_client_error() {
+        check.allowed = Some(true);
+        return check;
+    }
+    if !status.is_success() {
+        return check;
+    }
+    let mut body = Vec::new();
+    while let Ok(Some(chunk)) = response.chunk().await {
+        body.extend_from_slice(&chunk);
+        if body.len() >= MAX_ROBOTS_BYTES {
+            body.truncate(MAX_ROBOTS_BYTES);
+            break;
+        }
+    }
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+    let (allowed, rule) = robots_verdict(&String::from_utf8_lossy(&body), &path);
+    check.found = true;
+    check.allowed = Some(allowed);
+    check.matched_rule = rule;
+    check
+}
+
+/// Decides whether `User-agent: *` may fetch `path` under `robots`.
+///
+/// Follows RFC 9309: the longest matching Allow or Disallow rule wins, Allow
+/// wins ties, and `*` and a trailing `$` are supported in rules. Returns the
+/// verdict and the deciding rule.
+fn robots_verdict(robots: &str, path: &str) -> (bool, Option<String>) {
+    let mut best: Option<(usize, bool, String)> = None;
+    let mut in_wildcard_group = false;
+    let mut group_has_rules = false;
+    for line in robots.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().to_lowercase();
+        let value = value.trim();
+        match key.as_str() {
+            "user-agent" => {
+                // A user-agent line after rules starts a new group.
+                if group_has_rules {
+                    in_wildcard_group = false;
+                    group_has_rules = false;
+                }
+                in_wildcard_group |= value == "*";
+            }
+            "allow" | "disallow" => {
+                group_has_rules = true;
+                if !in_wildcard_group || value.is_empty() || !rule_matches(value, path) {
+                    continue;
+                }
+                let allow = key == "allow";
+                let better = match &best {
+                    Some((len, best_allow, _)) => {
+                        value.len() > *len || (value.len() == *len && allow && !best_allow)
+                    }
+                    None => true,
+                };
+                if better {
+                    let name = if allow { "Allow" } else { "Disallow" };
+                    best = Some((value.len(), allow, format!("{}: {}", name, value)));
+                }
+            }
+            _ => {}
+        }
+    }
+    match best {
+        Some((_, allow, rule)) => (allow, Some(rule)),
+        None => (true, None),
+    }
+}
+
+/// Matches a robots.txt path rule, with `*` wildcards and an optional
+/// trailing `$` anchor, against the start of `path`.
+fn rule_matches(rule: &str, path: &str) -> bool {
+    let (rule, anchored) = match rule.strip_suffix('$') {
+        Some(rule) => (rule, true),
+        None => (rule, false),
+    };
+    let mut parts = rule.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        let last = i + 1 == parts.len();
+        if last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header_exists, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_rule_matches() {
+        assert!(rule_matches("/private", "/private/page"));
+        assert!(!rule_matches("/private", "/public"));
+        assert!(rule_matches("/*.pdf$", "/docs/report.pdf"));
+        assert!(!rule_matches("/*.pdf$", "/docs/report.pdf?x=1"));
+        assert!(rule_matches("/a*/c", "/ab/c/d"));
+        assert!(rule_matches("/page$", "/page"));
+        assert!(!rule_matches("/page$", "/pages"));
+    }
+
+    #[test]
+    fn test_robots_verdict_uses_longest_wildcard_rule() {
+        let robots = "\
+User-agent: Googlebot
+Disallow: /
+
+User-agent: *
+Disallow: /news # archive
+Allow: /news/public
+";
+        assert_eq!(robots_verdict(robots, "/about"), (true, None));
+        assert_eq!(
+            robots_verdict(robots, "/news/2024"),
+            (false, Some("Disallow: /news".to_string()))
+        );
+        assert_eq!(
+            robots_verdict(robots, "/news/public/1"),
+            (true, Some("Allow: /news/public".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_robots_verdict_ignores_empty_disallow_and_other_agents() {
+        assert_eq!(
+            robots_verdict("User-agent: *\nDisallow:\n", "/anything"),
+            (true, None)
+        );
+        assert_eq!(
+            robots_verdict("User-agent: bot\nUser-agent: *\nDisallow: /\n", "/x"),
+            (false, Some("Disallow: /".to_string()))
+        );
+        assert_eq!(
+            robots_verdict(
+                "User-agent: *\nDisallow: /\nUser-agent: bot\nAllow: /\n",
+                "/x"
+            ),
+            (false, Some("Disallow: /".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_suggest_browser_profile() {
+        let profile = |url| suggest_browser_profile(&Url::parse(url).unwrap());
+        assert!(matches!(
+            profile("https://m.facebook.com/page"),
+            Some(BrowserProfile::Facebook)
+        ));
+        assert!(profile("https://notfacebook.com/").is_none());
+        assert!(profile("https://example.com/").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_non_http_urls() {
+        let err = preview_crawl_target("ftp://example.com/", 5, &CrawlPolicy::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SdaError::Validation(_)));
+    }
+
+    #[tokio::test]
+    async fn test_preview_follows_redirects_and_checks_robots() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/old"))
+            .respond_with(ResponseTemplate::new(301).insert_header("location", "/new"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/new"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/html; charset=utf-8"),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/robots.txt"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /new\n"),
+            )
+            .mount(&server)
+            .await;
+        // The SDA API key must never reach the target.
+        Mock::given(header_exists("x-api-key"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let preview =
+            preview_crawl_target(&format!("{}/old", server.uri()), 5, &CrawlPolicy::default())
+                .await
+                .unwrap();
+        assert!(preview.reachable);
+        assert!(preview.redirected);
+        assert_eq!(preview.status, Some(200));
+        assert_eq!(preview.final_url, format!("{}/new", server.uri()));
+        assert_eq!(
+            preview.redirects,
+            vec![RedirectHop {
+                url: format!("{}/old", server.uri()),
+                status: 301,
+            }]
+        );
+        assert_eq!(
+            preview.content_type.as_deref(),
+            Some("text/html; charset=utf-8")
+        );
+        let robots = preview.robots_txt.unwrap();
+        assert!(robots.found);
+        assert_eq!(robots.allowed, Some(false));
+        assert_eq!(robots.matched_rule.as_deref(), Some("Disallow: /new"));
+    }
+
+    #[tokio::test]
+    async fn test_preview_applies_crawl_policy_to_every_hop() {
+        use crate::crawl_policy::DomainPattern;
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/old"))
+            .respond_with(
+                ResponseTemplate::new(301).insert_header("location", "http://metadata.internal/"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let deny = vec![DomainPattern::parse("metadata.internal").unwrap()];
+        let policy = CrawlPolicy::new(Vec::new(), deny);
+        let preview = preview_crawl_target(&format!("{}/old", server.uri()), 5, &policy)
+            .await
+            .unwrap();
+        assert!(!preview.reachable);
+        assert_eq!(preview.final_url, "http://metadata.internal/");
+        assert!(
+            preview.error.as_deref().unwrap().contains("not followed"),
+            "{:?}",
+            preview.error
+        );
+        assert!(preview.robots_txt.is_none());
+
+        let allow = vec![DomainPattern::parse("example.org").unwrap()];
+        let policy = CrawlPolicy::new(allow, Vec::new());
+        let err = preview_crawl_target(&format!("{}/old", server.uri()), 5, &policy)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SdaError::Validation(_)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_preview_stops_at_redirect_limit() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/loop"))
+            .respond_with(ResponseTemplate::new(302).insert_header("location", "/loop"))
+            .mount(&server)
+            .await;
+
+        let preview = preview_crawl_target(
+            &format!("{}/loop", server.uri()),
+            2,
+            &CrawlPolicy::default(),
+        )
+        .await
+        .unwrap();
+        assert!(!preview.reachable);
+        assert!(preview.redirect_limit_reached);
+        assert_eq!(preview.redirects.len(), 2);
+        assert_eq!(preview.status, Some(302));
+        // No robots.txt mock, so the 404 means no restrictions.
+        assert_eq!(preview.robots_txt.unwrap().allowed, Some(true));
+    }
+
+    #[test]
+    fn test_count_links_counts_distinct_resources_and_same_site_pages() {
+        let base = Url::parse("https://example.com/news/").unwrap();
+        let html = r##"<html><head>
+            <link rel="stylesheet" href="/style.css"><link rel=icon href='/favicon.ico'>
+            <link rel="canonical" href="/news/"><script src="app.js"></script>
+            </head><body><IMG SRC="a.png"><img src="a.png"><img data-src="lazy.png">
+            <a href="/about">About</a><a href="/about#team">Team</a><a href="#top">Top</a>
+            <a href="https://other.org/">Elsewhere</a><a href="mailto:x@example.com">Mail</a>
+            </body></html>"##;
+        assert_eq!(count_links(html, &base), (4, 1));
+    }
+
+    #[test]
+    fn test_size_estimate_confidence() {
+        assert_eq!(
+            size_estimate(Some(5_000_000), false, 0),
+            (Some(5_000_000), EstimateConfidence::High)
+        );
+        assert_eq!(
+            size_estimate(Some(10_000), true, 2),
+            (
+                Some(10_000 + 2 * AVERAGE_RESOURCE_BYTES),
+                EstimateConfidence::Medium
+            )
+        );
+        assert_eq!(
+            size_estimate(None, false, 0),
+            (None, EstimateConfidence::Low)
+        );
+        // Absurd declared sizes saturate instead of overflowing.
+        assert_eq!(
+            size_estimate(Some(u64::MAX), true, usize::MAX),
+            (Some(u64::MAX), EstimateConfidence::Medium)
+        );
+        assert_eq!(
+            wacz_range(u64::MAX, EstimateConfidence::High),
+            [u64::MAX, u64::MAX]
+        );
+        assert_eq!(
+            wacz_range(u64::MAX, EstimateConfidence::Medium),
+            [u64::MAX / 2, u64::MAX]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_estimate_applies_crawl_policy_to_every_hop() {
+        use crate::crawl_policy::DomainPattern;
+
+        let server = MockServer::start().await;
+        Mock::given(path("/old"))
+            .respond_with(
+                ResponseTemplate::new(301).insert_header("location", "http://metadata.internal/"),
+            )
+            .mount(&server)
+            .await;
+
+        let deny = vec![DomainPattern::parse("metadata.internal").unwrap()];
+        let policy = CrawlPolicy::new(Vec::new(), deny);
+        let estimate = estimate_crawl_size(&format!("{}/old", server.uri()), &policy)
+            .await
+            .unwrap();
+        assert!(
+            estimate.error.as_deref().unwrap().contains("not followed"),
+            "{:?}",
+            estimate.error
+        );
+        assert!(estimate.estimated_wacz_bytes.is_none());
+
+        let allow = vec![DomainPattern::parse("example.org").unwrap()];
+        let policy = CrawlPolicy::new(allow, Vec::new());
+        let err = estimate_crawl_size(&format!("{}/old", server.uri()), &policy)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SdaError::Validation(_)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_estimate_falls_back_to_get_when_head_is_rejected() {
+        let server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/page"))
+            .respond_with(ResponseTemplate::new(405))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/page"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                r#"<img src="/1.jpg"><img src="/2.jpg"><a href="/next">Next</a>"#,
+                "text/html",
+            ))
+            .mount(&server)
+            .await;
+        // The SDA API key must never reach the target.
+        Mock::given(header_exists("x-api-key"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let estimate =
+            estimate_crawl_size(&format!("{}/page", server.uri()), &CrawlPolicy::default())
+                .await
+                .unwrap();
+        assert_eq!(estimate.head_supported, Some(false));
+        assert!(estimate.notes[0].contains("HEAD"), "{:?}", estimate.notes);
+        assert_eq!(estimate.status, Some(200));
+        assert_eq!(
+            (estimate.linked_resources, estimate.same_site_links),
+            (2, 1)
+        );
+        assert_eq!(estimate.confidence, EstimateConfidence::Medium);
+        let bytes = estimate.estimated_wacz_bytes.unwrap();
+        assert!(bytes > 2 * AVERAGE_RESOURCE_BYTES);
+        assert_eq!(estimate.estimated_wacz_range, Some([bytes / 2, bytes * 3]));
+        assert!(estimate.error.is_none());
+    }
+}
@@ -0,0 +1,321 @@
+//! Named SDA API instances loaded from a profiles file.
+//!
+//! A profiles file (`--profiles-file`) is a JSON object listing instances,
+//! such as staging and production, by name:
+//!
+//! ```json
+//! {
+//!   "default": "staging",
+//!   "profiles": {
+//!     "staging": {
+//!       "base_url": "https://staging.example.org/sda-api",
+//!       "api_key_file": "staging.key",
+//!       "dry_run": true
+//!     },
+//!     "production": {
+//!       "base_url": "https://api.sudandigitalarchive.com/sda-api",
+//!       "api_key_file": "/etc/sda/production.key",
+//!       "default_private": true
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! `--profile` picks one at startup; without it the file's `default`, or its
+//! only profile, is used. Every profile gets its own client, kept in
+//! `ProfileClients`, and the `use_profile` tool switches between them
+//! without a restart. Relative `api_key_file` paths are resolved against
+//! the profiles file's directory. Keys never leave this module except to
+//! configure the clients: `summaries` reports only where a key comes from.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+
+use crate::client::SdaClient;
+use crate::model::ProfileSummary;
+
+/// One SDA API instance and the defaults to use with it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// Base URL of the instance's API.
+    pub base_url: String,
+    /// API key, inline. Prefer `api_key_file`, which keeps the key out of
+    /// the profiles file.
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// File holding the API key; surrounding whitespace is ignored.
+    #[serde(default)]
+    pub api_key_file: Option<PathBuf>,
+    /// Turns on `--default-private` for this instance.
+    #[serde(default)]
+    pub default_private: bool,
+    /// Turns on `--dry-run` for this instance.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl Profile {
+    /// Returns the profile's API key, reading `api_key_file` if set, or
+    /// `None` if the profile has no key.
+    pub fn api_key(&self) -> Result<Option<String>> {
+        match (&self.api_key, &self.api_key_file) {
+            (Some(_), Some(_)) => bail!("set either api_key or api_key_file, not both"),
+            (Some(key), None) => Ok(Some(key.clone())),
+            (None, Some(path)) => {
+                let key = std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read API key file {}", path.display()))?;
+                let key = key.trim();
+                if key.is_empty() {
+                    bail!("API key file {} is empty", path.display());
+                }
+                Ok(Some(key.to_string()))
+            }
+            (None, None) => Ok(None),
+        }
+    }
+}
+
+/// The contents of a profiles file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfilesFile {
+    /// Profile used when `--profile` is not given.
+    #[serde(default)]
+    pub default: Option<String>,
+    /// Profiles by name.
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl ProfilesFile {
+    /// Reads and parses the profiles file at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read profiles file {}", path.display()))?;
+        let mut file: ProfilesFile = serde_json::from_str(&text)
+            .with_context(|| format!("Failed to parse profiles file {}", path.display()))?;
+        if file.profiles.is_empty() {
+            bail!("Profiles file {} defines no profiles", path.display());
+        }
+        let dir = path.parent().unwrap_or(Path::new("."));
+        for profile in file.profiles.values_mut() {
+            if let Some(key_file) = profile.api_key_file.as_mut()
+                && key_file.is_relative()
+            {
+                *key_file = dir.join(&*key_file);
+            }
+        }
+        Ok(file)
+    }
+
+    /// Returns the profile named `name`, or else the file's default, or
+    /// else its only profile.
+    pub fn select(&self, name: Option<&str>) -> Result<(&str, &Profile)> {
+        let mut names = self.profiles.keys();
+        let name = match (name.or(self.default.as_deref()), names.next(), names.next()) {
+            (Some(name), _, _) => name,
+            (None, Some(only), None) => only.as_str(),
+            (None, _, _) => bail!(
+                "The profiles file defines several profiles and no default; choose one with --profile ({})",
+                self.names()
+            ),
+        };
+        match self.profiles.get_key_value(name) {
+            Some((name, profile)) => Ok((name.as_str(), profile)),
+            None => bail!("Unknown profile '{}'; defined: {}", name, self.names()),
+        }
+    }
+
+    /// Describes every profile without its key, marking `active`.
+    pub fn summaries(&self, active: &str) -> Vec<ProfileSummary> {
+        self.profiles
+            .iter()
+            .map(|(name, profile)| ProfileSummary {
+                name: name.clone(),
+                active: name == active,
+                base_url: crate::server::redact_url(&profile.base_url),
+                api_key_file: profile
+                    .api_key_file
+                    .as_ref()
+                    .map(|path| path.display().to_string()),
+                has_inline_api_key: profile.api_key.is_some(),
+                default_private: profile.default_private,
+                dry_run: profile.dry_run,
+            })
+            .collect()
+    }
+
+    /// Comma-separated profile names, for error messages.
+    fn names(&self) -> String {
+        self.profiles
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// A profile's client and the defaults it turns on.
+#[derive(Clone)]
+pub struct ProfileClient {
+    /// Client for the profile's instance.
+    pub client: SdaClient,
+    /// Whether mutating tools run in dry-run mode with this profile.
+    pub dry_run: bool,
+    /// Whether new accessions default to private with this profile.
+    pub default_private: bool,
+}
+
+/// One client per profile, and the profile tool calls currently use.
+pub struct ProfileClients {
+    clients: BTreeMap<String, ProfileClient>,
+    active: RwLock<String>,
+}
+
+impl ProfileClients {
+    /// Creates the set with `active` in use, failing if it is not one of
+    /// `clients`.
+    pub fn new(active: String, clients: BTreeMap<String, ProfileClient>) -> Result<Self> {
+        if !clients.contains_key(&active) {
+            bail!("Unknown profile '{}'", active);
+        }
+        Ok(Self {
+            clients,
+            active: RwLock::new(active),
+        })
+    }
+
+    /// Returns the name and client of the profile in use.
+    pub fn active(&self) -> (String, ProfileClient) {
+        let name = self
+            .active
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let client = self.clients[&name].clone();
+        (name, client)
+    }
+
+    /// Makes `name` the profile in use, explaining the failure if there is
+    /// no such profile.
+    pub fn activate(&self, name: &str) -> std::result::Result<(), String> {
+        if !self.clients.contains_key(name) {
+            let names: Vec<&str> = self.clients.keys().map(String::as_str).collect();
+            return Err(format!(
+                "Unknown profile '{}'; defined: {}",
+                name,
+                names.join(", ")
+            ));
+        }
+        *self.active.write().unwrap_or_else(|e| e.into_inner()) = name.to_string();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::TempDir;
+
+    fn write_profiles(dir: &Path, json: &str) -> PathBuf {
+        let path = dir.join("profiles.json");
+        std::fs::write(&path, json).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_select_profile_by_name_default_or_only_one() {
+        let dir = TempDir::create("sda-profiles-test").unwrap();
+        let dir = dir.path();
+        let path = write_profiles(
+            dir,
+            r#"{
+                "default": "staging",
+                "profiles": {
+                    "staging": {"base_url": "https://staging.test/sda-api", "api_key_file": "staging.key"},
+                    "production": {"base_url": "https://prod.test/sda-api", "api_key": "prod-key"}
+                }
+            }"#,
+        );
+        std::fs::write(dir.join("staging.key"), "staging-key\n").unwrap();
+        let file = ProfilesFile::load(&path).unwrap();
+
+        let (name, profile) = file.select(None).unwrap();
+        assert_eq!(name, "staging");
+        assert_eq!(profile.api_key().unwrap().as_deref(), Some("staging-key"));
+        let (name, profile) = file.select(Some("production")).unwrap();
+        assert_eq!(name, "production");
+        assert_eq!(profile.api_key().unwrap().as_deref(), Some("prod-key"));
+        let err = file.select(Some("dev")).unwrap_err().to_string();
+        assert!(err.contains("production, staging"), "{}", err);
+
+        let path = write_profiles(
+            dir,
+            r#"{"profiles": {"only": {"base_url": "https://only.test"}}}"#,
+        );
+        let file = ProfilesFile::load(&path).unwrap();
+        assert_eq!(file.select(None).unwrap().0, "only");
+
+        let path = write_profiles(
+            dir,
+            r#"{"profiles": {"a": {"base_url": "https://a.test"}, "b": {"base_url": "https://b.test"}}}"#,
+        );
+        let err = ProfilesFile::load(&path)
+            .unwrap()
+            .select(None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("no default"), "{}", err);
+    }
+
+    #[test]
+    fn test_profile_clients_switch_the_active_profile() {
+        let client = |url: &str| ProfileClient {
+            client: SdaClient::new(url.to_string(), "key".to_string()).unwrap(),
+            dry_run: url.contains("staging"),
+            default_private: false,
+        };
+        let clients = BTreeMap::from([
+            ("staging".to_string(), client("https://staging.test")),
+            ("production".to_string(), client("https://prod.test")),
+        ]);
+        assert!(ProfileClients::new("dev".to_string(), clients.clone()).is_err());
+        let profiles = ProfileClients::new("staging".to_string(), clients).unwrap();
+
+        let (name, active) = profiles.active();
+        assert_eq!(name, "staging");
+        assert!(active.dry_run);
+        profiles.activate("production").unwrap();
+        let (name, active) = profiles.active();
+        assert_eq!(name, "production");
+        assert_eq!(active.client.base_url(), "https://prod.test");
+        let err = profiles.activate("dev").unwrap_err();
+        assert!(err.contains("production, staging"), "{}", err);
+        assert_eq!(profiles.active().0, "production");
+    }
+
+    #[test]
+    fn test_summaries_do_not_contain_keys() {
+        let file: ProfilesFile = serde_json::from_str(
+            r#"{"profiles": {
+                "a": {"base_url": "https://user:pw@a.test/", "api_key": "secret-a"},
+                "b": {"base_url": "https://b.test/", "api_key_file": "/keys/b", "dry_run": true}
+            }}"#,
+        )
+        .unwrap();
+        let summaries = file.summaries("b");
+        let json = serde_json::to_string(&summaries).unwrap();
+        assert!(
+            !json.contains("secret-a") && !json.contains("pw"),
+            "{}",
+            json
+        );
+        assert!(summaries[0].has_inline_api_key && !summaries[0].active);
+        assert!(summaries[1].active && summaries[1].dry_run);
+        assert_eq!(summaries[1].api_key_file.as_deref(), Some("/keys/b"));
+    }
+}
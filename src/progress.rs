@@ -0,0 +1,111 @@
+//! MCP progress notifications for long-running tools.
+//!
+//! A client opts in by sending a `progressToken` in the request's `_meta`.
+//! `Progress` sends `notifications/progress` for that token and does nothing
+//! when no token was supplied. Updates are throttled to one per
+//! `MIN_INTERVAL` so that per-chunk or per-item callers don't flood the
+//! transport, and they are delivered in order by a single forwarding task.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rmcp::RoleServer;
+use rmcp::model::{ProgressNotificationParam, ProgressToken};
+use rmcp::service::RequestContext;
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+/// Minimum time between two progress notifications for one request.
+const MIN_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Progress reporter for a single tool call.
+pub struct Progress {
+    /// Progress token and notification queue; `None` when the client did not
+    /// ask for progress.
+    target: Option<(ProgressToken, UnboundedSender<ProgressNotificationParam>)>,
+    /// When the last notification was queued.
+    last_sent: Mutex<Option<Instant>>,
+}
+
+impl Progress {
+    /// Creates a reporter for the request described by `context`.
+    pub fn new(context: &RequestContext<RoleServer>) -> Self {
+        let target = context.meta.get_progress_token().map(|token| {
+            let (sender, mut receiver) = mpsc::unbounded_channel();
+            let peer = context.peer.clone();
+            // Ends once the reporter, and with it the sender, is dropped.
+            tokio::spawn(async move {
+                while let Some(param) = receiver.recv().await {
+                    if let Err(e) = peer.notify_progress(param).await {
+                        tracing::debug!("Failed to send progress notification: {}", e);
+                        break;
+                    }
+                }
+            });
+            (token, sender)
+        });
+        Self {
+            target,
+            last_sent: Mutex::new(None),
+        }
+    }
+
+    /// Reports `progress` out of `total`, unless an update was sent less than
+    /// `MIN_INTERVAL` ago.
+    pub fn report(&self, progress: f64, total: Option<f64>, message: impl Into<String>) {
+        let Some((token, sender)) = &self.target else {
+            return;
+        };
+        if !self.should_send(Instant::now()) {
+            return;
+        }
+        let _ = sender.send(ProgressNotificationParam {
+            progress_token: token.clone(),
+            progress,
+            total,
+            message: Some(message.into()),
+        });
+    }
+
+    /// Returns whether enough time has passed since the last update, and if
+    /// so records `now` as the time of the next one.
+    fn should_send(&self, now: Instant) -> bool {
+        let mut last_sent = self.last_sent.lock().unwrap_or_else(|e| e.into_inner());
+        if last_sent.is_some_and(|last| now.duration_since(last) < MIN_INTERVAL) {
+            return false;
+        }
+        *last_sent = Some(now);
+        true
+    }
+}
+
+/// Formats a byte count as mebibytes for progress messages.
+pub fn format_mib(bytes: u64) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reporter() -> Progress {
+        Progress {
+            target: None,
+            last_sent: Mutex::new(None),
+        }
+    }
+
+    #[test]
+    fn test_updates_are_throttled() {
+        let progress = reporter();
+        let start = Instant::now();
+        assert!(progress.should_send(start));
+        assert!(!progress.should_send(start + Duration::from_millis(100)));
+        assert!(progress.should_send(start + MIN_INTERVAL));
+    }
+
+    #[test]
+    fn test_format_mib() {
+        assert_eq!(format_mib(0), "0.0 MiB");
+        assert_eq!(format_mib(3 * 1024 * 1024 + 512 * 1024), "3.5 MiB");
+    }
+}
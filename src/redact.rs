@@ -0,0 +1,133 @@
+//! Removing operator-chosen fields from tool results.
+//!
+//! Deployments that must not show agents some of the archive's data, such as
+//! Arabic descriptions or WACZ links, name those fields with `--redact-field`.
+//! Every JSON tool result then has each property of that name removed at any
+//! depth before it is sent, and the CSV and Dublin Core exports leave out
+//! the columns and elements built from it. Names are checked at startup against the fields
+//! of the models the tools return, so a typo fails loudly instead of
+//! silently redacting nothing.
+
+use std::collections::{BTreeSet, HashSet};
+
+use schemars::JsonSchema;
+
+use crate::model::*;
+
+/// Removes a fixed set of property names from JSON values.
+#[derive(Debug, Default)]
+pub struct FieldRedactor {
+    /// Property names to remove.
+    fields: HashSet<String>,
+}
+
+impl FieldRedactor {
+    /// Creates a redactor removing `fields`.
+    pub fn new(fields: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            fields: fields.into_iter().collect(),
+        }
+    }
+
+    /// Returns whether no field is redacted.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Returns whether the property `field` is redacted.
+    pub fn redacts(&self, field: &str) -> bool {
+        self.fields.contains(field)
+    }
+
+    /// Removes the redacted properties from `value` and every object nested in it.
+    pub fn apply(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                map.retain(|key, _| !self.fields.contains(key));
+                map.values_mut().for_each(|v| self.apply(v));
+            }
+            serde_json::Value::Array(items) => items.iter_mut().for_each(|v| self.apply(v)),
+            _ => {}
+        }
+    }
+}
+
+/// Parses a `--redact-field` name, accepting only fields of the models tool
+/// results are built from.
+pub fn parse_field(input: &str) -> Result<String, String> {
+    let name = input.trim();
+    let known = known_fields();
+    if known.contains(name) {
+        return Ok(name.to_string());
+    }
+    Err(format!(
+        "unknown field '{}': expected a field of an accession, collection or other archive record, such as description_ar or wacz_url",
+        input
+    ))
+}
+
+/// Returns the serialized field names of the archive records tools return.
+///
+/// Derived from the JSON schemas, including nested types, so the list stays
+/// in sync with the models.
+fn known_fields() -> BTreeSet<String> {
+    let mut fields = BTreeSet::new();
+    collect_fields::<AccessionsWithMetadataResponse>(&mut fields);
+    collect_fields::<GetOneAccessionResponse>(&mut fields);
+    collect_fields::<CollectionResponse>(&mut fields);
+    collect_fields::<CollectionExport>(&mut fields);
+    collect_fields::<DublinMetadataSubjectResponse>(&mut fields);
+    collect_fields::<ContributorResponse>(&mut fields);
+    collect_fields::<CreatorResponse>(&mut fields);
+    collect_fields::<LocationResponse>(&mut fields);
+    collect_fields::<RelationResponse>(&mut fields);
+    collect_fields::<WaczDownloadResponse>(&mut fields);
+    fields
+}
+
+/// Adds the property names anywhere in the schema of `T` to `fields`.
+fn collect_fields<T: JsonSchema>(fields: &mut BTreeSet<String>) {
+    fn walk(value: &serde_json::Value, fields: &mut BTreeSet<String>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                if let Some(properties) = map.get("properties").and_then(|p| p.as_object()) {
+                    fields.extend(properties.keys().cloned());
+                }
+                map.values().for_each(|v| walk(v, fields));
+            }
+            serde_json::Value::Array(items) => items.iter().for_each(|v| walk(v, fields)),
+            _ => {}
+        }
+    }
+    walk(schemars::schema_for!(T).as_value(), fields);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacts_fields_at_any_depth() {
+        let redactor = FieldRedactor::new(["description_ar".to_string(), "wacz_url".to_string()]);
+        let mut value = serde_json::json!({
+            "items": [
+                {"id": 1, "description_ar": "وصف", "metadata": {"wacz_url": "https://files.test/1.wacz"}},
+                {"id": 2, "description_ar": null}
+            ],
+            "num_pages": 1
+        });
+        redactor.apply(&mut value);
+        assert_eq!(
+            value,
+            serde_json::json!({"items": [{"id": 1, "metadata": {}}, {"id": 2}], "num_pages": 1})
+        );
+    }
+
+    #[test]
+    fn test_parse_field_accepts_only_model_fields() {
+        for field in ["description_ar", "wacz_url", "seed_url", "subjects"] {
+            assert_eq!(parse_field(field).as_deref(), Ok(field), "{}", field);
+        }
+        assert!(parse_field("descripton_ar").is_err());
+    }
+}
@@ -0,0 +1,314 @@
+//! Locating and decoding single entries of a zip file fetched in pieces.
+//!
+//! `inspect_wacz` reads a few small files from a WACZ without downloading
+//! the whole archive. It fetches the archive's tail, finds the central
+//! directory with `locate_central_directory`, fetches and lists it with
+//! `parse_central_directory`, and then fetches only the wanted entries,
+//! decoding each with `local_data_offset` and `decompress`. ZIP64 archives
+//! are supported, since WACZ files can exceed 4 GiB.
+
+use std::io::Read;
+
+use flate2::read::DeflateDecoder;
+
+/// Bytes to fetch from the end of a zip to be sure of finding the central
+/// directory's location: the end-of-central-directory record with the
+/// longest possible comment, the ZIP64 locator and the ZIP64 record.
+pub const TAIL_BYTES: u64 = 22 + 65_535 + 20 + 56;
+
+/// Length of a local file header before its name and extra field.
+pub const LOCAL_HEADER_BYTES: u64 = 30;
+
+/// Largest decompressed entry `decompress` returns, in bytes, so a
+/// compression bomb cannot exhaust memory.
+pub const MAX_ENTRY_BYTES: u64 = 64 * 1024 * 1024;
+
+const EOCD_SIGNATURE: u32 = 0x0605_4b50;
+const ZIP64_LOCATOR_SIGNATURE: u32 = 0x0706_4b50;
+const ZIP64_EOCD_SIGNATURE: u32 = 0x0606_4b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+
+/// Compression methods this module can decode.
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+
+/// An entry listed in a zip's central directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZipEntry {
+    /// Path of the entry inside the archive.
+    pub name: String,
+    /// Compression method: 0 (stored) or 8 (deflate) can be decoded.
+    pub method: u16,
+    /// Size of the compressed data, in bytes.
+    pub compressed_size: u64,
+    /// Size of the decompressed data, in bytes.
+    pub uncompressed_size: u64,
+    /// Offset of the entry's local file header from the start of the zip.
+    pub header_offset: u64,
+}
+
+/// Location of a zip's central directory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CentralDirectory {
+    /// Offset from the start of the zip.
+    pub offset: u64,
+    /// Length, in bytes.
+    pub size: u64,
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn u64_at(bytes: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(bytes.get(at..at + 8)?.try_into().ok()?))
+}
+
+/// Finds the central directory from the last bytes of a zip.
+///
+/// `tail` holds the zip's bytes from `tail_offset` to its end.
+pub fn locate_central_directory(tail: &[u8], tail_offset: u64) -> Result<CentralDirectory, String> {
+    let eocd = (0..tail.len().saturating_sub(21))
+        .rev()
+        .find(|&at| u32_at(tail, at) == Some(EOCD_SIGNATURE))
+        .ok_or("No end of central directory record found; not a zip file")?;
+    let size = u32_at(tail, eocd + 12).ok_or("Truncated end of central directory record")?;
+    let offset = u32_at(tail, eocd + 16).ok_or("Truncated end of central directory record")?;
+    if size != u32::MAX && offset != u32::MAX {
+        return Ok(CentralDirectory {
+            offset: offset.into(),
+            size: size.into(),
+        });
+    }
+
+    // ZIP64: the locator sits just before the record and points at the
+    // ZIP64 end of central directory record.
+    let locator = eocd
+        .checked_sub(20)
+        .filter(|&at| u32_at(tail, at) == Some(ZIP64_LOCATOR_SIGNATURE))
+        .ok_or("ZIP64 archive without a ZIP64 end of central directory locator")?;
+    let record_offset = u64_at(tail, locator + 8).ok_or("Truncated ZIP64 locator")?;
+    let record = record_offset
+        .checked_sub(tail_offset)
+        .and_then(|at| usize::try_from(at).ok())
+        .filter(|&at| u32_at(tail, at) == Some(ZIP64_EOCD_SIGNATURE))
+        .ok_or("ZIP64 end of central directory record not found")?;
+    Ok(CentralDirectory {
+        size: u64_at(tail, record + 40).ok_or("Truncated ZIP64 end of central directory")?,
+        offset: u64_at(tail, record + 48).ok_or("Truncated ZIP64 end of central directory")?,
+    })
+}
+
+/// Lists the entries of a central directory.
+pub fn parse_central_directory(directory: &[u8]) -> Result<Vec<ZipEntry>, String> {
+    let truncated = || "Truncated central directory entry".to_string();
+    let mut entries = Vec::new();
+    let mut at = 0;
+    while u32_at(directory, at) == Some(CENTRAL_HEADER_SIGNATURE) {
+        let method = u16_at(directory, at + 10).ok_or_else(truncated)?;
+        let mut compressed_size: u64 = u32_at(directory, at + 20).ok_or_else(truncated)?.into();
+        let mut uncompressed_size: u64 = u32_at(directory, at + 24).ok_or_else(truncated)?.into();
+        let name_len = usize::from(u16_at(directory, at + 28).ok_or_else(truncated)?);
+        let extra_len = usize::from(u16_at(directory, at + 30).ok_or_else(truncated)?);
+        let comment_len = usize::from(u16_at(directory, at + 32).ok_or_else(truncated)?);
+        let mut header_offset: u64 = u32_at(directory, at + 42).ok_or_else(truncated)?.into();
+        let name_start = at + 46;
+        let name = directory
+            .get(name_start..name_start + name_len)
+            .ok_or_else(truncated)?;
+        let extra = directory
+            .get(name_start + name_len..name_start + name_len + extra_len)
+            .ok_or_else(truncated)?;
+
+        // The ZIP64 extra field holds, in this order, the values whose
+        // 32-bit fields are saturated.
+        if let Some(mut zip64) = zip64_extra(extra) {
+            for field in [
+                &mut uncompressed_size,
+                &mut compressed_size,
+                &mut header_offset,
+            ] {
+                if *field == u64::from(u32::MAX) {
+                    *field = u64_at(zip64, 0).ok_or_else(truncated)?;
+                    zip64 = &zip64[8..];
+                }
+            }
+        }
+
+        entries.push(ZipEntry {
+            name: String::from_utf8_lossy(name).into_owned(),
+            method,
+            compressed_size,
+            uncompressed_size,
+            header_offset,
+        });
+        at = name_start + name_len + extra_len + comment_len;
+    }
+    Ok(entries)
+}
+
+/// Returns the data of the ZIP64 extended information extra field, if any.
+fn zip64_extra(mut extra: &[u8]) -> Option<&[u8]> {
+    while extra.len() >= 4 {
+        let id = u16_at(extra, 0)?;
+        let len = usize::from(u16_at(extra, 2)?);
+        let data = extra.get(4..4 + len)?;
+        if id == 0x0001 {
+            return Some(data);
+        }
+        extra = &extra[4 + len..];
+    }
+    None
+}
+
+/// Returns where an entry's data starts, relative to its local file header,
+/// from the header's first `LOCAL_HEADER_BYTES` bytes.
+pub fn local_data_offset(header: &[u8]) -> Result<u64, String> {
+    if u32_at(header, 0) != Some(LOCAL_HEADER_SIGNATURE) {
+        return Err("Local file header not found at the listed offset".to_string());
+    }
+    let name_len = u16_at(header, 26).ok_or("Truncated local file header")?;
+    let extra_len = u16_at(header, 28).ok_or("Truncated local file header")?;
+    Ok(LOCAL_HEADER_BYTES + u64::from(name_len) + u64::from(extra_len))
+}
+
+/// Decompresses an entry's data.
+///
+/// Fails for entries listed as larger than `MAX_ENTRY_BYTES`, and stops
+/// decompressing, with an error, once the output exceeds the entry's listed
+/// `uncompressed_size`.
+pub fn decompress(entry: &ZipEntry, data: &[u8]) -> Result<Vec<u8>, String> {
+    if entry.uncompressed_size > MAX_ENTRY_BYTES {
+        return Err(format!(
+            "{} is {} bytes uncompressed, more than the {} bytes that can be read",
+            entry.name, entry.uncompressed_size, MAX_ENTRY_BYTES
+        ));
+    }
+    let oversized = || {
+        format!(
+            "{} holds more than the {} bytes its directory entry lists",
+            entry.name, entry.uncompressed_size
+        )
+    };
+    match entry.method {
+        STORED if data.len() as u64 > entry.uncompressed_size => Err(oversized()),
+        STORED => Ok(data.to_vec()),
+        DEFLATED => {
+            let mut out = Vec::new();
+            DeflateDecoder::new(data)
+                .take(entry.uncompressed_size + 1)
+                .read_to_end(&mut out)
+                .map_err(|e| format!("Failed to decompress {}: {}", entry.name, e))?;
+            if out.len() as u64 > entry.uncompressed_size {
+                return Err(oversized());
+            }
+            Ok(out)
+        }
+        method => Err(format!(
+            "{} uses unsupported compression method {}",
+            entry.name, method
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+
+    fn sample_zip() -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let stored = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        let deflated = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+        zip.start_file("archive/data.warc.gz", stored).unwrap();
+        zip.write_all(&[0u8; 1000]).unwrap();
+        zip.start_file("datapackage.json", deflated).unwrap();
+        zip.write_all(br#"{"wacz_version": "1.1.1"}"#).unwrap();
+        zip.set_comment("a comment");
+        zip.finish().unwrap().into_inner()
+    }
+
+    /// Reads an entry the way `inspect_wacz` does, from slices of `zip`.
+    fn read_entry(zip: &[u8], name: &str) -> Vec<u8> {
+        let tail_offset = zip.len().saturating_sub(TAIL_BYTES as usize);
+        let directory = locate_central_directory(&zip[tail_offset..], tail_offset as u64).unwrap();
+        let start = directory.offset as usize;
+        let entries =
+            parse_central_directory(&zip[start..start + directory.size as usize]).unwrap();
+        let entry = entries.iter().find(|e| e.name == name).unwrap();
+        let header = entry.header_offset as usize;
+        let data = header + local_data_offset(&zip[header..]).unwrap() as usize;
+        decompress(entry, &zip[data..data + entry.compressed_size as usize]).unwrap()
+    }
+
+    #[test]
+    fn test_reads_entries_from_pieces() {
+        let zip = sample_zip();
+        assert_eq!(
+            read_entry(&zip, "datapackage.json"),
+            br#"{"wacz_version": "1.1.1"}"#
+        );
+        assert_eq!(read_entry(&zip, "archive/data.warc.gz"), vec![0u8; 1000]);
+    }
+
+    #[test]
+    fn test_lists_entry_sizes() {
+        let zip = sample_zip();
+        let directory = locate_central_directory(&zip, 0).unwrap();
+        let start = directory.offset as usize;
+        let entries =
+            parse_central_directory(&zip[start..start + directory.size as usize]).unwrap();
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["archive/data.warc.gz", "datapackage.json"]);
+        assert_eq!(entries[0].uncompressed_size, 1000);
+        assert_eq!(entries[0].compressed_size, 1000);
+        assert_eq!(entries[1].method, DEFLATED);
+    }
+
+    #[test]
+    fn test_reads_zip64_sizes_and_offsets() {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored)
+            .large_file(true);
+        zip.start_file("pages/pages.jsonl", options).unwrap();
+        zip.write_all(b"{}\n").unwrap();
+        let zip = zip.finish().unwrap().into_inner();
+
+        assert_eq!(read_entry(&zip, "pages/pages.jsonl"), b"{}\n");
+    }
+
+    #[test]
+    fn test_decompress_stops_past_the_listed_size() {
+        let zip = sample_zip();
+        let directory = locate_central_directory(&zip, 0).unwrap();
+        let start = directory.offset as usize;
+        let entries =
+            parse_central_directory(&zip[start..start + directory.size as usize]).unwrap();
+        let mut entry = entries[1].clone();
+        let header = entry.header_offset as usize;
+        let data = header + local_data_offset(&zip[header..]).unwrap() as usize;
+        let data = &zip[data..data + entry.compressed_size as usize];
+
+        entry.uncompressed_size = 10;
+        let err = decompress(&entry, data).unwrap_err();
+        assert!(err.contains("more than the 10 bytes"), "{}", err);
+
+        entry.uncompressed_size = MAX_ENTRY_BYTES + 1;
+        let err = decompress(&entry, data).unwrap_err();
+        assert!(err.contains("uncompressed"), "{}", err);
+    }
+
+    #[test]
+    fn test_rejects_non_zip_data() {
+        assert!(locate_central_directory(b"WARC/1.0\r\n", 0).is_err());
+        assert!(local_data_offset(b"not a header").is_err());
+    }
+}
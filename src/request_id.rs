@@ -0,0 +1,45 @@
+//! Correlation IDs for tool calls.
+//!
+//! Every tool call gets a fresh UUID that is sent to the SDA API as the
+//! `X-Request-Id` header on each request the call makes, recorded on the
+//! `tool_call` tracing span and appended to errors returned to the MCP client.
+//! Operators can then grep the server and API logs for the same identifier
+//! when an agent reports a failure.
+
+use std::future::Future;
+
+/// Header carrying the correlation ID on SDA API requests and responses.
+pub const HEADER: &str = "x-request-id";
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Returns a new random correlation ID.
+pub fn generate() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Returns the correlation ID of the tool call running on this task, if any.
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// Runs `future` with `id` as the current correlation ID.
+pub async fn scope<F: Future>(id: String, future: F) -> F::Output {
+    REQUEST_ID.scope(id, future).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_current_is_set_only_inside_scope() {
+        assert_eq!(current(), None);
+        let id = generate();
+        let inside = scope(id.clone(), async { current() }).await;
+        assert_eq!(inside, Some(id));
+        assert_eq!(current(), None);
+    }
+}
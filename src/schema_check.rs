@@ -0,0 +1,98 @@
+//! Spotting drift between SDA API responses and this server's models.
+//!
+//! In strict schema mode (`--strict-schema`, always on in debug builds) the
+//! client compares each decoded response with the raw JSON it came from.
+//! Fields the model does not know are silently dropped by serde, so they are
+//! found by serializing the decoded value again and listing the raw fields
+//! that did not survive the round trip. Decode failures get a snippet of the
+//! body, so a renamed or missing field can be seen at once.
+
+use serde_json::Value;
+
+/// Characters of a response body quoted in a strict-mode decode error.
+const SNIPPET_CHARS: usize = 512;
+
+/// Lists the fields of `raw` that are absent from `decoded`, as dotted
+/// paths such as `items[].new_field`.
+///
+/// `decoded` is the model re-serialized, so fields the model skips when
+/// empty are only reported if their raw value is not `null`. Array elements
+/// are compared pairwise and each path is listed once.
+pub fn unknown_fields(raw: &Value, decoded: &Value) -> Vec<String> {
+    let mut fields = Vec::new();
+    collect(raw, decoded, "", &mut fields);
+    fields
+}
+
+fn collect(raw: &Value, decoded: &Value, path: &str, fields: &mut Vec<String>) {
+    match (raw, decoded) {
+        (Value::Object(raw), Value::Object(decoded)) => {
+            for (key, value) in raw {
+                let field = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                match decoded.get(key) {
+                    Some(known) => collect(value, known, &field, fields),
+                    None if !value.is_null() && !fields.contains(&field) => fields.push(field),
+                    None => {}
+                }
+            }
+        }
+        (Value::Array(raw), Value::Array(decoded)) => {
+            let element = format!("{}[]", path);
+            for (value, known) in raw.iter().zip(decoded) {
+                collect(value, known, &element, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns the start of `body` for an error message, marking truncation.
+pub fn body_snippet(body: &[u8]) -> String {
+    let text = String::from_utf8_lossy(body);
+    let mut snippet: String = text.chars().take(SNIPPET_CHARS).collect();
+    if text.chars().nth(SNIPPET_CHARS).is_some() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_unknown_fields_lists_nested_paths_once() {
+        let raw = json!({
+            "items": [
+                {"id": 1, "subject": "a", "subject_slug": "a"},
+                {"id": 2, "subject": "b", "subject_slug": "b"},
+            ],
+            "num_pages": 1,
+            "total_items": 2,
+            "cursor": null,
+        });
+        let decoded = json!({
+            "items": [{"id": 1, "subject": "a"}, {"id": 2, "subject": "b"}],
+            "num_pages": 1,
+        });
+        assert_eq!(
+            unknown_fields(&raw, &decoded),
+            ["items[].subject_slug", "total_items"]
+        );
+        assert!(unknown_fields(&decoded, &decoded).is_empty());
+    }
+
+    #[test]
+    fn test_body_snippet_truncates_long_bodies() {
+        assert_eq!(body_snippet(b"{\"id\": 1}"), "{\"id\": 1}");
+        let long = "x".repeat(SNIPPET_CHARS + 10);
+        let snippet = body_snippet(long.as_bytes());
+        assert_eq!(snippet.chars().count(), SNIPPET_CHARS + 1);
+        assert!(snippet.ends_with('…'));
+    }
+}
@@ -0,0 +1,142 @@
+//! Running the stdio service, optionally restarting it after transport errors.
+//!
+//! Without a `RestartPolicy` the first serve error ends the process, as
+//! before. With one (`--restart-on-error`), a recoverable failure — a
+//! transport error, a malformed first message or a crashed service task —
+//! is logged and the serve loop started again after a backoff, up to the
+//! policy's limit. Failures that would recur on every attempt, such as the
+//! client closing stdin or a protocol version the server refuses, still end
+//! the process.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use rmcp::{
+    ServiceExt,
+    service::{QuitReason, ServerInitializeError},
+    transport::stdio,
+};
+
+use crate::server::SdaServer;
+
+/// Longest wait between restarts, however many have happened.
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How often, and how quickly, a failed serve loop is restarted.
+#[derive(Debug, Clone, Copy)]
+pub struct RestartPolicy {
+    /// Restarts allowed over the life of the process.
+    pub max_restarts: u32,
+    /// Wait before the first restart; doubled for each one after.
+    pub backoff: Duration,
+}
+
+impl RestartPolicy {
+    /// Returns the wait before restart number `restart` (zero-based), capped
+    /// at `MAX_RESTART_BACKOFF`.
+    fn delay(&self, restart: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(restart))
+            .min(MAX_RESTART_BACKOFF)
+    }
+}
+
+/// Why a serve loop ended.
+enum Outcome {
+    /// The client disconnected or the service was cancelled.
+    Finished,
+    /// A failure that a fresh serve loop may get past.
+    Recoverable(String),
+    /// A failure that would recur on every attempt.
+    Fatal(anyhow::Error),
+}
+
+/// Returns whether a failed initialization is worth retrying.
+///
+/// A closed connection means the client has gone, and a refused
+/// initialization reflects the server's own configuration, so neither is
+/// helped by starting again.
+fn is_recoverable(error: &ServerInitializeError) -> bool {
+    match error {
+        ServerInitializeError::TransportError { .. } => true,
+        ServerInitializeError::ExpectedInitializeRequest(message)
+        | ServerInitializeError::ExpectedInitializedNotification(message) => message.is_some(),
+        _ => false,
+    }
+}
+
+/// Serves one MCP session over stdio until it ends.
+async fn serve_once(server: SdaServer) -> Outcome {
+    let service = match server.serve(stdio()).await {
+        Ok(service) => service,
+        Err(e) if is_recoverable(&e) => return Outcome::Recoverable(e.to_string()),
+        Err(e) => return Outcome::Fatal(e.into()),
+    };
+    match service.waiting().await {
+        Ok(QuitReason::Closed | QuitReason::Cancelled) => Outcome::Finished,
+        Ok(QuitReason::JoinError(e)) | Err(e) => Outcome::Recoverable(e.to_string()),
+    }
+}
+
+/// Serves `server` over stdio, restarting after recoverable failures when a
+/// policy is given.
+pub async fn serve_stdio(server: SdaServer, policy: Option<RestartPolicy>) -> Result<()> {
+    let mut restarts = 0;
+    loop {
+        let error = match serve_once(server.clone()).await {
+            Outcome::Finished => return Ok(()),
+            Outcome::Fatal(e) => {
+                tracing::error!("serving error: {:?}", e);
+                return Err(e);
+            }
+            Outcome::Recoverable(error) => error,
+        };
+        let Some(policy) = policy.filter(|p| restarts < p.max_restarts) else {
+            tracing::error!("serving error: {}", error);
+            anyhow::bail!("MCP service failed: {}", error);
+        };
+        let delay = policy.delay(restarts);
+        restarts += 1;
+        tracing::warn!(
+            restart = restarts,
+            max_restarts = policy.max_restarts,
+            delay_ms = delay.as_millis() as u64,
+            "MCP service failed, restarting: {}",
+            error
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restart_delay_doubles_up_to_cap() {
+        let policy = RestartPolicy {
+            max_restarts: 10,
+            backoff: Duration::from_secs(1),
+        };
+        assert_eq!(policy.delay(0), Duration::from_secs(1));
+        assert_eq!(policy.delay(3), Duration::from_secs(8));
+        assert_eq!(policy.delay(9), MAX_RESTART_BACKOFF);
+        assert_eq!(policy.delay(u32::MAX), MAX_RESTART_BACKOFF);
+    }
+
+    #[test]
+    fn test_only_transport_failures_are_recoverable() {
+        assert!(!is_recoverable(&ServerInitializeError::ConnectionClosed(
+            "stdin closed".to_string()
+        )));
+        assert!(!is_recoverable(
+            &ServerInitializeError::ExpectedInitializeRequest(None)
+        ));
+        assert!(!is_recoverable(&ServerInitializeError::Cancelled));
+        assert!(!is_recoverable(
+            &ServerInitializeError::UnsupportedProtocolVersion(
+                rmcp::model::ProtocolVersion::V_2024_11_05
+            )
+        ));
+    }
+}
@@ -0,0 +1,159 @@
+//! Short-lived server-side state handed out as tokens.
+//!
+//! Continuations of truncated results and accession cursors both keep
+//! something in memory between tool calls and give the client a token for
+//! one position in it, written `<id>.<index>`. A `TokenStore` holds these
+//! entries under random IDs, forgets an entry once it has gone unused for
+//! its time to live, and keeps at most `MAX_ENTRIES`, dropping the least
+//! recently used one to make room.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Most entries kept at once; the least recently used is dropped beyond this.
+const MAX_ENTRIES: usize = 32;
+
+/// Separates the entry ID from the index in a token.
+const TOKEN_SEPARATOR: char = '.';
+
+/// A stored value and when it was last used.
+struct Entry<T> {
+    /// When the entry was stored or last read.
+    used_at: Instant,
+    /// The stored value.
+    value: T,
+}
+
+/// Values kept between tool calls, keyed by entry ID.
+pub struct TokenStore<T> {
+    /// How long an unused entry is kept.
+    ttl: Duration,
+    /// Entries by ID.
+    entries: Mutex<HashMap<String, Entry<T>>>,
+}
+
+impl<T> TokenStore<T> {
+    /// Creates a store dropping entries unused for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Stores `value` and returns its new entry ID.
+    pub fn insert(&self, value: T) -> String {
+        let id = crate::request_id::generate();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, entry| entry.used_at.elapsed() < self.ttl);
+        if entries.len() >= MAX_ENTRIES
+            && let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.used_at)
+                .map(|(id, _)| id.clone())
+        {
+            entries.remove(&oldest);
+        }
+        entries.insert(
+            id.clone(),
+            Entry {
+                used_at: Instant::now(),
+                value,
+            },
+        );
+        id
+    }
+
+    /// Calls `read` with the entry ID, index and value named by `token`,
+    /// marking the entry as used. Returns `None` if the token is malformed,
+    /// its entry is unknown or has expired, or `read` returns `None`.
+    pub fn read<R>(
+        &self,
+        token: &str,
+        read: impl FnOnce(&str, usize, &T) -> Option<R>,
+    ) -> Option<R> {
+        let (id, index) = token.rsplit_once(TOKEN_SEPARATOR)?;
+        let index: usize = index.parse().ok()?;
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = entries.get_mut(id)?;
+        if entry.used_at.elapsed() >= self.ttl {
+            entries.remove(id);
+            return None;
+        }
+        entry.used_at = Instant::now();
+        read(id, index, &entry.value)
+    }
+
+    /// Drops the entry named by `token`, or by a bare entry ID, returning
+    /// whether it was still kept.
+    pub fn remove(&self, token: &str) -> bool {
+        let id = token
+            .rsplit_once(TOKEN_SEPARATOR)
+            .map_or(token, |(id, _)| id);
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(id)
+            .is_some_and(|entry| entry.used_at.elapsed() < self.ttl)
+    }
+
+    /// Whether no entry is stored.
+    #[cfg(test)]
+    pub fn is_empty(&self) -> bool {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_empty()
+    }
+}
+
+/// Names position `index` of entry `id`.
+pub fn token(id: &str, index: usize) -> String {
+    format!("{}{}{}", id, TOKEN_SEPARATOR, index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_name_an_entry_and_an_index() {
+        let store = TokenStore::new(Duration::from_secs(60));
+        let id = store.insert("value");
+        let read = |token: &str| {
+            store.read(token, |id, index, value| {
+                Some((id.to_string(), index, *value))
+            })
+        };
+        assert_eq!(read(&token(&id, 3)), Some((id.clone(), 3, "value")));
+        assert_eq!(read(&id), None);
+        assert_eq!(read(&format!("{}.next", id)), None);
+        assert_eq!(read("unknown.0"), None);
+
+        assert!(store.remove(&token(&id, 3)));
+        assert!(!store.remove(&id));
+        assert!(store.is_empty());
+
+        let expired = TokenStore::new(Duration::ZERO);
+        let id = expired.insert(());
+        assert!(expired.read(&token(&id, 0), |_, _, _| Some(())).is_none());
+    }
+
+    #[test]
+    fn test_least_recently_used_entry_is_dropped_when_full() {
+        let store = TokenStore::new(Duration::from_secs(60));
+        let first = store.insert(0);
+        let second = store.insert(1);
+        std::thread::sleep(Duration::from_millis(2));
+        for value in 2..MAX_ENTRIES {
+            store.insert(value);
+        }
+        // Reading the first entry makes the second the least recently used.
+        std::thread::sleep(Duration::from_millis(2));
+        assert!(store.read(&token(&first, 0), |_, _, _| Some(())).is_some());
+        store.insert(MAX_ENTRIES);
+        assert!(store.read(&token(&first, 0), |_, _, _| Some(())).is_some());
+        assert!(store.read(&token(&second, 0), |_, _, _| Some(())).is_none());
+    }
+}
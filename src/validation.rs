@@ -0,0 +1,456 @@
+//! Client-side checks for crawl, subject and collection requests.
+//!
+//! These catch malformed requests before an expensive crawl is started or a
+//! vague API error is returned. They only look at the request itself; checks
+//! that need the API (such as whether subject IDs exist) live in
+//! `SdaClient::validate_crawl_request`.
+
+use chrono::NaiveDateTime;
+
+use crate::model::{
+    BrowserProfile, CreateAccessionCrawlRequest, DublinMetadataFormat, MetadataLanguage,
+    ValidationProblem,
+};
+
+/// Format `metadata_time` must use: ISO 8601 without a timezone.
+const METADATA_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// Formats the API accepts for new crawls.
+pub const CRAWL_FORMATS: [DublinMetadataFormat; 1] = [DublinMetadataFormat::Wacz];
+
+/// Longest subject name accepted, in characters.
+pub const MAX_SUBJECT_LENGTH: usize = 200;
+
+/// Longest collection title accepted, in characters.
+pub const MAX_TITLE_LENGTH: usize = 500;
+
+/// Longest collection description accepted, in characters.
+pub const MAX_DESCRIPTION_LENGTH: usize = 5000;
+
+/// Sites each browser profile is meant for, including their subdomains.
+const BROWSER_PROFILE_HOSTS: [(BrowserProfile, &[&str]); 1] = [(
+    BrowserProfile::Facebook,
+    &["facebook.com", "fb.com", "fb.watch"],
+)];
+
+/// Returns whether `host` is `domain` or one of its subdomains.
+fn host_matches(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+/// Returns the browser profile pages on `host` need, if any.
+pub fn browser_profile_for_host(host: &str) -> Option<BrowserProfile> {
+    let host = host.to_lowercase();
+    BROWSER_PROFILE_HOSTS
+        .iter()
+        .find(|(_, domains)| domains.iter().any(|d| host_matches(&host, d)))
+        .map(|(profile, _)| profile.clone())
+}
+
+/// Checks `profile` against the host of `url`: returns a warning when the
+/// profile is meant for another site, or when none is given for a site that
+/// needs one. Unparseable URLs are left to `check_crawl_request`.
+pub fn check_browser_profile(url: &str, profile: Option<&BrowserProfile>) -> Option<String> {
+    let url = reqwest::Url::parse(url.trim()).ok()?;
+    let host = url.host_str()?;
+    match (profile, browser_profile_for_host(host)) {
+        (Some(profile), needed) if needed.as_ref() != Some(profile) => Some(format!(
+            "The {} browser profile is not meant for '{}'",
+            profile_name(profile),
+            host
+        )),
+        (None, Some(needed)) => Some(format!(
+            "'{}' usually needs the {} browser profile, and crawls of it without one often fail",
+            host,
+            profile_name(&needed)
+        )),
+        _ => None,
+    }
+}
+
+/// Name of `profile` as given in `browser_profile`.
+fn profile_name(profile: &BrowserProfile) -> &'static str {
+    match profile {
+        BrowserProfile::Facebook => "facebook",
+    }
+}
+
+/// Checks a free-text field: non-blank when `required`, at most `max_length`
+/// characters and free of control characters. Line breaks and tabs are
+/// allowed when `multiline` is set.
+fn check_text(
+    field: &str,
+    value: &str,
+    max_length: usize,
+    required: bool,
+    multiline: bool,
+) -> Result<(), String> {
+    if value.trim().is_empty() {
+        if required || !value.is_empty() {
+            return Err(format!("{} must not be empty or only whitespace", field));
+        }
+        return Ok(());
+    }
+    let length = value.chars().count();
+    if length > max_length {
+        return Err(format!(
+            "{} is {} characters long; the maximum is {}",
+            field, length, max_length
+        ));
+    }
+    let allowed = |c: char| multiline && matches!(c, '\n' | '\r' | '\t');
+    if let Some(c) = value.chars().find(|&c| c.is_control() && !allowed(c)) {
+        return Err(format!(
+            "{} must not contain control characters (found U+{:04X})",
+            field, c as u32
+        ));
+    }
+    Ok(())
+}
+
+/// Checks a subject name before it is created or renamed.
+pub fn check_subject_name(metadata_subject: &str) -> Result<(), String> {
+    check_text(
+        "metadata_subject",
+        metadata_subject,
+        MAX_SUBJECT_LENGTH,
+        true,
+        false,
+    )
+}
+
+/// Checks a collection's title and description before it is created or
+/// updated. The description may be left empty.
+pub fn check_collection_text(title: &str, description: &str) -> Result<(), String> {
+    check_text("title", title, MAX_TITLE_LENGTH, true, false)?;
+    check_text(
+        "description",
+        description,
+        MAX_DESCRIPTION_LENGTH,
+        false,
+        true,
+    )
+}
+
+/// Checks that `format` is one the API accepts for new crawls.
+///
+/// The error message lists the supported formats.
+pub fn check_crawl_format(format: DublinMetadataFormat) -> Result<(), String> {
+    if CRAWL_FORMATS.contains(&format) {
+        return Ok(());
+    }
+    let supported: Vec<String> = CRAWL_FORMATS
+        .iter()
+        .map(|f| serde_json::to_string(f).unwrap_or_default())
+        .collect();
+    Err(format!(
+        "metadata_format {} is not accepted for crawls; supported formats: {}",
+        serde_json::to_string(&format).unwrap_or_default(),
+        supported.join(", ")
+    ))
+}
+
+/// Checks the `s3_filename` of an accession backed by an already-uploaded
+/// WACZ: an object key such as `captures/2024/site.wacz`, not a URL.
+pub fn check_s3_filename(s3_filename: &str) -> Result<(), String> {
+    if s3_filename.trim().is_empty() {
+        return Err("s3_filename must not be empty".to_string());
+    }
+    if s3_filename.trim() != s3_filename || s3_filename.chars().any(char::is_control) {
+        return Err(format!(
+            "s3_filename must not have surrounding whitespace or control characters, got {:?}",
+            s3_filename
+        ));
+    }
+    if s3_filename.contains("://") || s3_filename.starts_with('/') {
+        return Err(format!(
+            "s3_filename must be the object key within the archive's bucket, e.g. 'captures/site.wacz', not a URL or absolute path, got '{}'",
+            s3_filename
+        ));
+    }
+    if s3_filename.split('/').any(|segment| segment == "..") {
+        return Err(format!(
+            "s3_filename must not contain '..' segments, got '{}'",
+            s3_filename
+        ));
+    }
+    if !s3_filename.to_lowercase().ends_with(".wacz") {
+        return Err(format!(
+            "s3_filename must name a .wacz file, got '{}'",
+            s3_filename
+        ));
+    }
+    Ok(())
+}
+
+/// Drops zero and negative subject IDs and repeats of an ID, keeping the
+/// first occurrence of each in order.
+pub fn normalize_subject_ids(ids: &[i64]) -> Vec<i64> {
+    let mut normalized: Vec<i64> = Vec::with_capacity(ids.len());
+    for &id in ids {
+        if id > 0 && !normalized.contains(&id) {
+            normalized.push(id);
+        }
+    }
+    normalized
+}
+
+/// Checks everything about a crawl request that can be verified locally.
+///
+/// Returns one problem per failed check, or an empty list if the request is
+/// well-formed.
+pub fn check_crawl_request(request: &CreateAccessionCrawlRequest) -> Vec<ValidationProblem> {
+    let mut problems = Vec::new();
+    let mut problem = |field: &str, message: String| {
+        problems.push(ValidationProblem {
+            field: field.to_string(),
+            message,
+        })
+    };
+
+    let host = match reqwest::Url::parse(request.url.trim()) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") && url.host_str().is_some() => {
+            url.host_str().map(str::to_lowercase)
+        }
+        Ok(url) => {
+            problem(
+                "url",
+                format!("URL must be http or https with a host, got '{}'", url),
+            );
+            None
+        }
+        Err(e) => {
+            problem("url", format!("Invalid URL '{}': {}", request.url, e));
+            None
+        }
+    };
+
+    if request.metadata_language == MetadataLanguage::None {
+        problem(
+            "metadata_language",
+            "metadata_language must be \"english\" or \"arabic\"".to_string(),
+        );
+    }
+    if let Err(message) = check_crawl_format(request.metadata_format) {
+        problem("metadata_format", message);
+    }
+    if request.metadata_title.trim().is_empty() {
+        problem(
+            "metadata_title",
+            "metadata_title must not be empty".to_string(),
+        );
+    }
+    if NaiveDateTime::parse_from_str(&request.metadata_time, METADATA_TIME_FORMAT).is_err() {
+        problem(
+            "metadata_time",
+            format!(
+                "metadata_time must be ISO 8601 without a timezone, e.g. '2026-02-01T00:00:00', got '{}'",
+                request.metadata_time
+            ),
+        );
+    }
+    if request.metadata_contributor_ids.len() != request.metadata_contributor_role_ids.len() {
+        problem(
+            "metadata_contributor_role_ids",
+            format!(
+                "{} contributor role ID(s) given for {} contributor ID(s); they must be 1:1",
+                request.metadata_contributor_role_ids.len(),
+                request.metadata_contributor_ids.len()
+            ),
+        );
+    }
+    if let Some(Err(message)) = request.s3_filename.as_deref().map(check_s3_filename) {
+        problem("s3_filename", message);
+    }
+    if let (Some(profile), Some(host)) = (&request.browser_profile, &host)
+        && browser_profile_for_host(host).as_ref() != Some(profile)
+    {
+        problem(
+            "browser_profile",
+            format!(
+                "The {} browser profile only applies to its own site's URLs, not '{}'",
+                profile_name(profile),
+                host
+            ),
+        );
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> CreateAccessionCrawlRequest {
+        CreateAccessionCrawlRequest {
+            url: "https://www.facebook.com/some.page".to_string(),
+            metadata_language: MetadataLanguage::English,
+            metadata_title: "Example".to_string(),
+            metadata_time: "2026-02-01T00:00:00".to_string(),
+            metadata_subjects: vec![1],
+            is_private: false,
+            metadata_format: DublinMetadataFormat::Wacz,
+            browser_profile: Some(BrowserProfile::Facebook),
+            metadata_description: None,
+            s3_filename: None,
+            metadata_contributor_ids: vec![4],
+            metadata_contributor_role_ids: vec![None],
+            metadata_creator_id: None,
+            metadata_location_id: None,
+            send_email_notification: false,
+            idempotency_key: None,
+        }
+    }
+
+    #[test]
+    fn test_check_crawl_request_accepts_valid_request() {
+        assert!(check_crawl_request(&request()).is_empty());
+    }
+
+    #[test]
+    fn test_check_crawl_request_reports_each_problem() {
+        let request = CreateAccessionCrawlRequest {
+            url: "https://example.com/news".to_string(),
+            metadata_language: MetadataLanguage::None,
+            metadata_title: " ".to_string(),
+            metadata_time: "2026-02-01T00:00:00Z".to_string(),
+            metadata_contributor_role_ids: vec![],
+            ..request()
+        };
+
+        let fields: Vec<String> = check_crawl_request(&request)
+            .into_iter()
+            .map(|p| p.field)
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "metadata_language",
+                "metadata_title",
+                "metadata_time",
+                "metadata_contributor_role_ids",
+                "browser_profile"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_browser_profile_matches_the_seed_host() {
+        let facebook = Some(&BrowserProfile::Facebook);
+        assert_eq!(
+            browser_profile_for_host("M.Facebook.com"),
+            Some(BrowserProfile::Facebook)
+        );
+        assert!(browser_profile_for_host("notfacebook.com").is_none());
+        assert!(check_browser_profile("https://fb.watch/abc", facebook).is_none());
+        assert!(check_browser_profile("https://example.com/", None).is_none());
+        assert_eq!(
+            check_browser_profile("https://example.com/news", facebook).as_deref(),
+            Some("The facebook browser profile is not meant for 'example.com'")
+        );
+        assert!(
+            check_browser_profile("https://www.facebook.com/page", None)
+                .unwrap()
+                .contains("needs the facebook browser profile")
+        );
+    }
+
+    #[test]
+    fn test_check_s3_filename() {
+        assert!(check_s3_filename("captures/2024/site.wacz").is_ok());
+        assert!(check_s3_filename("SITE.WACZ").is_ok());
+        for invalid in [
+            "",
+            "  ",
+            " site.wacz",
+            "s3://bucket/site.wacz",
+            "/captures/site.wacz",
+            "captures/../site.wacz",
+            "site.warc.gz",
+        ] {
+            assert!(
+                check_s3_filename(invalid).is_err(),
+                "{:?} should be rejected",
+                invalid
+            );
+        }
+
+        let mut request = request();
+        request.s3_filename = Some("s3://bucket/site.wacz".to_string());
+        assert!(
+            check_crawl_request(&request)
+                .iter()
+                .any(|p| p.field == "s3_filename")
+        );
+    }
+
+    #[test]
+    fn test_normalize_subject_ids() {
+        assert_eq!(normalize_subject_ids(&[7, 0, 3, 7, -1, 3, 12]), [7, 3, 12]);
+        assert!(normalize_subject_ids(&[0, -5]).is_empty());
+    }
+
+    #[test]
+    fn test_check_subject_name_rejects_blank_names() {
+        assert!(check_subject_name("Protests").is_ok());
+        assert_eq!(
+            check_subject_name("").unwrap_err(),
+            "metadata_subject must not be empty or only whitespace"
+        );
+        assert!(check_subject_name(" \t ").is_err());
+    }
+
+    #[test]
+    fn test_check_subject_name_rejects_long_names_and_control_characters() {
+        assert!(check_subject_name(&"ا".repeat(MAX_SUBJECT_LENGTH)).is_ok());
+        let err = check_subject_name(&"ا".repeat(MAX_SUBJECT_LENGTH + 1)).unwrap_err();
+        assert!(err.contains("201 characters long; the maximum is 200"));
+        let err = check_subject_name("Pro\ntests").unwrap_err();
+        assert!(err.contains("control characters (found U+000A)"));
+    }
+
+    #[test]
+    fn test_check_collection_text() {
+        assert!(check_collection_text("Elections", "").is_ok());
+        assert!(check_collection_text("Elections", "Line one\nLine two").is_ok());
+        assert!(
+            check_collection_text("  ", "")
+                .unwrap_err()
+                .starts_with("title ")
+        );
+        assert!(
+            check_collection_text("Elections", "   ")
+                .unwrap_err()
+                .starts_with("description ")
+        );
+        let long = "x".repeat(MAX_DESCRIPTION_LENGTH + 1);
+        assert!(
+            check_collection_text("Elections", &long)
+                .unwrap_err()
+                .contains("maximum is 5000")
+        );
+        assert!(check_collection_text("Elec\u{7}tions", "").is_err());
+    }
+
+    #[test]
+    fn test_check_crawl_format_lists_supported_formats() {
+        assert!(check_crawl_format(DublinMetadataFormat::Wacz).is_ok());
+        let message = check_crawl_format(DublinMetadataFormat::Warc).unwrap_err();
+        assert!(message.contains("\"warc\" is not accepted"));
+        assert!(message.ends_with("supported formats: \"wacz\""));
+    }
+
+    #[test]
+    fn test_check_crawl_request_rejects_non_http_url() {
+        let request = CreateAccessionCrawlRequest {
+            url: "ftp://example.com/file".to_string(),
+            browser_profile: None,
+            ..request()
+        };
+
+        let problems = check_crawl_request(&request);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].field, "url");
+    }
+}
@@ -0,0 +1,943 @@
+//! Minimal WACZ and WARC reading for extracting archived page text.
+//!
+//! A WACZ file is a zip holding `pages/pages.jsonl` (the captured pages) and
+//! one or more WARC files under `archive/`. To get the readable content of an
+//! accession, the first listed page is looked up among the WARC `response`
+//! records and its HTTP body is decoded and, for HTML, reduced to plain text.
+//! Raw WARC files (optionally gzipped) are read the same way, using the first
+//! HTML response as the main page.
+//!
+//! Other captured pages are listed from `pages/pages.jsonl` and
+//! `pages/extraPages.jsonl`, and read by the same scan for their URL.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek};
+use std::path::Path;
+
+use flate2::read::{GzDecoder, MultiGzDecoder, ZlibDecoder};
+
+/// Largest HTTP response record read into memory, in bytes.
+const MAX_RECORD_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Header names (lowercased) and values, in order.
+type Headers = Vec<(String, String)>;
+
+/// Elements whose content is never readable text.
+///
+/// `<head>` is not skipped as a whole so its `<title>` can be read; its other
+/// children (`<meta>`, `<link>`) carry no text.
+const SKIPPED_ELEMENTS: [&str; 5] = ["script", "style", "noscript", "template", "svg"];
+
+/// Elements that start a new line in the extracted text.
+const BLOCK_ELEMENTS: [&str; 24] = [
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "p",
+    "pre",
+    "section",
+    "tr",
+];
+
+/// Path of the WACZ package descriptor.
+pub const DATAPACKAGE_PATH: &str = "datapackage.json";
+
+/// Path of the WACZ page list.
+pub const PAGES_PATH: &str = "pages/pages.jsonl";
+
+/// Path of the WACZ list of pages found beyond the seeds, when the crawl
+/// followed links.
+pub const EXTRA_PAGES_PATH: &str = "pages/extraPages.jsonl";
+
+/// Capture metadata of a WACZ, from its package descriptor and page list.
+#[derive(Debug, Default, PartialEq)]
+pub struct WaczMetadata {
+    /// `wacz_version` of the package.
+    pub wacz_version: Option<String>,
+    /// Package title.
+    pub title: Option<String>,
+    /// Software that wrote the package.
+    pub software: Option<String>,
+    /// When the package was created.
+    pub created: Option<String>,
+    /// URL of the package's main page.
+    pub main_page_url: Option<String>,
+    /// Capture time of the main page.
+    pub main_page_date: Option<String>,
+    /// Files listed in the package, with their sizes in bytes when given.
+    pub resources: Vec<(String, Option<u64>)>,
+    /// Number of captured pages; `None` when the page list was not read.
+    pub page_count: Option<u64>,
+    /// Earliest page capture time (`ts`).
+    pub first_capture: Option<String>,
+    /// Latest page capture time (`ts`).
+    pub last_capture: Option<String>,
+}
+
+/// Reads capture metadata from a `datapackage.json` and, if available, a
+/// `pages.jsonl`.
+pub fn summarize_metadata(
+    datapackage: &[u8],
+    pages: Option<&[u8]>,
+) -> Result<WaczMetadata, String> {
+    let package: serde_json::Value = serde_json::from_slice(datapackage)
+        .map_err(|e| format!("Failed to parse {}: {}", DATAPACKAGE_PATH, e))?;
+    let text = |key: &str| {
+        package
+            .get(key)
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    };
+    let resources = package
+        .get("resources")
+        .and_then(|r| r.as_array())
+        .map(|resources| {
+            resources
+                .iter()
+                .filter_map(|r| {
+                    let path = r.get("path")?.as_str()?.to_string();
+                    Some((path, r.get("bytes").and_then(|b| b.as_u64())))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut metadata = WaczMetadata {
+        wacz_version: text("wacz_version"),
+        title: text("title"),
+        software: text("software"),
+        created: text("created"),
+        main_page_url: text("mainPageURL").or_else(|| text("mainPageUrl")),
+        main_page_date: text("mainPageDate"),
+        resources,
+        ..Default::default()
+    };
+    if let Some(pages) = pages {
+        let mut count = 0;
+        for line in pages.split(|&b| b == b'\n') {
+            let Ok(page) = serde_json::from_slice::<serde_json::Value>(line) else {
+                continue;
+            };
+            // The first line is a format header without a `url`.
+            if page.get("url").is_none() {
+                continue;
+            }
+            count += 1;
+            if let Some(ts) = page.get("ts").and_then(|t| t.as_str()) {
+                if metadata
+                    .first_capture
+                    .as_deref()
+                    .is_none_or(|first| ts < first)
+                {
+                    metadata.first_capture = Some(ts.to_string());
+                }
+                if metadata
+                    .last_capture
+                    .as_deref()
+                    .is_none_or(|last| ts > last)
+                {
+                    metadata.last_capture = Some(ts.to_string());
+                }
+            }
+        }
+        metadata.page_count = Some(count);
+    }
+    Ok(metadata)
+}
+
+/// Reads capture metadata from the WACZ file at `path`.
+pub fn read_metadata(path: &Path) -> Result<WaczMetadata, String> {
+    let mut zip = open_wacz(path)?;
+    let datapackage = read_entry(&mut zip, DATAPACKAGE_PATH)?
+        .ok_or_else(|| format!("The WACZ has no {}", DATAPACKAGE_PATH))?;
+    let pages = read_entry(&mut zip, PAGES_PATH)?;
+    summarize_metadata(&datapackage, pages.as_deref())
+}
+
+/// Opens the WACZ file at `path` as a zip.
+fn open_wacz(path: &Path) -> Result<zip::ZipArchive<File>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open archive: {}", e))?;
+    zip::ZipArchive::new(file).map_err(|e| format!("Failed to open WACZ zip: {}", e))
+}
+
+/// Reads the entry `name` of a WACZ, or `None` if it has no such entry.
+fn read_entry(zip: &mut zip::ZipArchive<File>, name: &str) -> Result<Option<Vec<u8>>, String> {
+    let Ok(mut entry) = zip.by_name(name) else {
+        return Ok(None);
+    };
+    let mut bytes = Vec::new();
+    entry
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read {} from WACZ: {}", name, e))?;
+    Ok(Some(bytes))
+}
+
+/// A page listed in a WACZ's page lists.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListedPage {
+    /// The page's `id`, or its position across the page lists (from 1)
+    /// when it has none.
+    pub id: String,
+    /// URL of the page.
+    pub url: String,
+    /// Page title, when listed.
+    pub title: Option<String>,
+    /// Capture time (`ts`).
+    pub ts: Option<String>,
+    /// Whether the page is a crawl seed, from `pages.jsonl`, rather than a
+    /// page found by following links, from `extraPages.jsonl`.
+    pub seed: bool,
+}
+
+/// Lists the pages in a WACZ's `pages.jsonl` and `extraPages.jsonl`, in
+/// that order.
+pub fn list_pages(pages: Option<&[u8]>, extra_pages: Option<&[u8]>) -> Vec<ListedPage> {
+    let lists = [(pages, true), (extra_pages, false)];
+    let mut listed = Vec::new();
+    for (bytes, seed) in lists {
+        let Some(bytes) = bytes else {
+            continue;
+        };
+        for line in bytes.split(|&b| b == b'\n') {
+            let Ok(page) = serde_json::from_slice::<serde_json::Value>(line) else {
+                continue;
+            };
+            // The first line is a format header without a `url`.
+            let Some(url) = page.get("url").and_then(|u| u.as_str()) else {
+                continue;
+            };
+            let text = |key: &str| {
+                page.get(key)
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+                    .filter(|v| !v.trim().is_empty())
+            };
+            listed.push(ListedPage {
+                id: text("id").unwrap_or_else(|| (listed.len() + 1).to_string()),
+                url: url.to_string(),
+                title: text("title"),
+                ts: text("ts"),
+                seed,
+            });
+        }
+    }
+    listed
+}
+
+/// Lists the pages of the WACZ file at `path`.
+pub fn read_page_list(path: &Path) -> Result<Vec<ListedPage>, String> {
+    let mut zip = open_wacz(path)?;
+    let pages = read_entry(&mut zip, PAGES_PATH)?;
+    let extra_pages = read_entry(&mut zip, EXTRA_PAGES_PATH)?;
+    Ok(list_pages(pages.as_deref(), extra_pages.as_deref()))
+}
+
+/// Extracts the page with id `page_id` from the WACZ file at `path`.
+///
+/// Returns the page as listed and its content: extracted text, or with
+/// `raw` the captured body as is. At most `max_chars` characters are
+/// returned.
+pub fn read_page(
+    path: &Path,
+    page_id: &str,
+    max_chars: usize,
+    raw: bool,
+) -> Result<(ListedPage, ArchivedPage), String> {
+    let listed = read_page_list(path)?
+        .into_iter()
+        .find(|page| page.id == page_id)
+        .ok_or_else(|| format!("The WACZ lists no page with id '{}'", page_id))?;
+    let entry = PageEntry {
+        url: listed.url.clone(),
+        title: listed.title.clone(),
+    };
+    let response = find_in_warcs(&mut open_wacz(path)?, Some(&entry), true)?.ok_or_else(|| {
+        format!(
+            "No successful response for page '{}' ({}) was captured in the WACZ",
+            page_id, listed.url
+        )
+    })?;
+    let page = response.into_page(max_chars, raw);
+    Ok((listed, page))
+}
+
+/// Readable content of the main page captured in an archive.
+#[derive(Debug, PartialEq)]
+pub struct ArchivedPage {
+    /// URL of the captured page.
+    pub url: String,
+    /// Page title, from the archive's page list or the HTML `<title>`.
+    pub title: Option<String>,
+    /// `Content-Type` of the captured response.
+    pub content_type: Option<String>,
+    /// Extracted text, truncated to the requested number of characters.
+    pub text: String,
+    /// Length of the full extracted text, in characters.
+    pub total_chars: usize,
+    /// Whether `text` was cut short.
+    pub truncated: bool,
+}
+
+/// Extracts the main page of the WACZ or WARC file at `path`.
+///
+/// The format is detected from the file's leading bytes. At most
+/// `max_chars` characters of text are returned.
+pub fn read_main_page(path: &Path, max_chars: usize) -> Result<ArchivedPage, String> {
+    let mut file = File::open(path).map_err(|e| format!("Failed to open archive: {}", e))?;
+    let mut magic = [0u8; 2];
+    let read = file
+        .read(&mut magic)
+        .map_err(|e| format!("Failed to read archive: {}", e))?;
+    file.rewind()
+        .map_err(|e| format!("Failed to read archive: {}", e))?;
+
+    let response = match &magic[..read] {
+        b"PK" => read_wacz(file)?,
+        [0x1f, 0x8b] => find_response(BufReader::new(MultiGzDecoder::new(file)), None)?,
+        _ => find_response(BufReader::new(file), None)?,
+    };
+    let Some(response) = response else {
+        return Err("No HTML response record found in the archive".to_string());
+    };
+    Ok(response.into_page(max_chars, false))
+}
+
+/// A captured page from the archive's page list.
+struct PageEntry {
+    url: String,
+    title: Option<String>,
+}
+
+/// A decoded HTTP response captured in a WARC file.
+struct CapturedResponse {
+    url: String,
+    title: Option<String>,
+    content_type: Option<String>,
+    body: Vec<u8>,
+}
+
+impl CapturedResponse {
+    /// Converts the body to text, or keeps it as is with `raw`, and applies
+    /// the size cap.
+    fn into_page(self, max_chars: usize, raw: bool) -> ArchivedPage {
+        let body = String::from_utf8_lossy(&self.body);
+        let is_html = self
+            .content_type
+            .as_deref()
+            .is_none_or(|t| t.to_ascii_lowercase().contains("html"));
+        let (html_title, text) = match (is_html, raw) {
+            (true, false) => html_to_text(&body),
+            (true, true) => (html_to_text(&body).0, body.into_owned()),
+            (false, false) => (None, body.trim().to_string()),
+            (false, true) => (None, body.into_owned()),
+        };
+        let total_chars = text.chars().count();
+        let truncated = total_chars > max_chars;
+        let text = if truncated {
+            text.chars().take(max_chars).collect()
+        } else {
+            text
+        };
+        ArchivedPage {
+            url: self.url,
+            title: self.title.or(html_title),
+            content_type: self.content_type,
+            text,
+            total_chars,
+            truncated,
+        }
+    }
+}
+
+/// Finds the main page's response in a WACZ file.
+fn read_wacz(file: File) -> Result<Option<CapturedResponse>, String> {
+    let mut zip =
+        zip::ZipArchive::new(file).map_err(|e| format!("Failed to open WACZ zip: {}", e))?;
+    let page = match zip.by_name(PAGES_PATH) {
+        Ok(entry) => first_page(BufReader::new(entry)),
+        Err(_) => None,
+    };
+    find_in_warcs(&mut zip, page.as_ref(), false)
+}
+
+/// Scans the WARC files of a WACZ, in name order, for the response to
+/// `page`, moving on to the next WARC until one holds it. Without `exact`,
+/// the first successful HTML response found is returned when no WARC holds
+/// `page`, as `find_response` does in a single WARC; with it, `None` is.
+fn find_in_warcs(
+    zip: &mut zip::ZipArchive<File>,
+    page: Option<&PageEntry>,
+    exact: bool,
+) -> Result<Option<CapturedResponse>, String> {
+    let mut warcs: Vec<String> = zip
+        .file_names()
+        .filter(|name| {
+            name.starts_with("archive/") && (name.ends_with(".warc.gz") || name.ends_with(".warc"))
+        })
+        .map(str::to_string)
+        .collect();
+    warcs.sort();
+    let mut fallback = None;
+    for name in warcs {
+        let entry = zip
+            .by_name(&name)
+            .map_err(|e| format!("Failed to read {} from WACZ: {}", name, e))?;
+        let response = if name.ends_with(".gz") {
+            find_response(BufReader::new(MultiGzDecoder::new(entry)), page)?
+        } else {
+            find_response(BufReader::new(entry), page)?
+        };
+        let Some(response) = response else {
+            continue;
+        };
+        if page.is_none_or(|p| p.url == response.url) {
+            return Ok(Some(response));
+        }
+        if !exact && fallback.is_none() {
+            fallback = Some(response);
+        }
+    }
+    Ok(fallback)
+}
+
+/// Returns the first page listed in a `pages.jsonl` file.
+///
+/// The first line is a format header without a `url` and is skipped.
+fn first_page<R: BufRead>(reader: R) -> Option<PageEntry> {
+    reader.lines().map_while(Result::ok).find_map(|line| {
+        let value: serde_json::Value = serde_json::from_str(&line).ok()?;
+        let url = value.get("url")?.as_str()?.to_string();
+        let title = value
+            .get("title")
+            .and_then(|t| t.as_str())
+            .map(str::to_string)
+            .filter(|t| !t.trim().is_empty());
+        Some(PageEntry { url, title })
+    })
+}
+
+/// Scans WARC records for the response to `page`, or for the first
+/// successful HTML response when `page` is unknown or not found.
+fn find_response<R: BufRead>(
+    mut reader: R,
+    page: Option<&PageEntry>,
+) -> Result<Option<CapturedResponse>, String> {
+    let mut fallback = None;
+    while let Some(headers) =
+        read_warc_headers(&mut reader).map_err(|e| format!("Failed to read WARC: {}", e))?
+    {
+        let length: u64 = header(&headers, "content-length")
+            .and_then(|v| v.parse().ok())
+            .ok_or("WARC record without a valid Content-Length")?;
+        let target = header(&headers, "warc-target-uri")
+            .map(|v| v.trim_matches(['<', '>']).to_string())
+            .unwrap_or_default();
+        let is_http_response = header(&headers, "warc-type") == Some("response")
+            && header(&headers, "content-type").is_some_and(|t| t.starts_with("application/http"));
+        let is_page = page.is_some_and(|p| p.url == target);
+
+        if !is_http_response || length > MAX_RECORD_BYTES || (!is_page && fallback.is_some()) {
+            io::copy(&mut (&mut reader).take(length), &mut io::sink())
+                .map_err(|e| format!("Failed to read WARC: {}", e))?;
+            continue;
+        }
+        let mut block = Vec::with_capacity(length as usize);
+        (&mut reader)
+            .take(length)
+            .read_to_end(&mut block)
+            .map_err(|e| format!("Failed to read WARC: {}", e))?;
+
+        let Some((status, http_headers, body)) = parse_http_response(&block) else {
+            continue;
+        };
+        let content_type = header(&http_headers, "content-type").map(str::to_string);
+        let is_html = content_type
+            .as_deref()
+            .is_some_and(|t| t.to_ascii_lowercase().contains("html"));
+        if !(200..300).contains(&status) || (!is_page && !is_html) {
+            continue;
+        }
+        let response = CapturedResponse {
+            url: target,
+            title: page.filter(|_| is_page).and_then(|p| p.title.clone()),
+            content_type,
+            body: decode_body(body, &http_headers)?,
+        };
+        if is_page {
+            return Ok(Some(response));
+        }
+        fallback = Some(response);
+    }
+    Ok(fallback)
+}
+
+/// Reads the headers of the next WARC record, or `None` at the end of input.
+///
+/// Leaves the reader at the start of the record's content block.
+fn read_warc_headers<R: BufRead>(reader: &mut R) -> io::Result<Option<Headers>> {
+    let mut line = String::new();
+    // Skip the blank lines that terminate the previous record.
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        if !line.trim().is_empty() {
+            break;
+        }
+    }
+    if !line.starts_with("WARC/") {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("expected a WARC record, found {:?}", line.trim()),
+        ));
+    }
+
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            return Ok(Some(headers));
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+}
+
+/// Returns the value of a header, given its lowercase name.
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, v)| v.as_str())
+}
+
+/// Splits a captured HTTP response into status, headers and raw body.
+fn parse_http_response(block: &[u8]) -> Option<(u16, Headers, &[u8])> {
+    let end = block.windows(4).position(|w| w == b"\r\n\r\n")?;
+    let head = String::from_utf8_lossy(&block[..end]);
+    let mut lines = head.lines();
+    let status = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+    Some((status, headers, &block[end + 4..]))
+}
+
+/// Undoes chunked transfer encoding and gzip/deflate content encoding.
+fn decode_body(body: &[u8], headers: &[(String, String)]) -> Result<Vec<u8>, String> {
+    let body = if header(headers, "transfer-encoding")
+        .is_some_and(|v| v.eq_ignore_ascii_case("chunked"))
+    {
+        dechunk(body).ok_or("Malformed chunked response body in archive")?
+    } else {
+        body.to_vec()
+    };
+
+    let mut decoded = Vec::new();
+    let result = match header(headers, "content-encoding").map(str::to_ascii_lowercase) {
+        None => return Ok(body),
+        Some(encoding) if encoding == "identity" => return Ok(body),
+        Some(encoding) if encoding == "gzip" || encoding == "x-gzip" => {
+            GzDecoder::new(&body[..]).read_to_end(&mut decoded)
+        }
+        Some(encoding) if encoding == "deflate" => {
+            ZlibDecoder::new(&body[..]).read_to_end(&mut decoded)
+        }
+        Some(encoding) => {
+            return Err(format!(
+                "Unsupported content encoding '{}' in archived response",
+                encoding
+            ));
+        }
+    };
+    result.map_err(|e| format!("Failed to decode archived response body: {}", e))?;
+    Ok(decoded)
+}
+
+/// Reassembles a body sent with `Transfer-Encoding: chunked`.
+fn dechunk(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(body.len());
+    loop {
+        let line_end = body.windows(2).position(|w| w == b"\r\n")?;
+        let size_field = std::str::from_utf8(&body[..line_end]).ok()?;
+        let size_field = size_field.split(';').next()?.trim();
+        let size = usize::from_str_radix(size_field, 16).ok()?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Some(out);
+        }
+        out.extend_from_slice(body.get(..size)?);
+        body = body.get(size + 2..).unwrap_or_default();
+    }
+}
+
+/// Reduces an HTML document to its title and readable text.
+///
+/// Tags, comments and non-content elements (scripts, styles) are
+/// dropped, block elements become line breaks, entities are decoded and
+/// whitespace is collapsed.
+fn html_to_text(html: &str) -> (Option<String>, String) {
+    let mut title = None;
+    let mut text = String::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(end) = rest.find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if closing {
+            if BLOCK_ELEMENTS.contains(&name.as_str()) {
+                text.push('\n');
+            }
+            continue;
+        }
+        if name == "title" && title.is_none() {
+            let (inner, after) = split_at_closing_tag(rest, "title");
+            title = Some(normalize_whitespace(&decode_entities(inner))).filter(|t| !t.is_empty());
+            rest = after;
+        } else if SKIPPED_ELEMENTS.contains(&name.as_str()) && !tag.ends_with('/') {
+            rest = split_at_closing_tag(rest, &name).1;
+        } else if BLOCK_ELEMENTS.contains(&name.as_str()) {
+            text.push('\n');
+        }
+    }
+    text.push_str(rest);
+
+    let text = decode_entities(&text)
+        .lines()
+        .map(normalize_whitespace)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    (title, text)
+}
+
+/// Splits `html` at the closing tag of `name`, returning the content before
+/// it and the remainder after it. Without a closing tag, everything is content.
+fn split_at_closing_tag<'a>(html: &'a str, name: &str) -> (&'a str, &'a str) {
+    let lower = html.to_ascii_lowercase();
+    let closing = format!("</{}", name);
+    match lower.find(&closing) {
+        Some(start) => {
+            let after = &html[start..];
+            let end = after.find('>').map_or(after.len(), |i| i + 1);
+            (&html[..start], &after[end..])
+        }
+        None => (html, ""),
+    }
+}
+
+/// Collapses runs of whitespace into single spaces and trims the ends.
+fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Decodes the common named entities and all numeric character references.
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity.strip_prefix('#').and_then(|n| {
+                    let code = match n.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => n.parse().ok()?,
+                    };
+                    char::from_u32(code)
+                }),
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn warc_response(url: &str, http: &str) -> String {
+        format!(
+            "WARC/1.1\r\nWARC-Type: response\r\nWARC-Target-URI: {}\r\nContent-Type: application/http; msgtype=response\r\nContent-Length: {}\r\n\r\n{}\r\n\r\n",
+            url,
+            http.len(),
+            http
+        )
+    }
+
+    fn warc_request(url: &str) -> String {
+        let http = format!("GET {} HTTP/1.1\r\nHost: example.com\r\n\r\n", url);
+        format!(
+            "WARC/1.1\r\nWARC-Type: request\r\nWARC-Target-URI: {}\r\nContent-Type: application/http; msgtype=request\r\nContent-Length: {}\r\n\r\n{}\r\n\r\n",
+            url,
+            http.len(),
+            http
+        )
+    }
+
+    const PAGE: &str = "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\n\r\n<html><head><title>Khartoum &amp; Omdurman</title><style>p{}</style></head><body><p>First   paragraph.</p><script>alert(1)</script><p>Second&nbsp;one &#1587;</p></body></html>";
+
+    #[test]
+    fn test_html_to_text_strips_markup() {
+        let (title, text) = html_to_text(
+            "<html><head><title> A  page </title></head><body><!-- note --><h1>Heading</h1>Some <b>bold</b> text<br>next line<script>var x = '<p>';</script></body></html>",
+        );
+        assert_eq!(title.as_deref(), Some("A page"));
+        assert_eq!(text, "Heading\nSome bold text\nnext line");
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(
+            decode_entities("a &lt;b&gt; &#x41;&#66; &unknown; &"),
+            "a <b> AB &unknown; &"
+        );
+    }
+
+    #[test]
+    fn test_dechunk() {
+        assert_eq!(
+            dechunk(b"5\r\nHello\r\n7;ext=1\r\n, world\r\n0\r\n\r\n").unwrap(),
+            b"Hello, world"
+        );
+        assert_eq!(dechunk(b"5\r\nHel"), None);
+    }
+
+    #[test]
+    fn test_find_response_prefers_listed_page() {
+        let other = "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\n\r\n<p>Other</p>";
+        let warc = format!(
+            "{}{}{}",
+            warc_response("https://example.com/other", other),
+            warc_request("https://example.com/"),
+            warc_response("https://example.com/", PAGE)
+        );
+        let page = PageEntry {
+            url: "https://example.com/".to_string(),
+            title: None,
+        };
+
+        let response = find_response(warc.as_bytes(), Some(&page))
+            .unwrap()
+            .unwrap();
+        assert_eq!(response.url, "https://example.com/");
+        let page = response.into_page(1000, false);
+        assert_eq!(page.title.as_deref(), Some("Khartoum & Omdurman"));
+        assert_eq!(page.text, "First paragraph.\nSecond one س");
+        assert!(!page.truncated);
+    }
+
+    #[test]
+    fn test_summarize_metadata_counts_pages_and_capture_range() {
+        let datapackage = br#"{
+            "profile": "data-package",
+            "wacz_version": "1.1.1",
+            "software": "Browsertrix-Crawler 1.0",
+            "created": "2024-05-01T12:05:00Z",
+            "mainPageURL": "https://example.com/",
+            "resources": [
+                {"name": "pages.jsonl", "path": "pages/pages.jsonl", "bytes": 120},
+                {"name": "data.warc.gz", "path": "archive/data.warc.gz", "bytes": 4096}
+            ]
+        }"#;
+        let pages = b"{\"format\":\"json-pages-1.0\",\"id\":\"pages\"}\n\
+            {\"url\":\"https://example.com/a\",\"ts\":\"2024-05-01T12:01:00Z\"}\n\
+            {\"url\":\"https://example.com/\",\"ts\":\"2024-05-01T12:00:00Z\"}\n\
+            {\"url\":\"https://example.com/b\"}\n";
+
+        let metadata = summarize_metadata(datapackage, Some(pages)).unwrap();
+        assert_eq!(metadata.wacz_version.as_deref(), Some("1.1.1"));
+        assert_eq!(
+            metadata.main_page_url.as_deref(),
+            Some("https://example.com/")
+        );
+        assert_eq!(
+            metadata.resources,
+            vec![
+                ("pages/pages.jsonl".to_string(), Some(120)),
+                ("archive/data.warc.gz".to_string(), Some(4096)),
+            ]
+        );
+        assert_eq!(metadata.page_count, Some(3));
+        assert_eq!(
+            metadata.first_capture.as_deref(),
+            Some("2024-05-01T12:00:00Z")
+        );
+        assert_eq!(
+            metadata.last_capture.as_deref(),
+            Some("2024-05-01T12:01:00Z")
+        );
+
+        let without_pages = summarize_metadata(datapackage, None).unwrap();
+        assert_eq!(without_pages.page_count, None);
+        assert!(summarize_metadata(b"not json", None).is_err());
+    }
+
+    #[test]
+    fn test_read_main_page_from_wacz_truncates_text() {
+        let warc = warc_response("https://example.com/", PAGE);
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(warc.as_bytes()).unwrap();
+        let warc_gz = gz.finish().unwrap();
+
+        let path = std::env::temp_dir().join(format!("sda-test-{}.wacz", uuid::Uuid::new_v4()));
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        zip.start_file("pages/pages.jsonl", options).unwrap();
+        zip.write_all(
+            b"{\"format\":\"json-pages-1.0\",\"id\":\"pages\"}\n{\"url\":\"https://example.com/\",\"title\":\"Listed title\"}\n",
+        )
+        .unwrap();
+        zip.start_file("archive/data.warc.gz", options).unwrap();
+        zip.write_all(&warc_gz).unwrap();
+        zip.finish().unwrap();
+
+        let page = read_main_page(&path, 5).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(page.url, "https://example.com/");
+        assert_eq!(page.title.as_deref(), Some("Listed title"));
+        assert_eq!(page.text, "First");
+        assert_eq!(page.total_chars, 29);
+        assert!(page.truncated);
+    }
+
+    #[test]
+    fn test_read_page_by_id_from_page_lists() {
+        let about =
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\n\r\n<title>About</title><p>About us</p>";
+        let warc = format!(
+            "{}{}",
+            warc_response("https://example.com/", PAGE),
+            warc_response("https://example.com/about", about)
+        );
+        let path = std::env::temp_dir().join(format!("sda-test-{}.wacz", uuid::Uuid::new_v4()));
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file(PAGES_PATH, options).unwrap();
+        zip.write_all(
+            b"{\"format\":\"json-pages-1.0\",\"id\":\"pages\"}\n{\"id\":\"seed-1\",\"url\":\"https://example.com/\",\"ts\":\"2024-05-01T12:00:00Z\"}\n",
+        )
+        .unwrap();
+        zip.start_file(EXTRA_PAGES_PATH, options).unwrap();
+        zip.write_all(
+            b"{\"format\":\"json-pages-1.0\",\"id\":\"extra-pages\"}\n{\"url\":\"https://example.com/about\"}\n{\"url\":\"https://example.com/missing\"}\n",
+        )
+        .unwrap();
+        zip.start_file("archive/data.warc", options).unwrap();
+        zip.write_all(warc.as_bytes()).unwrap();
+        zip.finish().unwrap();
+
+        let pages = read_page_list(&path).unwrap();
+        let ids: Vec<(&str, bool)> = pages.iter().map(|p| (p.id.as_str(), p.seed)).collect();
+        assert_eq!(ids, [("seed-1", true), ("2", false), ("3", false)]);
+        assert_eq!(pages[0].ts.as_deref(), Some("2024-05-01T12:00:00Z"));
+
+        let (listed, page) = read_page(&path, "2", 1000, false).unwrap();
+        assert_eq!(listed.url, "https://example.com/about");
+        assert_eq!(page.title.as_deref(), Some("About"));
+        assert_eq!(page.text, "About us");
+
+        let (_, html) = read_page(&path, "seed-1", 1000, true).unwrap();
+        assert!(html.text.contains("<p>First   paragraph.</p><script>"));
+        assert_eq!(html.title.as_deref(), Some("Khartoum & Omdurman"));
+
+        let missing = read_page(&path, "3", 1000, false).unwrap_err();
+        let unknown = read_page(&path, "seed-9", 1000, false).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(
+            missing.contains("https://example.com/missing"),
+            "{}",
+            missing
+        );
+        assert!(unknown.contains("no page with id 'seed-9'"), "{}", unknown);
+    }
+
+    #[test]
+    fn test_pages_are_found_in_later_warcs() {
+        let about =
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\n\r\n<title>About</title><p>About us</p>";
+        let path = std::env::temp_dir().join(format!("sda-test-{}.wacz", uuid::Uuid::new_v4()));
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file(PAGES_PATH, options).unwrap();
+        zip.write_all(
+            b"{\"format\":\"json-pages-1.0\",\"id\":\"pages\"}\n{\"id\":\"about\",\"url\":\"https://example.com/about\"}\n{\"id\":\"gone\",\"url\":\"https://example.com/gone\"}\n",
+        )
+        .unwrap();
+        zip.start_file("archive/data-1.warc", options).unwrap();
+        zip.write_all(warc_response("https://example.com/", PAGE).as_bytes())
+            .unwrap();
+        zip.start_file("archive/data-2.warc", options).unwrap();
+        zip.write_all(warc_response("https://example.com/about", about).as_bytes())
+            .unwrap();
+        zip.finish().unwrap();
+
+        let main = read_main_page(&path, 1000);
+        let (_, page) = read_page(&path, "about", 1000, false).unwrap();
+        let gone = read_page(&path, "gone", 1000, false);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(main.unwrap().url, "https://example.com/about");
+        assert_eq!(page.url, "https://example.com/about");
+        assert_eq!(page.text, "About us");
+        assert!(gone.unwrap_err().contains("https://example.com/gone"));
+    }
+}